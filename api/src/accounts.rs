@@ -5,16 +5,22 @@ use crate::{
     context::Context,
     failpoint::fail_point,
     metrics::metrics,
-    param::{AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam},
+    page::AddressPage,
+    param::{
+        AddressParam, LedgerVersionParam, MoveIdentifierParam, MoveStructTagParam, ProofQuery,
+        TimestampParam,
+    },
 };
 
 use diem_api_types::{
-    AccountData, Address, Error, LedgerInfo, MoveModuleBytecode, Response, TransactionId,
+    AccountData, AccountStateProof, Address, Error, LedgerInfo, MoveModuleBytecode, MoveResource,
+    Response, TransactionId, WithAccountStateProof,
 };
 use diem_types::{
     account_config::AccountResource,
     account_state::AccountState,
     event::{EventHandle, EventKey},
+    ledger_info::LedgerInfoWithSignatures,
 };
 
 use anyhow::Result;
@@ -22,13 +28,15 @@ use move_core_types::{
     identifier::Identifier, language_storage::StructTag, move_resource::MoveStructType,
     value::MoveValue,
 };
-use std::convert::TryInto;
+use serde::Serialize;
+use std::convert::{TryFrom, TryInto};
 use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
 
 // GET /accounts/<address>
 pub fn get_account(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam)
         .and(warp::get())
+        .and(warp::query::<ProofQuery>())
         .and(context.filter())
         .and_then(handle_get_account)
         .with(metrics("get_account"))
@@ -39,8 +47,9 @@ pub fn get_account(context: Context) -> BoxedFilter<(impl Reply,)> {
 pub fn get_account_resources(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "resources")
         .and(warp::get())
+        .and(warp::query::<ProofQuery>())
         .and(context.filter())
-        .map(|address, ctx| (None, address, ctx))
+        .map(|address, proof_query, ctx| (None, address, proof_query, ctx))
         .untuple_one()
         .and_then(handle_get_account_resources)
         .with(metrics("get_account_resources"))
@@ -51,20 +60,33 @@ pub fn get_account_resources(context: Context) -> BoxedFilter<(impl Reply,)> {
 pub fn get_account_resources_by_ledger_version(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("ledger" / LedgerVersionParam / "accounts" / AddressParam / "resources")
         .and(warp::get())
+        .and(warp::query::<ProofQuery>())
         .and(context.filter())
-        .map(|version, address, ctx| (Some(version), address, ctx))
+        .map(|version, address, proof_query, ctx| (Some(version), address, proof_query, ctx))
         .untuple_one()
         .and_then(handle_get_account_resources)
         .with(metrics("get_account_resources_by_ledger_version"))
         .boxed()
 }
 
+// GET /ledger/at_time/<timestamp>/accounts/<address>/resources
+pub fn get_account_resources_at_time(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("ledger" / "at_time" / TimestampParam / "accounts" / AddressParam / "resources")
+        .and(warp::get())
+        .and(warp::query::<ProofQuery>())
+        .and(context.filter())
+        .and_then(handle_get_account_resources_at_time)
+        .with(metrics("get_account_resources_at_time"))
+        .boxed()
+}
+
 // GET /accounts/<address>/modules
 pub fn get_account_modules(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "modules")
         .and(warp::get())
+        .and(warp::query::<ProofQuery>())
         .and(context.filter())
-        .map(|address, ctx| (None, address, ctx))
+        .map(|address, proof_query, ctx| (None, address, proof_query, ctx))
         .untuple_one()
         .and_then(handle_get_account_modules)
         .with(metrics("get_account_modules"))
@@ -75,44 +97,115 @@ pub fn get_account_modules(context: Context) -> BoxedFilter<(impl Reply,)> {
 pub fn get_account_modules_by_ledger_version(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("ledger" / LedgerVersionParam / "accounts" / AddressParam / "modules")
         .and(warp::get())
+        .and(warp::query::<ProofQuery>())
         .and(context.filter())
-        .map(|version, address, ctx| (Some(version), address, ctx))
+        .map(|version, address, proof_query, ctx| (Some(version), address, proof_query, ctx))
         .untuple_one()
         .and_then(handle_get_account_modules)
         .with(metrics("get_account_modules_by_ledger_version"))
         .boxed()
 }
 
+// GET /ledger/at_time/<timestamp>/accounts/<address>/modules
+pub fn get_account_modules_at_time(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("ledger" / "at_time" / TimestampParam / "accounts" / AddressParam / "modules")
+        .and(warp::get())
+        .and(warp::query::<ProofQuery>())
+        .and(context.filter())
+        .and_then(handle_get_account_modules_at_time)
+        .with(metrics("get_account_modules_at_time"))
+        .boxed()
+}
+
+// GET /resources/<resource_type>/accounts
+pub fn get_accounts_by_resource(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("resources" / MoveStructTagParam / "accounts")
+        .and(warp::get())
+        .and(warp::query::<AddressPage>())
+        .and(context.filter())
+        .and_then(handle_get_accounts_by_resource)
+        .with(metrics("get_accounts_by_resource"))
+        .boxed()
+}
+
 async fn handle_get_account(
     address: AddressParam,
+    proof_query: ProofQuery,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account")?;
-    Ok(Account::new(None, address, context)?.account()?)
+    Ok(Account::new(None, address, context)?.account(proof_query.with_proof()?)?)
 }
 
 async fn handle_get_account_resources(
     ledger_version: Option<LedgerVersionParam>,
     address: AddressParam,
+    proof_query: ProofQuery,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_resources")?;
-    Ok(Account::new(ledger_version, address, context)?.resources()?)
+    Ok(Account::new(ledger_version, address, context)?.resources(proof_query.with_proof()?)?)
 }
 
 async fn handle_get_account_modules(
     ledger_version: Option<LedgerVersionParam>,
     address: AddressParam,
+    proof_query: ProofQuery,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_modules")?;
-    Ok(Account::new(ledger_version, address, context)?.modules()?)
+    Ok(Account::new(ledger_version, address, context)?.modules(proof_query.with_proof()?)?)
+}
+
+async fn handle_get_account_resources_at_time(
+    timestamp: TimestampParam,
+    address: AddressParam,
+    proof_query: ProofQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_account_resources_at_time")?;
+    Ok(Account::at_time(timestamp, address, context)?.resources(proof_query.with_proof()?)?)
+}
+
+async fn handle_get_account_modules_at_time(
+    timestamp: TimestampParam,
+    address: AddressParam,
+    proof_query: ProofQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_account_modules_at_time")?;
+    Ok(Account::at_time(timestamp, address, context)?.modules(proof_query.with_proof()?)?)
+}
+
+async fn handle_get_accounts_by_resource(
+    resource_type: MoveStructTagParam,
+    page: AddressPage,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_accounts_by_resource")?;
+    Ok(list_accounts_by_resource(resource_type, page, context)?)
+}
+
+fn list_accounts_by_resource(
+    resource_type: MoveStructTagParam,
+    page: AddressPage,
+    context: Context,
+) -> Result<impl Reply, Error> {
+    let struct_tag: StructTag = resource_type.parse("resource type")?.try_into()?;
+    let latest_ledger_info = context.get_latest_ledger_info()?;
+    let addresses: Vec<Address> = context
+        .get_accounts_by_resource(&struct_tag, page.start()?.map(Into::into), page.limit()?)?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Response::new(latest_ledger_info, &addresses)
 }
 
 pub(crate) struct Account {
     ledger_version: u64,
     address: Address,
     latest_ledger_info: LedgerInfo,
+    latest_ledger_info_with_signatures: LedgerInfoWithSignatures,
     context: Context,
 }
 
@@ -122,7 +215,10 @@ impl Account {
         address: AddressParam,
         context: Context,
     ) -> Result<Self, Error> {
-        let latest_ledger_info = context.get_latest_ledger_info()?;
+        let latest_ledger_info_with_signatures =
+            context.get_latest_ledger_info_with_signatures()?;
+        let latest_ledger_info =
+            LedgerInfo::new(&context.chain_id(), &latest_ledger_info_with_signatures);
         let ledger_version = ledger_version
             .map(|v| v.parse("ledger version"))
             .unwrap_or_else(|| Ok(latest_ledger_info.version()))?;
@@ -139,36 +235,115 @@ impl Account {
             ledger_version,
             address: address.parse("account address")?,
             latest_ledger_info,
+            latest_ledger_info_with_signatures,
             context,
         })
     }
 
-    pub fn account(self) -> Result<impl Reply, Error> {
-        let account_state = self.account_state()?;
-        let account: AccountData = account_state
-            .get_account_resource()?
-            .ok_or_else(|| self.resource_not_found(&AccountResource::struct_tag()))?
-            .into();
+    /// Reads the account state as of `timestamp` (in microseconds), i.e. after all transactions
+    /// in blocks proposed at or before it. This requires a block proposed after `timestamp` to be
+    /// committed, otherwise the state at `timestamp` is not final yet.
+    pub fn at_time(
+        timestamp: TimestampParam,
+        address: AddressParam,
+        context: Context,
+    ) -> Result<Self, Error> {
+        let latest_ledger_info_with_signatures =
+            context.get_latest_ledger_info_with_signatures()?;
+        let latest_ledger_info =
+            LedgerInfo::new(&context.chain_id(), &latest_ledger_info_with_signatures);
+        let timestamp = timestamp.parse("timestamp")?;
+
+        if timestamp >= latest_ledger_info.timestamp() {
+            return Err(Error::not_found(
+                "ledger",
+                format!("timestamp({})", timestamp),
+                latest_ledger_info.version(),
+            ));
+        }
+        let ledger_version =
+            context.get_version_at_timestamp(timestamp, latest_ledger_info.version())?;
+
+        Ok(Self {
+            ledger_version,
+            address: address.parse("account address")?,
+            latest_ledger_info,
+            latest_ledger_info_with_signatures,
+            context,
+        })
+    }
 
+    pub fn account(self, with_proof: bool) -> Result<impl Reply, Error> {
+        if with_proof {
+            return self.respond_with_proof(Self::account_data);
+        }
+        let account = self.account_data(self.account_state()?)?;
         Response::new(self.latest_ledger_info, &account)
     }
 
-    pub fn resources(self) -> Result<impl Reply, Error> {
-        let resources = self
+    pub fn resources(self, with_proof: bool) -> Result<impl Reply, Error> {
+        if with_proof {
+            return self.respond_with_proof(Self::resources_data);
+        }
+        let resources = self.resources_data(self.account_state()?)?;
+        Response::new(self.latest_ledger_info, &resources)
+    }
+
+    pub fn modules(self, with_proof: bool) -> Result<impl Reply, Error> {
+        if with_proof {
+            return self.respond_with_proof(Self::modules_data);
+        }
+        let modules = self.modules_data(self.account_state()?)?;
+        Response::new(self.latest_ledger_info, &modules)
+    }
+
+    fn account_data(&self, account_state: AccountState) -> Result<AccountData, Error> {
+        Ok(account_state
+            .get_account_resource()?
+            .ok_or_else(|| self.resource_not_found(&AccountResource::struct_tag()))?
+            .into())
+    }
+
+    fn resources_data(&self, account_state: AccountState) -> Result<Vec<MoveResource>, Error> {
+        Ok(self
             .context
             .move_converter()
-            .try_into_resources(self.account_state()?.get_resources())?;
-        Response::new(self.latest_ledger_info, &resources)
+            .try_into_resources(account_state.get_resources())?)
     }
 
-    pub fn modules(self) -> Result<impl Reply, Error> {
-        let modules = self
-            .account_state()?
+    fn modules_data(&self, account_state: AccountState) -> Result<Vec<MoveModuleBytecode>, Error> {
+        Ok(account_state
             .into_modules()
             .map(MoveModuleBytecode::new)
             .map(|m| m.try_parse_abi())
-            .collect::<Result<Vec<MoveModuleBytecode>>>()?;
-        Response::new(self.latest_ledger_info, &modules)
+            .collect::<Result<Vec<MoveModuleBytecode>>>()?)
+    }
+
+    /// Responds with the data read by `read` from the account state, along with the proof of the
+    /// account state relative to the latest ledger info. Unlike the responses without proof, a
+    /// missing account is not an error: the data is `null` and the proof proves the absence.
+    fn respond_with_proof<T, F>(self, read: F) -> Result<Response, Error>
+    where
+        T: Serialize,
+        F: FnOnce(&Self, AccountState) -> Result<T, Error>,
+    {
+        let account_state_with_proof = self.context.get_account_state_with_proof(
+            self.address.into(),
+            self.ledger_version,
+            self.latest_ledger_info.version(),
+        )?;
+        let data = match &account_state_with_proof.blob {
+            Some(blob) => Some(read(&self, AccountState::try_from(blob)?)?),
+            None => None,
+        };
+        let proof = AccountStateProof::new(
+            &self.latest_ledger_info_with_signatures,
+            &account_state_with_proof,
+        )?;
+        Response::new(
+            self.latest_ledger_info,
+            &WithAccountStateProof { data, proof },
+        )
     }
 
     pub fn find_event_key(
@@ -1,21 +1,33 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use diem_api_types::{Error, LedgerInfo, MoveConverter, TransactionOnChainData};
+use diem_api_types::{
+    Error, LedgerInfo, MoveConverter, TransactionOnChainData, ViewFunctionOutput,
+    ViewFunctionRequest,
+};
 use diem_config::config::{ApiConfig, JsonRpcConfig, RoleType};
 use diem_crypto::HashValue;
-use diem_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use diem_mempool::{
+    GasPriceEstimate, MempoolClientRequest, MempoolClientSender, MempoolStats,
+    MempoolTransactionStatusEvent, PendingTransactionState, SubmissionStatus,
+};
+use diem_state_view::StateViewId;
 use diem_types::{
     account_address::AccountAddress,
     account_state::AccountState,
-    account_state_blob::AccountStateBlob,
+    account_state_blob::{AccountStateBlob, AccountStateWithProof},
     chain_id::ChainId,
     contract_event::ContractEvent,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
-    transaction::{SignedTransaction, TransactionWithProof},
+    transaction::{
+        RawTransaction, SignedTransaction, TransactionOutput, TransactionWithProof, Version,
+    },
 };
-use storage_interface::{MoveDbReader, Order};
+use diem_vm::{data_cache::RemoteStorage, DiemVM};
+use move_core_types::language_storage::StructTag;
+use scratchpad::SparseMerkleTree;
+use storage_interface::{state_view::VerifiedStateView, MoveDbReader, Order};
 
 use anyhow::{ensure, format_err, Result};
 use futures::{channel::oneshot, SinkExt};
@@ -24,6 +36,7 @@ use std::{
     convert::{Infallible, TryFrom},
     sync::Arc,
 };
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
 use warp::{filters::BoxedFilter, Filter, Reply};
 
 // Context holds application scope context
@@ -35,6 +48,8 @@ pub struct Context {
     role: RoleType,
     jsonrpc_config: JsonRpcConfig,
     api_config: ApiConfig,
+    committed_version: watch::Receiver<Version>,
+    stream_subscriptions: Arc<Semaphore>,
 }
 
 impl Context {
@@ -45,7 +60,9 @@ impl Context {
         role: RoleType,
         jsonrpc_config: JsonRpcConfig,
         api_config: ApiConfig,
+        committed_version: watch::Receiver<Version>,
     ) -> Self {
+        let stream_subscriptions = Arc::new(Semaphore::new(api_config.max_stream_subscriptions));
         Self {
             chain_id,
             db,
@@ -53,6 +70,8 @@ impl Context {
             role,
             jsonrpc_config,
             api_config,
+            committed_version,
+            stream_subscriptions,
         }
     }
 
@@ -68,6 +87,52 @@ impl Context {
         self.api_config.content_length_limit()
     }
 
+    pub fn max_transaction_batch_size(&self) -> usize {
+        self.api_config.max_transaction_batch_size
+    }
+
+    pub fn view_function_timeout_ms(&self) -> u64 {
+        self.api_config.view_function_timeout_ms
+    }
+
+    pub fn simulate_transaction_timeout_ms(&self) -> u64 {
+        self.api_config.simulate_transaction_timeout_ms
+    }
+
+    pub fn graphql_enabled(&self) -> bool {
+        self.api_config.graphql_enabled
+    }
+
+    pub fn graphql_max_depth(&self) -> usize {
+        self.api_config.graphql_max_depth
+    }
+
+    pub fn graphql_max_complexity(&self) -> usize {
+        self.api_config.graphql_max_complexity
+    }
+
+    /// Returns a receiver of the latest committed version, which is notified as new transactions
+    /// are committed.
+    pub fn committed_version(&self) -> watch::Receiver<Version> {
+        self.committed_version.clone()
+    }
+
+    /// Takes one of the `max_stream_subscriptions` slots, which is released when the returned
+    /// permit is dropped. Returns `None` if all slots are taken.
+    pub fn try_acquire_stream_subscription(&self) -> Option<OwnedSemaphorePermit> {
+        Arc::clone(&self.stream_subscriptions)
+            .try_acquire_owned()
+            .ok()
+    }
+
+    pub fn max_stream_subscriptions(&self) -> usize {
+        self.api_config.max_stream_subscriptions
+    }
+
+    pub fn admin_token(&self) -> Option<&str> {
+        self.api_config.admin_token.as_deref()
+    }
+
     pub fn filter(self) -> impl Filter<Extract = (Context,), Error = Infallible> + Clone {
         warp::any().map(move || self.clone())
     }
@@ -82,6 +147,21 @@ impl Context {
         callback.await?
     }
 
+    pub async fn submit_transactions(
+        &self,
+        txns: Vec<SignedTransaction>,
+    ) -> Result<Vec<Result<SubmissionStatus>>> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::SubmitTransactionBatch(
+                txns, req_sender,
+            ))
+            .await?;
+
+        callback.await?
+    }
+
     pub fn get_latest_ledger_info(&self) -> Result<LedgerInfo, Error> {
         Ok(LedgerInfo::new(
             &self.chain_id(),
@@ -116,6 +196,86 @@ impl Context {
         Ok(account_state_blob)
     }
 
+    /// Returns a view of the account states at `version`, which verifies the account states it
+    /// reads against the state root hash at `version` and caches them.
+    fn state_view(&self, version: Version) -> Result<VerifiedStateView> {
+        let state_root = self
+            .db
+            .get_transaction_by_version(version, version, false)?
+            .proof
+            .transaction_info()
+            .state_change_hash();
+        Ok(VerifiedStateView::new(
+            StateViewId::Miscellaneous,
+            self.db.clone().as_db_reader(),
+            Some(version),
+            state_root,
+            SparseMerkleTree::new(state_root),
+        ))
+    }
+
+    /// Executes `raw_txn` against the state at `version` without committing it.
+    pub fn simulate_transaction(
+        &self,
+        raw_txn: RawTransaction,
+        version: u64,
+    ) -> Result<TransactionOutput> {
+        let state_view = self.state_view(version)?;
+        let (_, output) = DiemVM::simulate_transaction(raw_txn, &state_view)?;
+        Ok(output)
+    }
+
+    /// Executes the public function that `req` calls against the state at `version`, and returns
+    /// the values it returns. Changes the function makes to the state are discarded.
+    pub fn execute_view_function(
+        &self,
+        req: ViewFunctionRequest,
+        version: u64,
+    ) -> Result<ViewFunctionOutput, Error> {
+        let state_view = self.state_view(version)?;
+        let storage = RemoteStorage::new(&state_view);
+        let converter = MoveConverter::new(&storage);
+        let (call, return_types) = converter
+            .try_into_view_function_call(req)
+            .map_err(|e| Error::invalid_request_body(format!("invalid view function: {}", e)))?;
+        let (module, function, ty_args, args) = call.into_inner();
+        let (return_values, gas_used) = DiemVM::execute_view_function(
+            &state_view,
+            &module,
+            &function,
+            ty_args,
+            args,
+            self.api_config.view_function_gas_budget,
+        )
+        .map_err(|status| Error::bad_request(converter.explain_view_function_error(status)))?;
+        Ok(converter.try_into_view_function_output(&return_types, return_values, gas_used)?)
+    }
+
+    pub fn get_account_state_with_proof(
+        &self,
+        address: AccountAddress,
+        version: u64,
+        ledger_version: u64,
+    ) -> Result<AccountStateWithProof> {
+        self.db
+            .get_account_state_with_proof(address, version, ledger_version)
+    }
+
+    pub fn get_accounts_by_resource(
+        &self,
+        struct_tag: &StructTag,
+        start: Option<AccountAddress>,
+        limit: u16,
+    ) -> Result<Vec<AccountAddress>> {
+        self.db
+            .get_accounts_by_resource(struct_tag, start, limit as u64)
+    }
+
+    pub fn get_version_at_timestamp(&self, timestamp: u64, ledger_version: u64) -> Result<u64> {
+        self.db
+            .get_last_version_before_timestamp(timestamp.saturating_add(1), ledger_version)
+    }
+
     pub fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         self.db.get_block_timestamp(version)
     }
@@ -210,6 +370,88 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    pub async fn get_gas_price_estimate(&self, currency: String) -> Result<GasPriceEstimate> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetGasPriceEstimate(
+                currency, req_sender,
+            ))
+            .await?;
+
+        callback.await?
+    }
+
+    pub async fn get_mempool_account_transactions(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Vec<(SignedTransaction, PendingTransactionState)>> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetAccountTransactions(
+                address, req_sender,
+            ))
+            .await?;
+
+        Ok(callback.await?)
+    }
+
+    pub async fn get_mempool_stats(&self) -> Result<MempoolStats> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetStats(req_sender))
+            .await?;
+
+        Ok(callback.await?)
+    }
+
+    pub async fn get_mempool_transaction_status_history(
+        &self,
+        hash: HashValue,
+    ) -> Result<Vec<MempoolTransactionStatusEvent>> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetTransactionStatusHistory(
+                hash, req_sender,
+            ))
+            .await?;
+
+        Ok(callback.await?)
+    }
+
+    pub async fn evict_mempool_account_transactions(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Vec<SignedTransaction>> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::EvictAccountTransactions(
+                address, req_sender,
+            ))
+            .await?;
+
+        Ok(callback.await?)
+    }
+
+    pub async fn evict_mempool_transaction_by_hash(
+        &self,
+        hash: HashValue,
+    ) -> Result<Option<SignedTransaction>> {
+        let (req_sender, callback) = oneshot::channel();
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::EvictTransactionByHash(
+                hash, req_sender,
+            ))
+            .await?;
+
+        Ok(callback.await?)
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
@@ -251,6 +493,21 @@ impl Context {
             .collect::<Vec<_>>())
     }
 
+    pub fn get_latest_event_sequence_number(
+        &self,
+        event_key: &EventKey,
+        ledger_version: u64,
+    ) -> Result<Option<u64>> {
+        let events = self.db.get_events_with_proofs(
+            event_key,
+            u64::MAX,
+            Order::Descending,
+            1,
+            Some(ledger_version),
+        )?;
+        Ok(events.first().map(|e| e.event.sequence_number()))
+    }
+
     pub fn health_check_route(&self) -> BoxedFilter<(impl Reply,)> {
         diem_json_rpc::runtime::health_check_route(self.db.clone())
     }
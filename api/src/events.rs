@@ -6,7 +6,7 @@ use crate::{
     context::Context,
     failpoint::fail_point,
     metrics::metrics,
-    page::Page,
+    page::CursorPage,
     param::{AddressParam, EventKeyParam, MoveIdentifierParam, MoveStructTagParam},
 };
 
@@ -14,13 +14,17 @@ use diem_api_types::{Error, LedgerInfo, Response};
 
 use anyhow::Result;
 use diem_types::event::EventKey;
-use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+use warp::{
+    filters::{path::FullPath, BoxedFilter},
+    Filter, Rejection, Reply,
+};
 
 // GET /events/<event_key>
 pub fn get_events_by_event_key(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("events" / EventKeyParam)
         .and(warp::get())
-        .and(warp::query::<Page>())
+        .and(warp::query::<CursorPage>())
+        .and(warp::path::full())
         .and(context.filter())
         .and_then(handle_get_events_by_event_key)
         .with(metrics("get_events_by_event_key"))
@@ -31,7 +35,8 @@ pub fn get_events_by_event_key(context: Context) -> BoxedFilter<(impl Reply,)> {
 pub fn get_events_by_event_handle(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "events" / MoveStructTagParam / MoveIdentifierParam)
         .and(warp::get())
-        .and(warp::query::<Page>())
+        .and(warp::query::<CursorPage>())
+        .and(warp::path::full())
         .and(context.filter())
         .and_then(handle_get_events_by_event_handle)
         .with(metrics("get_events_by_event_handle"))
@@ -40,24 +45,26 @@ pub fn get_events_by_event_handle(context: Context) -> BoxedFilter<(impl Reply,)
 
 async fn handle_get_events_by_event_key(
     event_key: EventKeyParam,
-    page: Page,
+    page: CursorPage,
+    path: FullPath,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_events_by_event_key")?;
-    Ok(Events::new(event_key.parse("event key")?.into(), context)?.list(page)?)
+    Ok(Events::new(event_key.parse("event key")?.into(), context)?.list(page, path)?)
 }
 
 async fn handle_get_events_by_event_handle(
     address: AddressParam,
     struct_tag: MoveStructTagParam,
     field_name: MoveIdentifierParam,
-    page: Page,
+    page: CursorPage,
+    path: FullPath,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_events_by_event_handle")?;
     let key =
         Account::new(None, address, context.clone())?.find_event_key(struct_tag, field_name)?;
-    Ok(Events::new(key, context)?.list(page)?)
+    Ok(Events::new(key, context)?.list(page, path)?)
 }
 
 struct Events {
@@ -76,16 +83,23 @@ impl Events {
         })
     }
 
-    pub fn list(self, page: Page) -> Result<impl Reply, Error> {
-        let contract_events = self.context.get_events(
-            &self.key,
-            page.start(0, u64::MAX)?,
-            page.limit()?,
-            self.ledger_info.version(),
+    pub fn list(self, page: CursorPage, path: FullPath) -> Result<impl Reply, Error> {
+        let ledger_version = self.ledger_info.version();
+        let (contract_events, links) = page.read(
+            || {
+                Ok(self
+                    .context
+                    .get_latest_event_sequence_number(&self.key, ledger_version)?)
+            },
+            |start, limit| {
+                Ok(self
+                    .context
+                    .get_events(&self.key, start, limit, ledger_version)?)
+            },
         )?;
 
         let converter = self.context.move_converter();
         let events = converter.try_into_events(&contract_events)?;
-        Response::new(self.ledger_info, &events)
+        links.reply(path, Response::new(self.ledger_info, &events)?)
     }
 }
@@ -0,0 +1,50 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context, failpoint::fail_point, metrics::metrics, param::MoveIdentifierParam,
+};
+
+use diem_api_types::{Error, GasPriceEstimate, Response};
+
+use serde::Deserialize;
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+// GET /gas_price_estimate?currency={currency code}
+pub fn get_gas_price_estimate(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("gas_price_estimate")
+        .and(warp::get())
+        .and(warp::query::<GasPriceEstimateQuery>())
+        .and(context.filter())
+        .and_then(handle_get_gas_price_estimate)
+        .with(metrics("get_gas_price_estimate"))
+        .boxed()
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct GasPriceEstimateQuery {
+    currency: Option<MoveIdentifierParam>,
+}
+
+async fn handle_get_gas_price_estimate(
+    query: GasPriceEstimateQuery,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_gas_price_estimate")?;
+    let currency = query
+        .currency
+        .ok_or_else(|| Error::bad_request("missing parameter currency"))?
+        .parse("currency")?;
+
+    let ledger_info = context.get_latest_ledger_info()?;
+    let estimate = context
+        .get_gas_price_estimate(currency.into_string())
+        .await
+        .map_err(Error::from)?;
+    let estimate = GasPriceEstimate {
+        low: estimate.low.into(),
+        median: estimate.median.into(),
+        high: estimate.high.into(),
+    };
+    Ok(Response::new(ledger_info, &estimate)?)
+}
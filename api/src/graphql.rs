@@ -0,0 +1,370 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A GraphQL schema serving the same ledger data as the REST routes, so that a client can read
+//! e.g. an account along with its resources and transactions in one request.
+
+use crate::{
+    context::Context, failpoint::fail_point, metrics::metrics,
+    transactions::render_onchain_transactions,
+};
+
+use diem_api_types::{
+    AccountData, Address, Error, MoveModuleBytecode, MoveResource, TransactionId,
+};
+use diem_types::account_state::AccountState;
+
+use async_graphql::{
+    Context as GraphQLContext, EmptyMutation, EmptySubscription, Json, Object, Result, Schema,
+    SimpleObject,
+};
+use std::str::FromStr;
+use warp::{filters::BoxedFilter, reply, Filter, Rejection, Reply};
+
+pub type ApiSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+const DEFAULT_LIMIT: u16 = 25;
+const MAX_LIMIT: u16 = 1000;
+
+// POST /graphql
+pub fn graphql(context: Context) -> BoxedFilter<(impl Reply,)> {
+    let enabled = context.graphql_enabled();
+    let schema = schema(context.clone());
+    warp::path!("graphql")
+        .and(warp::post())
+        .and_then(move || async move {
+            if enabled {
+                Ok(())
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+        .untuple_one()
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<async_graphql::Request>())
+        .and(warp::any().map(move || schema.clone()))
+        .and_then(handle_graphql)
+        .with(metrics("graphql"))
+        .boxed()
+}
+
+/// Builds the schema, limiting the depth and complexity of queries so that a query can't make
+/// the node read an unbounded amount of data.
+pub fn schema(context: Context) -> ApiSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(context.graphql_max_depth())
+        .limit_complexity(context.graphql_max_complexity())
+        .data(context)
+        .finish()
+}
+
+async fn handle_graphql(
+    req: async_graphql::Request,
+    schema: ApiSchema,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_graphql")?;
+    Ok(reply::json(&schema.execute(req).await))
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// The latest ledger information.
+    async fn ledger_info(&self, ctx: &GraphQLContext<'_>) -> Result<LedgerInfo> {
+        let ledger_info = ctx.data_unchecked::<Context>().get_latest_ledger_info()?;
+        Ok(LedgerInfo {
+            chain_id: ledger_info.chain_id,
+            ledger_version: ledger_info.ledger_version.to_string(),
+            ledger_timestamp: ledger_info.ledger_timestamp.to_string(),
+        })
+    }
+
+    /// The account at `address` as of `ledger_version`, by default the latest version. Null if
+    /// the account doesn't exist.
+    async fn account(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        address: String,
+        ledger_version: Option<String>,
+    ) -> Result<Option<Account>> {
+        let context = ctx.data_unchecked::<Context>();
+        let address: Address = parse_arg("address", address)?;
+        let latest_version = context.get_latest_ledger_info()?.version();
+        let ledger_version = match ledger_version {
+            Some(version) => parse_arg("ledger_version", version)?,
+            None => latest_version,
+        };
+        if ledger_version > latest_version {
+            return Err(Error::not_found(
+                "ledger",
+                TransactionId::Version(ledger_version),
+                latest_version,
+            )
+            .into());
+        }
+        Ok(context
+            .get_account_state(address.into(), ledger_version)?
+            .map(|state| Account {
+                address,
+                ledger_version,
+                state,
+            }))
+    }
+
+    /// The on-chain transaction at `version`. Null if it's not committed yet.
+    async fn transaction(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        version: String,
+    ) -> Result<Option<Transaction>> {
+        let context = ctx.data_unchecked::<Context>();
+        let version = parse_arg("version", version)?;
+        let ledger_version = context.get_latest_ledger_info()?.version();
+        if version > ledger_version {
+            return Ok(None);
+        }
+        let data = context.get_transaction_by_version(version, ledger_version)?;
+        Ok(render_onchain_transactions(context, vec![data])?
+            .pop()
+            .map(Transaction))
+    }
+
+    /// On-chain transactions from version `start`, by default the last page of transactions.
+    #[graphql(complexity = "limit.unwrap_or(DEFAULT_LIMIT) as usize * child_complexity")]
+    async fn transactions(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        start: Option<String>,
+        limit: Option<u16>,
+    ) -> Result<Vec<Transaction>> {
+        let context = ctx.data_unchecked::<Context>();
+        let limit = parse_limit(limit)?;
+        let ledger_version = context.get_latest_ledger_info()?.version();
+        let start = match start {
+            Some(start) => parse_arg("start", start)?,
+            None => ledger_version.saturating_sub(limit as u64),
+        };
+        if start > ledger_version {
+            return Ok(vec![]);
+        }
+        let data = context.get_transactions(start, limit, ledger_version)?;
+        Ok(render_onchain_transactions(context, data)?
+            .into_iter()
+            .map(Transaction)
+            .collect())
+    }
+
+    /// Events with the event key `key`, from sequence number `start`, by default 0.
+    #[graphql(complexity = "limit.unwrap_or(DEFAULT_LIMIT) as usize * child_complexity")]
+    async fn events(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        key: String,
+        start: Option<String>,
+        limit: Option<u16>,
+    ) -> Result<Vec<Event>> {
+        let context = ctx.data_unchecked::<Context>();
+        let key: diem_api_types::EventKey = parse_arg("key", key)?;
+        let start = start.map(|s| parse_arg("start", s)).transpose()?;
+        let limit = parse_limit(limit)?;
+        let ledger_version = context.get_latest_ledger_info()?.version();
+        let events = context.get_events(&key.into(), start.unwrap_or(0), limit, ledger_version)?;
+        Ok(context
+            .move_converter()
+            .try_into_events(&events)?
+            .into_iter()
+            .map(Event)
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct LedgerInfo {
+    chain_id: u8,
+    ledger_version: String,
+    ledger_timestamp: String,
+}
+
+pub struct Account {
+    address: Address,
+    ledger_version: u64,
+    state: AccountState,
+}
+
+#[Object]
+impl Account {
+    async fn address(&self) -> String {
+        self.address.to_string()
+    }
+
+    async fn sequence_number(&self) -> Result<Option<String>> {
+        Ok(self
+            .account_data()?
+            .map(|account| account.sequence_number.to_string()))
+    }
+
+    async fn authentication_key(&self) -> Result<Option<String>> {
+        Ok(self
+            .account_data()?
+            .map(|account| account.authentication_key.to_string()))
+    }
+
+    async fn resources(&self, ctx: &GraphQLContext<'_>) -> Result<Vec<Resource>> {
+        Ok(ctx
+            .data_unchecked::<Context>()
+            .move_converter()
+            .try_into_resources(self.state.get_resources())?
+            .into_iter()
+            .map(Resource)
+            .collect())
+    }
+
+    async fn modules(&self) -> Result<Vec<Module>> {
+        Ok(self
+            .state
+            .get_modules()
+            .map(|bytes| MoveModuleBytecode::new(bytes.clone()).try_parse_abi())
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .into_iter()
+            .map(Module)
+            .collect())
+    }
+
+    /// Transactions sent by the account, from sequence number `start`, by default 0.
+    #[graphql(complexity = "limit.unwrap_or(DEFAULT_LIMIT) as usize * child_complexity")]
+    async fn transactions(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        start: Option<String>,
+        limit: Option<u16>,
+    ) -> Result<Vec<Transaction>> {
+        let context = ctx.data_unchecked::<Context>();
+        let start = start.map(|s| parse_arg("start", s)).transpose()?;
+        let limit = parse_limit(limit)?;
+        let data = context.get_account_transactions(
+            self.address.into(),
+            start.unwrap_or(0),
+            limit,
+            self.ledger_version,
+        )?;
+        Ok(render_onchain_transactions(context, data)?
+            .into_iter()
+            .map(Transaction)
+            .collect())
+    }
+}
+
+impl Account {
+    fn account_data(&self) -> anyhow::Result<Option<AccountData>> {
+        Ok(self.state.get_account_resource()?.map(AccountData::from))
+    }
+}
+
+pub struct Resource(MoveResource);
+
+#[Object]
+impl Resource {
+    #[graphql(name = "type")]
+    async fn typ(&self) -> String {
+        self.0.typ.to_string()
+    }
+
+    /// The resource in the JSON format of the REST API.
+    async fn data(&self) -> Result<Json<serde_json::Value>> {
+        Ok(Json(serde_json::to_value(&self.0.data)?))
+    }
+}
+
+pub struct Module(MoveModuleBytecode);
+
+#[Object]
+impl Module {
+    async fn bytecode(&self) -> String {
+        self.0.bytecode.to_string()
+    }
+
+    /// The module ABI in the JSON format of the REST API.
+    async fn abi(&self) -> Result<Json<serde_json::Value>> {
+        Ok(Json(serde_json::to_value(&self.0.abi)?))
+    }
+}
+
+pub struct Transaction(diem_api_types::Transaction);
+
+#[Object]
+impl Transaction {
+    async fn version(&self) -> Result<String> {
+        Ok(self.0.transaction_info()?.version.to_string())
+    }
+
+    async fn hash(&self) -> Result<String> {
+        Ok(self.0.transaction_info()?.hash.to_string())
+    }
+
+    async fn timestamp(&self) -> String {
+        self.0.timestamp().to_string()
+    }
+
+    async fn success(&self) -> bool {
+        self.0.success()
+    }
+
+    async fn vm_status(&self) -> String {
+        self.0.vm_status()
+    }
+
+    async fn events(&self) -> Vec<Event> {
+        let events = match &self.0 {
+            diem_api_types::Transaction::UserTransaction(txn) => txn.events.clone(),
+            diem_api_types::Transaction::GenesisTransaction(txn) => txn.events.clone(),
+            _ => vec![],
+        };
+        events.into_iter().map(Event).collect()
+    }
+
+    /// The transaction in the JSON format of the REST API.
+    async fn data(&self) -> Result<Json<serde_json::Value>> {
+        Ok(Json(serde_json::to_value(&self.0)?))
+    }
+}
+
+pub struct Event(diem_api_types::Event);
+
+#[Object]
+impl Event {
+    async fn key(&self) -> String {
+        self.0.key.to_string()
+    }
+
+    async fn sequence_number(&self) -> String {
+        self.0.sequence_number.to_string()
+    }
+
+    #[graphql(name = "type")]
+    async fn typ(&self) -> String {
+        self.0.typ.to_string()
+    }
+
+    /// The event data in the JSON format of the REST API.
+    async fn data(&self) -> Json<serde_json::Value> {
+        Json(self.0.data.clone())
+    }
+}
+
+/// Parses an argument the way the REST routes parse path and query parameters. Numbers that
+/// may not fit in a GraphQL `Int` are strings, as in the JSON format of the REST API.
+fn parse_arg<T: FromStr>(name: &str, value: String) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::invalid_param(name, &value))
+}
+
+fn parse_limit(limit: Option<u16>) -> Result<u16, Error> {
+    match limit.unwrap_or(DEFAULT_LIMIT) {
+        limit @ 1..=MAX_LIMIT => Ok(limit),
+        limit => Err(Error::invalid_param("limit", limit)),
+    }
+}
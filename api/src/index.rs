@@ -6,9 +6,9 @@ use crate::{
     context::Context,
     events,
     failpoint::fail_point,
-    log,
+    gas_price_estimate, graphql, log, mempool,
     metrics::{metrics, status_metrics},
-    transactions,
+    streams, transactions, view_function,
 };
 use diem_api_types::{Error, Response};
 
@@ -37,14 +37,32 @@ pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Inf
         .or(accounts::get_account_modules_by_ledger_version(
             context.clone(),
         ))
+        .or(accounts::get_account_resources_at_time(context.clone()))
+        .or(accounts::get_account_modules_at_time(context.clone()))
+        .or(accounts::get_accounts_by_resource(context.clone()))
         .or(transactions::get_transaction(context.clone()))
         .or(transactions::get_transactions(context.clone()))
         .or(transactions::get_account_transactions(context.clone()))
         .or(transactions::submit_bcs_transactions(context.clone()))
         .or(transactions::submit_json_transactions(context.clone()))
+        .or(transactions::submit_bcs_transaction_batch(context.clone()))
+        .or(transactions::submit_json_transaction_batch(context.clone()))
         .or(transactions::create_signing_message(context.clone()))
+        .or(transactions::simulate_transaction(context.clone()))
         .or(events::get_events_by_event_key(context.clone()))
         .or(events::get_events_by_event_handle(context.clone()))
+        .or(streams::stream(context.clone()))
+        .or(gas_price_estimate::get_gas_price_estimate(context.clone()))
+        .or(view_function::execute_view_function(context.clone()))
+        .or(view_function::execute_view_function_by_ledger_version(
+            context.clone(),
+        ))
+        .or(graphql::graphql(context.clone()))
+        .or(mempool::get_account_transactions(context.clone()))
+        .or(mempool::get_transaction_status_history(context.clone()))
+        .or(mempool::get_stats(context.clone()))
+        .or(mempool::evict_account_transactions(context.clone()))
+        .or(mempool::evict_transaction_by_hash(context.clone()))
         .or(context.health_check_route().with(metrics("health_check")))
         // jsonrpc routes must before `recover` and after `index`
         // so that POST '/' can be handled by jsonrpc routes instead of `index` route
@@ -4,13 +4,18 @@
 mod accounts;
 mod context;
 mod events;
+mod gas_price_estimate;
+mod graphql;
 mod index;
 pub(crate) mod log;
+mod mempool;
 mod metrics;
 mod page;
 pub(crate) mod param;
 pub mod runtime;
+mod streams;
 mod transactions;
+mod view_function;
 
 mod failpoint;
 #[cfg(any(test))]
@@ -0,0 +1,237 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Routes inspecting the transactions pending in mempool, and admin routes evicting them.
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    param::{AddressParam, TransactionHashParam},
+};
+
+use diem_api_types::{
+    Error, MempoolStats, MempoolTransaction, MempoolTransactionState, MempoolTransactionStatus,
+    MempoolTransactionStatusEvent, Response, Transaction,
+};
+use diem_mempool::PendingTransactionState;
+use diem_types::transaction::SignedTransaction;
+
+use anyhow::Result;
+use subtle::ConstantTimeEq;
+use warp::{
+    filters::BoxedFilter,
+    http::{header::AUTHORIZATION, StatusCode},
+    Filter, Rejection, Reply,
+};
+
+// GET /mempool/accounts/{address}/transactions
+pub fn get_account_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("mempool" / "accounts" / AddressParam / "transactions")
+        .and(warp::get())
+        .and(context.filter())
+        .and_then(handle_get_account_transactions)
+        .with(metrics("get_mempool_account_transactions"))
+        .boxed()
+}
+
+// GET /mempool/transactions/{txn-hash}/status
+pub fn get_transaction_status_history(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("mempool" / "transactions" / TransactionHashParam / "status")
+        .and(warp::get())
+        .and(context.filter())
+        .and_then(handle_get_transaction_status_history)
+        .with(metrics("get_mempool_transaction_status_history"))
+        .boxed()
+}
+
+// GET /mempool/stats
+pub fn get_stats(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("mempool" / "stats")
+        .and(warp::get())
+        .and(context.filter())
+        .and_then(handle_get_stats)
+        .with(metrics("get_mempool_stats"))
+        .boxed()
+}
+
+// DELETE /mempool/accounts/{address}/transactions
+pub fn evict_account_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("mempool" / "accounts" / AddressParam / "transactions")
+        .and(warp::delete())
+        .and(admin(context.clone()))
+        .and(context.filter())
+        .and_then(handle_evict_account_transactions)
+        .with(metrics("evict_mempool_account_transactions"))
+        .boxed()
+}
+
+// DELETE /mempool/transactions/{txn-hash}
+pub fn evict_transaction_by_hash(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("mempool" / "transactions" / TransactionHashParam)
+        .and(warp::delete())
+        .and(admin(context.clone()))
+        .and(context.filter())
+        .and_then(handle_evict_transaction_by_hash)
+        .with(metrics("evict_mempool_transaction_by_hash"))
+        .boxed()
+}
+
+/// Lets through requests with the `Authorization: Bearer {token}` header, where the token is the
+/// configured admin token. The token is compared in constant time. Admin routes are not found
+/// when there is no admin token.
+fn admin(context: Context) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>(AUTHORIZATION.as_str())
+        .and_then(move |authorization: Option<String>| {
+            let admin_token = context
+                .admin_token()
+                .map(|token| format!("Bearer {}", token));
+            async move {
+                match admin_token {
+                    None => Err(warp::reject::not_found()),
+                    Some(expected)
+                        if authorization.map_or(false, |authorization| {
+                            authorization.as_bytes().ct_eq(expected.as_bytes()).into()
+                        }) =>
+                    {
+                        Ok(())
+                    }
+                    Some(_) => Err(warp::reject::custom(Error::new(
+                        StatusCode::FORBIDDEN,
+                        "invalid admin token".to_owned(),
+                    ))),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+async fn handle_get_account_transactions(
+    address: AddressParam,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_mempool_account_transactions")?;
+    let address = address.parse("account address")?;
+    let ledger_info = context.get_latest_ledger_info()?;
+    let txns = context
+        .get_mempool_account_transactions(address.into())
+        .await
+        .map_err(Error::from)?;
+    let converter = context.move_converter();
+    let txns = txns
+        .into_iter()
+        .map(|(txn, state)| {
+            Ok(MempoolTransaction {
+                state: match state {
+                    PendingTransactionState::Ready => MempoolTransactionState::Ready,
+                    PendingTransactionState::Parked => MempoolTransactionState::Parked,
+                },
+                transaction: converter.try_into_pending_transaction(txn)?,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Response::new(ledger_info, &txns)?)
+}
+
+async fn handle_get_transaction_status_history(
+    hash: TransactionHashParam,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_mempool_transaction_status_history")?;
+    let hash = hash.parse("transaction hash")?;
+    let ledger_info = context.get_latest_ledger_info()?;
+    let history = context
+        .get_mempool_transaction_status_history(hash.into())
+        .await
+        .map_err(Error::from)?;
+    if history.is_empty() {
+        return Err(
+            Error::not_found("mempool transaction status", hash, ledger_info.version()).into(),
+        );
+    }
+    let history: Vec<_> = history.into_iter().map(status_event).collect();
+    Ok(Response::new(ledger_info, &history)?)
+}
+
+async fn handle_get_stats(context: Context) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_mempool_stats")?;
+    let ledger_info = context.get_latest_ledger_info()?;
+    let stats = context.get_mempool_stats().await.map_err(Error::from)?;
+    let stats = MempoolStats {
+        ready: (stats.ready as u64).into(),
+        parked: (stats.parked as u64).into(),
+    };
+    Ok(Response::new(ledger_info, &stats)?)
+}
+
+async fn handle_evict_account_transactions(
+    address: AddressParam,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_evict_mempool_account_transactions")?;
+    let address = address.parse("account address")?;
+    let ledger_info = context.get_latest_ledger_info()?;
+    let txns = context
+        .evict_mempool_account_transactions(address.into())
+        .await
+        .map_err(Error::from)?;
+    Ok(Response::new(
+        ledger_info,
+        &render_pending_transactions(&context, txns)?,
+    )?)
+}
+
+async fn handle_evict_transaction_by_hash(
+    hash: TransactionHashParam,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_evict_mempool_transaction_by_hash")?;
+    let hash = hash.parse("transaction hash")?;
+    let ledger_info = context.get_latest_ledger_info()?;
+    let txn = context
+        .evict_mempool_transaction_by_hash(hash.into())
+        .await
+        .map_err(Error::from)?
+        .ok_or_else(|| Error::not_found("mempool transaction", hash, ledger_info.version()))?;
+    let txn = context
+        .move_converter()
+        .try_into_pending_transaction(txn)
+        .map_err(Error::from)?;
+    Ok(Response::new(ledger_info, &txn)?)
+}
+
+fn render_pending_transactions(
+    context: &Context,
+    txns: Vec<SignedTransaction>,
+) -> Result<Vec<Transaction>, Error> {
+    let converter = context.move_converter();
+    Ok(txns
+        .into_iter()
+        .map(|txn| converter.try_into_pending_transaction(txn))
+        .collect::<Result<Vec<_>>>()?)
+}
+
+/// Converts a status change recorded by mempool into its API representation.
+pub(crate) fn status_event(
+    event: diem_mempool::MempoolTransactionStatusEvent,
+) -> MempoolTransactionStatusEvent {
+    use diem_mempool::MempoolTransactionStatus as Status;
+
+    let status = match event.status {
+        Status::Accepted => MempoolTransactionStatus::Accepted,
+        Status::Parked => MempoolTransactionStatus::Parked,
+        Status::Ready => MempoolTransactionStatus::Ready,
+        Status::Broadcast { peers } => MempoolTransactionStatus::Broadcast {
+            peers: peers.into(),
+        },
+        Status::PulledIntoBlock => MempoolTransactionStatus::PulledIntoBlock,
+        Status::Committed => MempoolTransactionStatus::Committed,
+        Status::Rejected { reason } => MempoolTransactionStatus::Rejected { reason },
+        Status::Expired => MempoolTransactionStatus::Expired,
+        Status::Evicted { reason } => MempoolTransactionStatus::Evicted { reason },
+    };
+    MempoolTransactionStatusEvent {
+        status,
+        timestamp: event.timestamp_usecs.into(),
+    }
+}
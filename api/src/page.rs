@@ -1,13 +1,19 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::param::{Param, TransactionVersionParam};
+use crate::param::{AddressParam, Param, TransactionVersionParam};
 
-use diem_api_types::{Error, TransactionId};
+use diem_api_types::{Address, Error, Response, TransactionId};
+use storage_interface::Order;
 
 use anyhow::Result;
 use serde::Deserialize;
-use std::num::NonZeroU16;
+use std::{convert::TryInto, fmt, num::NonZeroU16, str::FromStr};
+use warp::{
+    filters::path::FullPath,
+    http::header::{HeaderValue, LINK},
+    Reply,
+};
 
 const DEFAULT_PAGE_SIZE: u16 = 25;
 const MAX_PAGE_SIZE: u16 = 1000;
@@ -36,18 +42,192 @@ impl Page {
     }
 
     pub fn limit(&self) -> Result<u16, Error> {
-        let limit = self
-            .limit
-            .clone()
-            .map(|v| v.parse("limit"))
-            .unwrap_or_else(|| Ok(NonZeroU16::new(DEFAULT_PAGE_SIZE).unwrap()))?
-            .get();
-        if limit > MAX_PAGE_SIZE {
-            return Err(Error::invalid_param(
-                "limit",
-                format!("{}, exceed limit {}", limit, MAX_PAGE_SIZE),
-            ));
+        parse_limit(self.limit.clone())
+    }
+}
+
+/// Pagination over account addresses, e.g. accounts holding a given resource.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct AddressPage {
+    start: Option<AddressParam>,
+    limit: Option<Param<NonZeroU16>>,
+}
+
+impl AddressPage {
+    pub fn start(&self) -> Result<Option<Address>, Error> {
+        self.start.clone().map(|a| a.parse("start")).transpose()
+    }
+
+    pub fn limit(&self) -> Result<u16, Error> {
+        parse_limit(self.limit.clone())
+    }
+}
+
+/// Pagination over a listing of items identified by continuous sequence numbers starting from 0,
+/// e.g. events of an event stream or transactions sent by an account, in either order.
+///
+/// A page starts from the item at `start`, or the one at the opaque `cursor` given by the `Link`
+/// header of the previous response. By default, a page starts from the first item if the order is
+/// `asc`, and from the latest item if it's `desc`.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct CursorPage {
+    start: Option<Param<u64>>,
+    cursor: Option<Param<Cursor>>,
+    limit: Option<Param<NonZeroU16>>,
+    order: Option<Param<String>>,
+}
+
+impl CursorPage {
+    /// Reads the page by calling `read(first, limit)`, which reads at most `limit` items starting
+    /// from sequence number `first` in ascending order. `latest` returns the sequence number of the
+    /// latest item, `None` if there is none, and is only called if the page starts from the
+    /// latest item.
+    pub fn read<T, L, R>(&self, latest: L, read: R) -> Result<(Vec<T>, PageLinks), Error>
+    where
+        L: FnOnce() -> Result<Option<u64>, Error>,
+        R: FnOnce(u64, u16) -> Result<Vec<T>, Error>,
+    {
+        let order = self.order()?;
+        let start = self.start()?;
+        let limit = self.limit()?;
+        let mut links = PageLinks {
+            order,
+            next: None,
+            prev: None,
+        };
+
+        match order {
+            Order::Ascending => {
+                let start = start.unwrap_or(0);
+                let items = read(start, limit)?;
+                if items.len() == limit as usize {
+                    links.next = Some((Cursor(start.saturating_add(limit as u64)), limit));
+                }
+                if start > 0 {
+                    let prev_limit = std::cmp::min(start, limit as u64);
+                    links.prev = Some((Cursor(start - prev_limit), prev_limit as u16));
+                }
+                Ok((items, links))
+            }
+            Order::Descending => {
+                let (explicit_start, start) = match start {
+                    Some(start) => (true, start),
+                    None => match latest()? {
+                        Some(latest) => (false, latest),
+                        None => return Ok((vec![], links)),
+                    },
+                };
+                let first = start.saturating_sub(limit as u64 - 1);
+                let mut items = read(first, (start - first + 1) as u16)?;
+                items.reverse();
+                if first > 0 && !items.is_empty() {
+                    links.next = Some((Cursor(first - 1), limit));
+                }
+                if explicit_start && start < u64::MAX {
+                    links.prev = Some((Cursor(start.saturating_add(limit as u64)), limit));
+                }
+                Ok((items, links))
+            }
+        }
+    }
+
+    fn start(&self) -> Result<Option<u64>, Error> {
+        match (self.start.clone(), self.cursor.clone()) {
+            (Some(_), Some(_)) => Err(Error::bad_request(
+                "start and cursor can't be used together",
+            )),
+            (Some(start), None) => Ok(Some(start.parse("start")?)),
+            (None, Some(cursor)) => Ok(Some(cursor.parse("cursor")?.0)),
+            (None, None) => Ok(None),
         }
-        Ok(limit)
     }
+
+    fn limit(&self) -> Result<u16, Error> {
+        parse_limit(self.limit.clone())
+    }
+
+    fn order(&self) -> Result<Order, Error> {
+        match self.order.clone().map(|o| o.parse("order")).transpose()? {
+            None => Ok(Order::Ascending),
+            Some(order) if order == "asc" => Ok(Order::Ascending),
+            Some(order) if order == "desc" => Ok(Order::Descending),
+            Some(order) => Err(Error::invalid_param("order", order)),
+        }
+    }
+}
+
+/// The pages next to one read by [`CursorPage::read`], rendered as the `Link` header.
+pub(crate) struct PageLinks {
+    order: Order,
+    next: Option<(Cursor, u16)>,
+    prev: Option<(Cursor, u16)>,
+}
+
+impl PageLinks {
+    /// Adds the `Link` header to `response` of the request to `path`, if there is a next or
+    /// previous page.
+    pub fn reply(self, path: FullPath, response: Response) -> Result<impl Reply, Error> {
+        let order = match self.order {
+            Order::Ascending => "asc",
+            Order::Descending => "desc",
+        };
+        let links: Vec<_> = [("next", self.next), ("prev", self.prev)]
+            .iter()
+            .filter_map(|(rel, page)| {
+                page.as_ref().map(|(cursor, limit)| {
+                    format!(
+                        "<{}?cursor={}&limit={}&order={}>; rel=\"{}\"",
+                        path.as_str(),
+                        cursor,
+                        limit,
+                        order,
+                        rel
+                    )
+                })
+            })
+            .collect();
+
+        let mut response = response.into_response();
+        if !links.is_empty() {
+            let link = HeaderValue::from_str(&links.join(", ")).map_err(anyhow::Error::from)?;
+            response.headers_mut().insert(LINK, link);
+        }
+        Ok(response)
+    }
+}
+
+/// Opaque position of a page, which is the sequence number of its first item.
+#[derive(Clone, Copy, Debug)]
+struct Cursor(u64);
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0.to_be_bytes()))
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes: [u8; 8] = hex::decode(s)?
+            .as_slice()
+            .try_into()
+            .map_err(anyhow::Error::from)?;
+        Ok(Self(u64::from_be_bytes(bytes)))
+    }
+}
+
+fn parse_limit(limit: Option<Param<NonZeroU16>>) -> Result<u16, Error> {
+    let limit = limit
+        .map(|v| v.parse("limit"))
+        .unwrap_or_else(|| Ok(NonZeroU16::new(DEFAULT_PAGE_SIZE).unwrap()))?
+        .get();
+    if limit > MAX_PAGE_SIZE {
+        return Err(Error::invalid_param(
+            "limit",
+            format!("{}, exceed limit {}", limit, MAX_PAGE_SIZE),
+        ));
+    }
+    Ok(limit)
 }
@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use diem_api_types::{Address, Error, EventKey, MoveStructTag, TransactionId};
+use diem_api_types::{Address, Error, EventKey, HashValue, MoveStructTag, TransactionId};
 use move_core_types::identifier::Identifier;
 use percent_encoding::percent_decode_str;
 use serde::{Deserialize, Deserializer};
@@ -10,8 +10,10 @@ use std::{convert::Infallible, str::FromStr};
 
 pub type AddressParam = Param<Address>;
 pub type TransactionIdParam = Param<TransactionId>;
+pub type TransactionHashParam = Param<HashValue>;
 pub type TransactionVersionParam = Param<u64>;
 pub type LedgerVersionParam = Param<u64>;
+pub type TimestampParam = Param<u64>;
 pub type EventKeyParam = Param<EventKey>;
 pub type MoveStructTagParam = Param<MoveStructTag>;
 pub type MoveIdentifierParam = Param<Identifier>;
@@ -59,6 +61,21 @@ impl<'de, T: FromStr> Deserialize<'de> for Param<T> {
     }
 }
 
+/// Query string of routes that can return data read from an account state along with its proof.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct ProofQuery {
+    with_proof: Option<Param<bool>>,
+}
+
+impl ProofQuery {
+    pub fn with_proof(&self) -> Result<bool, Error> {
+        self.with_proof
+            .clone()
+            .map(|v| v.parse("with_proof"))
+            .unwrap_or(Ok(false))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::MoveIdentifierParam;
@@ -5,24 +5,30 @@ use crate::{context::Context, index};
 
 use diem_config::config::{ApiConfig, JsonRpcConfig, NodeConfig};
 use diem_mempool::MempoolClientSender;
-use diem_types::chain_id::ChainId;
-use futures::join;
+use diem_types::{chain_id::ChainId, transaction::Version};
+use event_notifications::CommitNotificationListener;
+use futures::{join, StreamExt};
 use storage_interface::MoveDbReader;
 use warp::{Filter, Reply};
 
 use std::{convert::Infallible, net::SocketAddr, sync::Arc};
-use tokio::runtime::{Builder, Runtime};
+use tokio::{
+    runtime::{Builder, Runtime},
+    sync::watch,
+};
 
 /// Creates HTTP server (warp-based) serves for both REST and JSON-RPC API.
 /// When api and json-rpc are configured with same port, both API will be served for the port.
 /// When api and json-rpc are configured with different port, both API will be served for
 /// both ports.
+/// `commit_listener` notifies the streaming API of new commits.
 /// Returns corresponding Tokio runtime
 pub fn bootstrap(
     config: &NodeConfig,
     chain_id: ChainId,
     db: Arc<dyn MoveDbReader>,
     mp_sender: MempoolClientSender,
+    commit_listener: CommitNotificationListener,
 ) -> anyhow::Result<Runtime> {
     let runtime = Builder::new_multi_thread()
         .thread_name("api")
@@ -36,8 +42,22 @@ pub fn bootstrap(
     let api = WebServer::from(api_config.clone());
     let jsonrpc = WebServer::from(json_rpc_config.clone());
 
+    let (committed_version_sender, committed_version) = watch::channel(db.get_latest_version()?);
+    runtime.spawn(forward_commit_notifications(
+        commit_listener,
+        committed_version_sender,
+    ));
+
     runtime.spawn(async move {
-        let context = Context::new(chain_id, db, mp_sender, role, json_rpc_config, api_config);
+        let context = Context::new(
+            chain_id,
+            db,
+            mp_sender,
+            role,
+            json_rpc_config,
+            api_config,
+            committed_version,
+        );
         let routes = index::routes(context);
         if api.address.port() == jsonrpc.address.port() {
             // when we rollout api, it's likely there is no api configuration for diem
@@ -56,6 +76,18 @@ pub fn bootstrap(
     Ok(runtime)
 }
 
+/// Publishes the latest committed version to the `Context`, which wakes up the streams waiting for
+/// new commits.
+async fn forward_commit_notifications(
+    mut commit_listener: CommitNotificationListener,
+    committed_version_sender: watch::Sender<Version>,
+) {
+    while let Some(notification) = commit_listener.next().await {
+        // The `Context` holds a receiver as long as the web server runs, so sending doesn't fail.
+        let _ = committed_version_sender.send(notification.version);
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 struct WebServer {
     pub address: SocketAddr,
@@ -112,8 +144,12 @@ mod tests {
     use std::time::Duration;
 
     use diem_config::config::NodeConfig;
+    use diem_infallible::RwLock;
     use diem_types::chain_id::ChainId;
+    use event_notifications::EventSubscriptionService;
     use serde_json::json;
+    use std::sync::Arc;
+    use storage_interface::DbReaderWriter;
 
     use crate::{
         runtime::bootstrap,
@@ -160,11 +196,18 @@ mod tests {
     pub fn bootstrap_with_config(cfg: NodeConfig) {
         let runtime = tokio::runtime::Runtime::new().unwrap();
         let context = runtime.block_on(new_test_context_async());
+        let commit_listener = EventSubscriptionService::new(
+            &[],
+            Arc::new(RwLock::new(DbReaderWriter::from_arc(context.db.clone()))),
+        )
+        .subscribe_to_commits()
+        .unwrap();
         let ret = bootstrap(
             &cfg,
             ChainId::test(),
             context.db.clone(),
             context.mempool.ac_client.clone(),
+            commit_listener,
         );
         assert!(ret.is_ok());
 
@@ -0,0 +1,235 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{context::Context, failpoint::fail_point, metrics::metrics, param::Param};
+
+use diem_api_types::{Address, Error, EventKey};
+use diem_types::{
+    account_address::AccountAddress,
+    event::EventKey as DiemEventKey,
+    transaction::{Transaction, Version},
+};
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::{
+    collections::{HashSet, VecDeque},
+    convert::Infallible,
+    str::FromStr,
+};
+use tokio::sync::{watch, OwnedSemaphorePermit};
+use warp::{
+    filters::BoxedFilter,
+    http::StatusCode,
+    sse::{self, Event},
+    Filter, Rejection, Reply,
+};
+
+/// The max number of transactions read from the DB at a time by a stream. A stream only reads
+/// more once the client has received what's read, so this also bounds the memory used by a slow
+/// client.
+const MAX_STREAM_BATCH_SIZE: u64 = 100;
+
+// GET /stream?start_version={u64}&event_keys={event_key,...}&addresses={address,...}
+pub fn stream(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("stream")
+        .and(warp::get())
+        .and(warp::query::<StreamQuery>())
+        .and(warp::header::optional::<String>("last-event-id"))
+        .and(context.filter())
+        .and_then(handle_stream)
+        .with(metrics("stream"))
+        .boxed()
+}
+
+async fn handle_stream(
+    query: StreamQuery,
+    last_event_id: Option<String>,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_stream")?;
+    let subscription = Subscription::new(query, last_event_id, context)?;
+    Ok(sse::reply(
+        sse::keep_alive().stream(subscription.into_stream()),
+    ))
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct StreamQuery {
+    start_version: Option<Param<u64>>,
+    event_keys: Option<String>,
+    addresses: Option<String>,
+}
+
+/// Streams committed transactions and events as server-sent events, starting from a version and
+/// continuing with new commits as they happen:
+///   * an `event` named `transaction` for each transaction sent by one of `addresses`, or each
+///     transaction if neither `addresses` nor `event_keys` is given,
+///   * an `event` named `event` for each event of one of `event_keys`.
+///
+/// The `id` of the last server-sent event of each version is the version, so that a client
+/// reconnecting with the `Last-Event-ID` header resumes from the version after it.
+///
+/// Transactions are read from the DB in batches as the client receives them, so a slow client
+/// falls behind instead of having the node buffer for it. At most `max_stream_subscriptions`
+/// subscriptions exist at a time, each holding a permit until the client disconnects.
+struct Subscription {
+    context: Context,
+    event_keys: HashSet<DiemEventKey>,
+    addresses: HashSet<AccountAddress>,
+    next_version: Version,
+    committed_version: watch::Receiver<Version>,
+    pending: VecDeque<Event>,
+    failed: bool,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Subscription {
+    fn new(
+        query: StreamQuery,
+        last_event_id: Option<String>,
+        context: Context,
+    ) -> Result<Self, Error> {
+        let committed_version = context.committed_version();
+        let next_version = match (last_event_id, query.start_version) {
+            (Some(last_event_id), _) => last_event_id
+                .parse::<Version>()
+                .map_err(|_| Error::invalid_param("Last-Event-ID", &last_event_id))?
+                .saturating_add(1),
+            (None, Some(start_version)) => start_version.parse("start_version")?,
+            (None, None) => committed_version.borrow().saturating_add(1),
+        };
+        let event_keys = parse_list::<EventKey, _>(query.event_keys, "event_keys")?;
+        let addresses = parse_list::<Address, _>(query.addresses, "addresses")?;
+        let permit = context.try_acquire_stream_subscription().ok_or_else(|| {
+            Error::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!(
+                    "too many stream subscriptions, the limit is {}",
+                    context.max_stream_subscriptions()
+                ),
+            )
+        })?;
+
+        Ok(Self {
+            event_keys,
+            addresses,
+            context,
+            next_version,
+            committed_version,
+            pending: VecDeque::new(),
+            failed: false,
+            _permit: permit,
+        })
+    }
+
+    fn into_stream(self) -> impl futures::Stream<Item = Result<Event, Infallible>> {
+        futures::stream::unfold(self, |mut subscription| async move {
+            subscription
+                .next_event()
+                .await
+                .map(|event| (Ok(event), subscription))
+        })
+    }
+
+    async fn next_event(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if self.failed {
+                return None;
+            }
+
+            let committed_version = *self.committed_version.borrow();
+            if self.next_version > committed_version {
+                // The sender is dropped only when the node shuts down.
+                self.committed_version.changed().await.ok()?;
+                continue;
+            }
+            if let Err(error) = self.read_batch(committed_version) {
+                // Report the error to the client and end the stream.
+                self.failed = true;
+                self.pending.push_back(
+                    Event::default()
+                        .event("error")
+                        .json_data(&error)
+                        .expect("Error should serialize"),
+                );
+            }
+        }
+    }
+
+    fn read_batch(&mut self, committed_version: Version) -> Result<(), Error> {
+        let limit = std::cmp::min(
+            committed_version - self.next_version + 1,
+            MAX_STREAM_BATCH_SIZE,
+        );
+        let txns =
+            self.context
+                .get_transactions(self.next_version, limit as u16, committed_version)?;
+        let mut timestamp = self.context.get_block_timestamp(self.next_version)?;
+        let converter = self.context.move_converter();
+
+        for txn in txns {
+            let version = txn.version;
+            if let Transaction::BlockMetadata(metadata) = &txn.transaction {
+                timestamp = metadata.timestamp_usec();
+            }
+            let events: Vec<_> = txn
+                .events
+                .iter()
+                .filter(|event| self.event_keys.contains(event.key()))
+                .cloned()
+                .collect();
+
+            let mut data = vec![];
+            if self.matches_transaction(&txn.transaction) {
+                let txn = converter.try_into_onchain_transaction(timestamp, txn)?;
+                data.push(("transaction", serde_json::to_string(&txn)?));
+            }
+            for event in converter.try_into_events(&events)? {
+                data.push(("event", serde_json::to_string(&event)?));
+            }
+
+            let num_data = data.len();
+            for (i, (name, data)) in data.into_iter().enumerate() {
+                let event = Event::default().event(name).data(data);
+                self.pending.push_back(if i + 1 == num_data {
+                    event.id(version.to_string())
+                } else {
+                    event
+                });
+            }
+            self.next_version = version + 1;
+        }
+        Ok(())
+    }
+
+    fn matches_transaction(&self, txn: &Transaction) -> bool {
+        if self.addresses.is_empty() && self.event_keys.is_empty() {
+            return true;
+        }
+        match txn {
+            Transaction::UserTransaction(txn) => self.addresses.contains(&txn.sender()),
+            _ => false,
+        }
+    }
+}
+
+/// Parses a comma separated list.
+fn parse_list<T, U>(list: Option<String>, name: &str) -> Result<HashSet<U>, Error>
+where
+    T: FromStr + Into<U>,
+    U: Eq + std::hash::Hash,
+{
+    list.iter()
+        .flat_map(|list| list.split(','))
+        .map(|item| {
+            Param::<T>::from_str(item)
+                .expect("Param::from_str is infallible")
+                .parse(name)
+                .map(Into::into)
+        })
+        .collect()
+}
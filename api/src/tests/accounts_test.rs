@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::tests::{assert_json, find_value, new_test_context};
-use diem_api_types::HexEncodedBytes;
-use serde_json::json;
+use diem_api_types::{AccountStateProof, HexEncodedBytes};
+use diem_types::{account_address::AccountAddress, account_state_blob::AccountStateBlob};
+use serde_json::{json, Value};
 
 #[tokio::test]
 async fn test_get_account_resources_returns_empty_array_for_account_has_no_resources() {
@@ -538,6 +539,73 @@ async fn test_get_account_resources_by_invalid_ledger_version() {
     );
 }
 
+#[tokio::test]
+async fn test_get_account_resources_at_time() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let address = account.address().to_hex_literal();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+    let block_1 = context.get_latest_ledger_info();
+    context.commit_block(&[]).await;
+    let block_2 = context.get_latest_ledger_info();
+    context.commit_block(&[]).await;
+
+    // A timestamp resolves to the last version of the last block proposed at or before it.
+    for (timestamp, version) in [
+        (block_1.timestamp(), block_1.version()),
+        (block_2.timestamp() - 1, block_1.version()),
+        (block_2.timestamp(), block_2.version()),
+    ] {
+        let resp = context
+            .get(&format!(
+                "{}?with_proof=true",
+                account_resources_at_time(&address, timestamp)
+            ))
+            .await;
+        assert_eq!(resp["proof"]["version"], version.to_string());
+        assert!(verify_account_state_proof(&resp, &address).is_some());
+        let account_resource = find_value(&resp["data"], |f| {
+            f["type"] == "0x1::DiemAccount::DiemAccount"
+        });
+        assert_eq!(account_resource["data"]["sequence_number"], "0");
+    }
+
+    let resources = context
+        .get(&account_resources_at_time(
+            &context.tc_account().address().to_hex_literal(),
+            block_1.timestamp(),
+        ))
+        .await;
+    let tc_account = find_value(&resources, |f| f["type"] == "0x1::DiemAccount::DiemAccount");
+    assert_eq!(tc_account["data"]["sequence_number"], "1");
+}
+
+#[tokio::test]
+async fn test_get_account_resources_at_time_without_later_block() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+    let info = context.get_latest_ledger_info();
+
+    let resp = context
+        .expect_status_code(404)
+        .get(&account_resources_at_time(
+            &context.tc_account().address().to_hex_literal(),
+            info.timestamp(),
+        ))
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 404,
+            "message": format!("ledger not found by timestamp({})", info.timestamp()),
+            "diem_ledger_version": info.ledger_version,
+        }),
+    );
+}
+
 #[tokio::test]
 async fn test_get_account_modules_by_ledger_version() {
     let context = new_test_context();
@@ -594,6 +662,158 @@ async fn test_get_core_account_data_not_found() {
     );
 }
 
+#[tokio::test]
+async fn test_get_core_account_data_with_proof() {
+    let context = new_test_context();
+    let auth_key = context.dd_account().authentication_key();
+    let resp = context.get("/accounts/0xdd?with_proof=true").await;
+    assert_eq!(
+        json!({
+            "sequence_number": "0",
+            "authentication_key": HexEncodedBytes::from(auth_key.to_vec()).to_string(),
+        }),
+        resp["data"]
+    );
+    let blob = verify_account_state_proof(&resp, "0xdd");
+    assert!(blob.is_some());
+}
+
+#[tokio::test]
+async fn test_get_core_account_data_not_found_with_proof() {
+    let context = new_test_context();
+    let resp = context.get("/accounts/0xf?with_proof=true").await;
+    assert_eq!(Value::Null, resp["data"]);
+    assert_eq!(Value::Null, resp["proof"]["account_state_blob"]);
+    let blob = verify_account_state_proof(&resp, "0xf");
+    assert!(blob.is_none());
+}
+
+#[tokio::test]
+async fn test_get_account_resources_with_proof() {
+    let context = new_test_context();
+    let resp = context
+        .get(&format!("{}?with_proof=true", account_resources("0xdd")))
+        .await;
+    assert_eq!(context.get(&account_resources("0xdd")).await, resp["data"]);
+    verify_account_state_proof(&resp, "0xdd");
+}
+
+#[tokio::test]
+async fn test_get_account_modules_by_ledger_version_with_proof() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let address = account.address().to_hex_literal();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+
+    // The account is created after version 0.
+    let resp = context
+        .get(&format!(
+            "{}?with_proof=true",
+            account_modules_with_ledger_version(&address, 0)
+        ))
+        .await;
+    assert_eq!(Value::Null, resp["data"]);
+    assert_eq!("0", resp["proof"]["version"]);
+    assert!(verify_account_state_proof(&resp, &address).is_none());
+
+    let resp = context
+        .get(&format!("{}?with_proof=true", account_modules(&address)))
+        .await;
+    assert_eq!(json!([]), resp["data"]);
+    assert!(verify_account_state_proof(&resp, &address).is_some());
+}
+
+#[tokio::test]
+async fn test_get_account_with_invalid_with_proof_param() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(400)
+        .get("/accounts/0xdd?with_proof=yes")
+        .await;
+    assert_eq!(
+        json!({
+            "code": 400,
+            "message": "invalid parameter with_proof: yes",
+        }),
+        resp
+    );
+}
+
+#[tokio::test]
+async fn test_get_accounts_by_resource() {
+    let context = new_test_context();
+    let resp = context
+        .get(&accounts_by_resource(
+            "0x1::DiemTimestamp::CurrentTimeMicroseconds",
+        ))
+        .await;
+    assert_eq!(json!(["0xa550c18"]), resp);
+}
+
+#[tokio::test]
+async fn test_get_accounts_by_resource_paginated() {
+    let context = new_test_context();
+    let resource = accounts_by_resource("0x1::DiemAccount::DiemAccount");
+
+    let all = context.get(&resource).await;
+    let all = all.as_array().unwrap();
+    assert!(all.len() > 2);
+    assert!(all.contains(&json!("0xa550c18")));
+    assert!(all.contains(&json!("0xb1e55ed")));
+
+    let resp = context.get(&format!("{}?limit=1", resource)).await;
+    assert_eq!(json!([all[0]]), resp);
+
+    let resp = context
+        .get(&format!(
+            "{}?start={}&limit=2",
+            resource,
+            all[1].as_str().unwrap()
+        ))
+        .await;
+    assert_eq!(json!([all[1], all[2]]), resp);
+}
+
+#[tokio::test]
+async fn test_get_accounts_by_resource_start_after_last() {
+    let context = new_test_context();
+    let resp = context
+        .get(&format!(
+            "{}?start=0xa550c19",
+            accounts_by_resource("0x1::DiemTimestamp::CurrentTimeMicroseconds")
+        ))
+        .await;
+    assert_eq!(json!([]), resp);
+}
+
+#[tokio::test]
+async fn test_get_accounts_by_invalid_resource_type() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(400)
+        .get(&accounts_by_resource("0x1::DiemAccount"))
+        .await;
+    assert_eq!(
+        json!({
+            "code": 400,
+            "message": "invalid parameter resource type: 0x1::DiemAccount",
+        }),
+        resp
+    );
+}
+
+fn verify_account_state_proof(resp: &Value, address: &str) -> Option<AccountStateBlob> {
+    let proof: AccountStateProof = serde_json::from_value(resp["proof"].clone()).unwrap();
+    let ledger_info = proof.ledger_info().unwrap();
+    proof
+        .verify_account_state(
+            &ledger_info,
+            AccountAddress::from_hex_literal(address).unwrap(),
+        )
+        .unwrap()
+}
+
 fn account_resources(address: &str) -> String {
     format!("/accounts/{}/resources", address)
 }
@@ -602,6 +822,14 @@ fn account_resources_with_ledger_version(address: &str, ledger_version: i128) ->
     format!("/ledger/{}{}", ledger_version, account_resources(address))
 }
 
+fn account_resources_at_time(address: &str, timestamp: u64) -> String {
+    format!(
+        "/ledger/at_time/{}{}",
+        timestamp,
+        account_resources(address)
+    )
+}
+
 fn account_modules(address: &str) -> String {
     format!("/accounts/{}/modules", address)
 }
@@ -609,3 +837,7 @@ fn account_modules(address: &str) -> String {
 fn account_modules_with_ledger_version(address: &str, ledger_version: i128) -> String {
     format!("/ledger/{}{}", ledger_version, account_modules(address))
 }
+
+fn accounts_by_resource(resource_type: &str) -> String {
+    format!("/resources/{}/accounts", resource_type)
+}
@@ -64,6 +64,87 @@ async fn test_get_events_filter_by_limit_page_size() {
     assert_eq!(resp.as_array().unwrap().len(), 2);
 }
 
+#[tokio::test]
+async fn test_get_events_in_descending_order_with_cursors() {
+    let context = new_test_context();
+    let path = "/events/0x00000000000000000000000000000000000000000a550c18";
+
+    let all = context.get(&format!("{}?limit=1000", path)).await;
+    let all: Vec<_> = all.as_array().unwrap().iter().rev().cloned().collect();
+    assert!(all.len() >= 4);
+
+    let (page, next, prev) = context
+        .get_page(&format!("{}?order=desc&limit=2", path))
+        .await;
+    assert_eq!(json!(all[..2]), page);
+    assert_eq!(prev, None);
+
+    let (page, _, prev) = context.get_page(&next.unwrap()).await;
+    assert_eq!(json!(all[2..4]), page);
+
+    let (page, _, _) = context.get_page(&prev.unwrap()).await;
+    assert_eq!(json!(all[..2]), page);
+
+    // The last page.
+    let oldest = all.len() - 1;
+    let (page, next, _) = context
+        .get_page(&format!("{}?order=desc&start=1&limit=2", path))
+        .await;
+    assert_eq!(json!(all[oldest - 1..]), page);
+    assert_eq!(next, None);
+}
+
+#[tokio::test]
+async fn test_get_events_in_ascending_order_with_cursors() {
+    let context = new_test_context();
+    let path = "/events/0x00000000000000000000000000000000000000000a550c18";
+
+    let (page, next, prev) = context.get_page(&format!("{}?limit=2", path)).await;
+    assert_eq!(page[0]["sequence_number"], "0");
+    assert_eq!(prev, None);
+
+    let (page, _, prev) = context.get_page(&next.unwrap()).await;
+    assert_eq!(page[0]["sequence_number"], "2");
+    assert_eq!(page[1]["sequence_number"], "3");
+
+    let (page, _, prev) = context.get_page(&prev.unwrap()).await;
+    assert_eq!(page[0]["sequence_number"], "0");
+    assert_eq!(prev, None);
+}
+
+#[tokio::test]
+async fn test_get_events_with_invalid_page_params() {
+    let context = new_test_context();
+    let path = "/events/0x00000000000000000000000000000000000000000a550c18";
+
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!("{}?order=latest", path))
+        .await;
+    assert_json(
+        resp,
+        json!({"code": 400, "message": "invalid parameter order: latest"}),
+    );
+
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!("{}?cursor=xyz", path))
+        .await;
+    assert_json(
+        resp,
+        json!({"code": 400, "message": "invalid parameter cursor: xyz"}),
+    );
+
+    let resp = context
+        .expect_status_code(400)
+        .get(&format!("{}?start=1&cursor=0000000000000001", path))
+        .await;
+    assert_json(
+        resp,
+        json!({"code": 400, "message": "start and cursor can't be used together"}),
+    );
+}
+
 #[tokio::test]
 async fn test_get_events_by_invalid_key() {
     let context = new_test_context();
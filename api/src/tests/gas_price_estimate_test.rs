@@ -0,0 +1,38 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::new_test_context;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_get_gas_price_estimate() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+
+    // The transactions created by the test context don't pay for gas.
+    let resp = context.get("/gas_price_estimate?currency=XUS").await;
+    assert_eq!(resp, json!({"low": "0", "median": "0", "high": "0"}));
+
+    // No transaction pays gas in XDX.
+    let resp = context.get("/gas_price_estimate?currency=XDX").await;
+    assert_eq!(resp, json!({"low": "0", "median": "0", "high": "0"}));
+}
+
+#[tokio::test]
+async fn test_get_gas_price_estimate_with_invalid_params() {
+    let context = new_test_context();
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/gas_price_estimate")
+        .await;
+    assert_eq!(resp["message"], "missing parameter currency");
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/gas_price_estimate?currency=X-US")
+        .await;
+    assert_eq!(resp["message"], "invalid parameter currency: X-US");
+}
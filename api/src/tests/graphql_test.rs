@@ -0,0 +1,115 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::new_test_context;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_query_account_with_resources_and_transactions() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn.clone()]).await;
+
+    let tc_address = context.tc_account().address().to_hex_literal();
+    let query = format!(
+        r#"{{
+            ledgerInfo {{ ledgerVersion }}
+            account(address: "{}") {{
+                address
+                sequenceNumber
+                resources {{ type }}
+                transactions {{ hash success events {{ type data }} }}
+            }}
+        }}"#,
+        tc_address
+    );
+    let resp = context.post("/graphql", json!({ "query": query })).await;
+    let data = &resp["data"];
+    assert_eq!(
+        data["ledgerInfo"]["ledgerVersion"],
+        context.get_latest_ledger_info().ledger_version.to_string()
+    );
+    assert_eq!(data["account"]["address"], tc_address);
+    assert_eq!(data["account"]["sequenceNumber"], "1");
+    assert!(data["account"]["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|resource| resource["type"] == "0x1::DiemAccount::DiemAccount"));
+
+    let txns = data["account"]["transactions"].as_array().unwrap();
+    assert_eq!(txns.len(), 1);
+    assert_eq!(txns[0]["hash"], txn.committed_hash().to_hex_literal());
+    assert_eq!(txns[0]["success"], true);
+    let created = txns[0]["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|event| event["type"] == "0x1::DiemAccount::CreateAccountEvent")
+        .unwrap();
+    assert_eq!(
+        created["data"]["created"],
+        account.address().to_hex_literal()
+    );
+}
+
+#[tokio::test]
+async fn test_query_missing_account() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let query = format!(
+        r#"{{ account(address: "{}") {{ address }} }}"#,
+        account.address().to_hex_literal()
+    );
+    let resp = context.post("/graphql", json!({ "query": query })).await;
+    assert_eq!(resp, json!({"data": {"account": null}}));
+}
+
+#[tokio::test]
+async fn test_query_with_invalid_argument() {
+    let context = new_test_context();
+    let resp = context
+        .post(
+            "/graphql",
+            json!({ "query": r#"{ account(address: "0xZZ") { address } }"# }),
+        )
+        .await;
+    assert_eq!(
+        resp["errors"][0]["message"],
+        "400 Bad Request: invalid parameter address: 0xZZ"
+    );
+
+    let resp = context
+        .post(
+            "/graphql",
+            json!({ "query": r#"{ transactions(limit: 1001) { version } }"# }),
+        )
+        .await;
+    assert_eq!(
+        resp["errors"][0]["message"],
+        "400 Bad Request: invalid parameter limit: 1001"
+    );
+}
+
+#[tokio::test]
+async fn test_query_exceeding_complexity_limit() {
+    let context = new_test_context();
+    let resp = context
+        .post(
+            "/graphql",
+            json!({
+                "query": r#"{
+                    transactions(limit: 1000) {
+                        version hash timestamp success vmStatus data
+                        events { key sequenceNumber type data }
+                    }
+                }"#
+            }),
+        )
+        .await;
+    assert!(resp["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("too complex"));
+}
@@ -0,0 +1,178 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{assert_json, new_test_context, test_context::ADMIN_TOKEN, TestContext};
+use diem_sdk::client::SignedTransaction;
+use serde_json::{json, Value};
+use warp::http::header::AUTHORIZATION;
+
+#[tokio::test]
+async fn test_get_mempool_account_transactions_and_stats() {
+    let mut context = new_test_context();
+    let (ready_hash, parked_hash) = submit_ready_and_parked_transactions(&mut context).await;
+    let tc = context.tc_account().address().to_hex_literal();
+
+    let resp = context
+        .get(&format!("/mempool/accounts/{}/transactions", tc))
+        .await;
+    let txns = resp.as_array().unwrap();
+    assert_eq!(txns.len(), 2);
+    assert_eq!(txns[0]["state"], "ready");
+    assert_eq!(txns[0]["transaction"]["type"], "pending_transaction");
+    assert_eq!(txns[0]["transaction"]["hash"], ready_hash);
+    assert_eq!(txns[0]["transaction"]["sequence_number"], "0");
+    assert_eq!(txns[1]["state"], "parked");
+    assert_eq!(txns[1]["transaction"]["hash"], parked_hash);
+    assert_eq!(txns[1]["transaction"]["sequence_number"], "2");
+
+    let resp = context
+        .get(&format!(
+            "/mempool/accounts/{}/transactions",
+            context.gen_account().address().to_hex_literal()
+        ))
+        .await;
+    assert_eq!(resp, json!([]));
+
+    let resp = context.get("/mempool/stats").await;
+    assert_json(resp, json!({"ready": "1", "parked": "1"}));
+}
+
+#[tokio::test]
+async fn test_evict_mempool_transaction_by_hash() {
+    let mut context = new_test_context();
+    let (ready_hash, parked_hash) = submit_ready_and_parked_transactions(&mut context).await;
+
+    let resp = delete_as_admin(&context, &format!("/mempool/transactions/{}", ready_hash)).await;
+    assert_eq!(resp["hash"], ready_hash);
+
+    // the transaction after the evicted one can't be ready anymore
+    let resp = context.get("/mempool/stats").await;
+    assert_json(resp, json!({"ready": "0", "parked": "1"}));
+
+    let resp = delete_as_admin(
+        &context.expect_status_code(404),
+        &format!("/mempool/transactions/{}", ready_hash),
+    )
+    .await;
+    assert_eq!(
+        resp["message"],
+        format!("mempool transaction not found by {}", ready_hash)
+    );
+
+    let resp = delete_as_admin(&context, &format!("/mempool/transactions/{}", parked_hash)).await;
+    assert_eq!(resp["hash"], parked_hash);
+}
+
+#[tokio::test]
+async fn test_get_mempool_transaction_status_history() {
+    let mut context = new_test_context();
+    let (ready_hash, _) = submit_ready_and_parked_transactions(&mut context).await;
+    let path = format!("/mempool/transactions/{}/status", ready_hash);
+
+    let resp = context.get(&path).await;
+    let statuses: Vec<_> = resp
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| event["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, vec!["accepted", "ready"]);
+
+    // the history outlives the transaction, which can't be found by hash anymore
+    delete_as_admin(&context, &format!("/mempool/transactions/{}", ready_hash)).await;
+    context
+        .expect_status_code(404)
+        .get(&format!("/transactions/{}", ready_hash))
+        .await;
+    let resp = context.get(&path).await;
+    let last = resp.as_array().unwrap().last().unwrap();
+    assert_eq!(last["type"], "evicted");
+    assert_eq!(last["reason"], "evicted by the node operator");
+
+    let unknown_hash = "0xdadfeddcca7cb6396c735e9094c76c6e4e9cb3e3ef814730693aed59bd87b31d";
+    let resp = context
+        .expect_status_code(404)
+        .get(&format!("/mempool/transactions/{}/status", unknown_hash))
+        .await;
+    assert_eq!(
+        resp["message"],
+        format!("mempool transaction status not found by {}", unknown_hash)
+    );
+}
+
+#[tokio::test]
+async fn test_evict_mempool_account_transactions() {
+    let mut context = new_test_context();
+    let (ready_hash, parked_hash) = submit_ready_and_parked_transactions(&mut context).await;
+    let path = format!(
+        "/mempool/accounts/{}/transactions",
+        context.tc_account().address().to_hex_literal()
+    );
+
+    let resp = delete_as_admin(&context, &path).await;
+    let txns = resp.as_array().unwrap();
+    assert_eq!(txns.len(), 2);
+    assert_eq!(txns[0]["hash"], ready_hash);
+    assert_eq!(txns[1]["hash"], parked_hash);
+
+    let resp = context.get(&path).await;
+    assert_eq!(resp, json!([]));
+    let resp = delete_as_admin(&context, &path).await;
+    assert_eq!(resp, json!([]));
+}
+
+#[tokio::test]
+async fn test_evict_mempool_transactions_with_invalid_admin_token() {
+    let context = new_test_context();
+    let path = format!(
+        "/mempool/accounts/{}/transactions",
+        context.tc_account().address().to_hex_literal()
+    );
+
+    for authorization in [None, Some("Bearer invalid"), Some(ADMIN_TOKEN)] {
+        let mut req = warp::test::request().method("DELETE").path(&path);
+        if let Some(authorization) = authorization {
+            req = req.header(AUTHORIZATION, authorization);
+        }
+        let resp = context.expect_status_code(403).execute(req).await;
+        assert_json(resp, json!({"code": 403, "message": "invalid admin token"}));
+    }
+}
+
+/// Submits a transaction of the treasury compliance account that is ready for the next block,
+/// and one that is parked for a missing sequence number, returning their hashes.
+async fn submit_ready_and_parked_transactions(context: &mut TestContext) -> (String, String) {
+    let mut tc = context.tc_account();
+    let ready_txn = create_parent_vasp(context, &mut tc);
+    *tc.sequence_number_mut() = 2;
+    let parked_txn = create_parent_vasp(context, &mut tc);
+
+    let mut hashes = vec![];
+    for txn in [ready_txn, parked_txn] {
+        let resp = context
+            .expect_status_code(202)
+            .post_bcs_txn("/transactions", bcs::to_bytes(&txn).unwrap())
+            .await;
+        hashes.push(resp["hash"].as_str().unwrap().to_owned());
+    }
+    (hashes[0].clone(), hashes[1].clone())
+}
+
+fn create_parent_vasp(
+    context: &mut TestContext,
+    creator: &mut diem_sdk::types::LocalAccount,
+) -> SignedTransaction {
+    let account = context.gen_account();
+    context.create_parent_vasp_by_account(creator, &account)
+}
+
+async fn delete_as_admin(context: &TestContext, path: &str) -> Value {
+    context
+        .execute(
+            warp::test::request()
+                .method("DELETE")
+                .path(path)
+                .header(AUTHORIZATION, format!("Bearer {}", ADMIN_TOKEN)),
+        )
+        .await
+}
@@ -3,11 +3,16 @@
 
 mod accounts_test;
 mod events_test;
+mod gas_price_estimate_test;
+mod graphql_test;
 mod index_test;
 mod invalid_post_request_test;
+mod mempool_test;
+mod streams_test;
 mod string_resource_test;
 mod test_context;
 mod transactions_test;
+mod view_function_test;
 
 use serde_json::Value;
 pub use test_context::{new_test_context, TestContext};
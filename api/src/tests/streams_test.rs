@@ -0,0 +1,231 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    index,
+    tests::{new_test_context, TestContext},
+};
+use hyper::{body::HttpBody, Body};
+use serde_json::Value;
+use std::time::Duration;
+use warp::Reply;
+
+const CREATE_ACCOUNT_EVENT_KEY: &str = "0x00000000000000000000000000000000000000000a550c18";
+
+#[tokio::test]
+async fn test_stream_all_transactions() {
+    let mut context = new_test_context();
+    let version = context.get_latest_ledger_info().version();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+
+    let mut stream = SseStream::open(
+        &context,
+        &format!("/stream?start_version={}", version + 1),
+        None,
+    )
+    .await;
+
+    let event = stream.next().await;
+    assert_eq!(event.name, "transaction");
+    assert_eq!(event.id, Some((version + 1).to_string()));
+    assert_eq!(event.data["type"], "block_metadata_transaction");
+    let event = stream.next().await;
+    assert_eq!(event.id, Some((version + 2).to_string()));
+    assert_eq!(event.data["type"], "user_transaction");
+    assert_eq!(event.data["version"], (version + 2).to_string());
+
+    // New commits are streamed as they happen.
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+    let event = stream.next().await;
+    assert_eq!(event.id, Some((version + 3).to_string()));
+    assert_eq!(event.data["type"], "block_metadata_transaction");
+    let event = stream.next().await;
+    assert_eq!(event.id, Some((version + 4).to_string()));
+    assert_eq!(event.data["type"], "user_transaction");
+}
+
+#[tokio::test]
+async fn test_stream_transactions_filtered_by_addresses() {
+    let mut context = new_test_context();
+    let version = context.get_latest_ledger_info().version();
+    let tc = context.tc_account();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+
+    let mut stream = SseStream::open(
+        &context,
+        &format!(
+            "/stream?start_version={}&addresses={}",
+            version + 1,
+            tc.address().to_hex_literal()
+        ),
+        None,
+    )
+    .await;
+
+    let event = stream.next().await;
+    assert_eq!(event.name, "transaction");
+    assert_eq!(event.id, Some((version + 2).to_string()));
+    assert_eq!(event.data["type"], "user_transaction");
+    assert_eq!(event.data["sender"], tc.address().to_hex_literal());
+}
+
+#[tokio::test]
+async fn test_stream_events_filtered_by_event_keys() {
+    let mut context = new_test_context();
+    let version = context.get_latest_ledger_info().version();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+
+    let mut stream = SseStream::open(
+        &context,
+        &format!(
+            "/stream?start_version={}&event_keys={}",
+            version + 1,
+            CREATE_ACCOUNT_EVENT_KEY
+        ),
+        None,
+    )
+    .await;
+
+    let event = stream.next().await;
+    assert_eq!(event.name, "event");
+    assert_eq!(event.id, Some((version + 2).to_string()));
+    assert_eq!(event.data["key"], CREATE_ACCOUNT_EVENT_KEY);
+    assert_eq!(event.data["type"], "0x1::DiemAccount::CreateAccountEvent");
+    assert_eq!(
+        event.data["data"]["created"],
+        account.address().to_hex_literal()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_resumes_after_last_event_id() {
+    let mut context = new_test_context();
+    let version = context.get_latest_ledger_info().version();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+
+    let last_event_id = (version + 1).to_string();
+    let mut stream = SseStream::open(
+        &context,
+        &format!("/stream?start_version={}", version + 1),
+        Some(&last_event_id),
+    )
+    .await;
+
+    let event = stream.next().await;
+    assert_eq!(event.id, Some((version + 2).to_string()));
+    assert_eq!(event.data["type"], "user_transaction");
+}
+
+#[tokio::test]
+async fn test_stream_invalid_params() {
+    let context = new_test_context();
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/stream?start_version=abc")
+        .await;
+    assert_eq!(resp["message"], "invalid parameter start_version: abc");
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/stream?event_keys=0x1")
+        .await;
+    assert_eq!(resp["message"], "invalid parameter event_keys: 0x1");
+
+    let resp = context
+        .expect_status_code(400)
+        .get("/stream?addresses=0x1,xyz")
+        .await;
+    assert_eq!(resp["message"], "invalid parameter addresses: xyz");
+}
+
+#[tokio::test]
+async fn test_stream_subscriptions_limit() {
+    let context = new_test_context();
+
+    // The test context allows 2 concurrent subscriptions.
+    let stream_1 = SseStream::open(&context, "/stream", None).await;
+    let _stream_2 = SseStream::open(&context, "/stream", None).await;
+    let resp = context.expect_status_code(503).get("/stream").await;
+    assert_eq!(
+        resp["message"],
+        "too many stream subscriptions, the limit is 2"
+    );
+
+    // A subscription is released once its client disconnects.
+    drop(stream_1);
+    SseStream::open(&context, "/stream", None).await;
+}
+
+struct SseEvent {
+    name: String,
+    id: Option<String>,
+    data: Value,
+}
+
+struct SseStream {
+    body: Body,
+    buffer: String,
+}
+
+impl SseStream {
+    async fn open(context: &TestContext, path: &str, last_event_id: Option<&str>) -> Self {
+        let mut request = warp::test::request().method("GET").path(path);
+        if let Some(last_event_id) = last_event_id {
+            request = request.header("last-event-id", last_event_id);
+        }
+        let resp = request
+            .filter(&index::routes(context.context.clone()))
+            .await
+            .unwrap_or_else(|_| panic!("{} should not be rejected", path))
+            .into_response();
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers()["content-type"], "text/event-stream");
+        Self {
+            body: resp.into_body(),
+            buffer: String::new(),
+        }
+    }
+
+    /// Returns the next server-sent event, skipping keep-alive comments.
+    async fn next(&mut self) -> SseEvent {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let frame: String = self.buffer.drain(..end + 2).collect();
+                let mut name = None;
+                let mut id = None;
+                let mut data = None;
+                for line in frame.lines() {
+                    if let Some(value) = line.strip_prefix("event:") {
+                        name = Some(value.to_owned());
+                    } else if let Some(value) = line.strip_prefix("id:") {
+                        id = Some(value.to_owned());
+                    } else if let Some(value) = line.strip_prefix("data:") {
+                        data = Some(serde_json::from_str(value).unwrap());
+                    }
+                }
+                if let (Some(name), Some(data)) = (name, data) {
+                    return SseEvent { name, id, data };
+                }
+                continue;
+            }
+
+            let chunk = tokio::time::timeout(Duration::from_secs(10), self.body.data())
+                .await
+                .expect("timed out waiting for a server-sent event")
+                .expect("stream should not end")
+                .unwrap();
+            self.buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+    }
+}
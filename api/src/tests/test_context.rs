@@ -38,14 +38,17 @@ use executor::db_bootstrapper;
 use executor_types::BlockExecutorTrait;
 use hyper::Response;
 use mempool_notifications::MempoolNotificationSender;
-use storage_interface::DbReaderWriter;
+use storage_interface::{DbReader, DbReaderWriter};
 
 use executor::block_executor::BlockExecutor;
 use rand::{Rng, SeedableRng};
 use serde_json::{json, Value};
 use std::{boxed::Box, collections::BTreeMap, sync::Arc, time::SystemTime};
+use tokio::sync::watch;
 use vm_validator::vm_validator::VMValidator;
-use warp::http::header::CONTENT_TYPE;
+use warp::http::header::{CONTENT_TYPE, LINK};
+
+pub const ADMIN_TOKEN: &str = "admin-token";
 
 pub fn new_test_context() -> TestContext {
     let tmp_dir = TempPath::new();
@@ -67,6 +70,8 @@ pub fn new_test_context() -> TestContext {
     assert!(ret);
 
     let mempool = MockSharedMempool::new_in_runtime(&db_rw, VMValidator::new(db.clone()));
+    let (committed_version_sender, committed_version) =
+        watch::channel(db.get_latest_version().unwrap());
 
     TestContext::new(
         Context::new(
@@ -75,8 +80,15 @@ pub fn new_test_context() -> TestContext {
             mempool.ac_client.clone(),
             RoleType::Validator,
             JsonRpcConfig::default(),
-            ApiConfig::default(),
+            ApiConfig {
+                max_stream_subscriptions: 2,
+                graphql_enabled: true,
+                admin_token: Some(ADMIN_TOKEN.to_owned()),
+                ..ApiConfig::default()
+            },
+            committed_version,
         ),
+        committed_version_sender,
         rng,
         root_keys,
         validator_owner,
@@ -92,6 +104,7 @@ pub struct TestContext {
     pub validator_owner: AccountAddress,
     pub mempool: Arc<MockSharedMempool>,
     pub db: Arc<DiemDB>,
+    committed_version_sender: Arc<watch::Sender<u64>>,
     rng: rand::rngs::StdRng,
     root_keys: Arc<RootKeys>,
     executor: Arc<dyn BlockExecutorTrait>,
@@ -101,6 +114,7 @@ pub struct TestContext {
 impl TestContext {
     pub fn new(
         context: Context,
+        committed_version_sender: watch::Sender<u64>,
         rng: rand::rngs::StdRng,
         root_keys: RootKeys,
         validator_owner: AccountAddress,
@@ -110,6 +124,7 @@ impl TestContext {
     ) -> Self {
         Self {
             context,
+            committed_version_sender: Arc::new(committed_version_sender),
             rng,
             root_keys: Arc::new(root_keys),
             validator_owner,
@@ -242,6 +257,9 @@ impl TestContext {
                 self.new_ledger_info(&metadata, result.root_hash(), txns.len()),
             )
             .unwrap();
+        self.committed_version_sender
+            .send(self.db.get_latest_version().unwrap())
+            .unwrap();
 
         self.mempool
             .mempool_notifier
@@ -335,6 +353,29 @@ impl TestContext {
             .await
     }
 
+    /// Gets the page at `path`, along with the paths of the next and previous pages in the `Link`
+    /// header.
+    pub async fn get_page(&self, path: &str) -> (Value, Option<String>, Option<String>) {
+        let resp = self
+            .reply(warp::test::request().method("GET").path(path))
+            .await;
+        assert_eq!(self.expect_status_code, resp.status());
+        let link = |rel: &str| {
+            let suffix = format!(">; rel=\"{}\"", rel);
+            resp.headers().get(LINK).and_then(|header| {
+                header
+                    .to_str()
+                    .unwrap()
+                    .split(", ")
+                    .find(|link| link.ends_with(&suffix))
+                    .map(|link| link[1..link.len() - suffix.len()].to_owned())
+            })
+        };
+        let (next, prev) = (link("next"), link("prev"));
+        let body = serde_json::from_slice(resp.body()).expect("response body is JSON");
+        (body, next, prev)
+    }
+
     pub async fn post(&self, path: &str, body: Value) -> Value {
         self.execute(warp::test::request().method("POST").path(path).json(&body))
             .await
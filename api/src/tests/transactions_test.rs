@@ -636,7 +636,7 @@ async fn test_post_transaction_rejected_by_mempool() {
         resp,
         json!({
           "code": 400,
-          "message": "transaction is rejected: InvalidUpdate - Transaction already in mempool"
+          "message": "transaction is rejected: InvalidUpdate - gas unit price: 0, min gas unit price to replace: 1"
         }),
     );
 }
@@ -869,8 +869,20 @@ async fn test_get_pending_transaction_by_hash() {
 
     let txn_hash = pending_txn["hash"].as_str().unwrap();
 
-    let txn = context.get(&format!("/transactions/{}", txn_hash)).await;
+    let mut txn = context.get(&format!("/transactions/{}", txn_hash)).await;
+    let status_history = txn
+        .as_object_mut()
+        .unwrap()
+        .remove("status_history")
+        .unwrap();
     assert_json(txn, pending_txn);
+    let statuses: Vec<_> = status_history
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| event["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, vec!["accepted", "ready"]);
 
     let not_found = context
         .expect_status_code(404)
@@ -1149,6 +1161,45 @@ async fn test_get_account_transactions_filter_transactions_by_start_sequence_num
     assert_json(txns, json!([]));
 }
 
+#[tokio::test]
+async fn test_get_account_transactions_in_descending_order() {
+    let mut context = new_test_context();
+    let mut tc_account = context.tc_account();
+    let mut txns = vec![];
+    for _ in 0..3 {
+        let account = context.gen_account();
+        txns.push(context.create_parent_vasp_by_account(&mut tc_account, &account));
+    }
+    context.commit_block(&txns).await;
+    let path = format!("/accounts/{}/transactions", context.tc_account().address());
+
+    let (page, next, prev) = context
+        .get_page(&format!("{}?order=desc&limit=2", path))
+        .await;
+    assert_eq!(page[0]["sequence_number"], "2");
+    assert_eq!(page[1]["sequence_number"], "1");
+    assert_eq!(prev, None);
+
+    let (page, next, prev) = context.get_page(&next.unwrap()).await;
+    assert_eq!(page.as_array().unwrap().len(), 1);
+    assert_eq!(page[0]["sequence_number"], "0");
+    assert_eq!(next, None);
+
+    let (page, _, _) = context.get_page(&prev.unwrap()).await;
+    assert_eq!(page[0]["sequence_number"], "2");
+}
+
+#[tokio::test]
+async fn test_get_account_transactions_in_descending_order_for_account_without_transactions() {
+    let context = new_test_context();
+    let (page, next, prev) = context
+        .get_page("/accounts/0xdd/transactions?order=desc")
+        .await;
+    assert_json(page, json!([]));
+    assert_eq!(next, None);
+    assert_eq!(prev, None);
+}
+
 #[tokio::test]
 async fn test_get_account_transactions_filter_transactions_by_limit() {
     let mut context = new_test_context();
@@ -1681,3 +1732,239 @@ fn gen_string(len: u64) -> String {
         .map(char::from)
         .collect()
 }
+
+#[tokio::test]
+async fn test_simulate_transaction() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let body = user_transaction_request(&context, txn);
+
+    let resp = context.post("/transactions/simulate", body.clone()).await;
+    assert_eq!(resp["success"], true);
+    assert_eq!(resp["vm_status"], "Executed successfully");
+    assert!(resp["gas_used"].as_str().unwrap().parse::<u64>().unwrap() > 0);
+    let address = account.address().to_hex_literal();
+    assert!(resp["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|change| change["type"] == "write_resource" && change["address"] == address));
+    let event = find_value(&resp["events"], |event| {
+        event["type"] == "0x1::DiemAccount::CreateAccountEvent"
+    });
+    assert_eq!(event["data"]["created"], address);
+
+    // Nothing is committed, so the same transaction can still be submitted.
+    assert_eq!(context.get_latest_ledger_info().version(), 0);
+    context
+        .expect_status_code(202)
+        .post("/transactions", body)
+        .await;
+}
+
+#[tokio::test]
+async fn test_simulate_transaction_without_valid_signature() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let mut body = user_transaction_request(&context, txn);
+    body["signature"]["signature"] = json!(format!("0x{}", hex::encode([0u8; 64])));
+
+    let resp = context.post("/transactions/simulate", body).await;
+    assert_eq!(resp["success"], true);
+    assert_eq!(resp["vm_status"], "Executed successfully");
+}
+
+#[tokio::test]
+async fn test_simulate_unsigned_transaction() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let mut body = user_transaction_request(&context, txn);
+    body.as_object_mut().unwrap().remove("signature");
+
+    let resp = context.post("/transactions/simulate", body).await;
+    assert_eq!(resp["success"], true);
+    assert_eq!(resp["vm_status"], "Executed successfully");
+
+    // The sender's authentication key is not changed by the simulation.
+    let tc_address = context.tc_account().address().to_hex_literal();
+    let resources = context
+        .get(&format!("/accounts/{}/resources", tc_address))
+        .await;
+    let tc_account = find_value(&resources, |f| f["type"] == "0x1::DiemAccount::DiemAccount");
+    let simulated_tc_account = resp["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|change| {
+            change["type"] == "write_resource"
+                && change["address"] == tc_address
+                && change["data"]["type"] == "0x1::DiemAccount::DiemAccount"
+        })
+        .unwrap();
+    assert_eq!(
+        simulated_tc_account["data"]["data"]["authentication_key"],
+        tc_account["data"]["authentication_key"]
+    );
+    assert_eq!(simulated_tc_account["data"]["data"]["sequence_number"], "1");
+}
+
+#[tokio::test]
+async fn test_simulate_transaction_with_failed_execution() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    // Only the treasury compliance account can create parent VASP accounts.
+    let mut dd = context.dd_account();
+    let txn = context.create_parent_vasp_by_account(&mut dd, &account);
+    let body = user_transaction_request(&context, txn);
+
+    let resp = context.post("/transactions/simulate", body).await;
+    assert_eq!(resp["success"], false);
+    assert!(resp["vm_status"]
+        .as_str()
+        .unwrap()
+        .starts_with("Move abort"));
+    assert_eq!(resp["events"], json!([]));
+}
+
+#[tokio::test]
+async fn test_simulate_discarded_transaction() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let mut body = user_transaction_request(&context, txn);
+    body["sequence_number"] = json!("100");
+
+    let resp = context
+        .expect_status_code(400)
+        .post("/transactions/simulate", body)
+        .await;
+    assert_json(
+        resp,
+        json!({
+          "code": 400,
+          "message": "invalid transaction: SEQUENCE_NUMBER_TOO_NEW"
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_post_json_transaction_batch() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let body = user_transaction_request(&context, txn.clone());
+    let mut invalid_body = body.clone();
+    invalid_body["sequence_number"] = json!("100");
+    let mut unsigned_body = body.clone();
+    unsigned_body.as_object_mut().unwrap().remove("signature");
+
+    let resp = context
+        .expect_status_code(202)
+        .post(
+            "/transactions/batch",
+            json!([unsigned_body, body, invalid_body]),
+        )
+        .await;
+    assert_json(
+        resp[0].clone(),
+        json!({
+            "accepted": false,
+            "error": "failed to create SignedTransaction from UserTransactionRequest: missing signature",
+        }),
+    );
+    assert_json(
+        resp[1].clone(),
+        json!({
+            "hash": txn.committed_hash().to_hex_literal(),
+            "accepted": true,
+            "mempool_status": "Accepted",
+        }),
+    );
+    assert_eq!(resp[2]["accepted"], false);
+    assert_eq!(resp[2]["mempool_status"], "VmError");
+    assert_eq!(resp[2]["vm_status"], "INVALID_SIGNATURE");
+}
+
+#[tokio::test]
+async fn test_post_bcs_transaction_batch() {
+    let mut context = new_test_context();
+    let account1 = context.gen_account();
+    let account2 = context.gen_account();
+    let txn1 = context.create_parent_vasp(&account1);
+    let txn2 = context.create_parent_vasp(&account2);
+    let body = bcs::to_bytes(&vec![txn1.clone(), txn2.clone()]).unwrap();
+
+    let resp = context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions/batch", &body)
+        .await;
+    assert_json(
+        resp,
+        json!([
+            {
+                "hash": txn1.committed_hash().to_hex_literal(),
+                "accepted": true,
+                "mempool_status": "Accepted",
+            },
+            {
+                "hash": txn2.committed_hash().to_hex_literal(),
+                "accepted": false,
+                "mempool_status": "InvalidUpdate - gas unit price: 0, min gas unit price to replace: 1",
+            },
+        ]),
+    );
+}
+
+#[tokio::test]
+async fn test_post_transaction_batch_exceeding_size_limit() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let txn = context.create_parent_vasp(&account);
+    let max_batch_size = context.context.max_transaction_batch_size();
+    let body = bcs::to_bytes(&vec![txn; max_batch_size + 1]).unwrap();
+
+    let resp = context
+        .expect_status_code(400)
+        .post_bcs_txn("/transactions/batch", &body)
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": format!(
+                "transaction batch size {} exceeds limit {}",
+                max_batch_size + 1,
+                max_batch_size
+            ),
+        }),
+    );
+}
+
+#[tokio::test]
+async fn test_post_empty_transaction_batch() {
+    let mut context = new_test_context();
+
+    let resp = context
+        .expect_status_code(400)
+        .post("/transactions/batch", json!([]))
+        .await;
+    assert_json(
+        resp,
+        json!({
+            "code": 400,
+            "message": "transaction batch is empty",
+        }),
+    );
+}
+
+fn user_transaction_request(context: &TestContext, txn: SignedTransaction) -> serde_json::Value {
+    let pending_txn = context
+        .context
+        .move_converter()
+        .try_into_pending_transaction(txn)
+        .unwrap();
+    serde_json::to_value(&pending_txn).unwrap()
+}
@@ -0,0 +1,109 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::new_test_context;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_execute_view_function() {
+    let mut context = new_test_context();
+    let account = context.gen_account();
+    let address = account.address().to_hex_literal();
+    let txn = context.create_parent_vasp(&account);
+    context.commit_block(&[txn]).await;
+
+    let body = json!({
+        "function": "0x1::DiemAccount::exists_at",
+        "type_arguments": [],
+        "arguments": [address],
+    });
+    let resp = context.post("/view", body.clone()).await;
+    assert_eq!(resp["values"], json!([true]));
+    assert!(resp["gas_used"].as_str().unwrap().parse::<u64>().unwrap() > 0);
+
+    // The account doesn't exist before the transaction creating it.
+    let resp = context.post("/ledger/0/view", body).await;
+    assert_eq!(resp["values"], json!([false]));
+
+    let resp = context
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::DiemAccount::balance",
+                "type_arguments": ["0x1::XUS::XUS"],
+                "arguments": [address],
+            }),
+        )
+        .await;
+    assert_eq!(resp["values"], json!(["0"]));
+}
+
+#[tokio::test]
+async fn test_execute_view_function_aborted() {
+    let context = new_test_context();
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::DiemAccount::balance",
+                "type_arguments": ["0x1::XUS::XUS"],
+                "arguments": [context.tc_account().address().to_hex_literal()],
+            }),
+        )
+        .await;
+    assert!(resp["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Move abort by NOT_PUBLISHED - EPAYER_DOESNT_HOLD_CURRENCY"));
+}
+
+#[tokio::test]
+async fn test_execute_invalid_view_function() {
+    let context = new_test_context();
+    let address = context.tc_account().address().to_hex_literal();
+
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::DiemAccount::sequence_number_for_account",
+                "type_arguments": [],
+                "arguments": [address],
+            }),
+        )
+        .await;
+    assert_eq!(
+        resp["message"],
+        "invalid view function: could not find public function by 0x1::DiemAccount::sequence_number_for_account"
+    );
+
+    let resp = context
+        .expect_status_code(400)
+        .post(
+            "/view",
+            json!({
+                "function": "0x1::DiemAccount::extract_withdraw_capability",
+                "type_arguments": [],
+                "arguments": [],
+            }),
+        )
+        .await;
+    assert_eq!(
+        resp["message"],
+        "invalid view function: function 0x1::DiemAccount::extract_withdraw_capability takes a signer, which can't be given to a view function"
+    );
+
+    context
+        .expect_status_code(404)
+        .post(
+            "/ledger/100/view",
+            json!({
+                "function": "0x1::DiemAccount::exists_at",
+                "type_arguments": [],
+                "arguments": [address],
+            }),
+        )
+        .await;
+}
@@ -4,23 +4,27 @@
 use crate::{
     context::Context,
     failpoint::fail_point,
+    mempool,
     metrics::metrics,
-    page::Page,
+    page::{CursorPage, Page},
     param::{AddressParam, TransactionIdParam},
 };
 
 use diem_api_types::{
     mime_types::BCS_SIGNED_TRANSACTION, Error, LedgerInfo, Response, Transaction, TransactionData,
-    TransactionId, TransactionOnChainData, TransactionSigningMessage, UserTransactionRequest,
+    TransactionId, TransactionOnChainData, TransactionSigningMessage, TransactionSubmissionResult,
+    UserTransactionRequest,
 };
 use diem_types::{
+    account_address::AccountAddress,
     mempool_status::MempoolStatusCode,
-    transaction::{RawTransaction, SignedTransaction},
+    transaction::{RawTransaction, SignedTransaction, TransactionStatus},
 };
 
-use anyhow::Result;
+use anyhow::{format_err, Result};
+use std::time::Duration;
 use warp::{
-    filters::BoxedFilter,
+    filters::{path::FullPath, BoxedFilter},
     http::{header::CONTENT_TYPE, StatusCode},
     reply, Filter, Rejection, Reply,
 };
@@ -46,11 +50,12 @@ pub fn get_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
-// GET /accounts/{address}/transactions?start={u64}&limit={u16}
+// GET /accounts/{address}/transactions?start={u64}&cursor={cursor}&limit={u16}&order={asc|desc}
 pub fn get_account_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "transactions")
         .and(warp::get())
-        .and(warp::query::<Page>())
+        .and(warp::query::<CursorPage>())
+        .and(warp::path::full())
         .and(context.filter())
         .and_then(handle_get_account_transactions)
         .with(metrics("get_account_transactions"))
@@ -94,6 +99,39 @@ pub fn submit_bcs_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+// POST /transactions/batch with JSON
+pub fn submit_json_transaction_batch(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("transactions" / "batch")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<Vec<UserTransactionRequest>>())
+        .and(context.filter())
+        .and_then(handle_submit_json_transaction_batch)
+        .with(metrics("submit_json_transaction_batch"))
+        .boxed()
+}
+
+// POST /transactions/batch with BCS
+pub fn submit_bcs_transaction_batch(context: Context) -> BoxedFilter<(impl Reply,)> {
+    // See `submit_bcs_transactions` for why the content-type is checked.
+    warp::path!("transactions" / "batch")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::header::exact(
+            CONTENT_TYPE.as_str(),
+            BCS_SIGNED_TRANSACTION,
+        ))
+        .and(warp::body::bytes())
+        .and(context.filter())
+        .and_then(handle_submit_bcs_transaction_batch)
+        .with(metrics("submit_bcs_transaction_batch"))
+        .boxed()
+}
+
 // POST /transactions/signing_message
 pub fn create_signing_message(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions" / "signing_message")
@@ -108,6 +146,20 @@ pub fn create_signing_message(context: Context) -> BoxedFilter<(impl Reply,)> {
         .boxed()
 }
 
+// POST /transactions/simulate
+pub fn simulate_transaction(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("transactions" / "simulate")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<UserTransactionRequest>())
+        .and(context.filter())
+        .and_then(handle_simulate_transaction)
+        .with(metrics("simulate_transaction"))
+        .boxed()
+}
+
 async fn handle_get_transaction(
     id: TransactionIdParam,
     context: Context,
@@ -125,11 +177,12 @@ async fn handle_get_transactions(page: Page, context: Context) -> Result<impl Re
 
 async fn handle_get_account_transactions(
     address: AddressParam,
-    page: Page,
+    page: CursorPage,
+    path: FullPath,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_transactions")?;
-    Ok(Transactions::new(context)?.list_by_account(address, page)?)
+    Ok(Transactions::new(context)?.list_by_account(address, page, path)?)
 }
 
 async fn handle_submit_json_transactions(
@@ -152,6 +205,26 @@ async fn handle_submit_bcs_transactions(
     Ok(Transactions::new(context)?.create(txn).await?)
 }
 
+async fn handle_submit_json_transaction_batch(
+    body: Vec<UserTransactionRequest>,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_submit_json_transaction_batch")?;
+    Ok(Transactions::new(context)?
+        .create_batch_from_requests(body)
+        .await?)
+}
+
+async fn handle_submit_bcs_transaction_batch(
+    body: bytes::Bytes,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_submit_bcs_transaction_batch")?;
+    let txns = bcs::from_bytes(&body)
+        .map_err(|err| Error::invalid_request_body(format!("deserialize error: {}", err)))?;
+    Ok(Transactions::new(context)?.create_batch(txns).await?)
+}
+
 async fn handle_create_signing_message(
     body: UserTransactionRequest,
     context: Context,
@@ -160,6 +233,14 @@ async fn handle_create_signing_message(
     Ok(Transactions::new(context)?.signing_message(body)?)
 }
 
+async fn handle_simulate_transaction(
+    body: UserTransactionRequest,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_simulate_transaction")?;
+    Ok(Transactions::new(context)?.simulate(body).await?)
+}
+
 struct Transactions {
     ledger_info: LedgerInfo,
     context: Context,
@@ -213,6 +294,89 @@ impl Transactions {
         }
     }
 
+    pub async fn create_batch_from_requests(
+        self,
+        reqs: Vec<UserTransactionRequest>,
+    ) -> Result<impl Reply, Error> {
+        let converter = self.context.move_converter();
+        let txns = reqs
+            .into_iter()
+            .map(|req| {
+                converter
+                    .try_into_signed_transaction(req, self.context.chain_id())
+                    .map_err(|e| {
+                        format!(
+                            "failed to create SignedTransaction from UserTransactionRequest: {}",
+                            e
+                        )
+                    })
+            })
+            .collect();
+        self.submit_batch(txns).await
+    }
+
+    pub async fn create_batch(self, txns: Vec<SignedTransaction>) -> Result<impl Reply, Error> {
+        self.submit_batch(txns.into_iter().map(Ok).collect()).await
+    }
+
+    /// Submits transactions to mempool at once, replying with the outcome of each, in the same
+    /// order, whether it's accepted or not. Transactions that could not be created are not
+    /// submitted, and are reported with their error instead.
+    async fn submit_batch(
+        self,
+        txns: Vec<Result<SignedTransaction, String>>,
+    ) -> Result<impl Reply, Error> {
+        if txns.is_empty() {
+            return Err(Error::bad_request("transaction batch is empty"));
+        }
+        let max_batch_size = self.context.max_transaction_batch_size();
+        if txns.len() > max_batch_size {
+            return Err(Error::bad_request(format!(
+                "transaction batch size {} exceeds limit {}",
+                txns.len(),
+                max_batch_size
+            )));
+        }
+
+        let submitted: Vec<_> = txns
+            .iter()
+            .filter_map(|txn| txn.as_ref().ok().cloned())
+            .collect();
+        let mut statuses = self
+            .context
+            .submit_transactions(submitted)
+            .await?
+            .into_iter();
+        let mut results = Vec::with_capacity(txns.len());
+        for txn in txns {
+            let txn = match txn {
+                Ok(txn) => txn,
+                Err(err) => {
+                    results.push(TransactionSubmissionResult::error(None, err));
+                    continue;
+                }
+            };
+            let hash = txn.committed_hash();
+            let status = statuses.next().ok_or_else(|| {
+                Error::internal(format_err!(
+                    "missing mempool status of transaction {}",
+                    hash
+                ))
+            })?;
+            results.push(match status {
+                Ok((mempool_status, vm_status)) => {
+                    TransactionSubmissionResult::new(hash, &mempool_status, vm_status)
+                }
+                Err(err) => TransactionSubmissionResult::error(
+                    Some(hash),
+                    format!("failed to validate transaction: {}", err),
+                ),
+            });
+        }
+        let resp = Response::new(self.ledger_info, &results)?;
+        Ok(reply::with_status(resp, StatusCode::ACCEPTED))
+    }
+
     pub fn list(self, page: Page) -> Result<impl Reply, Error> {
         let ledger_version = self.ledger_info.version();
         let limit = page.limit()?;
@@ -230,34 +394,38 @@ impl Transactions {
         self.render_transactions(data)
     }
 
-    pub fn list_by_account(self, address: AddressParam, page: Page) -> Result<impl Reply, Error> {
-        let data = self.context.get_account_transactions(
-            address.parse("account address")?.into(),
-            page.start(0, u64::MAX)?,
-            page.limit()?,
-            self.ledger_info.version(),
+    pub fn list_by_account(
+        self,
+        address: AddressParam,
+        page: CursorPage,
+        path: FullPath,
+    ) -> Result<impl Reply, Error> {
+        let address: AccountAddress = address.parse("account address")?.into();
+        let ledger_version = self.ledger_info.version();
+        let (data, links) = page.read(
+            || {
+                // Transactions sent by the account have sequence numbers up to the one before the
+                // account sequence number.
+                let sequence_number =
+                    match self.context.get_account_state(address, ledger_version)? {
+                        Some(account_state) => account_state
+                            .get_account_resource()?
+                            .map_or(0, |account| account.sequence_number()),
+                        None => 0,
+                    };
+                Ok(sequence_number.checked_sub(1))
+            },
+            |start, limit| {
+                Ok(self
+                    .context
+                    .get_account_transactions(address, start, limit, ledger_version)?)
+            },
         )?;
-        self.render_transactions(data)
+        links.reply(path, self.render_transactions(data)?)
     }
 
-    fn render_transactions(self, data: Vec<TransactionOnChainData>) -> Result<impl Reply, Error> {
-        if data.is_empty() {
-            let txns: Vec<Transaction> = vec![];
-            return Response::new(self.ledger_info, &txns);
-        }
-        let first_version = data[0].version;
-        let mut timestamp = self.context.get_block_timestamp(first_version)?;
-        let converter = self.context.move_converter();
-        let txns: Vec<Transaction> = data
-            .into_iter()
-            .map(|t| {
-                let txn = converter.try_into_onchain_transaction(timestamp, t)?;
-                // update timestamp, when txn is metadata block transaction
-                // new timestamp is used for the following transactions
-                timestamp = txn.timestamp();
-                Ok(txn)
-            })
-            .collect::<Result<_>>()?;
+    fn render_transactions(self, data: Vec<TransactionOnChainData>) -> Result<Response, Error> {
+        let txns = render_onchain_transactions(&self.context, data)?;
         Response::new(self.ledger_info, &txns)
     }
 
@@ -274,7 +442,18 @@ impl Transactions {
                 let timestamp = self.context.get_block_timestamp(txn.version)?;
                 converter.try_into_onchain_transaction(timestamp, txn)?
             }
-            TransactionData::Pending(txn) => converter.try_into_pending_transaction(*txn)?,
+            TransactionData::Pending(txn) => {
+                let mut txn = converter.try_into_pending_transaction(*txn)?;
+                if let Transaction::PendingTransaction(pending) = &mut txn {
+                    let history = self
+                        .context
+                        .get_mempool_transaction_status_history(pending.hash.into())
+                        .await?;
+                    pending.status_history =
+                        Some(history.into_iter().map(mempool::status_event).collect());
+                }
+                txn
+            }
         };
 
         Response::new(self.ledger_info, &txn)
@@ -294,6 +473,63 @@ impl Transactions {
         )
     }
 
+    /// Executes the transaction against the latest ledger state without committing it. The
+    /// signature is not verified and may be omitted, so that a transaction can be simulated before
+    /// it's signed.
+    pub async fn simulate(self, req: UserTransactionRequest) -> Result<impl Reply, Error> {
+        let raw_txn = {
+            let converter = self.context.move_converter();
+            if req.signature.is_some() {
+                converter
+                    .try_into_signed_transaction(req, self.context.chain_id())
+                    .map_err(|e| {
+                        Error::invalid_request_body(format!(
+                            "failed to create SignedTransaction from UserTransactionRequest: {}",
+                            e
+                        ))
+                    })?
+                    .into_raw_transaction()
+            } else {
+                converter
+                    .try_into_raw_transaction(req, self.context.chain_id())
+                    .map_err(|e| {
+                        Error::invalid_request_body(format!(
+                            "failed to create RawTransaction from UserTransactionRequest: {}",
+                            e
+                        ))
+                    })?
+            }
+        };
+
+        // The VM runs on a blocking thread so that simulations don't hold up the other requests,
+        // and the timeout bounds how long the request waits, e.g. when the DB is slow.
+        let version = self.ledger_info.version();
+        let timeout_ms = self.context.simulate_transaction_timeout_ms();
+        let context = self.context.clone();
+        let output = tokio::time::timeout(
+            Duration::from_millis(timeout_ms),
+            tokio::task::spawn_blocking(move || context.simulate_transaction(raw_txn, version)),
+        )
+        .await
+        .map_err(|_| {
+            Error::bad_request(format!(
+                "transaction simulation did not finish within {}ms",
+                timeout_ms
+            ))
+        })?
+        .map_err(|e| Error::internal(e.into()))??;
+
+        let converter = self.context.move_converter();
+        if let TransactionStatus::Discard(status_code) = output.status() {
+            return Err(Error::bad_request(format!(
+                "invalid transaction: {:?}",
+                status_code
+            )));
+        }
+        let simulated_txn = converter.try_into_simulated_transaction(output)?;
+        Response::new(self.ledger_info, &simulated_txn)
+    }
+
     fn transaction_not_found(&self, id: TransactionId) -> Error {
         Error::not_found("transaction", id, self.ledger_info.version())
     }
@@ -327,3 +563,27 @@ impl Transactions {
         })
     }
 }
+
+/// Converts on-chain transactions, in order of version, into their API representation.
+pub(crate) fn render_onchain_transactions(
+    context: &Context,
+    data: Vec<TransactionOnChainData>,
+) -> Result<Vec<Transaction>, Error> {
+    if data.is_empty() {
+        return Ok(vec![]);
+    }
+    let first_version = data[0].version;
+    let mut timestamp = context.get_block_timestamp(first_version)?;
+    let converter = context.move_converter();
+    let txns = data
+        .into_iter()
+        .map(|t| {
+            let txn = converter.try_into_onchain_transaction(timestamp, t)?;
+            // update timestamp, when txn is metadata block transaction
+            // new timestamp is used for the following transactions
+            timestamp = txn.timestamp();
+            Ok(txn)
+        })
+        .collect::<Result<_>>()?;
+    Ok(txns)
+}
@@ -0,0 +1,78 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{context::Context, failpoint::fail_point, metrics::metrics, param::LedgerVersionParam};
+
+use diem_api_types::{Error, Response, TransactionId, ViewFunctionRequest};
+
+use std::time::Duration;
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+// POST /view
+pub fn execute_view_function(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("view")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<ViewFunctionRequest>())
+        .and(context.filter())
+        .map(|req, ctx| (None, req, ctx))
+        .untuple_one()
+        .and_then(handle_execute_view_function)
+        .with(metrics("execute_view_function"))
+        .boxed()
+}
+
+// POST /ledger/<version>/view
+pub fn execute_view_function_by_ledger_version(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("ledger" / LedgerVersionParam / "view")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(
+            context.content_length_limit(),
+        ))
+        .and(warp::body::json::<ViewFunctionRequest>())
+        .and(context.filter())
+        .map(|version, req, ctx| (Some(version), req, ctx))
+        .untuple_one()
+        .and_then(handle_execute_view_function)
+        .with(metrics("execute_view_function_by_ledger_version"))
+        .boxed()
+}
+
+async fn handle_execute_view_function(
+    ledger_version: Option<LedgerVersionParam>,
+    req: ViewFunctionRequest,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_execute_view_function")?;
+    let ledger_info = context.get_latest_ledger_info()?;
+    let version = ledger_version
+        .map(|v| v.parse("ledger version"))
+        .unwrap_or_else(|| Ok(ledger_info.version()))?;
+    if version > ledger_info.version() {
+        return Err(Error::not_found(
+            "ledger",
+            TransactionId::Version(version),
+            ledger_info.version(),
+        )
+        .into());
+    }
+
+    // Gas metering bounds how long the function runs, the timeout bounds how long the request
+    // waits, e.g. when the DB is slow.
+    let timeout_ms = context.view_function_timeout_ms();
+    let output = tokio::time::timeout(
+        Duration::from_millis(timeout_ms),
+        tokio::task::spawn_blocking(move || context.execute_view_function(req, version)),
+    )
+    .await
+    .map_err(|_| {
+        Error::bad_request(format!(
+            "view function did not return within {}ms",
+            timeout_ms
+        ))
+    })?
+    .map_err(|e| Error::internal(e.into()))??;
+    Ok(Response::new(ledger_info, &output)?)
+}
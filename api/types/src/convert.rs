@@ -4,8 +4,9 @@
 use crate::{
     Bytecode, DirectWriteSet, Event, HexEncodedBytes, MoveFunction, MoveModuleBytecode,
     MoveResource, MoveScriptBytecode, MoveType, MoveValue, ScriptFunctionId, ScriptFunctionPayload,
-    ScriptPayload, ScriptWriteSet, Transaction, TransactionInfo, TransactionOnChainData,
-    TransactionPayload, UserTransactionRequest, WriteSet, WriteSetChange, WriteSetPayload,
+    ScriptPayload, ScriptWriteSet, SimulatedTransaction, Transaction, TransactionInfo,
+    TransactionOnChainData, TransactionPayload, UserTransactionRequest, ViewFunctionOutput,
+    ViewFunctionRequest, WriteSet, WriteSetChange, WriteSetPayload,
 };
 use diem_crypto::HashValue;
 use diem_transaction_builder::error_explain;
@@ -13,20 +14,23 @@ use diem_types::{
     access_path::{AccessPath, Path},
     chain_id::ChainId,
     contract_event::ContractEvent,
-    transaction::{ModuleBundle, RawTransaction, Script, ScriptFunction, SignedTransaction},
-    vm_status::{AbortLocation, KeptVMStatus},
+    transaction::{
+        ModuleBundle, RawTransaction, Script, ScriptFunction, SignedTransaction, TransactionOutput,
+        TransactionStatus,
+    },
+    vm_status::{AbortLocation, KeptVMStatus, VMStatus},
     write_set::WriteOp,
 };
 use move_binary_format::file_format::FunctionHandleIndex;
 use move_core_types::{
     identifier::Identifier,
-    language_storage::{ModuleId, StructTag},
+    language_storage::{ModuleId, StructTag, TypeTag},
     resolver::MoveResolver,
 };
 use move_resource_viewer::MoveValueAnnotator;
 
 use crate::transaction::ModuleBundlePayload;
-use anyhow::{ensure, format_err, Result};
+use anyhow::{bail, ensure, format_err, Result};
 use serde_json::Value;
 use std::{
     convert::{TryFrom, TryInto},
@@ -108,6 +112,29 @@ impl<'a, R: MoveResolver + ?Sized> MoveConverter<'a, R> {
         }
     }
 
+    pub fn try_into_simulated_transaction(
+        &self,
+        output: TransactionOutput,
+    ) -> Result<SimulatedTransaction> {
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status,
+            status => bail!("simulated transaction is not kept: {:?}", status),
+        };
+        Ok(SimulatedTransaction {
+            gas_used: output.gas_used().into(),
+            success: status.is_success(),
+            vm_status: self.explain_vm_status(status),
+            changes: output
+                .write_set()
+                .iter()
+                .map(|(access_path, op)| {
+                    self.try_into_write_set_change(access_path.clone(), op.clone())
+                })
+                .collect::<Result<_>>()?,
+            events: self.try_into_events(output.events())?,
+        })
+    }
+
     pub fn try_into_transaction_payload(
         &self,
         payload: diem_types::transaction::TransactionPayload,
@@ -349,6 +376,105 @@ impl<'a, R: MoveResolver + ?Sized> MoveConverter<'a, R> {
         Ok(ret)
     }
 
+    /// Finds the public function a view function request calls, and converts the request into a
+    /// call with BCS serialized arguments. Returns the call along with the types of the values
+    /// the function returns.
+    pub fn try_into_view_function_call(
+        &self,
+        req: ViewFunctionRequest,
+    ) -> Result<(ScriptFunction, Vec<TypeTag>)> {
+        let ViewFunctionRequest {
+            function,
+            type_arguments,
+            arguments,
+        } = req;
+
+        let code = self.inner.get_module(&function.module.clone().into())? as Rc<dyn Bytecode>;
+        let func = code
+            .find_public_function(function.name.as_ident_str())
+            .ok_or_else(|| format_err!("could not find public function by {}", function))?;
+        ensure!(
+            func.generic_type_params.len() == type_arguments.len(),
+            "expect {} type arguments for function {}, but got {}",
+            func.generic_type_params.len(),
+            function,
+            type_arguments.len()
+        );
+        ensure!(
+            !func.params.iter().any(MoveType::is_signer),
+            "function {} takes a signer, which can't be given to a view function",
+            function
+        );
+        let ty_args = type_arguments
+            .into_iter()
+            .map(|v| v.try_into())
+            .collect::<Result<Vec<TypeTag>>>()?;
+        let return_types = func
+            .return_
+            .iter()
+            .map(|typ| Self::try_into_instantiated_type_tag(typ, &ty_args))
+            .collect::<Result<_>>()?;
+        let args = self
+            .try_into_move_values(func, arguments)?
+            .iter()
+            .map(bcs::to_bytes)
+            .collect::<Result<_, bcs::Error>>()?;
+
+        Ok((
+            ScriptFunction::new(function.module.into(), function.name, ty_args, args),
+            return_types,
+        ))
+    }
+
+    pub fn try_into_view_function_output(
+        &self,
+        return_types: &[TypeTag],
+        return_values: Vec<Vec<u8>>,
+        gas_used: u64,
+    ) -> Result<ViewFunctionOutput> {
+        let values = return_types
+            .iter()
+            .zip(return_values)
+            .map(|(typ, bytes)| self.inner.view_value(typ, &bytes)?.try_into())
+            .collect::<Result<_>>()?;
+        Ok(ViewFunctionOutput {
+            values,
+            gas_used: gas_used.into(),
+        })
+    }
+
+    /// Explains why a view function failed to execute.
+    pub fn explain_view_function_error(&self, status: VMStatus) -> String {
+        match status.keep_or_discard() {
+            Ok(status) => self.explain_vm_status(&status),
+            Err(code) => format!("{:?}", code),
+        }
+    }
+
+    fn try_into_instantiated_type_tag(typ: &MoveType, ty_args: &[TypeTag]) -> Result<TypeTag> {
+        Ok(match typ {
+            MoveType::GenericTypeParam { index } => ty_args
+                .get(*index as usize)
+                .cloned()
+                .ok_or_else(|| format_err!("missing type argument for type parameter {}", index))?,
+            MoveType::Vector { items } => TypeTag::Vector(Box::new(
+                Self::try_into_instantiated_type_tag(items, ty_args)?,
+            )),
+            MoveType::Struct(tag) => TypeTag::Struct(StructTag {
+                address: (&tag.address).into(),
+                module: tag.module.clone(),
+                name: tag.name.clone(),
+                type_params: tag
+                    .generic_type_params
+                    .iter()
+                    .map(|typ| Self::try_into_instantiated_type_tag(typ, ty_args))
+                    .collect::<Result<_>>()?,
+            }),
+            MoveType::Reference { .. } => bail!("unsupported return type {:?}", typ),
+            _ => typ.clone().try_into()?,
+        })
+    }
+
     pub fn try_into_move_values(
         &self,
         func: MoveFunction,
@@ -0,0 +1,20 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::U64;
+
+use serde::{Deserialize, Serialize};
+
+/// Suggested gas unit prices for transactions paying gas in a currency, from the gas unit prices
+/// of the transactions in mempool and of recently committed transactions.
+///
+/// All prices are 0 if there are no such transactions.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct GasPriceEstimate {
+    /// The 25th percentile, for transactions that can wait.
+    pub low: U64,
+    /// The 50th percentile.
+    pub median: U64,
+    /// The 90th percentile, for transactions to be committed ahead of most others.
+    pub high: U64,
+}
@@ -7,12 +7,16 @@ mod bytecode;
 mod convert;
 mod error;
 mod event_key;
+mod gas_price_estimate;
 mod hash;
 mod ledger_info;
+mod mempool;
 pub mod mime_types;
 mod move_types;
+mod proof;
 mod response;
 mod transaction;
+mod view_function;
 
 pub use account::AccountData;
 pub use address::Address;
@@ -20,18 +24,25 @@ pub use bytecode::Bytecode;
 pub use convert::MoveConverter;
 pub use error::Error;
 pub use event_key::EventKey;
+pub use gas_price_estimate::GasPriceEstimate;
 pub use hash::HashValue;
 pub use ledger_info::LedgerInfo;
+pub use mempool::{
+    MempoolStats, MempoolTransaction, MempoolTransactionState, MempoolTransactionStatus,
+    MempoolTransactionStatusEvent,
+};
 pub use move_types::{
     HexEncodedBytes, MoveFunction, MoveModule, MoveModuleBytecode, MoveModuleId, MoveResource,
     MoveScriptBytecode, MoveStructTag, MoveStructValue, MoveType, MoveValue, ScriptFunctionId,
     U128, U64,
 };
+pub use proof::{AccountStateProof, WithAccountStateProof};
 pub use response::{Response, X_DIEM_CHAIN_ID, X_DIEM_LEDGER_TIMESTAMP, X_DIEM_LEDGER_VERSION};
 pub use transaction::{
     BlockMetadataTransaction, DirectWriteSet, Event, GenesisTransaction, PendingTransaction,
-    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, Transaction, TransactionData,
-    TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
-    TransactionSigningMessage, UserTransaction, UserTransactionRequest, WriteSet, WriteSetChange,
-    WriteSetPayload,
+    ScriptFunctionPayload, ScriptPayload, ScriptWriteSet, SimulatedTransaction, Transaction,
+    TransactionData, TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
+    TransactionSigningMessage, TransactionSubmissionResult, UserTransaction,
+    UserTransactionRequest, WriteSet, WriteSetChange, WriteSetPayload,
 };
+pub use view_function::{ViewFunctionOutput, ViewFunctionRequest};
@@ -0,0 +1,66 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Transaction, U64};
+
+use serde::{Deserialize, Serialize};
+
+/// A transaction in mempool, along with its state.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MempoolTransaction {
+    pub state: MempoolTransactionState,
+    pub transaction: Transaction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolTransactionState {
+    /// Can be included in the next block.
+    Ready,
+    /// Waits for transactions with lower sequence numbers from the same sender.
+    Parked,
+}
+
+/// A status change of a transaction on its way from submission to commit.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MempoolTransactionStatusEvent {
+    #[serde(flatten)]
+    pub status: MempoolTransactionStatus,
+    /// Microseconds since the unix epoch.
+    pub timestamp: U64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MempoolTransactionStatus {
+    /// Passed validation and entered mempool.
+    Accepted,
+    /// Waits for transactions with lower sequence numbers from the same sender.
+    Parked,
+    /// Can be included in the next block.
+    Ready,
+    /// Broadcast to `peers` peers in total.
+    Broadcast {
+        peers: U64,
+    },
+    /// Proposed for a block by consensus.
+    PulledIntoBlock,
+    Committed,
+    /// Rejected during execution.
+    Rejected {
+        reason: String,
+    },
+    /// Expired before being committed.
+    Expired,
+    /// Removed from mempool before being committed.
+    Evicted {
+        reason: String,
+    },
+}
+
+/// Number of transactions in mempool by state.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MempoolStats {
+    pub ready: U64,
+    pub parked: U64,
+}
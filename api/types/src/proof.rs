@@ -0,0 +1,123 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{HexEncodedBytes, U64};
+
+use anyhow::Result;
+use diem_types::{
+    account_address::AccountAddress,
+    account_state_blob::{AccountStateBlob, AccountStateWithProof},
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{AccountStateProof as AccountStateProofInner, TransactionInfoWithProof},
+    validator_verifier::ValidatorVerifier,
+};
+use serde::{Deserialize, Serialize};
+
+/// Data read from the state of an account, along with the proof of the account state.
+///
+/// `data` is `None` if the account doesn't exist, in which case `proof` proves that.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WithAccountStateProof<T> {
+    pub data: Option<T>,
+    pub proof: AccountStateProof,
+}
+
+/// The state of an account and its proof, relative to a ledger info signed by the validators, so
+/// that clients don't have to trust the server about the account state, including about the
+/// account not existing.
+///
+/// All fields except `version` are hex-encoded BCS bytes of the corresponding `diem_types` types.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountStateProof {
+    /// `LedgerInfoWithSignatures` the proof is relative to.
+    pub ledger_info: HexEncodedBytes,
+    /// The version of the account state.
+    pub version: U64,
+    /// `AccountStateBlob`, `None` if the account doesn't exist.
+    pub account_state_blob: Option<HexEncodedBytes>,
+    /// `TransactionInfo` at `version`.
+    pub transaction_info: HexEncodedBytes,
+    /// `TransactionAccumulatorProof` of the `TransactionInfo`.
+    pub ledger_info_to_transaction_info_proof: HexEncodedBytes,
+    /// `SparseMerkleProof` of the account state, which is a non-inclusion proof if the account
+    /// doesn't exist.
+    pub transaction_info_to_account_proof: HexEncodedBytes,
+}
+
+impl AccountStateProof {
+    pub fn new(
+        ledger_info: &LedgerInfoWithSignatures,
+        account_state_with_proof: &AccountStateWithProof,
+    ) -> Result<Self> {
+        let proof = &account_state_with_proof.proof;
+        Ok(Self {
+            ledger_info: bcs::to_bytes(ledger_info)?.into(),
+            version: account_state_with_proof.version.into(),
+            account_state_blob: account_state_with_proof
+                .blob
+                .as_ref()
+                .map(bcs::to_bytes)
+                .transpose()?
+                .map(Into::into),
+            transaction_info: bcs::to_bytes(
+                proof.transaction_info_with_proof().transaction_info(),
+            )?
+            .into(),
+            ledger_info_to_transaction_info_proof: bcs::to_bytes(
+                proof
+                    .transaction_info_with_proof()
+                    .ledger_info_to_transaction_info_proof(),
+            )?
+            .into(),
+            transaction_info_to_account_proof: bcs::to_bytes(
+                proof.transaction_info_to_account_proof(),
+            )?
+            .into(),
+        })
+    }
+
+    pub fn ledger_info(&self) -> Result<LedgerInfoWithSignatures> {
+        Ok(bcs::from_bytes(self.ledger_info.inner())?)
+    }
+
+    pub fn account_state_with_proof(&self) -> Result<AccountStateWithProof> {
+        let blob = self
+            .account_state_blob
+            .as_ref()
+            .map(|blob| bcs::from_bytes(blob.inner()))
+            .transpose()?;
+        let proof = AccountStateProofInner::new(
+            TransactionInfoWithProof::new(
+                bcs::from_bytes(self.ledger_info_to_transaction_info_proof.inner())?,
+                bcs::from_bytes(self.transaction_info.inner())?,
+            ),
+            bcs::from_bytes(self.transaction_info_to_account_proof.inner())?,
+        );
+        Ok(AccountStateWithProof::new(self.version.into(), blob, proof))
+    }
+
+    /// Verifies that the ledger info is signed by `validator_verifier` and that the account state
+    /// of `address` is in the ledger, returning the account state blob, or `None` if the account
+    /// doesn't exist.
+    pub fn verify(
+        &self,
+        validator_verifier: &ValidatorVerifier,
+        address: AccountAddress,
+    ) -> Result<Option<AccountStateBlob>> {
+        let ledger_info = self.ledger_info()?;
+        ledger_info.verify_signatures(validator_verifier)?;
+        self.verify_account_state(&ledger_info, address)
+    }
+
+    /// Verifies that the account state of `address` is in the ledger represented by
+    /// `ledger_info`, which is expected to be verified by the caller.
+    pub fn verify_account_state(
+        &self,
+        ledger_info: &LedgerInfoWithSignatures,
+        address: AccountAddress,
+    ) -> Result<Option<AccountStateBlob>> {
+        let account_state_with_proof = self.account_state_with_proof()?;
+        account_state_with_proof.verify(ledger_info.ledger_info(), self.version.into(), address)?;
+        Ok(account_state_with_proof.blob)
+    }
+}
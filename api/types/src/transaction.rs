@@ -2,13 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    Address, EventKey, HashValue, HexEncodedBytes, MoveModuleBytecode, MoveModuleId, MoveResource,
-    MoveScriptBytecode, MoveStructTag, MoveType, MoveValue, ScriptFunctionId, U64,
+    Address, EventKey, HashValue, HexEncodedBytes, MempoolTransactionStatusEvent,
+    MoveModuleBytecode, MoveModuleId, MoveResource, MoveScriptBytecode, MoveStructTag, MoveType,
+    MoveValue, ScriptFunctionId, U64,
 };
 
 use anyhow::bail;
 use diem_crypto::{
     ed25519::{self, Ed25519PublicKey},
+    hash::HashValue as DiemHashValue,
     multi_ed25519::{self, MultiEd25519PublicKey},
     validatable::Validatable,
 };
@@ -16,10 +18,12 @@ use diem_types::{
     account_address::AccountAddress,
     block_metadata::BlockMetadata,
     contract_event::ContractEvent,
+    mempool_status::{MempoolStatus, MempoolStatusCode},
     transaction::{
         authenticator::{AccountAuthenticator, TransactionAuthenticator},
         Script, SignedTransaction, TransactionWithProof,
     },
+    vm_status::DiscardedVMStatus,
 };
 
 use serde::{Deserialize, Serialize};
@@ -155,6 +159,7 @@ impl From<(SignedTransaction, TransactionPayload)> for Transaction {
         Transaction::PendingTransaction(PendingTransaction {
             request: (&txn, payload).into(),
             hash: txn.committed_hash().into(),
+            status_history: None,
         })
     }
 }
@@ -246,6 +251,10 @@ pub struct PendingTransaction {
     pub hash: HashValue,
     #[serde(flatten)]
     pub request: UserTransactionRequest,
+    /// The status changes of the transaction in mempool, from the oldest to the latest. Only set
+    /// when the transaction is looked up by hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_history: Option<Vec<MempoolTransactionStatusEvent>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -271,6 +280,62 @@ pub struct UserTransactionRequest {
     pub signature: Option<TransactionSignature>,
 }
 
+/// The outcome of executing a user transaction against the latest ledger state without
+/// committing it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimulatedTransaction {
+    pub gas_used: U64,
+    pub success: bool,
+    pub vm_status: String,
+    pub changes: Vec<WriteSetChange>,
+    pub events: Vec<Event>,
+}
+
+/// The outcome of submitting one of a batch of transactions to mempool.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSubmissionResult {
+    /// Absent if the transaction could not be created from the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<HashValue>,
+    /// Whether the transaction is accepted, i.e. pending.
+    pub accepted: bool,
+    /// Absent if the transaction did not make it to mempool, see `error`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mempool_status: Option<String>,
+    /// Why the VM discarded the transaction, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm_status: Option<String>,
+    /// Why the transaction could not be created or validated, if so.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TransactionSubmissionResult {
+    pub fn new(
+        hash: DiemHashValue,
+        mempool_status: &MempoolStatus,
+        vm_status: Option<DiscardedVMStatus>,
+    ) -> Self {
+        Self {
+            hash: Some(hash.into()),
+            accepted: mempool_status.code == MempoolStatusCode::Accepted,
+            mempool_status: Some(mempool_status.to_string()),
+            vm_status: vm_status.map(|status| format!("{:?}", status)),
+            error: None,
+        }
+    }
+
+    pub fn error<S: fmt::Display>(hash: Option<DiemHashValue>, error: S) -> Self {
+        Self {
+            hash: hash.map(Into::into),
+            accepted: false,
+            mempool_status: None,
+            vm_status: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct GenesisTransaction {
     #[serde(flatten)]
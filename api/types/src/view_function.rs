@@ -0,0 +1,21 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{MoveType, MoveValue, ScriptFunctionId, U64};
+
+use serde::{Deserialize, Serialize};
+
+/// A request to execute a public Move function for its return values, without changing the
+/// ledger state.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ViewFunctionRequest {
+    pub function: ScriptFunctionId,
+    pub type_arguments: Vec<MoveType>,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ViewFunctionOutput {
+    pub values: Vec<MoveValue>,
+    pub gas_used: U64,
+}
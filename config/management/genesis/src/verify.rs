@@ -217,8 +217,10 @@ fn compute_genesis(
         db_path,
         false,
         None,
+        None,
         RocksdbConfig::default(),
-        true, /* account_count_migration */
+        true,  /* account_count_migration */
+        false, /* account_by_resource_index */
     )
     .map_err(|e| Error::UnexpectedError(e.to_string()))?;
     let db_rw = DbReaderWriter::new(diemdb);
@@ -18,11 +18,39 @@ pub struct ApiConfig {
     // optional for compatible with old configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content_length_limit: Option<u64>,
+    // max number of transactions submitted in one batch
+    pub max_transaction_batch_size: usize,
+    // max number of concurrent subscriptions to /stream
+    pub max_stream_subscriptions: usize,
+    // max gas units a view function may use
+    pub view_function_gas_budget: u64,
+    // max milliseconds to wait for a view function to return
+    pub view_function_timeout_ms: u64,
+    // max milliseconds to wait for a transaction simulation to finish
+    pub simulate_transaction_timeout_ms: u64,
+    // serve GraphQL queries at /graphql
+    pub graphql_enabled: bool,
+    // max depth of GraphQL queries
+    pub graphql_max_depth: usize,
+    // max complexity of GraphQL queries, roughly the number of fields they may resolve
+    pub graphql_max_complexity: usize,
+    // bearer token authorizing the mempool admin routes, which are disabled when it's not set.
+    // The routes are served on `address` along with the others, so when setting a token, keep
+    // `address` local or behind a proxy that doesn't forward them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_token: Option<String>,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_PORT: u16 = 8080;
 pub const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 4 * 1024 * 1024; // 4mb
+pub const DEFAULT_MAX_TRANSACTION_BATCH_SIZE: usize = 1000;
+pub const DEFAULT_MAX_STREAM_SUBSCRIPTIONS: usize = 100;
+pub const DEFAULT_VIEW_FUNCTION_GAS_BUDGET: u64 = 1_000_000;
+pub const DEFAULT_VIEW_FUNCTION_TIMEOUT_MS: u64 = 1000;
+pub const DEFAULT_SIMULATE_TRANSACTION_TIMEOUT_MS: u64 = 1000;
+pub const DEFAULT_GRAPHQL_MAX_DEPTH: usize = 10;
+pub const DEFAULT_GRAPHQL_MAX_COMPLEXITY: usize = 10_000;
 
 fn default_enabled() -> bool {
     true
@@ -38,6 +66,15 @@ impl Default for ApiConfig {
             tls_cert_path: None,
             tls_key_path: None,
             content_length_limit: None,
+            max_transaction_batch_size: DEFAULT_MAX_TRANSACTION_BATCH_SIZE,
+            max_stream_subscriptions: DEFAULT_MAX_STREAM_SUBSCRIPTIONS,
+            view_function_gas_budget: DEFAULT_VIEW_FUNCTION_GAS_BUDGET,
+            view_function_timeout_ms: DEFAULT_VIEW_FUNCTION_TIMEOUT_MS,
+            simulate_transaction_timeout_ms: DEFAULT_SIMULATE_TRANSACTION_TIMEOUT_MS,
+            graphql_enabled: false,
+            graphql_max_depth: DEFAULT_GRAPHQL_MAX_DEPTH,
+            graphql_max_complexity: DEFAULT_GRAPHQL_MAX_COMPLEXITY,
+            admin_token: None,
         }
     }
 }
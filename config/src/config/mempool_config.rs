@@ -10,15 +10,41 @@ pub struct MempoolConfig {
     pub capacity_per_user: usize,
     // number of failovers to broadcast to when the primary network is alive
     pub default_failovers: usize,
+    // number of transactions with the highest priority in mempool whose gas prices are sampled to
+    // estimate gas prices
+    pub gas_price_estimate_mempool_transactions: u64,
+    // number of most recently committed transactions whose gas prices are sampled, along with
+    // the transactions in mempool, to estimate gas prices
+    pub gas_price_estimate_recent_transactions: u64,
+    // how long a gas price estimate is reused before it's estimated again
+    pub gas_price_estimate_ttl_secs: u64,
+    // records the transactions in mempool on disk, so that they are reloaded after a restart
+    pub journal_enabled: bool,
     pub max_broadcasts_per_peer: usize,
     pub mempool_snapshot_interval_secs: u64,
+    // minimum increase, in percent, of the gas unit price of a transaction replacing one with the
+    // same sender and sequence number in mempool
+    pub replacement_gas_price_bump_percent: u64,
     pub shared_mempool_ack_timeout_ms: u64,
     pub shared_mempool_backoff_interval_ms: u64,
     pub shared_mempool_batch_size: usize,
     pub shared_mempool_max_concurrent_inbound_syncs: usize,
+    // rate limits the transactions broadcast by each peer, so that a single peer can't fill
+    // mempool: at most `shared_mempool_peer_txn_bucket_size` transactions in a burst, refilled
+    // by `shared_mempool_peer_txn_bucket_rate` transactions per second. Peers on the validator
+    // network are never rate limited
+    pub shared_mempool_peer_rate_limit_enabled: bool,
+    pub shared_mempool_peer_txn_bucket_rate: usize,
+    pub shared_mempool_peer_txn_bucket_size: usize,
     pub shared_mempool_tick_interval_ms: u64,
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
+    // max number of transactions whose status changes are kept, the ones changed least recently
+    // are dropped first
+    pub transaction_status_history_capacity: usize,
+    // how long the status changes of a transaction are kept after the last one, to be queried
+    // by clients
+    pub transaction_status_ttl_secs: u64,
 }
 
 impl Default for MempoolConfig {
@@ -29,13 +55,23 @@ impl Default for MempoolConfig {
             shared_mempool_batch_size: 100,
             shared_mempool_ack_timeout_ms: 2_000,
             shared_mempool_max_concurrent_inbound_syncs: 2,
+            shared_mempool_peer_rate_limit_enabled: false,
+            shared_mempool_peer_txn_bucket_rate: 2_000,
+            shared_mempool_peer_txn_bucket_size: 10_000,
             max_broadcasts_per_peer: 1,
             mempool_snapshot_interval_secs: 180,
+            replacement_gas_price_bump_percent: 10,
             capacity: 1_000_000,
             capacity_per_user: 100,
             default_failovers: 3,
+            gas_price_estimate_mempool_transactions: 1_000,
+            gas_price_estimate_recent_transactions: 1_000,
+            gas_price_estimate_ttl_secs: 10,
+            journal_enabled: false,
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
+            transaction_status_history_capacity: 100_000,
+            transaction_status_ttl_secs: 600,
         }
     }
 }
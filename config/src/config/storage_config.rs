@@ -32,16 +32,61 @@ impl Default for RocksdbConfig {
     }
 }
 
+/// Configures DB checkpoints taken on request through the backup service. Checkpoints are
+/// disabled unless both the directory and the admin token are set.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct BackupCheckpointConfig {
+    /// Directory checkpoints are created in, each in a sub directory named by the request.
+    pub dir: Option<PathBuf>,
+    /// Bearer token authorizing checkpoint requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin_token: Option<String>,
+}
+
+/// Configures the background consistency checker of DiemDB, which recomputes hashes of the
+/// transactions, events and state committed and compares them with what's recorded in the
+/// transaction infos.
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ConsistencyCheckerConfig {
+    pub enabled: bool,
+    /// Number of versions to check each time the checker wakes up.
+    pub batch_size: usize,
+    /// Interval between batches, in milliseconds. The checker is meant to run with low priority,
+    /// so this should be long enough for it not to compete for IO with normal operation.
+    pub interval_ms: u64,
+}
+
+impl Default for ConsistencyCheckerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            batch_size: 1000,
+            interval_ms: 1000,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct StorageConfig {
     pub address: SocketAddr,
     pub backup_service_address: SocketAddr,
+    pub backup_checkpoint: BackupCheckpointConfig,
     pub dir: PathBuf,
     pub grpc_max_receive_len: Option<i32>,
     /// None disables pruning. The windows is in number of versions, consider system tps
     /// (transaction per second) when calculating proper window.
     pub prune_window: Option<u64>,
+    /// None disables pruning of the ledger history (transactions, transaction infos, events and
+    /// write sets). The window is in number of versions, and once data of a version falls out of
+    /// it, queries on that version fail with a "pruned" error.
+    pub ledger_prune_window: Option<u64>,
+    /// Maintains an index of accounts by the types of resources they hold, which allows listing
+    /// all accounts holding a given resource. Only transactions committed while this is enabled
+    /// are reflected in the index.
+    pub account_by_resource_index: bool,
     #[serde(skip)]
     data_dir: PathBuf,
     /// Read, Write, Connect timeout for network operations in milliseconds
@@ -55,6 +100,8 @@ pub struct StorageConfig {
     /// wiped and re-synced.
     #[serde(default)]
     pub account_count_migration: bool,
+    /// Checks the ledger and state in the DB for consistency in the background.
+    pub consistency_checker: ConsistencyCheckerConfig,
 }
 
 impl Default for StorageConfig {
@@ -62,6 +109,7 @@ impl Default for StorageConfig {
         StorageConfig {
             address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 6666),
             backup_service_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 6186),
+            backup_checkpoint: BackupCheckpointConfig::default(),
             dir: PathBuf::from("db"),
             grpc_max_receive_len: Some(100_000_000),
             // The prune window must at least out live a RPC request because its sub requests are
@@ -71,11 +119,16 @@ impl Default for StorageConfig {
             // conservatively safe minimal prune window. It'll take a few Gigabytes of disk space
             // depending on the size of an average account blob.
             prune_window: Some(1_000_000),
+            // Keep the full ledger history by default, as some clients rely on querying old
+            // transactions and events.
+            ledger_prune_window: None,
+            account_by_resource_index: false,
             data_dir: PathBuf::from("/opt/diem/data"),
             // Default read/write/connection timeout, in milliseconds
             timeout_ms: 30_000,
             rocksdb_config: RocksdbConfig::default(),
             account_count_migration: false,
+            consistency_checker: ConsistencyCheckerConfig::default(),
         }
     }
 }
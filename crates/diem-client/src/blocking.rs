@@ -13,7 +13,7 @@ use crate::{
     views::{
         AccountStateWithProofView, AccountTransactionsWithProofView, AccountView,
         AccumulatorConsistencyProofView, CurrencyInfoView, EventByVersionWithProofView, EventView,
-        EventWithProofView, MetadataView, StateProofView, TransactionView,
+        EventWithProofView, GasPriceEstimateView, MetadataView, StateProofView, TransactionView,
         TransactionsWithProofsView,
     },
     Error, Result, Retry, State,
@@ -296,6 +296,13 @@ impl BlockingClient {
         self.send(MethodRequest::get_event_by_version_with_proof(key, version))
     }
 
+    pub fn get_gas_price_estimate(
+        &self,
+        currency: String,
+    ) -> Result<Response<GasPriceEstimateView>> {
+        self.send(MethodRequest::get_gas_price_estimate(currency))
+    }
+
     /// Return the events of type `T` that have been emitted to `event_key` since `start_seq`, with a max of `limit`
     /// results
     /// Returns an empty vector if there are no such event
@@ -13,7 +13,7 @@ use crate::{
     views::{
         AccountStateWithProofView, AccountTransactionsWithProofView, AccountView,
         AccumulatorConsistencyProofView, CurrencyInfoView, EventByVersionWithProofView, EventView,
-        EventWithProofView, MetadataView, StateProofView, TransactionView,
+        EventWithProofView, GasPriceEstimateView, MetadataView, StateProofView, TransactionView,
         TransactionsWithProofsView,
     },
     Error, Result, Retry, State,
@@ -318,6 +318,14 @@ impl Client {
             .await
     }
 
+    pub async fn get_gas_price_estimate(
+        &self,
+        currency: String,
+    ) -> Result<Response<GasPriceEstimateView>> {
+        self.send(MethodRequest::get_gas_price_estimate(currency))
+            .await
+    }
+
     /// Return the events of type `T` that have been emitted to `event_key` since `start_seq`, with a max of `limit`
     /// results
     /// Returns an empty vector if there are no such events
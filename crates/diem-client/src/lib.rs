@@ -82,6 +82,7 @@ pub enum Method {
     GetAccountTransactionsWithProofs,
     GetEventsWithProofs,
     GetEventByVersionWithProof,
+    GetGasPriceEstimate,
 }
 
 cfg_async_or_blocking! {
@@ -32,6 +32,7 @@ pub enum MethodRequest {
     GetAccountTransactionsWithProofs(AccountAddress, u64, u64, bool, Option<u64>),
     GetEventsWithProofs(EventKey, u64, u64),
     GetEventByVersionWithProof(EventKey, Option<u64>),
+    GetGasPriceEstimate((String,)),
 }
 
 impl MethodRequest {
@@ -144,6 +145,10 @@ impl MethodRequest {
         Self::GetEventByVersionWithProof(key, version)
     }
 
+    pub fn get_gas_price_estimate(currency: String) -> Self {
+        Self::GetGasPriceEstimate((currency,))
+    }
+
     pub fn method(&self) -> Method {
         match self {
             MethodRequest::Submit(_) => Method::Submit,
@@ -166,6 +171,7 @@ impl MethodRequest {
             }
             MethodRequest::GetEventsWithProofs(_, _, _) => Method::GetEventsWithProofs,
             MethodRequest::GetEventByVersionWithProof(_, _) => Method::GetEventByVersionWithProof,
+            MethodRequest::GetGasPriceEstimate(_) => Method::GetGasPriceEstimate,
         }
     }
 }
@@ -6,7 +6,7 @@ use crate::{
     views::{
         AccountStateWithProofView, AccountTransactionsWithProofView, AccountView,
         AccumulatorConsistencyProofView, CurrencyInfoView, EventByVersionWithProofView, EventView,
-        EventWithProofView, MetadataView, StateProofView, TransactionView,
+        EventWithProofView, GasPriceEstimateView, MetadataView, StateProofView, TransactionView,
         TransactionsWithProofsView,
     },
     Error, State,
@@ -84,6 +84,7 @@ pub enum MethodResponse {
     GetAccountTransactionsWithProofs(AccountTransactionsWithProofView),
     GetEventsWithProofs(Vec<EventWithProofView>),
     GetEventByVersionWithProof(EventByVersionWithProofView),
+    GetGasPriceEstimate(GasPriceEstimateView),
 }
 
 impl MethodResponse {
@@ -125,6 +126,9 @@ impl MethodResponse {
             Method::GetEventByVersionWithProof => {
                 MethodResponse::GetEventByVersionWithProof(serde_json::from_value(json)?)
             }
+            Method::GetGasPriceEstimate => {
+                MethodResponse::GetGasPriceEstimate(serde_json::from_value(json)?)
+            }
         };
 
         Ok(response)
@@ -152,6 +156,7 @@ impl MethodResponse {
             }
             MethodResponse::GetEventsWithProofs(_) => Method::GetEventsWithProofs,
             MethodResponse::GetEventByVersionWithProof(_) => Method::GetEventByVersionWithProof,
+            MethodResponse::GetGasPriceEstimate(_) => Method::GetGasPriceEstimate,
         }
     }
 
@@ -280,6 +285,16 @@ impl MethodResponse {
         }
     }
 
+    pub fn try_into_get_gas_price_estimate(self) -> Result<GasPriceEstimateView, Error> {
+        match self {
+            MethodResponse::GetGasPriceEstimate(view) => Ok(view),
+            _ => Err(Error::rpc_response(format!(
+                "expected MethodResponse::GetGasPriceEstimate found MethodResponse::{:?}",
+                self.method()
+            ))),
+        }
+    }
+
     pub fn try_into_get_currencies(self) -> Result<Vec<CurrencyInfoView>, Error> {
         match self {
             MethodResponse::GetCurrencies(currencies) => Ok(currencies),
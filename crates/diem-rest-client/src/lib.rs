@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, Result};
-pub use diem_api_types::{MoveModuleBytecode, PendingTransaction, Transaction};
+pub use diem_api_types::{GasPriceEstimate, MoveModuleBytecode, PendingTransaction, Transaction};
 use diem_client::{Response, State};
 use diem_crypto::HashValue;
 use diem_types::{account_address::AccountAddress, transaction::SignedTransaction};
@@ -274,6 +274,23 @@ impl Client {
         self.json(response).await
     }
 
+    /// Returns suggested gas unit prices for transactions paying gas in `currency`, e.g. "XUS".
+    pub async fn get_gas_price_estimate(
+        &self,
+        currency: &str,
+    ) -> Result<Response<GasPriceEstimate>> {
+        let url = self.base_url.join("gas_price_estimate")?;
+
+        let response = self
+            .inner
+            .get(url)
+            .query(&[("currency", currency)])
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
     pub async fn get_account_resources(
         &self,
         address: AccountAddress,
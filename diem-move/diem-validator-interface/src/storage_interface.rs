@@ -23,8 +23,10 @@ impl DBDebuggerInterface {
             db_root_path,
             true,
             None,
+            None,
             RocksdbConfig::default(),
-            true, /* account_count_migration, ignored anyway */
+            true,  /* account_count_migration, ignored anyway */
+            false, /* account_by_resource_index */
         )?)))
     }
 }
@@ -16,6 +16,7 @@ use crate::{
     errors::expect_only_successful_execution,
     logging::AdapterLogSchema,
     script_to_script_function,
+    simulation::SimulationStateView,
     system_module_names::*,
     transaction_metadata::TransactionMetadata,
     VMExecutor, VMValidator,
@@ -31,8 +32,8 @@ use diem_types::{
         DIEM_VERSION_2, DIEM_VERSION_3,
     },
     transaction::{
-        ChangeSet, ModuleBundle, SignatureCheckedTransaction, SignedTransaction, Transaction,
-        TransactionOutput, TransactionPayload, TransactionStatus, VMValidatorResult,
+        ChangeSet, ModuleBundle, RawTransaction, SignatureCheckedTransaction, SignedTransaction,
+        Transaction, TransactionOutput, TransactionPayload, TransactionStatus, VMValidatorResult,
         WriteSetPayload,
     },
     vm_status::{KeptVMStatus, StatusCode, VMStatus},
@@ -42,9 +43,9 @@ use fail::fail_point;
 use move_binary_format::errors::VMResult;
 use move_core_types::{
     account_address::AccountAddress,
-    gas_schedule::GasAlgebra,
+    gas_schedule::{GasAlgebra, GasUnits},
     identifier::IdentStr,
-    language_storage::ModuleId,
+    language_storage::{ModuleId, TypeTag},
     resolver::MoveResolver,
     transaction_argument::convert_txn_args,
     value::{serialize_values, MoveValue},
@@ -713,6 +714,46 @@ impl DiemVM {
         BLOCK_TRANSACTION_COUNT.observe(count as f64);
         Ok(res)
     }
+
+    /// Executes a user transaction against `state_view`, so that clients can find out its outcome
+    /// and gas usage before submitting it, or before signing it. The prologue requires the
+    /// sender's public key to match its authentication key, so the transaction is signed with a
+    /// throwaway key which the sender's authentication key is overridden with for the duration of
+    /// the simulation. The output must never be committed.
+    pub fn simulate_transaction(
+        raw_txn: RawTransaction,
+        state_view: &impl StateView,
+    ) -> Result<(VMStatus, TransactionOutput)> {
+        let (txn, state_view) = SimulationStateView::new(raw_txn, state_view)?;
+        let data_cache = StateViewCache::new(&state_view);
+        let vm = DiemVM::new(&data_cache);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let (vm_status, output) = vm.execute_user_transaction(&data_cache, &txn, &log_context);
+        Ok((vm_status, state_view.restore_authentication_key(output)?))
+    }
+
+    /// Executes a public Move function against `state_view` for its return values, metering gas
+    /// up to `gas_budget`. Any changes the function makes to global state are discarded. Returns
+    /// the BCS serialized return values and the gas used.
+    pub fn execute_view_function(
+        state_view: &impl StateView,
+        module: &ModuleId,
+        function_name: &IdentStr,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+        gas_budget: u64,
+    ) -> Result<(Vec<Vec<u8>>, u64), VMStatus> {
+        let data_cache = StateViewCache::new(state_view);
+        let vm = DiemVM::new(&data_cache);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let gas_schedule = vm.0.get_gas_schedule(&log_context)?;
+        let mut gas_status = GasStatus::new(gas_schedule, GasUnits::new(gas_budget));
+        let mut session = vm.0.new_session(&data_cache);
+        let return_values = session
+            .execute_function(module, function_name, ty_args, args, &mut gas_status)
+            .map_err(|e| e.into_vm_status())?;
+        Ok((return_values, gas_budget - gas_status.remaining_gas().get()))
+    }
 }
 
 // Executor external API
@@ -119,6 +119,7 @@ pub mod natives;
 pub mod parallel_executor;
 pub mod read_write_set_analysis;
 pub mod script_to_script_function;
+mod simulation;
 pub mod system_module_names;
 pub mod transaction_metadata;
 
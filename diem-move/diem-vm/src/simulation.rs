@@ -0,0 +1,147 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for simulating transactions that have not been signed yet.
+
+use anyhow::{format_err, Result};
+use diem_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
+use diem_state_view::{StateView, StateViewId};
+use diem_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::DiemAccountResource,
+    transaction::{
+        authenticator::AuthenticationKey, RawTransaction, SignatureCheckedTransaction,
+        TransactionOutput,
+    },
+    write_set::{WriteOp, WriteSetMut},
+};
+use move_core_types::move_resource::MoveResource;
+use std::convert::TryFrom;
+
+/// A `StateView` in which the authentication key of the sender of a simulated transaction is
+/// replaced by the one of the throwaway key that the transaction is signed with.
+pub(crate) struct SimulationStateView<'a, S> {
+    state_view: &'a S,
+    sender: AccountAddress,
+    /// The sender's `DiemAccount` resource with the throwaway authentication key.
+    account_resource: Option<Vec<u8>>,
+    /// The sender's actual authentication key.
+    authentication_key: Vec<u8>,
+}
+
+impl<'a, S: StateView> SimulationStateView<'a, S> {
+    /// Signs `raw_txn` with the throwaway key, and overrides the sender's authentication key with
+    /// the one of the throwaway key.
+    pub fn new(
+        raw_txn: RawTransaction,
+        state_view: &'a S,
+    ) -> Result<(SignatureCheckedTransaction, Self)> {
+        let private_key = Ed25519PrivateKey::try_from(&[1u8; Ed25519PrivateKey::LENGTH][..])?;
+        let public_key = private_key.public_key();
+        let simulated_authentication_key = AuthenticationKey::ed25519(&public_key).to_vec();
+
+        let sender = raw_txn.sender();
+        let (account_resource, authentication_key) =
+            match state_view.get(&account_resource_path(sender))? {
+                Some(blob) => {
+                    let resource = bcs::from_bytes::<DiemAccountResource>(&blob)?;
+                    (
+                        Some(replace_authentication_key(
+                            &blob,
+                            &resource,
+                            &simulated_authentication_key,
+                        )?),
+                        resource.authentication_key().to_vec(),
+                    )
+                }
+                None => (None, vec![]),
+            };
+
+        let txn = raw_txn.sign(&private_key, public_key)?;
+        Ok((
+            txn,
+            Self {
+                state_view,
+                sender,
+                account_resource,
+                authentication_key,
+            },
+        ))
+    }
+
+    /// Puts the sender's actual authentication key back into the `DiemAccount` resource written
+    /// by the simulated transaction.
+    pub fn restore_authentication_key(
+        &self,
+        output: TransactionOutput,
+    ) -> Result<TransactionOutput> {
+        let account_resource_path = account_resource_path(self.sender);
+        let gas_used = output.gas_used();
+        let status = output.status().clone();
+        let (write_set, events) = output.into();
+        let write_set = write_set
+            .into_iter()
+            .map(|(access_path, op)| {
+                let op = match op {
+                    WriteOp::Value(blob) if access_path == account_resource_path => {
+                        let resource = bcs::from_bytes::<DiemAccountResource>(&blob)?;
+                        WriteOp::Value(replace_authentication_key(
+                            &blob,
+                            &resource,
+                            &self.authentication_key,
+                        )?)
+                    }
+                    op => op,
+                };
+                Ok((access_path, op))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TransactionOutput::new(
+            WriteSetMut::new(write_set).freeze()?,
+            events,
+            gas_used,
+            status,
+        ))
+    }
+}
+
+impl<S: StateView> StateView for SimulationStateView<'_, S> {
+    fn id(&self) -> StateViewId {
+        self.state_view.id()
+    }
+
+    fn get(&self, access_path: &AccessPath) -> Result<Option<Vec<u8>>> {
+        match &self.account_resource {
+            Some(blob) if *access_path == account_resource_path(self.sender) => {
+                Ok(Some(blob.clone()))
+            }
+            _ => self.state_view.get(access_path),
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.state_view.is_genesis()
+    }
+}
+
+fn account_resource_path(address: AccountAddress) -> AccessPath {
+    AccessPath::new(address, DiemAccountResource::resource_path())
+}
+
+/// Returns `blob`, the serialized `resource`, with its authentication key replaced by
+/// `authentication_key`. The key is the first field of the resource, so only the prefix of the
+/// blob which encodes it needs to be replaced.
+fn replace_authentication_key(
+    blob: &[u8],
+    resource: &DiemAccountResource,
+    authentication_key: &[u8],
+) -> Result<Vec<u8>> {
+    let prefix_len = bcs::to_bytes(resource.authentication_key())?.len();
+    let rest = blob
+        .get(prefix_len..)
+        .ok_or_else(|| format_err!("malformed DiemAccount resource"))?;
+    let mut replaced = bcs::to_bytes(authentication_key)?;
+    replaced.extend_from_slice(rest);
+    Ok(replaced)
+}
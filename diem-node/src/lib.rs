@@ -448,14 +448,20 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             &node_config.storage.dir(),
             false, /* readonly */
             node_config.storage.prune_window,
+            node_config.storage.ledger_prune_window,
             node_config.storage.rocksdb_config,
             node_config.storage.account_count_migration,
+            node_config.storage.account_by_resource_index,
         )
         .expect("DB should open."),
     );
+    if node_config.storage.consistency_checker.enabled {
+        diem_db.start_consistency_checker(node_config.storage.consistency_checker);
+    }
     let _simple_storage_service = start_storage_service_with_db(node_config, Arc::clone(&diem_db));
     let backup_service = start_backup_service(
         node_config.storage.backup_service_address,
+        node_config.storage.backup_checkpoint.clone(),
         Arc::clone(&diem_db),
     );
 
@@ -490,6 +496,13 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         .subscribe_to_reconfigurations()
         .unwrap();
 
+    // Create a commit subscription for the streaming API (if the API is enabled).
+    let api_commit_subscription = if node_config.api.enabled {
+        Some(event_subscription_service.subscribe_to_commits().unwrap())
+    } else {
+        None
+    };
+
     // Create a consensus subscription for reconfiguration events (if this node is a validator).
     let consensus_reconfig_subscription = if node_config.base.role.is_validator() {
         Some(
@@ -620,7 +633,15 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
 
     let api_runtime = if node_config.api.enabled {
         // bootstrap_api bootstraps a web-server serves for both REST and JSON-RPC API
-        bootstrap_api(node_config, chain_id, diem_db, mp_client_sender).unwrap()
+        bootstrap_api(
+            node_config,
+            chain_id,
+            diem_db,
+            mp_client_sender,
+            api_commit_subscription
+                .expect("Commit subscription should exist if the API is enabled"),
+        )
+        .unwrap()
     } else {
         bootstrap_rpc(node_config, chain_id, diem_db, mp_client_sender)
     };
@@ -55,8 +55,10 @@ fn main() -> Result<()> {
             &opt.db_dir,
             false,
             None, /* pruner */
+            None, /* ledger_pruner */
             RocksdbConfig::default(),
             opt.account_count_migration,
+            false, /* account_by_resource_index */
         )
     } else {
         // When not committing, we open the DB as secondary so the tool is usable along side a
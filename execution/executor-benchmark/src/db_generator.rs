@@ -48,8 +48,10 @@ pub fn run(
             &db_dir,
             false,        /* readonly */
             prune_window, /* pruner */
+            None,         /* ledger_pruner */
             RocksdbConfig::default(),
-            true, /* account_count_migration */
+            true,  /* account_count_migration */
+            false, /* account_by_resource_index */
         )
         .expect("DB should open."),
     );
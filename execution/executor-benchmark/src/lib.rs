@@ -30,8 +30,10 @@ pub fn init_db_and_executor(config: &NodeConfig) -> (Arc<dyn DbReader>, BlockExe
             &config.storage.dir(),
             false, /* readonly */
             None,  /* pruner */
+            None,  /* ledger_pruner */
             RocksdbConfig::default(),
-            true, /* account_count_migration */
+            true,  /* account_count_migration */
+            false, /* account_by_resource_index */
         )
         .expect("DB should open."),
     );
@@ -59,8 +61,10 @@ pub fn run_benchmark(
         &source_dir,
         true, /* readonly */
         None, /* pruner */
+        None, /* ledger_pruner */
         RocksdbConfig::default(),
-        true, /* account_count_migration */
+        true,  /* account_count_migration */
+        false, /* account_by_resource_index */
     )
     .expect("db open failure.")
     .create_checkpoint(checkpoint_dir.as_ref().join("diemdb"))
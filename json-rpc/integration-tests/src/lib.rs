@@ -791,7 +791,7 @@ impl PublicUsageTest for MempoolValidationError {
             let resp = env.submit(&txn2);
             assert_eq!(
                 resp.error.expect("error").message,
-                "Server error: Mempool submission error: \"Transaction already in mempool\""
+                "Server error: Mempool submission error: \"gas unit price: 0, min gas unit price to replace: 1\""
                     .to_string(),
             );
         });
@@ -3,7 +3,7 @@
 
 use crate::{methods, runtime, tests};
 use diem_config::config;
-use diem_mempool::MempoolClientRequest;
+use diem_mempool::{GasPriceEstimate, MempoolClientRequest};
 use diem_proptest_helpers::ValueGenerator;
 use diem_types::account_state_blob::AccountStateWithProof;
 use futures::{channel::mpsc::channel, StreamExt};
@@ -80,6 +80,7 @@ fn test_method_fuzzer() {
         &gen_request_params!(["00000000000000000000000000000000000000000a550c18", 0]),
         "get_event_by_version_with_proof",
     );
+    method_fuzzer(&gen_request_params!(["XUS"]), "get_gas_price_estimate");
 }
 
 pub fn method_fuzzer(params_data: &[u8], method: &str) {
@@ -154,14 +155,20 @@ pub fn request_fuzzer(json_request: serde_json::Value) {
         .unwrap();
 
     rt.spawn(async move {
-        if let Some(MempoolClientRequest::SubmitTransaction(_, cb)) = mp_events.next().await {
-            cb.send(Ok((
-                diem_types::mempool_status::MempoolStatus::new(
-                    diem_types::mempool_status::MempoolStatusCode::Accepted,
-                ),
-                None,
-            )))
-            .unwrap();
+        match mp_events.next().await {
+            Some(MempoolClientRequest::SubmitTransaction(_, cb)) => {
+                cb.send(Ok((
+                    diem_types::mempool_status::MempoolStatus::new(
+                        diem_types::mempool_status::MempoolStatusCode::Accepted,
+                    ),
+                    None,
+                )))
+                .unwrap();
+            }
+            Some(MempoolClientRequest::GetGasPriceEstimate(_, cb)) => {
+                cb.send(Ok(GasPriceEstimate::default())).unwrap();
+            }
+            _ => (),
         }
     });
     let body = rt.block_on(async {
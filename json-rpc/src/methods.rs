@@ -8,8 +8,8 @@ use crate::{
     views::{
         AccountStateWithProofView, AccountTransactionsWithProofView, AccountView,
         AccumulatorConsistencyProofView, CurrencyInfoView, EventByVersionWithProofView, EventView,
-        EventWithProofView, MetadataView, StateProofView, TransactionListView, TransactionView,
-        TransactionsWithProofsView,
+        EventWithProofView, GasPriceEstimateView, MetadataView, StateProofView,
+        TransactionListView, TransactionView, TransactionsWithProofsView,
     },
 };
 use anyhow::Result;
@@ -18,11 +18,11 @@ use diem_json_rpc_types::request::{
     GetAccountParams, GetAccountStateWithProofParams, GetAccountTransactionParams,
     GetAccountTransactionsParams, GetAccountTransactionsWithProofsParams,
     GetAccumulatorConsistencyProofParams, GetCurrenciesParams, GetEventByVersionWithProof,
-    GetEventsParams, GetEventsWithProofsParams, GetMetadataParams, GetNetworkStatusParams,
-    GetResourcesParams, GetStateProofParams, GetTransactionsParams,
+    GetEventsParams, GetEventsWithProofsParams, GetGasPriceEstimateParams, GetMetadataParams,
+    GetNetworkStatusParams, GetResourcesParams, GetStateProofParams, GetTransactionsParams,
     GetTransactionsWithProofsParams, MethodRequest, SubmitParams,
 };
-use diem_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use diem_mempool::{GasPriceEstimate, MempoolClientRequest, MempoolClientSender, SubmissionStatus};
 use diem_types::{
     chain_id::ChainId, ledger_info::LedgerInfoWithSignatures, mempool_status::MempoolStatusCode,
     transaction::SignedTransaction,
@@ -80,6 +80,19 @@ impl JsonRpcService {
         callback.await?
     }
 
+    pub async fn gas_price_estimate_request(&self, currency: String) -> Result<GasPriceEstimate> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mempool_sender
+            .clone()
+            .send(MempoolClientRequest::GetGasPriceEstimate(
+                currency, req_sender,
+            ))
+            .await?;
+
+        callback.await?
+    }
+
     pub fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures> {
         fail_point!("jsonrpc::get_latest_ledger_info", |_| {
             Err(anyhow::anyhow!(
@@ -196,6 +209,9 @@ impl<'a> Handler<'a> {
             MethodRequest::GetEventByVersionWithProof(params) => {
                 serde_json::to_value(self.get_event_by_version_with_proof(params).await?)?
             }
+            MethodRequest::GetGasPriceEstimate(params) => {
+                serde_json::to_value(self.get_gas_price_estimate(params).await?)?
+            }
         };
         Ok(response)
     }
@@ -448,4 +464,21 @@ impl<'a> Handler<'a> {
             version,
         )
     }
+
+    /// Returns suggested gas unit prices in a currency, from the gas unit prices of the
+    /// transactions in mempool and of recently committed transactions
+    async fn get_gas_price_estimate(
+        &self,
+        params: GetGasPriceEstimateParams,
+    ) -> Result<GasPriceEstimateView, JsonRpcError> {
+        let estimate = self
+            .service
+            .gas_price_estimate_request(params.currency)
+            .await?;
+        Ok(GasPriceEstimateView {
+            low: estimate.low,
+            median: estimate.median,
+            high: estimate.high,
+        })
+    }
 }
@@ -9,12 +9,12 @@ use crate::{
         MockDiemDB,
     },
     util::{sdk_info_from_user_agent, SdkInfo, SdkLang, SdkVersion},
-    views::VMStatusView,
+    views::{GasPriceEstimateView, VMStatusView},
 };
 use diem_client::{views::TransactionDataView, BlockingClient, MethodRequest};
 use diem_config::{config::DEFAULT_CONTENT_LENGTH_LIMIT, utils};
 use diem_crypto::{ed25519::Ed25519PrivateKey, hash::CryptoHash, HashValue, PrivateKey, Uniform};
-use diem_mempool::MempoolClientRequest;
+use diem_mempool::{GasPriceEstimate, MempoolClientRequest};
 use diem_metrics::get_all_metrics;
 use diem_types::{
     account_address::AccountAddress,
@@ -1112,6 +1112,44 @@ fn test_transaction_submission() {
     assert_eq!(status_code, StatusCode::SENDING_ACCOUNT_DOES_NOT_EXIST);
 }
 
+#[test]
+fn test_get_gas_price_estimate() {
+    let (mp_sender, mut mp_events) = channel(1);
+    let mock_db = mock_db();
+    let port = utils::get_available_port();
+    let address = format!("0.0.0.0:{}", port);
+    let runtime = test_bootstrap(address.parse().unwrap(), Arc::new(mock_db), mp_sender);
+    let client = BlockingClient::new(format!("http://127.0.0.1:{}/v1", port));
+
+    // future that mocks shared mempool execution
+    runtime.spawn(async move {
+        while let Some(MempoolClientRequest::GetGasPriceEstimate(currency, cb)) =
+            mp_events.next().await
+        {
+            assert_eq!(currency, "XUS");
+            cb.send(Ok(GasPriceEstimate {
+                low: 1,
+                median: 2,
+                high: 10,
+            }))
+            .unwrap();
+        }
+    });
+
+    let estimate = client
+        .get_gas_price_estimate("XUS".to_owned())
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        estimate,
+        GasPriceEstimateView {
+            low: 1,
+            median: 2,
+            high: 10,
+        }
+    );
+}
+
 #[test]
 fn test_get_account() {
     let (mock_db, client, _runtime) = create_database_client_and_runtime();
@@ -388,7 +388,11 @@ impl ResourceResolver for MockDiemDB {
     }
 }
 
-impl MoveDbReader for MockDiemDB {}
+impl MoveDbReader for MockDiemDB {
+    fn as_db_reader(self: Arc<Self>) -> Arc<dyn DbReader> {
+        self
+    }
+}
 
 // returns MockDiemDB for unit-testing
 #[allow(unused)]
@@ -57,6 +57,7 @@ pub enum Method {
     GetAccountTransactionsWithProofs,
     GetEventsWithProofs,
     GetEventByVersionWithProof,
+    GetGasPriceEstimate,
 }
 
 impl Method {
@@ -79,6 +80,7 @@ impl Method {
             Method::GetAccountTransactionsWithProofs => "get_account_transactions_with_proofs",
             Method::GetEventsWithProofs => "get_events_with_proofs",
             Method::GetEventByVersionWithProof => "get_event_by_version_with_proof",
+            Method::GetGasPriceEstimate => "get_gas_price_estimate",
         }
     }
 }
@@ -84,6 +84,7 @@ pub enum MethodRequest {
     GetAccountTransactionsWithProofs(GetAccountTransactionsWithProofsParams),
     GetEventsWithProofs(GetEventsWithProofsParams),
     GetEventByVersionWithProof(GetEventByVersionWithProof),
+    GetGasPriceEstimate(GetGasPriceEstimateParams),
 }
 
 impl MethodRequest {
@@ -126,6 +127,9 @@ impl MethodRequest {
             Method::GetEventByVersionWithProof => {
                 MethodRequest::GetEventByVersionWithProof(serde_json::from_value(value)?)
             }
+            Method::GetGasPriceEstimate => {
+                MethodRequest::GetGasPriceEstimate(serde_json::from_value(value)?)
+            }
         };
 
         Ok(method_request)
@@ -154,6 +158,7 @@ impl MethodRequest {
             }
             MethodRequest::GetEventsWithProofs(_) => Method::GetEventsWithProofs,
             MethodRequest::GetEventByVersionWithProof(_) => Method::GetEventByVersionWithProof,
+            MethodRequest::GetGasPriceEstimate(_) => Method::GetGasPriceEstimate,
         }
     }
 }
@@ -383,6 +388,11 @@ pub struct GetEventByVersionWithProof {
     pub version: Option<u64>,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GetGasPriceEstimateParams {
+    pub currency: String,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -996,4 +1006,29 @@ mod test {
         // Object with more params
         parse_ok(json!({ "key": key, "version": 10, "foo": 99 }));
     }
+
+    #[test]
+    fn get_gas_price_estimate() {
+        let parse = |value| serde_json::from_value::<GetGasPriceEstimateParams>(value);
+        let parse_ok = |value| parse(value).unwrap();
+        let parse_err = |value| parse(value).unwrap_err();
+
+        // Array with all params
+        parse_ok(json!(["XUS"]));
+
+        // Array with too many params
+        parse_err(json!(["XUS", 10]));
+
+        // Array with wrong param
+        parse_err(json!([10]));
+
+        // Empty array without required params should fail
+        parse_err(json!([]));
+
+        // Object params
+        parse_ok(json!({ "currency": "XUS" }));
+
+        // Object without required params should fail
+        parse_err(json!({}));
+    }
 }
@@ -1400,6 +1400,13 @@ impl From<&CurrencyInfoResource> for CurrencyInfoView {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GasPriceEstimateView {
+    pub low: u64,
+    pub median: u64,
+    pub high: u64,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct StateProofView {
     pub ledger_info_with_signatures: BytesView,
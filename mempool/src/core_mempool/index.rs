@@ -65,6 +65,11 @@ impl PriorityIndex {
         self.data.iter().rev()
     }
 
+    /// Returns the key of the transaction that is the last to be included in a block.
+    pub(crate) fn lowest(&self) -> Option<&OrderedQueueKey> {
+        self.data.iter().next()
+    }
+
     pub(crate) fn size(&self) -> usize {
         self.data.len()
     }
@@ -285,7 +290,8 @@ impl ParkingLotIndex {
         }
     }
 
-    pub(crate) fn insert(&mut self, txn: &MempoolTransaction) {
+    /// Parks `txn`, returning whether it wasn't parked already.
+    pub(crate) fn insert(&mut self, txn: &MempoolTransaction) -> bool {
         let sender = &txn.txn.sender();
         let sequence_number = txn.txn.sequence_number();
         let is_new_entry = match self.account_indices.get(sender) {
@@ -299,7 +305,7 @@ impl ParkingLotIndex {
                         "Parking lot invariant violated: for account {}, account index exists but missing entry in data",
                         sender
                     );
-                    return;
+                    return false;
                 }
             }
             None => {
@@ -312,6 +318,7 @@ impl ParkingLotIndex {
         if is_new_entry {
             self.size += 1;
         }
+        is_new_entry
     }
 
     pub(crate) fn remove(&mut self, txn: &MempoolTransaction) {
@@ -0,0 +1,38 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::tests::common::TestTransaction;
+use diem_temppath::TempPath;
+
+#[test]
+fn test_put_delete_get() {
+    let tmp_dir = TempPath::new();
+    let journal = MempoolJournal::new(&tmp_dir).unwrap();
+    assert!(journal.get_transactions().unwrap().is_empty());
+
+    let txn_0 = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    let txn_1 = TestTransaction::new(0, 1, 1).make_signed_transaction();
+    journal.put(&txn_1);
+    journal.put(&txn_0);
+    assert_eq!(
+        journal.get_transactions().unwrap(),
+        vec![txn_0.clone(), txn_1.clone()]
+    );
+
+    // a transaction with the same sender and sequence number replaces the recorded one
+    let txn_1_update = TestTransaction::new(0, 1, 5).make_signed_transaction();
+    journal.put(&txn_1_update);
+    assert_eq!(
+        journal.get_transactions().unwrap(),
+        vec![txn_0.clone(), txn_1_update]
+    );
+
+    journal.delete(&txn_0.sender(), 1);
+    assert_eq!(journal.get_transactions().unwrap(), vec![txn_0.clone()]);
+
+    // the journal is read back after reopening
+    drop(journal);
+    let journal = MempoolJournal::new(&tmp_dir).unwrap();
+    assert_eq!(journal.get_transactions().unwrap(), vec![txn_0]);
+}
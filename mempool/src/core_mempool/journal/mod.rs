@@ -0,0 +1,155 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! On-disk journal of the transactions in mempool, so that pending transactions survive a node
+//! restart.
+//!
+//! Writes are queued to a background thread, so that they don't hold up mempool, which records
+//! them while holding its lock.
+
+#[cfg(test)]
+mod journal_test;
+mod schema;
+
+use crate::{
+    counters,
+    logging::{LogEntry, LogSchema},
+};
+use anyhow::{format_err, Result};
+use diem_logger::prelude::*;
+use diem_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use schema::{TransactionSchema, TRANSACTION_CF_NAME};
+use schemadb::{Options, ReadOptions, SchemaBatch, DB, DEFAULT_CF_NAME};
+use std::{
+    path::Path,
+    sync::{mpsc, Arc},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+enum JournalOp {
+    Put(SignedTransaction),
+    Delete(AccountAddress, u64),
+    /// Acknowledged once all the ops queued before it are written.
+    Flush(mpsc::SyncSender<()>),
+}
+
+pub struct MempoolJournal {
+    db: Arc<DB>,
+    ops: Option<mpsc::Sender<JournalOp>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl MempoolJournal {
+    pub fn new<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let column_families = vec![/* UNUSED CF = */ DEFAULT_CF_NAME, TRANSACTION_CF_NAME];
+
+        let path = db_root_path.as_ref().join("mempooldb");
+        let instant = Instant::now();
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = Arc::new(DB::open(path.clone(), "mempool", column_families, &opts)?);
+
+        info!(
+            "Opened MempoolDB at {:?} in {} ms",
+            path,
+            instant.elapsed().as_millis()
+        );
+
+        let (ops, receiver) = mpsc::channel();
+        let writer = {
+            let db = Arc::clone(&db);
+            thread::Builder::new()
+                .name("mempool-journal".into())
+                .spawn(move || write_ops(&db, receiver))?
+        };
+
+        Ok(Self {
+            db,
+            ops: Some(ops),
+            writer: Some(writer),
+        })
+    }
+
+    /// Records a transaction accepted into mempool, replacing any transaction recorded with the
+    /// same sender and sequence number.
+    pub fn put(&self, txn: &SignedTransaction) {
+        self.send(JournalOp::Put(txn.clone()));
+    }
+
+    /// Records the removal of a transaction from mempool.
+    pub fn delete(&self, address: &AccountAddress, sequence_number: u64) {
+        self.send(JournalOp::Delete(*address, sequence_number));
+    }
+
+    /// Get all recorded transactions, ordered by sender and sequence number, once the pending
+    /// writes are done.
+    pub fn get_transactions(&self) -> Result<Vec<SignedTransaction>> {
+        let (flushed, flushed_receiver) = mpsc::sync_channel(1);
+        self.send(JournalOp::Flush(flushed));
+        flushed_receiver
+            .recv()
+            .map_err(|_| format_err!("mempool journal writer is gone"))?;
+
+        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        iter.map(|entry| entry.map(|(_key, txn)| txn)).collect()
+    }
+
+    fn send(&self, op: JournalOp) {
+        let sent = self.ops.as_ref().map(|ops| ops.send(op));
+        if !matches!(sent, Some(Ok(()))) {
+            log_journal_error(Err(format_err!("mempool journal writer is gone")));
+        }
+    }
+}
+
+impl Drop for MempoolJournal {
+    /// Waits for the pending writes, so that the journal can be reopened right away.
+    fn drop(&mut self) {
+        self.ops.take();
+        if let Some(writer) = self.writer.take() {
+            if writer.join().is_err() {
+                log_journal_error(Err(format_err!("mempool journal writer panicked")));
+            }
+        }
+    }
+}
+
+/// Writes the queued ops until the journal is dropped, batching the ops that are queued while
+/// the previous batch is written.
+fn write_ops(db: &DB, receiver: mpsc::Receiver<JournalOp>) {
+    while let Ok(op) = receiver.recv() {
+        let mut batch = SchemaBatch::new();
+        let mut flushed = vec![];
+        for op in std::iter::once(op).chain(receiver.try_iter()) {
+            let result = match op {
+                JournalOp::Put(txn) => {
+                    batch.put::<TransactionSchema>(&(txn.sender(), txn.sequence_number()), &txn)
+                }
+                JournalOp::Delete(address, sequence_number) => {
+                    batch.delete::<TransactionSchema>(&(address, sequence_number))
+                }
+                JournalOp::Flush(sender) => {
+                    flushed.push(sender);
+                    Ok(())
+                }
+            };
+            log_journal_error(result);
+        }
+        log_journal_error(db.write_schemas(batch));
+        for sender in flushed {
+            let _ = sender.send(());
+        }
+    }
+}
+
+/// Journal writes are best effort: a failed write only costs the transaction its persistence
+/// across restarts.
+pub(crate) fn log_journal_error(result: Result<()>) {
+    if let Err(e) = result {
+        error!(LogSchema::new(LogEntry::JournalError).error(&e));
+        counters::JOURNAL_ERROR.inc();
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for transactions journaled by mempool.
+//!
+//! Serialized signed transaction bytes identified by sender and sequence number.
+//! ```text
+//! |<---------key--------->|<-----value----->|
+//! | sender | sequence_num | signed txn bytes |
+//! ```
+
+use anyhow::{ensure, Result};
+use diem_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use schemadb::{
+    schema::{KeyCodec, Schema, ValueCodec},
+    ColumnFamilyName,
+};
+use std::{
+    convert::{TryFrom, TryInto},
+    mem::size_of,
+};
+
+pub(super) const TRANSACTION_CF_NAME: ColumnFamilyName = "transaction";
+
+pub(super) struct TransactionSchema;
+
+impl Schema for TransactionSchema {
+    const COLUMN_FAMILY_NAME: ColumnFamilyName = TRANSACTION_CF_NAME;
+    type Key = (AccountAddress, u64);
+    type Value = SignedTransaction;
+}
+
+impl KeyCodec<TransactionSchema> for (AccountAddress, u64) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = self.0.to_vec();
+        encoded.extend_from_slice(&self.1.to_be_bytes());
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == AccountAddress::LENGTH + size_of::<u64>(),
+            "Unexpected data len {}, expected {}.",
+            data.len(),
+            AccountAddress::LENGTH + size_of::<u64>(),
+        );
+        let (address, sequence_number) = data.split_at(AccountAddress::LENGTH);
+        Ok((
+            AccountAddress::try_from(address)?,
+            u64::from_be_bytes(sequence_number.try_into()?),
+        ))
+    }
+}
+
+impl ValueCodec<TransactionSchema> for SignedTransaction {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,14 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::tests::common::TestTransaction;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+#[test]
+fn test_encode_decode() {
+    let txn = TestTransaction::new(0, 7, 1).make_signed_transaction();
+    assert_encode_decode::<TransactionSchema>(&(txn.sender(), 7), &txn);
+}
+
+test_no_panic_decoding!(TransactionSchema);
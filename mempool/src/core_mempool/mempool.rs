@@ -6,12 +6,17 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
+        journal::MempoolJournal,
         transaction::{MempoolTransaction, TimelineState},
         transaction_store::TransactionStore,
         ttl_cache::TtlCache,
     },
     counters,
     logging::{LogEntry, LogSchema, TxnsLog},
+    shared_mempool::types::{
+        MempoolStats, MempoolTransactionStatus, MempoolTransactionStatusEvent,
+        PendingTransactionState,
+    },
 };
 use diem_config::config::NodeConfig;
 use diem_crypto::HashValue;
@@ -42,8 +47,21 @@ pub struct Mempool {
 
 impl Mempool {
     pub fn new(config: &NodeConfig) -> Self {
+        let journal = if config.mempool.journal_enabled {
+            MempoolJournal::new(config.storage.dir())
+                .map_err(|e| {
+                    error!(
+                        LogSchema::new(LogEntry::JournalError).error(&e),
+                        "failed to open the mempool journal, continuing without it"
+                    );
+                    counters::JOURNAL_ERROR.inc();
+                })
+                .ok()
+        } else {
+            None
+        };
         Mempool {
-            transactions: TransactionStore::new(&config.mempool),
+            transactions: TransactionStore::new(&config.mempool, journal),
             sequence_number_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             metrics_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             system_transaction_timeout: Duration::from_secs(
@@ -123,6 +141,15 @@ impl Mempool {
         self.transactions.get_by_hash(hash)
     }
 
+    /// Whether a transaction of `sender` with `sequence_number` is in mempool.
+    pub(crate) fn contains_transaction(
+        &self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) -> bool {
+        self.transactions.get(sender, sequence_number).is_some()
+    }
+
     /// Used to add a transaction to the Mempool.
     /// Performs basic validation: checks account's sequence number.
     pub(crate) fn add_txn(
@@ -185,7 +212,7 @@ impl Mempool {
     ///  mempool should filter out such transactions.
     #[allow(clippy::explicit_counter_loop)]
     pub(crate) fn get_block(
-        &self,
+        &mut self,
         batch_size: u64,
         mut seen: HashSet<TxnPointer>,
     ) -> Vec<SignedTransaction> {
@@ -263,10 +290,22 @@ impl Mempool {
                 transaction.sequence_number(),
                 counters::GET_BLOCK_STAGE_LABEL,
             );
+            self.transactions.record_status(
+                &transaction.sender(),
+                transaction.sequence_number(),
+                MempoolTransactionStatus::PulledIntoBlock,
+            );
         }
         block
     }
 
+    /// Returns the gas unit prices of the transactions among the `limit` ones with the highest
+    /// priority ready for consensus that pay gas in `gas_currency_code`, from the highest to the
+    /// lowest priority.
+    pub(crate) fn gas_unit_prices(&self, gas_currency_code: &str, limit: usize) -> Vec<u64> {
+        self.transactions.gas_unit_prices(gas_currency_code, limit)
+    }
+
     /// Periodic core mempool garbage collection.
     /// Removes all expired transactions and clears expired entries in metrics
     /// cache and sequence number cache.
@@ -298,6 +337,73 @@ impl Mempool {
         self.transactions.timeline_range(start_id, end_id)
     }
 
+    /// Transactions recorded in the journal by a previous run of the node, if mempool persistence
+    /// is enabled.
+    pub(crate) fn journaled_transactions(&self) -> Vec<SignedTransaction> {
+        self.transactions.journaled_transactions()
+    }
+
+    /// Drops a journaled transaction that is no longer valid, e.g. because it expired or was
+    /// committed while the node was down.
+    pub(crate) fn discard_journaled_transaction(
+        &self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) {
+        self.transactions
+            .discard_journaled_transaction(sender, sequence_number)
+    }
+
+    /// Returns the transactions of `sender`, in order of sequence number, along with their state.
+    pub(crate) fn get_account_transactions(
+        &self,
+        sender: &AccountAddress,
+    ) -> Vec<(SignedTransaction, PendingTransactionState)> {
+        self.transactions.get_account_transactions(sender)
+    }
+
+    pub(crate) fn get_stats(&self) -> MempoolStats {
+        self.transactions.get_stats()
+    }
+
+    /// Records that `txns` were broadcast to one more peer.
+    pub(crate) fn record_broadcast(&mut self, txns: &[TxnPointer]) {
+        self.transactions.record_broadcast(txns);
+    }
+
+    /// Returns the recent status changes of the transaction with committed hash `hash`, from the
+    /// oldest to the latest.
+    pub(crate) fn get_status_history(
+        &self,
+        hash: &HashValue,
+    ) -> Vec<MempoolTransactionStatusEvent> {
+        self.transactions.get_status_history(hash)
+    }
+
+    /// Removes all transactions of `sender`, returning them.
+    pub(crate) fn evict_account_transactions(
+        &mut self,
+        sender: &AccountAddress,
+    ) -> Vec<SignedTransaction> {
+        let txns = self.transactions.evict_account_transactions(sender);
+        for txn in txns.iter() {
+            self.metrics_cache
+                .remove(&(txn.sender(), txn.sequence_number()));
+        }
+        txns
+    }
+
+    /// Removes the transaction with committed hash `hash`, returning it.
+    pub(crate) fn evict_transaction_by_hash(
+        &mut self,
+        hash: HashValue,
+    ) -> Option<SignedTransaction> {
+        let txn = self.transactions.evict_transaction_by_hash(hash)?;
+        self.metrics_cache
+            .remove(&(txn.sender(), txn.sequence_number()));
+        Some(txn)
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
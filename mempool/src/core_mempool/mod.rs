@@ -2,11 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod index;
+mod journal;
 mod mempool;
+mod status_history;
 mod transaction;
 mod transaction_store;
 mod ttl_cache;
 
-#[cfg(test)]
 pub use self::ttl_cache::TtlCache;
 pub use self::{index::TxnPointer, mempool::Mempool as CoreMempool, transaction::TimelineState};
@@ -0,0 +1,83 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recent status changes of transactions by hash, so that clients can follow a transaction from
+//! submission to commit, including after it left mempool.
+
+use crate::{
+    core_mempool::ttl_cache::TtlCache,
+    shared_mempool::types::{MempoolTransactionStatus, MempoolTransactionStatusEvent},
+};
+use diem_crypto::HashValue;
+use std::time::{Duration, SystemTime};
+
+/// Max number of status changes kept per transaction, the oldest ones are dropped first.
+const MAX_EVENTS_PER_TXN: usize = 16;
+
+pub struct StatusHistory {
+    events: TtlCache<HashValue, Vec<MempoolTransactionStatusEvent>>,
+}
+
+impl StatusHistory {
+    /// Keeps the status changes of up to `capacity` transactions, for `ttl` after their last
+    /// change.
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            events: TtlCache::new(capacity, ttl),
+        }
+    }
+
+    /// Records a status change of the transaction with committed hash `hash`, unless it is the
+    /// same as the last one.
+    pub(crate) fn record(&mut self, hash: HashValue, status: MempoolTransactionStatus) {
+        self.update(hash, |events| {
+            if events.last().map(|event| &event.status) != Some(&status) {
+                events.push(MempoolTransactionStatusEvent::new(status));
+            }
+        });
+    }
+
+    /// Records a broadcast of the transaction with committed hash `hash` to one more peer.
+    pub(crate) fn record_broadcast(&mut self, hash: HashValue) {
+        self.update(hash, |events| {
+            let peers = events
+                .iter()
+                .rev()
+                .find_map(|event| match event.status {
+                    MempoolTransactionStatus::Broadcast { peers } => Some(peers),
+                    _ => None,
+                })
+                .unwrap_or(0)
+                + 1;
+            // consecutive broadcasts are recorded as one status change
+            if let Some(MempoolTransactionStatus::Broadcast { .. }) =
+                events.last().map(|event| &event.status)
+            {
+                events.pop();
+            }
+            events.push(MempoolTransactionStatusEvent::new(
+                MempoolTransactionStatus::Broadcast { peers },
+            ));
+        });
+    }
+
+    pub(crate) fn get(&self, hash: &HashValue) -> Vec<MempoolTransactionStatusEvent> {
+        self.events.get(hash).cloned().unwrap_or_default()
+    }
+
+    pub(crate) fn gc(&mut self, gc_time: SystemTime) {
+        self.events.gc(gc_time);
+    }
+
+    fn update<F>(&mut self, hash: HashValue, f: F)
+    where
+        F: FnOnce(&mut Vec<MempoolTransactionStatusEvent>),
+    {
+        let mut events = self.events.remove(&hash).unwrap_or_default();
+        f(&mut events);
+        if events.len() > MAX_EVENTS_PER_TXN {
+            events.drain(..events.len() - MAX_EVENTS_PER_TXN);
+        }
+        self.events.insert(hash, events);
+    }
+}
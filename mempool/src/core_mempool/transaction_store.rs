@@ -5,13 +5,19 @@ use crate::{
     core_mempool::{
         index::{
             AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
-            TimelineIndex,
+            TimelineIndex, TxnPointer,
         },
+        journal::{log_journal_error, MempoolJournal},
+        status_history::StatusHistory,
         transaction::{MempoolTransaction, TimelineState},
         ttl_cache::TtlCache,
     },
     counters,
     logging::{LogEntry, LogEvent, LogSchema, TxnsLog},
+    shared_mempool::types::{
+        MempoolStats, MempoolTransactionStatus, MempoolTransactionStatusEvent,
+        PendingTransactionState,
+    },
 };
 use diem_config::config::MempoolConfig;
 use diem_crypto::HashValue;
@@ -23,6 +29,7 @@ use diem_types::{
     transaction::SignedTransaction,
 };
 use std::{
+    cmp,
     collections::HashMap,
     ops::Bound,
     time::{Duration, SystemTime},
@@ -52,13 +59,20 @@ pub struct TransactionStore {
     // one valid hash.
     hash_index: HashMap<HashValue, (AccountAddress, u64)>,
 
+    // on-disk record of the transactions in the store, if mempool persistence is enabled
+    journal: Option<MempoolJournal>,
+
+    // recent status changes of transactions, including the ones that left the store
+    status_history: StatusHistory,
+
     // configuration
     capacity: usize,
     capacity_per_user: usize,
+    replacement_gas_price_bump_percent: u64,
 }
 
 impl TransactionStore {
-    pub(crate) fn new(config: &MempoolConfig) -> Self {
+    pub(crate) fn new(config: &MempoolConfig, journal: Option<MempoolJournal>) -> Self {
         Self {
             // main DS
             transactions: HashMap::new(),
@@ -73,9 +87,17 @@ impl TransactionStore {
             parking_lot_index: ParkingLotIndex::new(),
             hash_index: HashMap::new(),
 
+            journal,
+
+            status_history: StatusHistory::new(
+                config.transaction_status_history_capacity,
+                Duration::from_secs(config.transaction_status_ttl_secs),
+            ),
+
             // configuration
             capacity: config.capacity,
             capacity_per_user: config.capacity_per_user,
+            replacement_gas_price_bump_percent: config.replacement_gas_price_bump_percent,
         }
     }
 
@@ -121,7 +143,8 @@ impl TransactionStore {
 
         // check if transaction is already present in Mempool
         // e.g. given request is update
-        // we allow increase in gas price to speed up process.
+        // we allow replacing it with any transaction at a high enough gas price (fee bump), e.g. to
+        // speed up process or to cancel it.
         // ignores the case transaction hash is same for retrying submit transaction.
         if let Some(txns) = self.transactions.get_mut(&address) {
             if let Some(current_version) =
@@ -130,18 +153,28 @@ impl TransactionStore {
                 if current_version.txn == txn.txn {
                     return MempoolStatus::new(MempoolStatusCode::Accepted);
                 }
-                if current_version.txn.max_gas_amount() == txn.txn.max_gas_amount()
-                    && current_version.txn.payload() == txn.txn.payload()
-                    && current_version.txn.expiration_timestamp_secs()
-                        == txn.txn.expiration_timestamp_secs()
-                    && current_version.get_gas_price() < txn.get_gas_price()
-                {
-                    if let Some(txn) = txns.remove(&txn.sequence_info.transaction_sequence_number) {
-                        self.index_remove(&txn);
-                    }
-                } else {
-                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate)
-                        .with_message("Transaction already in mempool".to_string());
+                let min_gas_price = min_replacement_gas_price(
+                    current_version.get_gas_price(),
+                    self.replacement_gas_price_bump_percent,
+                );
+                if txn.get_gas_price() < min_gas_price {
+                    return MempoolStatus::new(MempoolStatusCode::InvalidUpdate).with_message(
+                        format!(
+                            "gas unit price: {}, min gas unit price to replace: {}",
+                            txn.get_gas_price(),
+                            min_gas_price,
+                        ),
+                    );
+                }
+                // the replaced transaction leaves every index, the replacement gets a new
+                // timeline id below so that it is broadcast again
+                if let Some(txn) = txns.remove(&txn.sequence_info.transaction_sequence_number) {
+                    counters::CORE_MEMPOOL_REPLACED_TXNS.inc();
+                    self.status_history.record(
+                        txn.get_committed_hash(),
+                        evicted("replaced by a transaction with a higher gas unit price"),
+                    );
+                    self.index_remove(&txn);
                 }
             }
         }
@@ -150,6 +183,7 @@ impl TransactionStore {
             &txn,
             sequence_number.account_sequence_number_type.min_seq(),
         ) {
+            counters::mempool_dropped_txns_inc(counters::MEMPOOL_FULL_LABEL);
             return MempoolStatus::new(MempoolStatusCode::MempoolIsFull).with_message(format!(
                 "mempool size: {}, capacity: {}",
                 self.system_ttl_index.size(),
@@ -169,6 +203,7 @@ impl TransactionStore {
         if let Some(txns) = self.transactions.get_mut(&address) {
             // capacity check
             if txns.len() >= self.capacity_per_user {
+                counters::mempool_dropped_txns_inc(counters::TOO_MANY_TXNS_LABEL);
                 return MempoolStatus::new(MempoolStatusCode::TooManyTransactions).with_message(
                     format!(
                         "txns length: {} capacity per user: {}",
@@ -188,6 +223,11 @@ impl TransactionStore {
                     sequence_number.transaction_sequence_number,
                 ),
             );
+            if let Some(journal) = &self.journal {
+                journal.put(&txn.txn);
+            }
+            self.status_history
+                .record(txn.get_committed_hash(), MempoolTransactionStatus::Accepted);
             txns.insert(sequence_number.transaction_sequence_number, txn);
             self.track_indices();
         }
@@ -223,7 +263,8 @@ impl TransactionStore {
     }
 
     /// Checks if Mempool is full.
-    /// If it's full, tries to free some space by evicting transactions from the ParkingLot.
+    /// If it's full, tries to free some space by evicting transactions from the ParkingLot, or
+    /// else the ready transaction with the lowest priority if the new one has a higher priority.
     /// We only evict on attempt to insert a transaction that would be ready for broadcast upon insertion.
     fn check_is_full_after_eviction(
         &mut self,
@@ -246,8 +287,29 @@ impl TransactionStore {
                             txn.sequence_info.transaction_sequence_number
                         ))
                     );
+                    counters::mempool_dropped_txns_inc(counters::EVICTED_PARKED_LABEL);
+                    self.status_history
+                        .record(txn.get_committed_hash(), evicted(MEMPOOL_FULL_REASON));
                     self.index_remove(&txn);
                 }
+            } else if let Some(lowest) = self.priority_index.lowest().cloned() {
+                // a txn of the same sender may be the one the new txn is ready after, and a txn
+                // is only evicted for one with a strictly higher priority
+                if lowest.address != txn.get_sender()
+                    && (lowest.governance_role.priority(), lowest.gas_ranking_score)
+                        < (txn.governance_role.priority(), txn.ranking_score)
+                {
+                    let sequence_number = lowest.sequence_number.transaction_sequence_number;
+                    if let Some(txn) =
+                        self.remove_and_park_following(&lowest.address, sequence_number)
+                    {
+                        debug!(LogSchema::new(LogEntry::MempoolFullEvictedTxn)
+                            .txns(TxnsLog::new_txn(lowest.address, sequence_number)));
+                        counters::mempool_dropped_txns_inc(counters::EVICTED_LOW_PRIORITY_LABEL);
+                        self.status_history
+                            .record(txn.get_committed_hash(), evicted(MEMPOOL_FULL_REASON));
+                    }
+                }
             }
         }
         self.system_ttl_index.size() >= self.capacity
@@ -298,6 +360,7 @@ impl TransactionStore {
                 AccountSequenceInfo::CRSN { min_nonce, size } => {
                     for i in min_nonce..size {
                         if let Some(txn) = txns.get_mut(&i) {
+                            let was_ready = self.priority_index.contains(txn);
                             self.priority_index.insert(txn);
 
                             if txn.timeline_state == TimelineState::NotReady {
@@ -307,12 +370,19 @@ impl TransactionStore {
                             // Remove txn from parking lot after it has been promoted to
                             // priority_index / timeline_index, i.e., txn status is ready.
                             self.parking_lot_index.remove(txn);
+                            if !was_ready {
+                                self.status_history.record(
+                                    txn.get_committed_hash(),
+                                    MempoolTransactionStatus::Ready,
+                                );
+                            }
                             min_seq = i;
                         }
                     }
                 }
                 AccountSequenceInfo::Sequential(_) => {
                     while let Some(txn) = txns.get_mut(&min_seq) {
+                        let was_ready = self.priority_index.contains(txn);
                         self.priority_index.insert(txn);
 
                         if txn.timeline_state == TimelineState::NotReady {
@@ -322,6 +392,10 @@ impl TransactionStore {
                         // Remove txn from parking lot after it has been promoted to
                         // priority_index / timeline_index, i.e., txn status is ready.
                         self.parking_lot_index.remove(txn);
+                        if !was_ready {
+                            self.status_history
+                                .record(txn.get_committed_hash(), MempoolTransactionStatus::Ready);
+                        }
                         min_seq += 1;
                     }
                 }
@@ -332,7 +406,10 @@ impl TransactionStore {
                 match txn.timeline_state {
                     TimelineState::Ready(_) => {}
                     _ => {
-                        self.parking_lot_index.insert(txn);
+                        if self.parking_lot_index.insert(txn) {
+                            self.status_history
+                                .record(txn.get_committed_hash(), MempoolTransactionStatus::Parked);
+                        }
                         parking_lot_txns += 1;
                     }
                 }
@@ -364,6 +441,10 @@ impl TransactionStore {
                     transaction.get_sender(),
                     transaction.sequence_info.transaction_sequence_number,
                 );
+                self.status_history.record(
+                    transaction.get_committed_hash(),
+                    MempoolTransactionStatus::Committed,
+                );
                 self.index_remove(transaction);
             }
             trace!(
@@ -387,20 +468,89 @@ impl TransactionStore {
         self.process_ready_transactions(account, account_sequence_number);
     }
 
-    pub(crate) fn reject_transaction(&mut self, account: &AccountAddress, _sequence_number: u64) {
+    pub(crate) fn reject_transaction(&mut self, account: &AccountAddress, sequence_number: u64) {
         if let Some(txns) = self.transactions.remove(account) {
             let mut txns_log = TxnsLog::new();
             for transaction in txns.values() {
-                txns_log.add(
-                    transaction.get_sender(),
-                    transaction.sequence_info.transaction_sequence_number,
-                );
+                let txn_sequence_number = transaction.sequence_info.transaction_sequence_number;
+                txns_log.add(transaction.get_sender(), txn_sequence_number);
+                let status = if txn_sequence_number == sequence_number {
+                    MempoolTransactionStatus::Rejected {
+                        reason: "rejected during execution".to_string(),
+                    }
+                } else {
+                    evicted(&format!(
+                        "transaction {} of the account was rejected during execution",
+                        sequence_number
+                    ))
+                };
+                self.status_history
+                    .record(transaction.get_committed_hash(), status);
                 self.index_remove(transaction);
             }
             debug!(LogSchema::new(LogEntry::CleanRejectedTxn).txns(txns_log));
         }
     }
 
+    /// Removes all transactions of `account`, e.g. on request of the node operator.
+    pub(crate) fn evict_account_transactions(
+        &mut self,
+        account: &AccountAddress,
+    ) -> Vec<SignedTransaction> {
+        match self.transactions.remove(account) {
+            Some(txns) => {
+                let mut txns_log = TxnsLog::new();
+                for transaction in txns.values() {
+                    txns_log.add(
+                        transaction.get_sender(),
+                        transaction.sequence_info.transaction_sequence_number,
+                    );
+                    self.status_history
+                        .record(transaction.get_committed_hash(), evicted(OPERATOR_REASON));
+                    self.index_remove(transaction);
+                }
+                debug!(LogSchema::new(LogEntry::EvictTxns).txns(txns_log));
+                txns.into_iter().map(|(_, txn)| txn.txn).collect()
+            }
+            None => vec![],
+        }
+    }
+
+    /// Removes the transaction with committed hash `hash`, e.g. on request of the node operator.
+    pub(crate) fn evict_transaction_by_hash(
+        &mut self,
+        hash: HashValue,
+    ) -> Option<SignedTransaction> {
+        let (address, sequence_number) = *self.hash_index.get(&hash)?;
+        let txn = self.remove_and_park_following(&address, sequence_number)?;
+        self.status_history.record(hash, evicted(OPERATOR_REASON));
+        let txns_log = TxnsLog::new_txn(address, sequence_number);
+        debug!(LogSchema::new(LogEntry::EvictTxns).txns(txns_log));
+        Some(txn.txn)
+    }
+
+    /// Removes a transaction that may be ready, parking the following transactions of the
+    /// account as they can't be included in a block until it is resubmitted.
+    fn remove_and_park_following(
+        &mut self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<MempoolTransaction> {
+        let txns = self.transactions.get_mut(address)?;
+        // mark all following txns as non-ready, i.e. park them
+        for (_, t) in txns.range((Bound::Excluded(sequence_number), Bound::Unbounded)) {
+            if self.parking_lot_index.insert(t) {
+                self.status_history
+                    .record(t.get_committed_hash(), MempoolTransactionStatus::Parked);
+            }
+            self.priority_index.remove(t);
+            self.timeline_index.remove(t);
+        }
+        let txn = txns.remove(&sequence_number)?;
+        self.index_remove(&txn);
+        Some(txn)
+    }
+
     /// Removes transaction from all indexes.
     fn index_remove(&mut self, txn: &MempoolTransaction) {
         counters::CORE_MEMPOOL_REMOVED_TXNS.inc();
@@ -410,9 +560,39 @@ impl TransactionStore {
         self.timeline_index.remove(txn);
         self.parking_lot_index.remove(txn);
         self.hash_index.remove(&txn.get_committed_hash());
+        if let Some(journal) = &self.journal {
+            journal.delete(
+                &txn.get_sender(),
+                txn.sequence_info.transaction_sequence_number,
+            );
+        }
         self.track_indices();
     }
 
+    /// Transactions recorded in the journal, to be resubmitted on startup.
+    pub(crate) fn journaled_transactions(&self) -> Vec<SignedTransaction> {
+        match &self.journal {
+            Some(journal) => journal.get_transactions().unwrap_or_else(|e| {
+                log_journal_error(Err(e));
+                vec![]
+            }),
+            None => vec![],
+        }
+    }
+
+    /// Drops a journaled transaction that wasn't accepted back into the store on resubmission.
+    pub(crate) fn discard_journaled_transaction(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) {
+        if let Some(journal) = &self.journal {
+            if self.get(address, sequence_number).is_none() {
+                journal.delete(address, sequence_number);
+            }
+        }
+    }
+
     /// Read `count` transactions from timeline since `timeline_id`.
     /// Returns block of transactions and new last_timeline_id.
     pub(crate) fn read_timeline(
@@ -458,6 +638,7 @@ impl TransactionStore {
         let now = diem_infallible::duration_since_epoch();
 
         self.gc(now, true, metrics_cache);
+        self.status_history.gc(SystemTime::now());
     }
 
     /// Garbage collect old transactions based on client-specified expiration time.
@@ -509,7 +690,10 @@ impl TransactionStore {
                     });
                 // mark all following txns as non-ready, i.e. park them
                 for (_, t) in txns.range((park_range_start, park_range_end)) {
-                    self.parking_lot_index.insert(t);
+                    if self.parking_lot_index.insert(t) {
+                        self.status_history
+                            .record(t.get_committed_hash(), MempoolTransactionStatus::Parked);
+                    }
                     self.priority_index.remove(t);
                     self.timeline_index.remove(t);
                 }
@@ -533,6 +717,8 @@ impl TransactionStore {
                     }
 
                     // remove txn
+                    self.status_history
+                        .record(txn.get_committed_hash(), MempoolTransactionStatus::Expired);
                     self.index_remove(&txn);
                 }
             }
@@ -546,6 +732,21 @@ impl TransactionStore {
         self.priority_index.iter()
     }
 
+    /// Returns the gas unit prices of the transactions among the first `limit` ones in the
+    /// priority queue that pay gas in `gas_currency_code`, in the order of the queue.
+    pub(crate) fn gas_unit_prices(&self, gas_currency_code: &str, limit: usize) -> Vec<u64> {
+        self.iter_queue()
+            .take(limit)
+            .filter_map(|key| {
+                self.transactions
+                    .get(&key.address)
+                    .and_then(|txns| txns.get(&key.sequence_number.transaction_sequence_number))
+            })
+            .filter(|txn| txn.txn.gas_currency_code() == gas_currency_code)
+            .map(MempoolTransaction::get_gas_price)
+            .collect()
+    }
+
     pub(crate) fn gen_snapshot(
         &self,
         metrics_cache: &TtlCache<(AccountAddress, u64), SystemTime>,
@@ -553,20 +754,101 @@ impl TransactionStore {
         let mut txns_log = TxnsLog::new();
         for (account, txns) in self.transactions.iter() {
             for (seq_num, _txn) in txns.iter() {
-                let status = if self.parking_lot_index.contains(account, seq_num) {
-                    "parked"
-                } else {
-                    "ready"
-                };
+                let status = self.get_state(account, seq_num).to_string();
                 let timestamp = metrics_cache.get(&(*account, *seq_num)).cloned();
-                txns_log.add_full_metadata(*account, *seq_num, status, timestamp);
+                txns_log.add_full_metadata(*account, *seq_num, &status, timestamp);
             }
         }
         txns_log
     }
 
+    /// Returns the transactions of `account`, in order of sequence number, along with their state.
+    pub(crate) fn get_account_transactions(
+        &self,
+        account: &AccountAddress,
+    ) -> Vec<(SignedTransaction, PendingTransactionState)> {
+        self.transactions
+            .get(account)
+            .map(|txns| {
+                txns.iter()
+                    .map(|(seq_num, txn)| (txn.txn.clone(), self.get_state(account, seq_num)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Records a status change of the transaction of `address` with `sequence_number`, if it is
+    /// in the store.
+    pub(crate) fn record_status(
+        &mut self,
+        address: &AccountAddress,
+        sequence_number: u64,
+        status: MempoolTransactionStatus,
+    ) {
+        if let Some(txn) = self
+            .transactions
+            .get(address)
+            .and_then(|txns| txns.get(&sequence_number))
+        {
+            self.status_history.record(txn.get_committed_hash(), status);
+        }
+    }
+
+    /// Records that the transactions of `txns` still in the store were broadcast to one more peer.
+    pub(crate) fn record_broadcast(&mut self, txns: &[TxnPointer]) {
+        for (address, sequence_number) in txns {
+            if let Some(txn) = self
+                .transactions
+                .get(address)
+                .and_then(|txns| txns.get(sequence_number))
+            {
+                self.status_history
+                    .record_broadcast(txn.get_committed_hash());
+            }
+        }
+    }
+
+    pub(crate) fn get_status_history(
+        &self,
+        hash: &HashValue,
+    ) -> Vec<MempoolTransactionStatusEvent> {
+        self.status_history.get(hash)
+    }
+
+    pub(crate) fn get_stats(&self) -> MempoolStats {
+        let parked = self.parking_lot_index.size();
+        MempoolStats {
+            ready: self.system_ttl_index.size() - parked,
+            parked,
+        }
+    }
+
+    fn get_state(&self, account: &AccountAddress, seq_num: &u64) -> PendingTransactionState {
+        if self.parking_lot_index.contains(account, seq_num) {
+            PendingTransactionState::Parked
+        } else {
+            PendingTransactionState::Ready
+        }
+    }
+
     #[cfg(test)]
     pub(crate) fn get_parking_lot_size(&self) -> usize {
         self.parking_lot_index.size()
     }
 }
+
+const MEMPOOL_FULL_REASON: &str = "mempool is full";
+const OPERATOR_REASON: &str = "evicted by the node operator";
+
+fn evicted(reason: &str) -> MempoolTransactionStatus {
+    MempoolTransactionStatus::Evicted {
+        reason: reason.to_string(),
+    }
+}
+
+/// The lowest gas unit price at which a transaction replaces one priced at `gas_price`: higher by
+/// at least `bump_percent` percent, and in any case strictly higher.
+fn min_replacement_gas_price(gas_price: u64, bump_percent: u64) -> u64 {
+    let bump = gas_price.saturating_mul(bump_percent) / 100;
+    gas_price.saturating_add(cmp::max(bump, 1))
+}
@@ -51,7 +51,13 @@ pub const SUCCESS_LABEL: &str = "success";
 
 // Bounded executor task labels
 pub const CLIENT_EVENT_LABEL: &str = "client_event";
+pub const CLIENT_EVENT_SUBMIT_TXN_BATCH_LABEL: &str = "client_event_submit_txn_batch";
 pub const CLIENT_EVENT_GET_TXN_LABEL: &str = "client_event_get_txn";
+pub const CLIENT_EVENT_GET_GAS_PRICE_ESTIMATE_LABEL: &str = "client_event_get_gas_price_estimate";
+pub const CLIENT_EVENT_GET_ACCOUNT_TXNS_LABEL: &str = "client_event_get_account_txns";
+pub const CLIENT_EVENT_GET_STATS_LABEL: &str = "client_event_get_stats";
+pub const CLIENT_EVENT_EVICT_TXNS_LABEL: &str = "client_event_evict_txns";
+pub const CLIENT_EVENT_GET_TXN_STATUS_HISTORY_LABEL: &str = "client_event_get_txn_status_history";
 pub const RECONFIG_EVENT_LABEL: &str = "reconfig";
 pub const PEER_BROADCAST_EVENT_LABEL: &str = "peer_broadcast";
 
@@ -76,6 +82,13 @@ pub const SENT_LABEL: &str = "sent";
 pub const INVALID_REQUEST_ID: &str = "invalid_req_id";
 pub const UNKNOWN_PEER: &str = "unknown_peer";
 
+// Dropped txn reason labels
+pub const EVICTED_PARKED_LABEL: &str = "evicted_parked";
+pub const EVICTED_LOW_PRIORITY_LABEL: &str = "evicted_low_priority";
+pub const MEMPOOL_FULL_LABEL: &str = "mempool_full";
+pub const TOO_MANY_TXNS_LABEL: &str = "too_many_txns";
+pub const PEER_RATE_LIMITED_LABEL: &str = "peer_rate_limited";
+
 /// Counter tracking size of various indices in core mempool
 static CORE_MEMPOOL_INDEX_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -101,6 +114,35 @@ pub static CORE_MEMPOOL_REMOVED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter tracking number of txns replaced in core mempool by a txn with a higher gas price
+pub static CORE_MEMPOOL_REPLACED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_core_mempool_replaced_txns_count",
+        "Number of txns replaced in core mempool by a txn with a higher gas price"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of txns dropped by mempool, either evicted or not admitted, by reason
+static MEMPOOL_DROPPED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "diem_mempool_dropped_txns_count",
+        "Number of txns dropped by mempool, either evicted or not admitted",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+pub fn mempool_dropped_txns_inc(reason: &'static str) {
+    mempool_dropped_txns_inc_by(reason, 1)
+}
+
+pub fn mempool_dropped_txns_inc_by(reason: &'static str, num: usize) {
+    MEMPOOL_DROPPED_TXNS
+        .with_label_values(&[reason])
+        .inc_by(num as u64)
+}
+
 /// Counter tracking latency of txns reaching various stages in committing
 /// (e.g. time from txn entering core mempool to being pulled in consensus block)
 pub static CORE_MEMPOOL_TXN_COMMIT_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
@@ -440,6 +482,15 @@ pub static DB_ERROR: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter for failed reads and writes of the mempool journal
+pub static JOURNAL_ERROR: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "diem_mempool_journal_error_count",
+        "Number of times an error was encountered reading or writing the mempool journal"
+    )
+    .unwrap()
+});
+
 /// Counter for the current number of active upstream peers mempool can
 /// broadcast to, summed across each of its networks
 static ACTIVE_UPSTREAM_PEERS_COUNT: Lazy<IntGaugeVec> = Lazy::new(|| {
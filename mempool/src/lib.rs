@@ -60,8 +60,10 @@ mod tests;
 pub use shared_mempool::{
     bootstrap, network,
     types::{
-        ConsensusRequest, ConsensusResponse, MempoolClientRequest, MempoolClientSender,
-        MempoolEventsReceiver, SubmissionStatus, TransactionSummary,
+        ConsensusRequest, ConsensusResponse, GasPriceEstimate, MempoolClientRequest,
+        MempoolClientSender, MempoolEventsReceiver, MempoolStats, MempoolTransactionStatus,
+        MempoolTransactionStatusEvent, PendingTransactionState, SubmissionStatus,
+        TransactionSummary,
     },
 };
 #[cfg(any(test, feature = "fuzzing"))]
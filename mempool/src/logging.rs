@@ -130,6 +130,11 @@ pub enum LogEntry {
     ReconfigUpdate,
     JsonRpc,
     GetTransaction,
+    GetGasPriceEstimate,
+    GetAccountTransactions,
+    GetStats,
+    GetTransactionStatusHistory,
+    EvictTxns,
     GetBlock,
     Consensus,
     StateSyncCommit,
@@ -147,6 +152,8 @@ pub enum LogEntry {
     DBError,
     UnexpectedNetworkMsg,
     MempoolSnapshot,
+    JournalError,
+    JournalReload,
 }
 
 #[derive(Clone, Copy, Serialize)]
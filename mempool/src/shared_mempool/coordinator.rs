@@ -126,6 +126,27 @@ async fn handle_client_request<V>(
                 ))
                 .await;
         }
+        MempoolClientRequest::SubmitTransactionBatch(txns, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_SUBMIT_TXN_BATCH_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_SUBMIT_TXN_BATCH_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_transaction_batch_submission(
+                    smp.clone(),
+                    txns,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
         MempoolClientRequest::GetTransactionByHash(hash, callback) => {
             // This timer measures how long it took for the bounded executor to *schedule* the
             // task.
@@ -147,6 +168,131 @@ async fn handle_client_request<V>(
                 ))
                 .await;
         }
+        MempoolClientRequest::GetGasPriceEstimate(gas_currency_code, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_GAS_PRICE_ESTIMATE_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_GAS_PRICE_ESTIMATE_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_get_gas_price_estimate(
+                    smp.clone(),
+                    gas_currency_code,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
+        MempoolClientRequest::GetAccountTransactions(sender, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_ACCOUNT_TXNS_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_ACCOUNT_TXNS_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_get_account_transactions(
+                    smp.clone(),
+                    sender,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
+        MempoolClientRequest::GetStats(callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_STATS_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_STATS_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_get_stats(
+                    smp.clone(),
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
+        MempoolClientRequest::EvictAccountTransactions(sender, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_EVICT_TXNS_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_EVICT_TXNS_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_evict_account_transactions(
+                    smp.clone(),
+                    sender,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
+        MempoolClientRequest::EvictTransactionByHash(hash, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_EVICT_TXNS_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_EVICT_TXNS_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_evict_transaction_by_hash(
+                    smp.clone(),
+                    hash,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
+        MempoolClientRequest::GetTransactionStatusHistory(hash, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_TXN_STATUS_HISTORY_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_GET_TXN_STATUS_HISTORY_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_get_transaction_status_history(
+                    smp.clone(),
+                    hash,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        }
     }
 }
 
@@ -220,7 +366,8 @@ async fn handle_mempool_reconfig_event<V>(
 
 /// Handles all NewPeer, LostPeer, and network messages.
 /// - NewPeer events start new automatic broadcasts if the peer is upstream. If the peer is not upstream, we ignore it.
-/// - LostPeer events disable the upstream peer, which will cancel ongoing broadcasts.
+/// - LostPeer events disable the upstream peer, which will cancel ongoing broadcasts, and drop
+///   the rate limiter bucket of the peer.
 /// - Network messages follow a simple Request/Response framework to accept new transactions
 /// TODO: Move to RPC off of DirectSend
 async fn handle_network_event<V>(
@@ -260,6 +407,7 @@ async fn handle_network_event<V>(
                         .is_upstream_peer(&peer, Some(&metadata))
                 ));
             smp.network_interface.disable_peer(peer);
+            smp.peer_rate_limiter.remove_peer(&peer);
             notify_subscribers(SharedMempoolNotification::PeerStateChange, &smp.subscribers);
         }
         Event::Message(peer_id, msg) => {
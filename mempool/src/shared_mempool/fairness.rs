@@ -0,0 +1,65 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fairness between the peers broadcasting transactions to this node, so that a single peer
+//! can't fill mempool and starve the others.
+
+use crate::counters;
+use diem_config::{config::MempoolConfig, network_id::PeerNetworkId};
+use diem_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use diem_types::transaction::SignedTransaction;
+
+const LABEL: &str = "mempool_peer_txns";
+/// Buckets of newly connected peers start partially filled, so that reconnecting doesn't give a
+/// peer a full burst.
+const NEW_BUCKET_START_PERCENTAGE: u8 = 25;
+
+/// Token bucket rate limits on the number of transactions broadcast by each peer. Validators
+/// are trusted to forward each other's transactions, so peers on the validator network are not
+/// limited.
+pub(crate) struct PeerRateLimiter {
+    rate_limiter: TokenBucketRateLimiter<PeerNetworkId>,
+}
+
+impl PeerRateLimiter {
+    pub fn new(config: &MempoolConfig) -> Self {
+        let rate_limiter = if config.shared_mempool_peer_rate_limit_enabled {
+            TokenBucketRateLimiter::new(
+                LABEL,
+                String::new(),
+                NEW_BUCKET_START_PERCENTAGE,
+                config.shared_mempool_peer_txn_bucket_size,
+                config.shared_mempool_peer_txn_bucket_rate,
+                None,
+            )
+        } else {
+            TokenBucketRateLimiter::open(LABEL)
+        };
+        Self { rate_limiter }
+    }
+
+    /// Keeps the transactions of a broadcast that `peer` has tokens for, dropping the rest.
+    /// Returns whether any transaction was dropped, in which case the peer should back off.
+    pub fn throttle(&self, peer: PeerNetworkId, transactions: &mut Vec<SignedTransaction>) -> bool {
+        if peer.network_id().is_validator_network() {
+            return false;
+        }
+        let allowed = self
+            .rate_limiter
+            .bucket(peer)
+            .lock()
+            .acquire_tokens(transactions.len())
+            .unwrap_or(0);
+        let throttled = transactions.len().saturating_sub(allowed);
+        if throttled > 0 {
+            transactions.truncate(allowed);
+            counters::mempool_dropped_txns_inc_by(counters::PEER_RATE_LIMITED_LABEL, throttled);
+        }
+        throttled > 0
+    }
+
+    /// Forgets the bucket of a disconnected peer.
+    pub fn remove_peer(&self, peer: &PeerNetworkId) {
+        self.rate_limiter.try_garbage_collect_key(peer);
+    }
+}
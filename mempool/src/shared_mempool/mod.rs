@@ -1,6 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod fairness;
 pub mod network;
 mod runtime;
 pub(crate) mod types;
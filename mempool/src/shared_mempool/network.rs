@@ -4,6 +4,7 @@
 //! Interface between Mempool and Network layers.
 
 use crate::{
+    core_mempool::TxnPointer,
     counters,
     logging::{LogEntry, LogEvent, LogSchema},
     shared_mempool::{
@@ -508,9 +509,21 @@ impl MempoolNetworkInterface {
             self.determine_broadcast_batch(peer, scheduled_backoff, smp)?;
 
         let num_txns = transactions.len();
+        // only fresh broadcasts reach a new peer, retries resend to the same one
+        let broadcast_txns: Vec<TxnPointer> = if metric_label.is_none() {
+            transactions
+                .iter()
+                .map(|txn| (txn.sender(), txn.sequence_number()))
+                .collect()
+        } else {
+            vec![]
+        };
         let send_time = SystemTime::now();
         self.send_batch(peer, batch_id, transactions).await?;
         let num_pending_broadcasts = self.update_broadcast_state(peer, batch_id, send_time)?;
+        if !broadcast_txns.is_empty() {
+            smp.mempool.lock().record_broadcast(&broadcast_txns);
+        }
         notify_subscribers(SharedMempoolNotification::Broadcast, &smp.subscribers);
 
         // Log all the metrics
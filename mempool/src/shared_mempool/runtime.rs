@@ -6,6 +6,7 @@ use crate::{
     network::{MempoolNetworkEvents, MempoolNetworkSender},
     shared_mempool::{
         coordinator::{coordinator, gc_coordinator, snapshot_job},
+        tasks::reload_journaled_transactions,
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
     ConsensusRequest,
@@ -27,6 +28,7 @@ use vm_validator::vm_validator::{TransactionValidation, VMValidator};
 ///   - outbound_sync_task (task that periodically broadcasts transactions to peers).
 ///   - inbound_network_task (task that handles inbound mempool messages and network events).
 ///   - gc_task (task that performs GC of all expired transactions by SystemTTL).
+/// Transactions journaled by a previous run of the node are resubmitted before these start.
 pub(crate) fn start_shared_mempool<V>(
     executor: &Handle,
     config: &NodeConfig,
@@ -63,6 +65,10 @@ pub(crate) fn start_shared_mempool<V>(
         peer_metadata_storage,
     );
 
+    // Reload before the coordinator starts taking new transactions, so that they can't race
+    // with the journaled ones for the same sequence numbers.
+    reload_journaled_transactions(&smp);
+
     executor.spawn(coordinator(
         smp,
         executor.clone(),
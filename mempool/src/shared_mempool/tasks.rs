@@ -8,7 +8,8 @@ use crate::{
     logging::{LogEntry, LogEvent, LogSchema},
     network::{BroadcastError, MempoolSyncMsg},
     shared_mempool::types::{
-        notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification,
+        notify_subscribers, GasPriceEstimate, MempoolStats, MempoolTransactionStatusEvent,
+        PendingTransactionState, ScheduledBroadcast, SharedMempool, SharedMempoolNotification,
         SubmissionStatusBundle, TransactionSummary,
     },
     ConsensusRequest, ConsensusResponse, SubmissionStatus,
@@ -20,9 +21,10 @@ use diem_infallible::{Mutex, RwLock};
 use diem_logger::prelude::*;
 use diem_metrics::HistogramTimer;
 use diem_types::{
+    account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     on_chain_config::OnChainConfigPayload,
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, Transaction},
     vm_status::DiscardedVMStatus,
 };
 use futures::{channel::oneshot, stream::FuturesUnordered};
@@ -30,9 +32,9 @@ use network::application::interface::NetworkInterface;
 use rayon::prelude::*;
 use std::{
     cmp,
-    collections::HashSet,
+    collections::{HashMap, HashSet, VecDeque},
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::runtime::Handle;
 use vm_validator::vm_validator::{get_account_sequence_number, TransactionValidation};
@@ -119,6 +121,58 @@ pub(crate) async fn process_client_transaction_submission<V>(
     }
 }
 
+/// Processes a batch of transactions directly submitted by client.
+pub(crate) async fn process_client_transaction_batch_submission<V>(
+    smp: SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+    callback: oneshot::Sender<Result<Vec<Result<SubmissionStatus>>>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let _timer = counters::process_txn_submit_latency_timer_client();
+    let keys: Vec<_> = transactions
+        .iter()
+        .map(|txn| (txn.sender(), txn.sequence_number()))
+        .collect();
+    let (statuses, errors) =
+        process_incoming_transactions_with_errors(&smp, transactions, TimelineState::NotReady);
+    log_txn_process_results(&statuses, None);
+
+    // `process_incoming_transactions` doesn't keep the order of the transactions, so match the
+    // statuses back by sender and sequence number. Transactions with the same sender and
+    // sequence number are processed, and so matched, in order.
+    let mut statuses_by_key: HashMap<_, VecDeque<_>> = HashMap::new();
+    let results = statuses
+        .into_iter()
+        .map(|(txn, status)| (txn, Ok(status)))
+        .chain(errors.into_iter().map(|(txn, err)| (txn, Err(err))));
+    for (txn, result) in results {
+        statuses_by_key
+            .entry((txn.sender(), txn.sequence_number()))
+            .or_default()
+            .push_back(result);
+    }
+    let statuses = keys
+        .iter()
+        .map(|key| {
+            statuses_by_key
+                .get_mut(key)
+                .and_then(VecDeque::pop_front)
+                .unwrap_or_else(|| Err(anyhow::format_err!("transaction was not processed")))
+        })
+        .collect();
+
+    if callback.send(Ok(statuses)).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::JsonRpc,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes get transaction by hash request by client.
 pub(crate) async fn process_client_get_transaction<V>(
     smp: SharedMempool<V>,
@@ -141,10 +195,189 @@ pub(crate) async fn process_client_get_transaction<V>(
     }
 }
 
+/// Processes get gas price estimate request by client.
+pub(crate) async fn process_client_get_gas_price_estimate<V>(
+    smp: SharedMempool<V>,
+    gas_currency_code: String,
+    callback: oneshot::Sender<Result<GasPriceEstimate>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let estimate = gas_price_estimate(&smp, &gas_currency_code);
+
+    if callback.send(estimate).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::GetGasPriceEstimate,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Processes get account transactions request by client.
+pub(crate) async fn process_client_get_account_transactions<V>(
+    smp: SharedMempool<V>,
+    sender: AccountAddress,
+    callback: oneshot::Sender<Vec<(SignedTransaction, PendingTransactionState)>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let txns = smp.mempool.lock().get_account_transactions(&sender);
+
+    if callback.send(txns).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::GetAccountTransactions,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Processes get stats request by client.
+pub(crate) async fn process_client_get_stats<V>(
+    smp: SharedMempool<V>,
+    callback: oneshot::Sender<MempoolStats>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let stats = smp.mempool.lock().get_stats();
+
+    if callback.send(stats).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::GetStats,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Processes get transaction status history request by client.
+pub(crate) async fn process_client_get_transaction_status_history<V>(
+    smp: SharedMempool<V>,
+    hash: HashValue,
+    callback: oneshot::Sender<Vec<MempoolTransactionStatusEvent>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let history = smp.mempool.lock().get_status_history(&hash);
+
+    if callback.send(history).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::GetTransactionStatusHistory,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Processes evict account transactions request by client.
+pub(crate) async fn process_client_evict_account_transactions<V>(
+    smp: SharedMempool<V>,
+    sender: AccountAddress,
+    callback: oneshot::Sender<Vec<SignedTransaction>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let txns = smp.mempool.lock().evict_account_transactions(&sender);
+
+    if callback.send(txns).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::EvictTxns,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Processes evict transaction by hash request by client.
+pub(crate) async fn process_client_evict_transaction_by_hash<V>(
+    smp: SharedMempool<V>,
+    hash: HashValue,
+    callback: oneshot::Sender<Option<SignedTransaction>>,
+    timer: HistogramTimer,
+) where
+    V: TransactionValidation,
+{
+    timer.stop_and_record();
+    let txn = smp.mempool.lock().evict_transaction_by_hash(hash);
+
+    if callback.send(txn).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::EvictTxns,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Returns the `GasPriceEstimate` of `gas_currency_code`, which is only estimated again once the
+/// cached one has expired.
+fn gas_price_estimate<V>(
+    smp: &SharedMempool<V>,
+    gas_currency_code: &str,
+) -> Result<GasPriceEstimate>
+where
+    V: TransactionValidation,
+{
+    let gas_currency_code = gas_currency_code.to_string();
+    {
+        let mut estimates = smp.gas_price_estimates.lock();
+        estimates.gc(SystemTime::now());
+        if let Some(estimate) = estimates.get(&gas_currency_code) {
+            return Ok(*estimate);
+        }
+    }
+    let estimate = GasPriceEstimate::from_samples(gas_unit_prices(smp, &gas_currency_code)?);
+    smp.gas_price_estimates
+        .lock()
+        .insert(gas_currency_code, estimate);
+    Ok(estimate)
+}
+
+/// Samples the gas unit prices of the transactions with the highest priority in mempool and of
+/// the most recently committed transactions that pay gas in `gas_currency_code`.
+fn gas_unit_prices<V>(smp: &SharedMempool<V>, gas_currency_code: &str) -> Result<Vec<u64>>
+where
+    V: TransactionValidation,
+{
+    let mut gas_unit_prices = smp.mempool.lock().gas_unit_prices(
+        gas_currency_code,
+        smp.config.gas_price_estimate_mempool_transactions as usize,
+    );
+
+    let latest_version = smp.db.get_latest_version()?;
+    let limit = cmp::min(
+        smp.config.gas_price_estimate_recent_transactions,
+        latest_version + 1,
+    );
+    if limit > 0 {
+        let committed =
+            smp.db
+                .get_transactions(latest_version + 1 - limit, limit, latest_version, false)?;
+        gas_unit_prices.extend(committed.transactions.iter().filter_map(|txn| match txn {
+            Transaction::UserTransaction(txn) if txn.gas_currency_code() == gas_currency_code => {
+                Some(txn.gas_unit_price())
+            }
+            _ => None,
+        }));
+    }
+    Ok(gas_unit_prices)
+}
+
 /// Processes transactions from other nodes.
 pub(crate) async fn process_transaction_broadcast<V>(
     smp: SharedMempool<V>,
-    transactions: Vec<SignedTransaction>,
+    mut transactions: Vec<SignedTransaction>,
     request_id: Vec<u8>,
     timeline_state: TimelineState,
     peer: PeerNetworkId,
@@ -154,10 +387,15 @@ pub(crate) async fn process_transaction_broadcast<V>(
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer(peer.network_id());
-    let results = process_incoming_transactions(&smp, transactions, timeline_state);
+    let throttled = smp.peer_rate_limiter.throttle(peer, &mut transactions);
+    let results = if transactions.is_empty() {
+        vec![]
+    } else {
+        process_incoming_transactions(&smp, transactions, timeline_state)
+    };
     log_txn_process_results(&results, Some(peer));
 
-    let ack_response = gen_ack_response(request_id, results, &peer);
+    let ack_response = gen_ack_response(request_id, results, throttled, &peer);
     let network_sender = smp.network_interface.sender();
     if let Err(e) = network_sender.send_to(peer, ack_response) {
         counters::network_send_fail_inc(counters::ACK_TXNS);
@@ -171,13 +409,15 @@ pub(crate) async fn process_transaction_broadcast<V>(
     notify_subscribers(SharedMempoolNotification::ACK, &smp.subscribers);
 }
 
-/// If `MempoolIsFull` on any of the transactions, provide backpressure to the downstream peer.
+/// If `MempoolIsFull` on any of the transactions, or if the downstream peer was rate limited,
+/// provide backpressure to the downstream peer.
 fn gen_ack_response(
     request_id: Vec<u8>,
     results: Vec<SubmissionStatusBundle>,
+    throttled: bool,
     peer: &PeerNetworkId,
 ) -> MempoolSyncMsg {
-    let mut backoff_and_retry = false;
+    let mut backoff_and_retry = throttled;
     for (_, (mempool_status, _)) in results.into_iter() {
         if mempool_status.code == MempoolStatusCode::MempoolIsFull {
             backoff_and_retry = true;
@@ -230,7 +470,27 @@ pub(crate) fn process_incoming_transactions<V>(
 where
     V: TransactionValidation,
 {
+    process_incoming_transactions_with_errors(smp, transactions, timeline_state).0
+}
+
+/// Same as `process_incoming_transactions`, but also returns the transactions that the validator
+/// failed to validate, which get no status, together with the validator error.
+pub(crate) fn process_incoming_transactions_with_errors<V>(
+    smp: &SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+    timeline_state: TimelineState,
+) -> (
+    Vec<SubmissionStatusBundle>,
+    Vec<(SignedTransaction, anyhow::Error)>,
+)
+where
+    V: TransactionValidation,
+{
+    if transactions.is_empty() {
+        return (vec![], vec![]);
+    }
     let mut statuses = vec![];
+    let mut errors = vec![];
 
     let start_storage_read = Instant::now();
     // Track latency: fetching seq number
@@ -292,9 +552,11 @@ where
 
     {
         let mut mempool = smp.mempool.lock();
-        for (idx, (transaction, crsn_or_seqno)) in transactions.into_iter().enumerate() {
-            if let Ok(validation_result) = &validation_results[idx] {
-                match validation_result.status() {
+        for ((transaction, crsn_or_seqno), validation_result) in
+            transactions.into_iter().zip(validation_results)
+        {
+            match validation_result {
+                Ok(validation_result) => match validation_result.status() {
                     None => {
                         let gas_amount = transaction.max_gas_amount();
                         let ranking_score = validation_result.score();
@@ -318,12 +580,48 @@ where
                             ),
                         ));
                     }
-                }
+                },
+                Err(err) => errors.push((transaction, err)),
             }
         }
     }
     notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
-    statuses
+    (statuses, errors)
+}
+
+/// Resubmits the transactions recorded in the mempool journal by a previous run of the node.
+/// Transactions that don't make it back into mempool, e.g. expired or committed while the node
+/// was down, or failing validation, are dropped from the journal.
+pub(crate) fn reload_journaled_transactions<V>(smp: &SharedMempool<V>)
+where
+    V: TransactionValidation,
+{
+    let transactions = smp.mempool.lock().journaled_transactions();
+    if transactions.is_empty() {
+        return;
+    }
+
+    let keys: Vec<_> = transactions
+        .iter()
+        .map(|txn| (txn.sender(), txn.sequence_number()))
+        .collect();
+    process_incoming_transactions(smp, transactions, TimelineState::NotReady);
+
+    // Not only the rejected transactions are missing from mempool, but also the ones whose
+    // validation failed altogether, which get no status.
+    let mempool = smp.mempool.lock();
+    let mut discarded = 0;
+    for (sender, sequence_number) in keys.iter() {
+        if !mempool.contains_transaction(sender, *sequence_number) {
+            mempool.discard_journaled_transaction(sender, *sequence_number);
+            discarded += 1;
+        }
+    }
+    info!(
+        LogSchema::event_log(LogEntry::JournalReload, LogEvent::Success),
+        reloaded = keys.len() - discarded,
+        discarded = discarded,
+    );
 }
 
 fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<PeerNetworkId>) {
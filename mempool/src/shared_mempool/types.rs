@@ -3,8 +3,9 @@
 
 //! Objects used by/related to shared mempool
 use crate::{
-    core_mempool::CoreMempool, network::MempoolNetworkInterface,
-    shared_mempool::network::MempoolNetworkSender,
+    core_mempool::{CoreMempool, TtlCache},
+    network::MempoolNetworkInterface,
+    shared_mempool::{fairness::PeerRateLimiter, network::MempoolNetworkSender},
 };
 use anyhow::Result;
 use diem_config::{
@@ -13,6 +14,7 @@ use diem_config::{
 };
 use diem_crypto::HashValue;
 use diem_infallible::{Mutex, RwLock};
+use diem_logger::prelude::*;
 use diem_types::{
     account_address::AccountAddress, mempool_status::MempoolStatus, transaction::SignedTransaction,
     vm_status::DiscardedVMStatus,
@@ -30,12 +32,15 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::Waker,
-    time::{Instant, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
-use storage_interface::DbReader;
+use storage_interface::{DbReader, MAX_REQUEST_LIMIT};
 use tokio::runtime::Handle;
 use vm_validator::vm_validator::TransactionValidation;
 
+/// The maximum number of gas currency codes whose `GasPriceEstimate` is cached.
+const GAS_PRICE_ESTIMATE_CACHE_CAPACITY: usize = 16;
+
 /// Struct that owns all dependencies required by shared mempool routines.
 #[derive(Clone)]
 pub(crate) struct SharedMempool<V>
@@ -45,15 +50,18 @@ where
     pub mempool: Arc<Mutex<CoreMempool>>,
     pub config: MempoolConfig,
     pub(crate) network_interface: MempoolNetworkInterface,
+    pub(crate) peer_rate_limiter: Arc<PeerRateLimiter>,
     pub db: Arc<dyn DbReader>,
     pub validator: Arc<RwLock<V>>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
+    /// The recent `GasPriceEstimate`s by gas currency code.
+    pub(crate) gas_price_estimates: Arc<Mutex<TtlCache<String, GasPriceEstimate>>>,
 }
 
 impl<V: TransactionValidation + 'static> SharedMempool<V> {
     pub fn new(
         mempool: Arc<Mutex<CoreMempool>>,
-        config: MempoolConfig,
+        mut config: MempoolConfig,
         network_senders: HashMap<NetworkId, MempoolNetworkSender>,
         db: Arc<dyn DbReader>,
         validator: Arc<RwLock<V>>,
@@ -61,19 +69,44 @@ impl<V: TransactionValidation + 'static> SharedMempool<V> {
         role: RoleType,
         peer_metadata_storage: Arc<PeerMetadataStorage>,
     ) -> Self {
+        for (name, value) in [
+            (
+                "gas_price_estimate_mempool_transactions",
+                &mut config.gas_price_estimate_mempool_transactions,
+            ),
+            (
+                "gas_price_estimate_recent_transactions",
+                &mut config.gas_price_estimate_recent_transactions,
+            ),
+        ] {
+            if *value > MAX_REQUEST_LIMIT {
+                warn!(
+                    "mempool.{} is {}, which is more than {}; using {} instead",
+                    name, value, MAX_REQUEST_LIMIT, MAX_REQUEST_LIMIT
+                );
+                *value = MAX_REQUEST_LIMIT;
+            }
+        }
+        let gas_price_estimates = Arc::new(Mutex::new(TtlCache::new(
+            GAS_PRICE_ESTIMATE_CACHE_CAPACITY,
+            Duration::from_secs(config.gas_price_estimate_ttl_secs),
+        )));
         let network_interface = MempoolNetworkInterface::new(
             peer_metadata_storage,
             network_senders,
             role,
             config.clone(),
         );
+        let peer_rate_limiter = Arc::new(PeerRateLimiter::new(&config));
         SharedMempool {
             mempool,
             config,
             network_interface,
+            peer_rate_limiter,
             db,
             validator,
             subscribers,
+            gas_price_estimates,
         }
     }
 }
@@ -213,9 +246,127 @@ pub type SubmissionStatus = (MempoolStatus, Option<DiscardedVMStatus>);
 
 pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 
+/// Suggested gas unit prices for transactions paying gas in a currency, from the gas unit prices
+/// of the transactions in mempool and of recently committed transactions.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GasPriceEstimate {
+    /// The 25th percentile, for transactions that can wait.
+    pub low: u64,
+    /// The 50th percentile.
+    pub median: u64,
+    /// The 90th percentile, for transactions to be committed ahead of most others.
+    pub high: u64,
+}
+
+impl GasPriceEstimate {
+    /// Estimates from unordered gas unit price samples. All prices are 0 if there are none.
+    pub fn from_samples(mut gas_unit_prices: Vec<u64>) -> Self {
+        if gas_unit_prices.is_empty() {
+            return Self::default();
+        }
+        gas_unit_prices.sort_unstable();
+        let percentile = |p: usize| gas_unit_prices[(gas_unit_prices.len() - 1) * p / 100];
+        Self {
+            low: percentile(25),
+            median: percentile(50),
+            high: percentile(90),
+        }
+    }
+}
+
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
+    /// Submits transactions at once, responding with their statuses in the same order, or the
+    /// error of the validator for those it failed to validate.
+    SubmitTransactionBatch(
+        Vec<SignedTransaction>,
+        oneshot::Sender<Result<Vec<Result<SubmissionStatus>>>>,
+    ),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// Requests the `GasPriceEstimate` of a gas currency code.
+    GetGasPriceEstimate(String, oneshot::Sender<Result<GasPriceEstimate>>),
+    /// Requests the transactions of an account in mempool, in order of sequence number.
+    GetAccountTransactions(
+        AccountAddress,
+        oneshot::Sender<Vec<(SignedTransaction, PendingTransactionState)>>,
+    ),
+    /// Requests the number of transactions in mempool by state.
+    GetStats(oneshot::Sender<MempoolStats>),
+    /// Removes all transactions of an account from mempool, responding with them.
+    EvictAccountTransactions(AccountAddress, oneshot::Sender<Vec<SignedTransaction>>),
+    /// Removes a transaction from mempool by hash, responding with it if it was in mempool.
+    EvictTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// Requests the recent status changes of a transaction by hash, in order. Empty if the
+    /// transaction is unknown, or its status hasn't changed for a while.
+    GetTransactionStatusHistory(
+        HashValue,
+        oneshot::Sender<Vec<MempoolTransactionStatusEvent>>,
+    ),
+}
+
+/// State of a transaction in mempool.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PendingTransactionState {
+    /// Can be included in the next block.
+    Ready,
+    /// Waits for transactions with lower sequence numbers from the same sender.
+    Parked,
+}
+
+impl fmt::Display for PendingTransactionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PendingTransactionState::Ready => write!(f, "ready"),
+            PendingTransactionState::Parked => write!(f, "parked"),
+        }
+    }
+}
+
+/// A change in the status of a transaction in mempool.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MempoolTransactionStatus {
+    /// Accepted into mempool.
+    Accepted,
+    /// Waits for transactions with lower sequence numbers from the same sender.
+    Parked,
+    /// Can be included in the next block.
+    Ready,
+    /// Broadcast to `peers` peers so far.
+    Broadcast { peers: u64 },
+    /// Pulled into a block by consensus.
+    PulledIntoBlock,
+    /// Committed, or another transaction with the same sender and sequence number was.
+    Committed,
+    /// Rejected during execution, and removed from mempool.
+    Rejected { reason: String },
+    /// Expired, and removed from mempool.
+    Expired,
+    /// Removed from mempool before it could be committed.
+    Evicted { reason: String },
+}
+
+/// A `MempoolTransactionStatus` along with the time it was recorded.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MempoolTransactionStatusEvent {
+    pub status: MempoolTransactionStatus,
+    /// Microseconds since the Unix epoch.
+    pub timestamp_usecs: u64,
+}
+
+impl MempoolTransactionStatusEvent {
+    pub fn new(status: MempoolTransactionStatus) -> Self {
+        Self {
+            status,
+            timestamp_usecs: diem_infallible::duration_since_epoch().as_micros() as u64,
+        }
+    }
+}
+
+/// Number of transactions in mempool by `PendingTransactionState`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MempoolStats {
+    pub ready: usize,
+    pub parked: usize,
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
@@ -7,12 +7,14 @@ use crate::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
         TestTransaction,
     },
+    GasPriceEstimate, MempoolStats, MempoolTransactionStatus, PendingTransactionState,
 };
 use diem_config::config::NodeConfig;
 use diem_crypto::HashValue;
+use diem_temppath::TempPath;
 use diem_types::{
     account_config::AccountSequenceInfo,
-    transaction::{GovernanceRole, SignedTransaction},
+    transaction::{GovernanceRole, Script, SignedTransaction},
 };
 use std::{
     collections::HashSet,
@@ -285,6 +287,27 @@ fn test_update_transaction_in_mempool_crsn() {
     assert_eq!(consensus.get_block(&mut mempool, 1), vec![txns[1].clone()]);
 }
 
+#[test]
+fn test_update_transaction_requires_gas_price_bump() {
+    let (mut mempool, mut consensus) = setup_mempool();
+    let _ = add_txns_to_mempool(&mut mempool, vec![TestTransaction::new(0, 0, 100)]);
+
+    // the default bump is 10%
+    assert!(add_txn(&mut mempool, TestTransaction::new(0, 0, 109)).is_err());
+    let fixed_txns = add_txns_to_mempool(&mut mempool, vec![TestTransaction::new(0, 0, 110)]);
+    assert_eq!(
+        consensus.get_block(&mut mempool, 2),
+        vec![fixed_txns[0].clone()]
+    );
+
+    let mut config = NodeConfig::random();
+    config.mempool.replacement_gas_price_bump_percent = 0;
+    let mut mempool = CoreMempool::new(&config);
+    let _ = add_txns_to_mempool(&mut mempool, vec![TestTransaction::new(0, 0, 100)]);
+    assert!(add_txn(&mut mempool, TestTransaction::new(0, 0, 99)).is_err());
+    assert!(add_txn(&mut mempool, TestTransaction::new(0, 0, 101)).is_ok());
+}
+
 #[test]
 fn test_ignore_same_transaction_submitted_to_mempool() {
     let (mut mempool, _) = setup_mempool();
@@ -323,7 +346,7 @@ fn test_fail_for_same_gas_amount_and_not_same_expiration_time_crsn() {
 }
 
 #[test]
-fn test_update_invalid_transaction_in_mempool() {
+fn test_update_transaction_with_different_max_gas_amount() {
     let (mut mempool, mut consensus) = setup_mempool();
     let txns = add_txns_to_mempool(
         &mut mempool,
@@ -333,18 +356,15 @@ fn test_update_invalid_transaction_in_mempool() {
         &TestTransaction::new(0, 0, 5),
         200,
     );
-    let _added_tnx = add_signed_txn(&mut mempool, updated_txn);
+    assert!(add_signed_txn(&mut mempool, updated_txn.clone()).is_ok());
 
-    // Since both gas price and mas gas amount were updated, the ordering should not have changed.
-    // The second transaction with gas price 2 should come first.
+    // The replacement with a higher gas price comes first.
+    assert_eq!(consensus.get_block(&mut mempool, 1), vec![updated_txn]);
     assert_eq!(consensus.get_block(&mut mempool, 1), vec![txns[1].clone()]);
-    let next_tnx = consensus.get_block(&mut mempool, 1);
-    assert_eq!(next_tnx, vec![txns[0].clone()]);
-    assert_eq!(next_tnx[0].gas_unit_price(), 1);
 }
 
 #[test]
-fn test_update_invalid_transaction_in_mempool_crsn() {
+fn test_update_transaction_with_different_max_gas_amount_crsn() {
     let (mut mempool, mut consensus) = setup_mempool();
     let txns = add_txns_to_mempool(
         &mut mempool,
@@ -357,14 +377,23 @@ fn test_update_invalid_transaction_in_mempool_crsn() {
         &TestTransaction::new(0, 0, 5).crsn(0),
         200,
     );
-    let _added_tnx = add_signed_txn(&mut mempool, updated_txn);
+    assert!(add_signed_txn(&mut mempool, updated_txn.clone()).is_ok());
 
-    // Since both gas price and mas gas amount were updated, the ordering should not have changed.
-    // The second transaction with gas price 2 should come first.
+    // The replacement with a higher gas price comes first.
+    assert_eq!(consensus.get_block(&mut mempool, 1), vec![updated_txn]);
     assert_eq!(consensus.get_block(&mut mempool, 1), vec![txns[1].clone()]);
-    let next_tnx = consensus.get_block(&mut mempool, 1);
-    assert_eq!(next_tnx, vec![txns[0].clone()]);
-    assert_eq!(next_tnx[0].gas_unit_price(), 1);
+}
+
+#[test]
+fn test_update_transaction_with_different_payload() {
+    let (mut mempool, mut consensus) = setup_mempool();
+    let _ = add_txns_to_mempool(&mut mempool, vec![TestTransaction::new(0, 0, 1)]);
+    let updated_txn = TestTransaction::new(0, 0, 5)
+        .make_signed_transaction_with_script(Script::new(vec![1, 2, 3], vec![], vec![]));
+    assert!(add_signed_txn(&mut mempool, updated_txn.clone()).is_ok());
+
+    // The replaced transaction is gone.
+    assert_eq!(consensus.get_block(&mut mempool, 2), vec![updated_txn]);
 }
 
 #[test]
@@ -603,6 +632,46 @@ fn test_parking_lot_evict_only_for_ready_txn_insertion() {
     }
 }
 
+#[test]
+fn test_lowest_priority_eviction() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 3;
+    let mut pool = CoreMempool::new(&config);
+    for txn in vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 1),
+        TestTransaction::new(1, 0, 5),
+    ] {
+        add_txn(&mut pool, txn).unwrap();
+    }
+
+    // Mempool is full and the parking lot is empty. A txn with a higher gas price evicts the
+    // lowest priority txn.
+    add_txn(&mut pool, TestTransaction::new(2, 0, 3)).unwrap();
+    let txns: Vec<_> = pool
+        .get_block(3, HashSet::new())
+        .iter()
+        .map(SignedTransaction::sequence_number)
+        .collect();
+    assert_eq!(txns, vec![0, 0, 0]);
+
+    // A txn with the same gas price as the lowest priority txn doesn't evict it.
+    assert!(add_txn(&mut pool, TestTransaction::new(3, 0, 1)).is_err());
+
+    // The lowest priority txn of the same sender isn't evicted either, as the new txn is only
+    // ready after it.
+    assert!(add_txn(&mut pool, TestTransaction::new(0, 1, 2)).is_err());
+
+    add_txn(&mut pool, TestTransaction::new(3, 0, 2)).unwrap();
+    let mut txns: Vec<_> = pool
+        .get_block(3, HashSet::new())
+        .iter()
+        .map(SignedTransaction::gas_unit_price)
+        .collect();
+    txns.sort_unstable();
+    assert_eq!(txns, vec![2, 3, 5]);
+}
+
 #[test]
 fn test_gc_ready_transaction() {
     let mut pool = setup_mempool().0;
@@ -740,3 +809,230 @@ fn test_get_transaction_by_hash_after_the_txn_is_updated() {
     let txn_by_new_hash = pool.get_by_hash(new_txn_hash);
     assert_eq!(txn_by_new_hash, Some(new_txn));
 }
+
+#[test]
+fn test_get_account_transactions_and_stats() {
+    let (mut mempool, _) = setup_mempool();
+    // seq 2 of account 0 waits for seq 1
+    let txns = add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 2, 1),
+            TestTransaction::new(1, 0, 1),
+        ],
+    );
+
+    assert_eq!(
+        mempool.get_account_transactions(&txns[0].sender()),
+        vec![
+            (txns[0].clone(), PendingTransactionState::Ready),
+            (txns[1].clone(), PendingTransactionState::Parked),
+        ]
+    );
+    assert!(mempool
+        .get_account_transactions(&TestTransaction::get_address(2))
+        .is_empty());
+    assert_eq!(
+        mempool.get_stats(),
+        MempoolStats {
+            ready: 2,
+            parked: 1
+        }
+    );
+}
+
+#[test]
+fn test_evict_account_transactions() {
+    let (mut mempool, mut consensus) = setup_mempool();
+    let txns = add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(1, 0, 1),
+        ],
+    );
+
+    assert_eq!(
+        mempool.evict_account_transactions(&txns[0].sender()),
+        vec![txns[0].clone(), txns[1].clone()]
+    );
+    assert!(mempool
+        .evict_account_transactions(&txns[0].sender())
+        .is_empty());
+    assert_eq!(consensus.get_block(&mut mempool, 3), vec![txns[2].clone()]);
+}
+
+#[test]
+fn test_evict_transaction_by_hash() {
+    let (mut mempool, mut consensus) = setup_mempool();
+    let txns = add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(1, 0, 1),
+        ],
+    );
+
+    let hash = txns[0].clone().committed_hash();
+    assert_eq!(
+        mempool.evict_transaction_by_hash(hash),
+        Some(txns[0].clone())
+    );
+    assert_eq!(mempool.evict_transaction_by_hash(hash), None);
+
+    // the following transaction of the sender is parked until the evicted one is resubmitted
+    assert_eq!(
+        mempool.get_stats(),
+        MempoolStats {
+            ready: 1,
+            parked: 1
+        }
+    );
+    assert_eq!(consensus.get_block(&mut mempool, 3), vec![txns[2].clone()]);
+}
+
+#[test]
+fn test_transaction_status_history() {
+    let (mut mempool, mut consensus) = setup_mempool();
+    // seq 2 is parked until seq 1 arrives
+    let txns = add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 2, 1),
+            TestTransaction::new(0, 1, 1),
+        ],
+    );
+    let sender = txns[0].sender();
+    mempool.record_broadcast(&[(sender, 0), (sender, 1)]);
+    mempool.record_broadcast(&[(sender, 0)]);
+    assert_eq!(consensus.get_block(&mut mempool, 3).len(), 3);
+    mempool.remove_transaction(&sender, 0, false);
+    mempool.remove_transaction(&sender, 1, true);
+
+    let statuses = |txn: &SignedTransaction| -> Vec<MempoolTransactionStatus> {
+        mempool
+            .get_status_history(&txn.clone().committed_hash())
+            .into_iter()
+            .map(|event| event.status)
+            .collect()
+    };
+    assert_eq!(
+        statuses(&txns[0]),
+        vec![
+            MempoolTransactionStatus::Accepted,
+            MempoolTransactionStatus::Ready,
+            MempoolTransactionStatus::Broadcast { peers: 2 },
+            MempoolTransactionStatus::PulledIntoBlock,
+            MempoolTransactionStatus::Committed,
+        ]
+    );
+    assert_eq!(
+        statuses(&txns[1]),
+        vec![
+            MempoolTransactionStatus::Accepted,
+            MempoolTransactionStatus::Parked,
+            MempoolTransactionStatus::Ready,
+            MempoolTransactionStatus::PulledIntoBlock,
+            MempoolTransactionStatus::Evicted {
+                reason: "transaction 1 of the account was rejected during execution".to_string()
+            },
+        ]
+    );
+    assert_eq!(
+        statuses(&txns[2]),
+        vec![
+            MempoolTransactionStatus::Accepted,
+            MempoolTransactionStatus::Ready,
+            MempoolTransactionStatus::Broadcast { peers: 1 },
+            MempoolTransactionStatus::PulledIntoBlock,
+            MempoolTransactionStatus::Rejected {
+                reason: "rejected during execution".to_string()
+            },
+        ]
+    );
+    assert!(mempool.get_status_history(&HashValue::random()).is_empty());
+}
+
+#[test]
+fn test_gas_unit_prices() {
+    let (mut mempool, _) = setup_mempool();
+    add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 3),
+            TestTransaction::new(1, 0, 5),
+            TestTransaction::new(2, 0, 1),
+            // Parked, so not ready for consensus.
+            TestTransaction::new(3, 1, 10),
+        ],
+    );
+    assert_eq!(mempool.gas_unit_prices("XUS", 10), vec![5, 3, 1]);
+    assert_eq!(mempool.gas_unit_prices("XUS", 2), vec![5, 3]);
+    assert!(mempool.gas_unit_prices("XDX", 10).is_empty());
+}
+
+#[test]
+fn test_gas_price_estimate_from_samples() {
+    assert_eq!(
+        GasPriceEstimate::from_samples(vec![]),
+        GasPriceEstimate::default()
+    );
+    assert_eq!(
+        GasPriceEstimate::from_samples(vec![7]),
+        GasPriceEstimate {
+            low: 7,
+            median: 7,
+            high: 7,
+        }
+    );
+    assert_eq!(
+        GasPriceEstimate::from_samples((0..=100).rev().collect()),
+        GasPriceEstimate {
+            low: 25,
+            median: 50,
+            high: 90,
+        }
+    );
+}
+
+#[test]
+fn test_journal() {
+    let tmp_dir = TempPath::new();
+    let mut config = NodeConfig::random();
+    config.mempool.journal_enabled = true;
+    config.storage.dir = tmp_dir.path().to_path_buf();
+
+    let mut mempool = CoreMempool::new(&config);
+    let txns = add_txns_to_mempool(
+        &mut mempool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(1, 0, 1),
+            TestTransaction::new(2, 0, 1),
+        ],
+    );
+    // the journal follows updates and removals
+    let updated_txn = TestTransaction::new(1, 0, 5).make_signed_transaction();
+    add_signed_txn(&mut mempool, updated_txn.clone()).unwrap();
+    mempool.remove_transaction(&txns[0].sender(), 0, false);
+
+    // a new mempool on the same storage sees the journaled transactions, but doesn't load them
+    // until they are resubmitted
+    drop(mempool);
+    let mempool = CoreMempool::new(&config);
+    let mut journaled = mempool.journaled_transactions();
+    journaled.sort_by_key(|txn| txn.sender());
+    let mut expected = vec![updated_txn, txns[2].clone()];
+    expected.sort_by_key(|txn| txn.sender());
+    assert_eq!(journaled, expected);
+    assert!(mempool
+        .get_by_hash(txns[2].clone().committed_hash())
+        .is_none());
+
+    mempool.discard_journaled_transaction(&txns[2].sender(), 0);
+    assert_eq!(mempool.journaled_transactions().len(), 1);
+}
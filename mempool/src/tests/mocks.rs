@@ -166,7 +166,7 @@ impl MockSharedMempool {
     }
 
     pub fn get_txns(&self, size: u64) -> Vec<SignedTransaction> {
-        let pool = self.mempool.lock();
+        let mut pool = self.mempool.lock();
         pool.get_block(size, HashSet::new())
     }
 
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #[cfg(test)]
-mod common;
+pub(crate) mod common;
 #[cfg(test)]
 mod core_mempool_test;
 #[cfg(test)]
@@ -3,10 +3,11 @@
 
 use crate::{
     mocks::MockSharedMempool,
-    shared_mempool::types::TransactionSummary,
+    shared_mempool::{fairness::PeerRateLimiter, types::TransactionSummary},
     tests::common::{batch_add_signed_txn, TestTransaction},
     ConsensusRequest,
 };
+use diem_config::{config::MempoolConfig, network_id::PeerNetworkId};
 use diem_types::transaction::Transaction;
 use futures::{channel::oneshot, executor::block_on, sink::SinkExt};
 use mempool_notifications::MempoolNotificationSender;
@@ -93,3 +94,50 @@ fn test_mempool_notify_committed_txns() {
     assert_eq!(timeline.len(), 1);
     assert_eq!(timeline.get(0).unwrap(), &kept_txn);
 }
+
+#[test]
+fn test_peer_rate_limiter() {
+    let config = MempoolConfig {
+        shared_mempool_peer_rate_limit_enabled: true,
+        shared_mempool_peer_txn_bucket_rate: 1,
+        shared_mempool_peer_txn_bucket_size: 8,
+        ..MempoolConfig::default()
+    };
+    let rate_limiter = PeerRateLimiter::new(&config);
+    let (peer, other_peer) = (PeerNetworkId::random(), PeerNetworkId::random());
+    let broadcast = || {
+        (0..3)
+            .map(|seq| TestTransaction::new(0, seq, 1).make_signed_transaction())
+            .collect::<Vec<_>>()
+    };
+
+    // A new peer gets a quarter of a full bucket.
+    let mut txns = broadcast();
+    assert!(rate_limiter.throttle(peer, &mut txns));
+    assert_eq!(txns, broadcast()[..2].to_vec());
+    let mut txns = broadcast();
+    assert!(rate_limiter.throttle(peer, &mut txns));
+    assert!(txns.is_empty());
+
+    // Other peers are not affected.
+    let mut txns = broadcast();
+    assert!(rate_limiter.throttle(other_peer, &mut txns));
+    assert_eq!(txns.len(), 2);
+
+    // Validators are not rate limited.
+    let validator = PeerNetworkId::random_validator();
+    for _ in 0..5 {
+        let mut txns = broadcast();
+        assert!(!rate_limiter.throttle(validator, &mut txns));
+        assert_eq!(txns, broadcast());
+    }
+
+    let config = MempoolConfig {
+        shared_mempool_peer_rate_limit_enabled: false,
+        ..config
+    };
+    let rate_limiter = PeerRateLimiter::new(&config);
+    let mut txns = broadcast();
+    assert!(!rate_limiter.throttle(peer, &mut txns));
+    assert_eq!(txns, broadcast());
+}
@@ -12,6 +12,9 @@ use crate::{
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+#[cfg(feature = "client")]
+use crate::client::{views::GasPriceEstimateView, BlockingClient};
+
 pub use diem_transaction_builder::{experimental_stdlib, stdlib};
 use diem_types::transaction::{ChangeSet, ModuleBundle, Script, ScriptFunction, WriteSetPayload};
 
@@ -109,6 +112,38 @@ impl TransactionFactory {
         self
     }
 
+    /// Sets the gas unit price to the one `estimate` suggests at `level`.
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    pub fn with_gas_price_estimate(
+        self,
+        estimate: &GasPriceEstimateView,
+        level: GasPriceLevel,
+    ) -> Self {
+        let gas_unit_price = match level {
+            GasPriceLevel::Low => estimate.low,
+            GasPriceLevel::Median => estimate.median,
+            GasPriceLevel::High => estimate.high,
+        };
+        self.with_gas_unit_price(gas_unit_price)
+    }
+
+    /// Sets the gas unit price to the one suggested at `level` by the node `client` connects to,
+    /// for the gas currency of this factory. Use `with_gas_price_estimate` with the estimate from
+    /// an async `Client` instead.
+    #[cfg(feature = "client")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "client")))]
+    pub fn with_estimated_gas_unit_price(
+        self,
+        client: &BlockingClient,
+        level: GasPriceLevel,
+    ) -> crate::client::Result<Self> {
+        let estimate = client
+            .get_gas_price_estimate(self.gas_currency.as_str().to_owned())?
+            .into_inner();
+        Ok(self.with_gas_price_estimate(&estimate, level))
+    }
+
     pub fn with_gas_currency(mut self, gas_currency: Currency) -> Self {
         self.gas_currency = gas_currency;
         self
@@ -603,6 +638,15 @@ impl DualAttestationMessage {
     }
 }
 
+/// Which of the gas unit prices suggested by a node to pay, from the cheapest to the one most
+/// likely to get a transaction committed soon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasPriceLevel {
+    Low,
+    Median,
+    High,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Currency {
@@ -6,6 +6,7 @@
 use channel::{diem_channel, message_queues::QueueStyle};
 use diem_id_generator::{IdGenerator, U64IdGenerator};
 use diem_infallible::RwLock;
+use diem_logger::prelude::*;
 use diem_types::{
     account_state::AccountState,
     contract_event::ContractEvent,
@@ -37,6 +38,7 @@ mod tests;
 // will be retrieved using FIFO ordering.
 const EVENT_NOTIFICATION_CHANNEL_SIZE: usize = 100;
 const RECONFIG_NOTIFICATION_CHANNEL_SIZE: usize = 1;
+const COMMIT_NOTIFICATION_CHANNEL_SIZE: usize = 1;
 
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Serialize)]
 pub enum Error {
@@ -79,6 +81,9 @@ pub struct EventSubscriptionService {
     // Reconfig subscription registry
     reconfig_subscriptions: HashMap<SubscriptionId, ReconfigSubscription>,
 
+    // Commit subscription registry
+    commit_subscriptions: HashMap<SubscriptionId, CommitSubscription>,
+
     // Database to fetch on-chain configuration data
     storage: Arc<RwLock<DbReaderWriter>>,
 
@@ -95,6 +100,7 @@ impl EventSubscriptionService {
             event_key_subscriptions: HashMap::new(),
             subscription_id_to_event_subscription: HashMap::new(),
             reconfig_subscriptions: HashMap::new(),
+            commit_subscriptions: HashMap::new(),
             config_registry: config_registry.to_vec(),
             storage,
             subscription_id_generator: U64IdGenerator::new(),
@@ -186,6 +192,39 @@ impl EventSubscriptionService {
         })
     }
 
+    /// Returns a CommitNotificationListener that can be monitored for new
+    /// commits. Subscribers will be sent a notification containing the latest
+    /// synced version every time the subscription service is notified of new
+    /// events, regardless of whether any events were emitted. Only the latest
+    /// notification is kept, so subscribers that fall behind will simply see
+    /// the latest version (instead of every version in between).
+    pub fn subscribe_to_commits(&mut self) -> Result<CommitNotificationListener, Error> {
+        let (notification_sender, notification_receiver) =
+            diem_channel::new(QueueStyle::KLAST, COMMIT_NOTIFICATION_CHANNEL_SIZE, None);
+
+        // Create a new commit subscription
+        let subscription_id = self.get_new_subscription_id();
+        let commit_subscription = CommitSubscription {
+            subscription_id,
+            notification_sender,
+        };
+
+        // Store the new subscription
+        if let Some(old_subscription) = self
+            .commit_subscriptions
+            .insert(subscription_id, commit_subscription)
+        {
+            panic!(
+                "Duplicate commit subscription found! This should not occur! ID: {}, subscription: {:?}",
+                subscription_id, old_subscription
+            );
+        }
+
+        Ok(CommitNotificationListener {
+            notification_receiver,
+        })
+    }
+
     fn get_new_subscription_id(&mut self) -> u64 {
         self.subscription_id_generator.next()
     }
@@ -243,6 +282,26 @@ impl EventSubscriptionService {
         Ok(reconfig_event_found)
     }
 
+    /// This notifies all the commit subscribers of the latest synced version.
+    /// Commit subscribers are best effort, so failing to notify one doesn't fail
+    /// the notification. Instead, the subscription is removed, as sending only
+    /// fails once the listener has been dropped.
+    fn notify_commit_subscribers(&mut self, version: Version) {
+        self.commit_subscriptions
+            .retain(|subscription_id, commit_subscription| {
+                match commit_subscription.notify_subscriber_of_commit(version) {
+                    Ok(()) => true,
+                    Err(error) => {
+                        warn!(
+                            "Failed to notify commit subscriber {}, removing it. Error: {:?}",
+                            subscription_id, error
+                        );
+                        false
+                    }
+                }
+            });
+    }
+
     /// This notifies all the reconfiguration subscribers of the on-chain
     /// configurations at the specified version.
     fn notify_reconfiguration_subscribers(&mut self, version: Version) -> Result<(), Error> {
@@ -327,20 +386,23 @@ impl EventSubscriptionService {
 
 impl EventNotificationSender for EventSubscriptionService {
     fn notify_events(&mut self, version: Version, events: Vec<ContractEvent>) -> Result<(), Error> {
-        if events.is_empty() {
-            return Ok(()); // No events!
+        if !events.is_empty() {
+            // Notify event subscribers and check if a reconfiguration event was processed
+            let reconfig_event_processed = self.notify_event_subscribers(version, events)?;
+
+            // If a reconfiguration event was found, also notify the reconfig subscribers
+            // of the new configuration values.
+            if reconfig_event_processed {
+                self.notify_reconfiguration_subscribers(version)?;
+            }
         }
 
-        // Notify event subscribers and check if a reconfiguration event was processed
-        let reconfig_event_processed = self.notify_event_subscribers(version, events)?;
+        // Notify commit subscribers of the new version last (even if there are no
+        // events), so that the events and configs of the version have been sent
+        // by the time commit subscribers hear about it.
+        self.notify_commit_subscribers(version);
 
-        // If a reconfiguration event was found, also notify the reconfig subscribers
-        // of the new configuration values.
-        if reconfig_event_processed {
-            self.notify_reconfiguration_subscribers(version)
-        } else {
-            Ok(())
-        }
+        Ok(())
     }
 
     fn notify_initial_configs(&mut self, version: Version) -> Result<(), Error> {
@@ -402,6 +464,22 @@ impl ReconfigSubscription {
     }
 }
 
+/// A single commit subscription, holding the channel to send the
+/// corresponding notifications.
+#[derive(Debug)]
+struct CommitSubscription {
+    pub subscription_id: SubscriptionId,
+    pub notification_sender: channel::diem_channel::Sender<(), CommitNotification>,
+}
+
+impl CommitSubscription {
+    fn notify_subscriber_of_commit(&mut self, version: Version) -> Result<(), Error> {
+        self.notification_sender
+            .push((), CommitNotification { version })
+            .map_err(|error| Error::UnexpectedErrorEncountered(format!("{:?}", error)))
+    }
+}
+
 /// A notification for events.
 #[derive(Debug)]
 pub struct EventNotification {
@@ -416,12 +494,21 @@ pub struct ReconfigNotification {
     pub on_chain_configs: OnChainConfigPayload,
 }
 
+/// A notification for commits.
+#[derive(Debug)]
+pub struct CommitNotification {
+    pub version: Version,
+}
+
 /// A subscription listener for on-chain events.
 pub type EventNotificationListener = NotificationListener<EventNotification>;
 
 /// A subscription listener for reconfigurations.
 pub type ReconfigNotificationListener = NotificationListener<ReconfigNotification>;
 
+/// A subscription listener for commits.
+pub type CommitNotificationListener = NotificationListener<CommitNotification>;
+
 /// The component responsible for listening to subscription notifications.
 #[derive(Debug)]
 pub struct NotificationListener<T> {
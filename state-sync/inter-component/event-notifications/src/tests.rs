@@ -4,8 +4,8 @@
 #![forbid(unsafe_code)]
 
 use crate::{
-    Error, EventNotificationListener, EventNotificationSender, EventSubscriptionService,
-    ReconfigNotificationListener,
+    CommitNotificationListener, Error, EventNotificationListener, EventNotificationSender,
+    EventSubscriptionService, ReconfigNotificationListener,
 };
 use claim::{assert_lt, assert_matches, assert_ok};
 use diem_infallible::RwLock;
@@ -293,6 +293,47 @@ fn test_event_notification_queuing() {
     verify_no_event_notifications(vec![&mut listener_1, &mut listener_2]);
 }
 
+#[test]
+fn test_commit_subscribers() {
+    // Create subscription service and mock database
+    let mut event_service = create_event_subscription_service();
+
+    // Create commit subscribers
+    let mut listener_1 = event_service.subscribe_to_commits().unwrap();
+    let mut listener_2 = event_service.subscribe_to_commits().unwrap();
+
+    // Notify the subscription service of a commit without events and verify
+    // the listeners are notified.
+    notify_events(&mut event_service, 5, vec![]);
+    verify_commit_notifications_received(vec![&mut listener_1, &mut listener_2], 5);
+
+    // Notify the subscription service of 100 commits with events and verify
+    // only the latest version is received (i.e., old notifications are dropped).
+    let event = create_test_event(create_random_event_key());
+    for version in 6..106 {
+        notify_events(&mut event_service, version, vec![event.clone()]);
+    }
+    verify_commit_notifications_received(vec![&mut listener_1, &mut listener_2], 105);
+    verify_no_commit_notifications(vec![&mut listener_1, &mut listener_2]);
+}
+
+#[test]
+fn test_dropped_commit_subscribers() {
+    // Create subscription service and mock database
+    let mut event_service = create_event_subscription_service();
+
+    // Create commit subscribers and drop one of them
+    let listener_1 = event_service.subscribe_to_commits().unwrap();
+    let mut listener_2 = event_service.subscribe_to_commits().unwrap();
+    drop(listener_1);
+
+    // Notify the subscription service of a commit and verify the notification
+    // succeeds, the remaining listener is notified and the dropped one is removed.
+    notify_events(&mut event_service, 5, vec![]);
+    verify_commit_notifications_received(vec![&mut listener_2], 5);
+    assert_eq!(event_service.commit_subscriptions.len(), 1);
+}
+
 #[test]
 fn test_event_subscribers() {
     // Create subscription service and mock database
@@ -476,6 +517,27 @@ fn verify_no_reconfig_notifications(listeners: Vec<&mut ReconfigNotificationList
     }
 }
 
+// Ensures that no commit notifications have been received by the listeners
+fn verify_no_commit_notifications(listeners: Vec<&mut CommitNotificationListener>) {
+    for listener in listeners {
+        assert!(listener.select_next_some().now_or_never().is_none());
+    }
+}
+
+// Ensures that the specified listeners have received the expected commit notifications.
+fn verify_commit_notifications_received(
+    listeners: Vec<&mut CommitNotificationListener>,
+    expected_version: Version,
+) {
+    for listener in listeners {
+        if let Some(commit_notification) = listener.select_next_some().now_or_never() {
+            assert_eq!(commit_notification.version, expected_version);
+        } else {
+            panic!("Expected a commit notification but got None!");
+        }
+    }
+}
+
 // Ensures that the specified listeners have received the expected notifications.
 fn verify_event_notification_received(
     listeners: Vec<&mut EventNotificationListener>,
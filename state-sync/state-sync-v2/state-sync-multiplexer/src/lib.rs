@@ -193,7 +193,16 @@ mod tests {
     fn test_new_initialized_configs() {
         // Create a test database
         let tmp_dir = TempPath::new();
-        let db = DiemDB::open(&tmp_dir, false, None, RocksdbConfig::default(), true).unwrap();
+        let db = DiemDB::open(
+            &tmp_dir,
+            false,
+            None,
+            None,
+            RocksdbConfig::default(),
+            true,
+            false,
+        )
+        .unwrap();
         let (_, db_rw) = DbReaderWriter::wrap(db);
 
         // Bootstrap the database
@@ -11,9 +11,10 @@ use crate::{
 use ::network::ProtocolId;
 use bounded_executor::BoundedExecutor;
 use diem_config::config::StorageServiceConfig;
+use diem_crypto::HashValue;
 use diem_logger::prelude::*;
 use diem_types::{
-    account_state_blob::AccountStatesChunkWithProof,
+    account_state_blob::{AccountStatesChunkWithProof, AccountStatesRangeWithProof},
     epoch_change::EpochChangeProof,
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
@@ -22,8 +23,8 @@ use serde::{Deserialize, Serialize};
 use std::{sync::Arc, time::Duration};
 use storage_interface::DbReader;
 use storage_service_types::{
-    AccountStatesChunkWithProofRequest, CompleteDataRange, DataSummary,
-    EpochEndingLedgerInfoRequest, ProtocolMetadata, Result, ServerProtocolVersion,
+    AccountStatesChunkWithProofRequest, AccountStatesRangeWithProofRequest, CompleteDataRange,
+    DataSummary, EpochEndingLedgerInfoRequest, ProtocolMetadata, Result, ServerProtocolVersion,
     StorageServerSummary, StorageServiceError, StorageServiceRequest, StorageServiceResponse,
     TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
 };
@@ -169,6 +170,9 @@ impl<T: StorageReaderInterface> Handler<T> {
             StorageServiceRequest::GetTransactionsWithProof(request) => {
                 self.get_transactions_with_proof(request)
             }
+            StorageServiceRequest::GetAccountStatesRangeWithProof(request) => {
+                self.get_account_states_range_with_proof(request)
+            }
         };
 
         // If the request resulted in an unexpected error, return an internal error
@@ -210,6 +214,22 @@ impl<T: StorageReaderInterface> Handler<T> {
         ))
     }
 
+    fn get_account_states_range_with_proof(
+        &self,
+        request: &AccountStatesRangeWithProofRequest,
+    ) -> Result<StorageServiceResponse, Error> {
+        let account_states_range_with_proof = self.storage.get_account_states_range_with_proof(
+            request.version,
+            request.start_key,
+            request.end_key,
+            self.config.max_account_states_chunk_sizes,
+        )?;
+
+        Ok(StorageServiceResponse::AccountStatesRangeWithProof(
+            account_states_range_with_proof,
+        ))
+    }
+
     fn get_epoch_ending_ledger_infos(
         &self,
         request: &EpochEndingLedgerInfoRequest,
@@ -342,6 +362,18 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         start_account_index: u64,
         end_account_index: u64,
     ) -> Result<AccountStatesChunkWithProof, Error>;
+
+    /// Returns the account states with keys starting at `start_key` and
+    /// ending at `end_key` (inclusive), with at most `max_num_accounts`
+    /// accounts. If there are more accounts, the range in the response ends
+    /// at the last account returned.
+    fn get_account_states_range_with_proof(
+        &self,
+        version: u64,
+        start_key: HashValue,
+        end_key: HashValue,
+        max_num_accounts: u64,
+    ) -> Result<AccountStatesRangeWithProof, Error>;
 }
 
 /// The underlying implementation of the StorageReaderInterface, used by the
@@ -552,6 +584,26 @@ impl StorageReaderInterface for StorageReader {
             .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
         Ok(account_states_chunk_with_proof)
     }
+
+    fn get_account_states_range_with_proof(
+        &self,
+        version: u64,
+        start_key: HashValue,
+        end_key: HashValue,
+        max_num_accounts: u64,
+    ) -> Result<AccountStatesRangeWithProof, Error> {
+        if start_key > end_key {
+            return Err(Error::InvalidRequest(format!(
+                "end_key ({:x}) must be >= start_key ({:x})",
+                end_key, start_key
+            )));
+        }
+        let account_states_range_with_proof = self
+            .storage
+            .get_account_range_with_proof(version, start_key, end_key, max_num_accounts as usize)
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+        Ok(account_states_range_with_proof)
+    }
 }
 
 /// Calculate `(start..=end).len()`. Returns an error if `end < start` or
@@ -6,20 +6,25 @@
 use crate::{network::StorageServiceNetworkEvents, StorageReader, StorageServiceServer};
 use anyhow::Result;
 use channel::diem_channel;
-use claim::{assert_none, assert_some};
+use claim::{assert_matches, assert_none, assert_some};
 use diem_config::config::StorageServiceConfig;
 use diem_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
 use diem_logger::Level;
 use diem_types::{
     account_address::AccountAddress,
-    account_state_blob::AccountStatesChunkWithProof,
+    account_state_blob::{
+        AccountStateBlob, AccountStatesChunkWithProof, AccountStatesRangeWithProof,
+    },
     block_info::BlockInfo,
     chain_id::ChainId,
     contract_event::ContractEvent,
     epoch_change::EpochChangeProof,
     event::EventKey,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    proof::{SparseMerkleRangeProof, TransactionInfoListWithProof},
+    proof::{
+        SparseMerkleKeyRangeProof, SparseMerkleProof, SparseMerkleRangeProof,
+        TransactionInfoListWithProof,
+    },
     transaction::{
         RawTransaction, Script, SignedTransaction, Transaction, TransactionListWithProof,
         TransactionOutput, TransactionOutputListWithProof, TransactionPayload, TransactionStatus,
@@ -39,10 +44,10 @@ use network::{
 use std::{collections::BTreeMap, sync::Arc};
 use storage_interface::DbReader;
 use storage_service_types::{
-    AccountStatesChunkWithProofRequest, CompleteDataRange, DataSummary,
-    EpochEndingLedgerInfoRequest, ProtocolMetadata, ServerProtocolVersion, StorageServerSummary,
-    StorageServiceError, StorageServiceMessage, StorageServiceRequest, StorageServiceResponse,
-    TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+    AccountStatesChunkWithProofRequest, AccountStatesRangeWithProofRequest, CompleteDataRange,
+    DataSummary, EpochEndingLedgerInfoRequest, ProtocolMetadata, ServerProtocolVersion,
+    StorageServerSummary, StorageServiceError, StorageServiceMessage, StorageServiceRequest,
+    StorageServiceResponse, TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
 };
 
 // TODO(joshlind): Expand these test cases to better test storage interaction
@@ -109,6 +114,42 @@ async fn test_get_account_states_chunk_with_proof() {
     assert_eq!(response, expected_response);
 }
 
+#[tokio::test]
+async fn test_get_account_states_range_with_proof() {
+    let (mut mock_client, service) = MockClient::new();
+    tokio::spawn(service.start());
+
+    // Create a request to fetch the account states in a range of keys with a proof
+    let start_key = HashValue::zero();
+    let end_key = HashValue::new([0xff; HashValue::LENGTH]);
+    let request =
+        StorageServiceRequest::GetAccountStatesRangeWithProof(AccountStatesRangeWithProofRequest {
+            version: 0,
+            start_key,
+            end_key,
+        });
+
+    // Process the request
+    let response = mock_client.send_request(request).await.unwrap();
+
+    // Verify the response is cut short at the max chunk size
+    let max_num_accounts = StorageServiceConfig::default().max_account_states_chunk_sizes;
+    let expected_response = StorageServiceResponse::AccountStatesRangeWithProof(
+        create_test_account_states_range(start_key, end_key, max_num_accounts as usize),
+    );
+    assert_eq!(response, expected_response);
+
+    // Process a request with an invalid range and verify an error is returned
+    let request =
+        StorageServiceRequest::GetAccountStatesRangeWithProof(AccountStatesRangeWithProofRequest {
+            version: 0,
+            start_key: end_key,
+            end_key: start_key,
+        });
+    let response = mock_client.send_request(request).await.unwrap_err();
+    assert_matches!(response, StorageServiceError::InternalError(_));
+}
+
 #[tokio::test]
 async fn test_get_number_of_accounts_at_version() {
     let (mut mock_client, service) = MockClient::new();
@@ -409,6 +450,38 @@ fn create_test_output() -> TransactionOutput {
     TransactionOutput::new(WriteSet::default(), vec![], 0, TransactionStatus::Retry)
 }
 
+/// Creates a test account states range, holding `limit` accounts with keys
+/// starting at `start_key`
+fn create_test_account_states_range(
+    start_key: HashValue,
+    end_key: HashValue,
+    limit: usize,
+) -> AccountStatesRangeWithProof {
+    let account_blobs: Vec<(HashValue, AccountStateBlob)> = (0..limit)
+        .map(|i| {
+            let mut key = start_key.to_vec();
+            key[HashValue::LENGTH - 8..].copy_from_slice(&(i as u64).to_be_bytes());
+            (HashValue::from_slice(&key).unwrap(), vec![].into())
+        })
+        .filter(|(key, _)| *key <= end_key)
+        .collect();
+    let last_key = if account_blobs.len() == limit {
+        account_blobs.last().unwrap().0
+    } else {
+        end_key
+    };
+
+    AccountStatesRangeWithProof {
+        first_key: start_key,
+        last_key,
+        account_blobs,
+        proof: SparseMerkleKeyRangeProof::new(
+            SparseMerkleProof::new(None, vec![]),
+            SparseMerkleProof::new(None, vec![]),
+        ),
+    }
+}
+
 fn create_test_ledger_info_with_sigs(epoch: u64, version: u64) -> LedgerInfoWithSignatures {
     // Create a mock ledger info with signatures
     let ledger_info = LedgerInfo::new(
@@ -545,6 +618,16 @@ impl DbReader for MockDbReader {
         Ok(account_states_chunk_with_proof)
     }
 
+    fn get_account_range_with_proof(
+        &self,
+        _version: Version,
+        first_key: HashValue,
+        last_key: HashValue,
+        limit: usize,
+    ) -> Result<AccountStatesRangeWithProof> {
+        Ok(create_test_account_states_range(first_key, last_key, limit))
+    }
+
     fn get_state_prune_window(&self) -> Option<usize> {
         Some(STATE_PRUNE_WINDOW as usize)
     }
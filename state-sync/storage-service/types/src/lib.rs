@@ -4,8 +4,9 @@
 #![forbid(unsafe_code)]
 
 use diem_config::config::StorageServiceConfig;
+use diem_crypto::HashValue;
 use diem_types::{
-    account_state_blob::AccountStatesChunkWithProof,
+    account_state_blob::{AccountStatesChunkWithProof, AccountStatesRangeWithProof},
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
@@ -58,6 +59,7 @@ pub enum StorageServiceRequest {
     GetStorageServerSummary,               // Fetches a summary of the storage server state
     GetTransactionOutputsWithProof(TransactionOutputsWithProofRequest), // Fetches a list of transaction outputs with a proof
     GetTransactionsWithProof(TransactionsWithProofRequest), // Fetches a list of transactions with a proof
+    GetAccountStatesRangeWithProof(AccountStatesRangeWithProofRequest), // Fetches the account states in a range of keys with a proof
 }
 
 impl StorageServiceRequest {
@@ -71,6 +73,7 @@ impl StorageServiceRequest {
             Self::GetStorageServerSummary => "get_storage_server_summary",
             Self::GetTransactionOutputsWithProof(_) => "get_transaction_outputs_with_proof",
             Self::GetTransactionsWithProof(_) => "get_transactions_with_proof",
+            Self::GetAccountStatesRangeWithProof(_) => "get_account_states_range_with_proof",
         }
     }
 
@@ -91,6 +94,7 @@ pub enum StorageServiceResponse {
     StorageServerSummary(StorageServerSummary),
     TransactionOutputsWithProof(TransactionOutputListWithProof),
     TransactionsWithProof(TransactionListWithProof),
+    AccountStatesRangeWithProof(AccountStatesRangeWithProof),
 }
 
 // TODO(philiphayes): is there a proc-macro for this?
@@ -105,6 +109,7 @@ impl StorageServiceResponse {
             Self::StorageServerSummary(_) => "storage_server_summary",
             Self::TransactionOutputsWithProof(_) => "transaction_outputs_with_proof",
             Self::TransactionsWithProof(_) => "transactions_with_proof",
+            Self::AccountStatesRangeWithProof(_) => "account_states_range_with_proof",
         }
     }
 }
@@ -147,6 +152,19 @@ impl TryFrom<StorageServiceResponse> for AccountStatesChunkWithProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse> for AccountStatesRangeWithProof {
+    type Error = UnexpectedResponseError;
+    fn try_from(response: StorageServiceResponse) -> Result<Self, Self::Error> {
+        match response {
+            StorageServiceResponse::AccountStatesRangeWithProof(inner) => Ok(inner),
+            _ => Err(UnexpectedResponseError(format!(
+                "expected account_states_range_with_proof, found {}",
+                response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse> for EpochChangeProof {
     type Error = UnexpectedResponseError;
     fn try_from(response: StorageServiceResponse) -> Result<Self, Self::Error> {
@@ -234,6 +252,17 @@ pub struct AccountStatesChunkWithProofRequest {
     pub end_account_index: u64,   // The account index to stop fetching account states (inclusive)
 }
 
+/// A storage service request for fetching the account states with hashed
+/// addresses in a range at a specified version. If there are more accounts in
+/// the range than the server's max chunk size, the response is cut short and
+/// its range ends at the last account returned.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccountStatesRangeWithProofRequest {
+    pub version: u64,         // The version to fetch the account states at
+    pub start_key: HashValue, // The key to start fetching account states
+    pub end_key: HashValue,   // The key to stop fetching account states (inclusive)
+}
+
 /// A storage service request for fetching a transaction output list with a
 /// corresponding proof.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -334,6 +363,9 @@ impl ProtocolMetadata {
                     self.max_transaction_chunk_size >= chunk_size
                 })
             }),
+            // The number of accounts in the range isn't known in advance, so the
+            // server cuts the response short instead.
+            GetAccountStatesRangeWithProof(request) => request.start_key <= request.end_key,
         }
     }
 }
@@ -380,12 +412,17 @@ impl DataSummary {
             // storage services can always serve these metadata requests
             GetServerProtocolVersion => true,
             GetStorageServerSummary => true,
-            GetAccountStatesChunkWithProof(request) => {
-                let proof_version = request.version;
+            GetAccountStatesChunkWithProof(AccountStatesChunkWithProofRequest {
+                version, ..
+            })
+            | GetAccountStatesRangeWithProof(AccountStatesRangeWithProofRequest {
+                version, ..
+            }) => {
+                let proof_version = *version;
 
                 let can_serve_accounts = self
                     .account_states
-                    .map(|range| range.contains(request.version))
+                    .map(|range| range.contains(*version))
                     .unwrap_or(false);
 
                 let can_create_proof = self
@@ -648,6 +685,18 @@ mod tests {
         get_account_state_chunks_request(version, 0, 1000)
     }
 
+    fn get_account_states_range_request(
+        version: Version,
+        start_key: HashValue,
+        end_key: HashValue,
+    ) -> StorageServiceRequest {
+        StorageServiceRequest::GetAccountStatesRangeWithProof(AccountStatesRangeWithProofRequest {
+            version,
+            start_key,
+            end_key,
+        })
+    }
+
     #[test]
     fn test_complete_data_range() {
         // good ranges
@@ -784,6 +833,12 @@ mod tests {
         // can provide proof, but out of range ==> cannot service
         assert!(!summary.can_service(&get_account_states_request(50)));
         assert!(!summary.can_service(&get_account_states_request(99)));
+
+        // the same applies to requests for ranges of keys
+        let (start_key, end_key) = (HashValue::zero(), HashValue::new([0xff; 32]));
+        assert!(summary.can_service(&get_account_states_range_request(200, start_key, end_key)));
+        assert!(!summary.can_service(&get_account_states_range_request(251, start_key, end_key)));
+        assert!(!summary.can_service(&get_account_states_range_request(99, start_key, end_key)));
     }
 
     #[test]
@@ -806,6 +861,11 @@ mod tests {
 
         assert!(metadata.can_service(&get_account_state_chunks_request(200, 100, 199)));
         assert!(!metadata.can_service(&get_account_state_chunks_request(200, 100, 200)));
+
+        let (low_key, high_key) = (HashValue::zero(), HashValue::new([0xff; 32]));
+        assert!(metadata.can_service(&get_account_states_range_request(200, low_key, high_key)));
+        assert!(metadata.can_service(&get_account_states_range_request(200, low_key, low_key)));
+        assert!(!metadata.can_service(&get_account_states_range_request(200, high_key, low_key)));
     }
 
     proptest! {
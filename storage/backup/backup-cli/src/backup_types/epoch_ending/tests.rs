@@ -15,7 +15,7 @@ use crate::{
     },
 };
 use backup_service::start_backup_service;
-use diem_config::utils::get_available_port;
+use diem_config::{config::BackupCheckpointConfig, utils::get_available_port};
 use diem_temppath::TempPath;
 use diem_types::{
     ledger_info::LedgerInfoWithSignatures,
@@ -47,6 +47,7 @@ fn end_to_end() {
     let port = get_available_port();
     let rt = start_backup_service(
         SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+        BackupCheckpointConfig::default(),
         src_db,
     );
     let client = Arc::new(BackupServiceClient::new(format!(
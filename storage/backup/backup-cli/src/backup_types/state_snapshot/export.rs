@@ -0,0 +1,312 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exports a state snapshot backup into CSV files readable by common analytics tools, one row per
+//! resource held by an account, with the resource fields decoded into JSON.
+
+use crate::{
+    backup_types::state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        path_exists, read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt,
+        RestoreRunMode,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use diem_crypto::HashValue;
+use diem_logger::prelude::*;
+use diem_types::{
+    access_path::Path,
+    account_address::{AccountAddress, HashAccountAddress},
+    account_state::AccountState,
+    account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    transaction::Version,
+};
+use move_core_types::{
+    language_storage::{ModuleId, StructTag},
+    resolver::{ModuleResolver, ResourceResolver},
+};
+use move_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue, MoveValueAnnotator};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, convert::TryFrom, path::PathBuf, sync::Arc};
+use storage_interface::StateSnapshotReceiver;
+use structopt::StructOpt;
+
+/// Name of the file, in the output directory, describing the export.
+pub const EXPORT_MANIFEST_NAME: &str = "state.manifest.json";
+/// Name of the file, in the output directory, holding the proof of the exported state.
+pub const EXPORT_PROOF_NAME: &str = "state.proof";
+
+#[derive(StructOpt)]
+pub struct StateSnapshotExportOpt {
+    #[structopt(long = "state-manifest")]
+    pub manifest_handle: FileHandle,
+    #[structopt(
+        long = "output-dir",
+        parse(from_os_str),
+        help = "Directory to write the exported files into, which must not exist."
+    )]
+    pub output_dir: PathBuf,
+}
+
+/// A chunk of an exported state snapshot, corresponding to a `StateSnapshotChunk` in the backup.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotExportChunk {
+    /// index of the first account in this chunk over all accounts.
+    pub first_idx: usize,
+    /// index of the last account in this chunk over all accounts.
+    pub last_idx: usize,
+    /// key of the first account in this chunk.
+    pub first_key: HashValue,
+    /// key of the last account in this chunk.
+    pub last_key: HashValue,
+    /// Name of the CSV file in the output directory, with columns `address`, `resource_type` and
+    /// `value`, where `value` is the JSON representation of the resource fields.
+    pub file: String,
+}
+
+/// Manifest of an exported state snapshot.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotExport {
+    /// Version at which the exported state snapshot is taken.
+    pub version: Version,
+    /// Hash of the state tree root, proven by the proof file.
+    pub root_hash: HashValue,
+    /// The state snapshot backup manifest this is exported from, which can be used to verify the
+    /// exported data against the original account state blobs.
+    pub backup_manifest: FileHandle,
+    /// Name of the file in the output directory holding the BCS serialized
+    /// `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)`, same as the `proof` in
+    /// `StateSnapshotBackup`.
+    pub proof: String,
+    /// All exported resources in chunks.
+    pub chunks: Vec<StateSnapshotExportChunk>,
+}
+
+pub struct StateSnapshotExportController {
+    storage: Arc<dyn BackupStorage>,
+    manifest_handle: FileHandle,
+    output_dir: PathBuf,
+}
+
+impl StateSnapshotExportController {
+    pub fn new(opt: StateSnapshotExportOpt, storage: Arc<dyn BackupStorage>) -> Self {
+        Self {
+            storage,
+            manifest_handle: opt.manifest_handle,
+            output_dir: opt.output_dir,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!(
+            "State snapshot export started. Manifest: {}",
+            self.manifest_handle
+        );
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("State snapshot export failed: {}", e))?;
+        info!("State snapshot export succeeded.");
+        Ok(())
+    }
+}
+
+impl StateSnapshotExportController {
+    async fn run_impl(self) -> Result<()> {
+        ensure!(
+            !path_exists(&self.output_dir).await,
+            "Output directory {:?} already exists.",
+            self.output_dir,
+        );
+
+        let manifest: StateSnapshotBackup =
+            self.storage.load_json_file(&self.manifest_handle).await?;
+        let proof_bytes = self.storage.read_all(&manifest.proof).await?;
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&proof_bytes)?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        ensure!(
+            txn_info_with_proof.transaction_info().state_change_hash() == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            manifest.root_hash,
+            txn_info_with_proof.transaction_info().state_change_hash(),
+        );
+
+        // First pass: verify all chunks add up to the root hash, and collect the modules which
+        // are needed to decode resources in the second pass.
+        let mut receiver = RestoreRunMode::Verify.get_state_restore_receiver(
+            manifest.version,
+            manifest.root_hash,
+            false, /* account_count_migration */
+        )?;
+        let mut modules = SnapshotModules::default();
+        for chunk in &manifest.chunks {
+            let blobs = self.read_account_state_chunk(&chunk.blobs).await?;
+            for (_key, blob) in &blobs {
+                modules.add_modules(&AccountState::try_from(blob)?)?;
+            }
+            let proof = self.storage.load_bcs_file(&chunk.proof).await?;
+            receiver.add_chunk(blobs, proof)?;
+        }
+        receiver.finish()?;
+
+        // Second pass: decode and write out the resources.
+        tokio::fs::create_dir_all(&self.output_dir).await?;
+        let mut chunks = Vec::with_capacity(manifest.chunks.len());
+        for chunk in &manifest.chunks {
+            chunks.push(self.export_chunk(chunk, &modules).await?);
+        }
+
+        tokio::fs::write(self.output_dir.join(EXPORT_PROOF_NAME), &proof_bytes).await?;
+        let export = StateSnapshotExport {
+            version: manifest.version,
+            root_hash: manifest.root_hash,
+            backup_manifest: self.manifest_handle.clone(),
+            proof: EXPORT_PROOF_NAME.to_string(),
+            chunks,
+        };
+        tokio::fs::write(
+            self.output_dir.join(EXPORT_MANIFEST_NAME),
+            serde_json::to_string_pretty(&export)?,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn export_chunk(
+        &self,
+        chunk: &StateSnapshotChunk,
+        modules: &SnapshotModules,
+    ) -> Result<StateSnapshotExportChunk> {
+        let annotator = MoveValueAnnotator::new(modules);
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.write_record(&["address", "resource_type", "value"])?;
+
+        for (key, blob) in self.read_account_state_chunk(&chunk.blobs).await? {
+            let account_state = AccountState::try_from(&blob)?;
+            let address = match account_address(&account_state)? {
+                Some(address) => address,
+                None => {
+                    warn!("Can't determine address of account {}, skipping.", key);
+                    continue;
+                }
+            };
+            ensure!(
+                address.hash() == key,
+                "Account address {} doesn't match key {}.",
+                address,
+                key,
+            );
+
+            for (struct_tag, data) in account_state.get_resources() {
+                let resource = annotator.view_resource(&struct_tag, data)?;
+                writer.write_record(&[
+                    address.to_hex_literal(),
+                    struct_tag.to_string(),
+                    struct_to_json(&resource).to_string(),
+                ])?;
+            }
+        }
+
+        let file = format!("{}-{}.csv", chunk.first_idx, chunk.last_idx);
+        tokio::fs::write(self.output_dir.join(&file), writer.into_inner()?).await?;
+
+        Ok(StateSnapshotExportChunk {
+            first_idx: chunk.first_idx,
+            last_idx: chunk.last_idx,
+            first_key: chunk.first_key,
+            last_key: chunk.last_key,
+            file,
+        })
+    }
+
+    async fn read_account_state_chunk(
+        &self,
+        file_handle: &FileHandle,
+    ) -> Result<Vec<(HashValue, AccountStateBlob)>> {
+        let mut file = self.storage.open_for_read(file_handle).await?;
+
+        let mut chunk = vec![];
+
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            chunk.push(bcs::from_bytes(&record_bytes)?);
+        }
+
+        Ok(chunk)
+    }
+}
+
+/// Account state blobs are keyed by the hash of the address, so the address is recovered from the
+/// `AccountResource`, or the modules for accounts that don't have one, e.g. the core code address.
+fn account_address(account_state: &AccountState) -> Result<Option<AccountAddress>> {
+    if let Some(address) = account_state.get_account_address()? {
+        return Ok(Some(address));
+    }
+    for (path, _) in account_state.iter() {
+        if let Path::Code(module_id) = bcs::from_bytes(path)? {
+            return Ok(Some(*module_id.address()));
+        }
+    }
+    Ok(None)
+}
+
+fn struct_to_json(s: &AnnotatedMoveStruct) -> Value {
+    Value::Object(
+        s.value
+            .iter()
+            .map(|(name, value)| (name.to_string(), value_to_json(value)))
+            .collect(),
+    )
+}
+
+/// Similar to the REST API, 64 and 128 bit integers are represented as strings, since they don't
+/// fit into JSON numbers, and bytes are hex encoded.
+fn value_to_json(value: &AnnotatedMoveValue) -> Value {
+    match value {
+        AnnotatedMoveValue::U8(v) => json!(v),
+        AnnotatedMoveValue::U64(v) => json!(v.to_string()),
+        AnnotatedMoveValue::U128(v) => json!(v.to_string()),
+        AnnotatedMoveValue::Bool(v) => json!(v),
+        AnnotatedMoveValue::Address(v) => json!(v.to_hex_literal()),
+        AnnotatedMoveValue::Vector(_, vals) => {
+            Value::Array(vals.iter().map(value_to_json).collect())
+        }
+        AnnotatedMoveValue::Bytes(v) => json!(format!("0x{}", hex::encode(v))),
+        AnnotatedMoveValue::Struct(s) => struct_to_json(s),
+    }
+}
+
+/// All modules published in a state snapshot, used to decode the resources in the same snapshot.
+#[derive(Default)]
+struct SnapshotModules(HashMap<ModuleId, Vec<u8>>);
+
+impl SnapshotModules {
+    fn add_modules(&mut self, account_state: &AccountState) -> Result<()> {
+        for (path, module) in account_state.iter() {
+            if let Path::Code(module_id) = bcs::from_bytes(path)? {
+                self.0.insert(module_id, module.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ModuleResolver for SnapshotModules {
+    type Error = anyhow::Error;
+
+    fn get_module(&self, id: &ModuleId) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.get(id).cloned())
+    }
+}
+
+impl ResourceResolver for SnapshotModules {
+    type Error = anyhow::Error;
+
+    fn get_resource(&self, _address: &AccountAddress, _typ: &StructTag) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+}
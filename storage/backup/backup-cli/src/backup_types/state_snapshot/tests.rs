@@ -4,6 +4,10 @@
 use crate::{
     backup_types::state_snapshot::{
         backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        export::{
+            StateSnapshotExport, StateSnapshotExportController, StateSnapshotExportOpt,
+            EXPORT_MANIFEST_NAME,
+        },
         restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
     },
     storage::{local_fs::LocalFs, BackupStorage},
@@ -14,8 +18,9 @@ use crate::{
     },
 };
 use diem_temppath::TempPath;
-use diem_types::transaction::PRE_GENESIS_VERSION;
+use diem_types::{account_config::diem_root_address, transaction::PRE_GENESIS_VERSION};
 use diemdb::DiemDB;
+use executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
 use std::{convert::TryInto, sync::Arc};
 use storage_interface::DbReader;
 use tokio::time::Duration;
@@ -88,3 +93,76 @@ fn end_to_end() {
 
     rt.shutdown_timeout(Duration::from_secs(1));
 }
+
+#[test]
+fn export() {
+    let src_db = test_execution_with_storage_impl();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+    let export_dir = TempPath::new();
+
+    let latest_tree_state = src_db.get_latest_tree_state().unwrap();
+    let version = latest_tree_state.num_transactions - 1;
+    let state_root_hash = latest_tree_state.account_state_root_hash;
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+
+    let manifest_handle = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { version },
+                GlobalBackupOpt {
+                    max_chunk_size: 4096,
+                },
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    rt.block_on(
+        StateSnapshotExportController::new(
+            StateSnapshotExportOpt {
+                manifest_handle,
+                output_dir: export_dir.path().to_path_buf(),
+            },
+            store,
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let export: StateSnapshotExport = serde_json::from_slice(
+        &std::fs::read(export_dir.path().join(EXPORT_MANIFEST_NAME)).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(export.version, version);
+    assert_eq!(export.root_hash, state_root_hash);
+    assert!(export_dir.path().join(&export.proof).exists());
+    assert!(!export.chunks.is_empty());
+
+    let mut found_diem_root = false;
+    for chunk in &export.chunks {
+        let mut reader = csv::Reader::from_path(export_dir.path().join(&chunk.file)).unwrap();
+        for record in reader.records() {
+            let record = record.unwrap();
+            assert!(serde_json::from_str::<serde_json::Value>(&record[2])
+                .unwrap()
+                .is_object());
+            if record[0] == diem_root_address().to_hex_literal()
+                && &record[1] == "0x1::DiemAccount::DiemAccount"
+            {
+                found_diem_root = true;
+            }
+        }
+    }
+    assert!(found_diem_root);
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
@@ -0,0 +1,269 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::state_snapshot_diff::manifest::{
+        StateSnapshotDiffBackup, StateSnapshotDiffChunk,
+    },
+    metadata::Metadata,
+    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    utils::{
+        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
+        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use diem_crypto::HashValue;
+use diem_logger::prelude::*;
+use diem_types::{
+    account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleProof, TransactionInfoWithProof},
+    transaction::Version,
+};
+use once_cell::sync::Lazy;
+use std::{convert::TryInto, str::FromStr, sync::Arc};
+use structopt::StructOpt;
+use tokio::io::AsyncWriteExt;
+
+#[derive(StructOpt)]
+pub struct StateSnapshotDiffBackupOpt {
+    #[structopt(
+        long = "base-version",
+        help = "Version of the existing state snapshot, or differential state snapshot, which the \
+        new differential state snapshot is based on."
+    )]
+    pub base_version: Version,
+    #[structopt(
+        long = "state-version",
+        help = "Version at which a differential state snapshot to be taken."
+    )]
+    pub version: Version,
+}
+
+pub struct StateSnapshotDiffBackupController {
+    base_version: Version,
+    version: Version,
+    max_chunk_size: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl StateSnapshotDiffBackupController {
+    pub fn new(
+        opt: StateSnapshotDiffBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            base_version: opt.base_version,
+            version: opt.version,
+            max_chunk_size: global_opt.max_chunk_size,
+            client,
+            storage,
+        }
+    }
+
+    pub async fn run(self) -> Result<FileHandle> {
+        info!(
+            "Differential state snapshot backup started, for version {} based on version {}.",
+            self.version, self.base_version,
+        );
+        let ret = self
+            .run_impl()
+            .await
+            .map_err(|e| anyhow!("Differential state snapshot backup failed: {}", e))?;
+        info!(
+            "Differential state snapshot backup succeeded. Manifest: {}",
+            ret
+        );
+        Ok(ret)
+    }
+
+    async fn run_impl(self) -> Result<FileHandle> {
+        ensure!(
+            self.base_version < self.version,
+            "Base version {} must be older than version {}.",
+            self.base_version,
+            self.version,
+        );
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&self.backup_name())
+            .await?;
+
+        let mut chunks = vec![];
+        let mut chunk_bytes = vec![];
+        let mut chunk_proofs = vec![];
+        let mut chunk_first_idx: usize = 0;
+        let mut chunk_first_key = HashValue::zero();
+        let mut chunk_last_key = HashValue::zero();
+        let mut current_idx: usize = 0;
+
+        let mut diff_file = self
+            .client
+            .get_state_snapshot_diff(self.base_version, self.version)
+            .await?;
+        while let Some(record_bytes) = diff_file.read_record_bytes().await? {
+            // The proofs are split out of the records into a separate file.
+            let (key, blob, proof): (
+                HashValue,
+                AccountStateBlob,
+                SparseMerkleProof<AccountStateBlob>,
+            ) = bcs::from_bytes(&record_bytes)?;
+            let record_bytes = bcs::to_bytes(&(key, blob))?;
+
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+                let chunk = self
+                    .write_chunk(
+                        &backup_handle,
+                        &chunk_bytes,
+                        &chunk_proofs,
+                        chunk_first_idx,
+                        current_idx - 1,
+                        chunk_first_key,
+                        chunk_last_key,
+                    )
+                    .await?;
+                chunks.push(chunk);
+                chunk_bytes = vec![];
+                chunk_proofs = vec![];
+                chunk_first_idx = current_idx;
+            }
+
+            if chunk_bytes.is_empty() {
+                chunk_first_key = key;
+            }
+            chunk_bytes.extend(&(record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+            chunk_proofs.push(proof);
+            chunk_last_key = key;
+            current_idx += 1;
+        }
+
+        ensure!(!chunk_bytes.is_empty(), "State diff is empty.");
+        let chunk = self
+            .write_chunk(
+                &backup_handle,
+                &chunk_bytes,
+                &chunk_proofs,
+                chunk_first_idx,
+                current_idx - 1,
+                chunk_first_key,
+                chunk_last_key,
+            )
+            .await?;
+        chunks.push(chunk);
+
+        self.write_manifest(&backup_handle, chunks).await
+    }
+}
+
+impl StateSnapshotDiffBackupController {
+    fn backup_name(&self) -> String {
+        format!("state_diff_ver_{}-{}", self.base_version, self.version)
+    }
+
+    fn manifest_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state_diff.manifest").unwrap());
+        &NAME
+    }
+
+    fn proof_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("state_diff.proof").unwrap());
+        &NAME
+    }
+
+    fn chunk_name(first_idx: usize) -> ShellSafeName {
+        format!("{}-.chunk", first_idx).try_into().unwrap()
+    }
+
+    fn chunk_proofs_name(first_idx: usize, last_idx: usize) -> ShellSafeName {
+        format!("{}-{}.proofs", first_idx, last_idx)
+            .try_into()
+            .unwrap()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn write_chunk(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_bytes: &[u8],
+        chunk_proofs: &[SparseMerkleProof<AccountStateBlob>],
+        first_idx: usize,
+        last_idx: usize,
+        first_key: HashValue,
+        last_key: HashValue,
+    ) -> Result<StateSnapshotDiffChunk> {
+        let (chunk_handle, mut chunk_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
+            .await?;
+        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file.shutdown().await?;
+
+        let (proofs_handle, mut proofs_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_proofs_name(first_idx, last_idx))
+            .await?;
+        proofs_file.write_all(&bcs::to_bytes(chunk_proofs)?).await?;
+        proofs_file.shutdown().await?;
+
+        Ok(StateSnapshotDiffChunk {
+            first_idx,
+            last_idx,
+            first_key,
+            last_key,
+            blobs: chunk_handle,
+            proofs: proofs_handle,
+        })
+    }
+
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunks: Vec<StateSnapshotDiffChunk>,
+    ) -> Result<FileHandle> {
+        let proof_bytes = self.client.get_state_root_proof(self.version).await?;
+        let (txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&proof_bytes)?;
+
+        let (proof_handle, mut proof_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::proof_name())
+            .await?;
+        proof_file.write_all(&proof_bytes).await?;
+        proof_file.shutdown().await?;
+
+        let manifest = StateSnapshotDiffBackup {
+            base_version: self.base_version,
+            version: self.version,
+            root_hash: txn_info.transaction_info().state_change_hash(),
+            chunks,
+            proof: proof_handle,
+        };
+
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata = Metadata::new_state_snapshot_diff_backup(
+            self.base_version,
+            self.version,
+            manifest_handle.clone(),
+        );
+        self.storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+            .await?;
+
+        Ok(manifest_handle)
+    }
+}
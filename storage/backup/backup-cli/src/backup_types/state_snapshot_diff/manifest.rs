@@ -0,0 +1,52 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::FileHandle;
+use diem_crypto::HashValue;
+use diem_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// A chunk of a differential state snapshot manifest, representing changed accounts in the key
+/// range [`first_key`, `last_key`] (right side inclusive).
+///
+/// Unlike `StateSnapshotChunk`, there's no range proof for each chunk, because the changed
+/// accounts are scattered all over the tree. Instead, each account comes with a proof against the
+/// root hash at the version of the backup. That doesn't prove no changed account is left out,
+/// which is verified by the root hash of the tree resulted from applying all chunks on top of the
+/// base.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDiffChunk {
+    /// index of the first account in this chunk over all changed accounts.
+    pub first_idx: usize,
+    /// index of the last account in this chunk over all changed accounts.
+    pub last_idx: usize,
+    /// key of the first account in this chunk.
+    pub first_key: HashValue,
+    /// key of the last account in this chunk.
+    pub last_key: HashValue,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, account_state_blob)`
+    pub blobs: FileHandle,
+    /// BCS serialized `Vec<SparseMerkleProof<AccountStateBlob>>`, proving each account in `blobs`
+    /// in the same order.
+    pub proofs: FileHandle,
+}
+
+/// Differential state snapshot backup manifest, representing all accounts changed after a base
+/// version till specified version. Applied on top of the state at the base version, restored from
+/// a `StateSnapshotBackup` or another `StateSnapshotDiffBackup`, it yields the complete state view
+/// at specified version.
+#[derive(Deserialize, Serialize)]
+pub struct StateSnapshotDiffBackup {
+    /// Version of the state this backup is based on.
+    pub base_version: Version,
+    /// Version at which this differential state snapshot is taken.
+    pub version: Version,
+    /// Hash of the state tree root at `version`.
+    pub root_hash: HashValue,
+    /// All changed account blobs in chunks.
+    pub chunks: Vec<StateSnapshotDiffChunk>,
+    /// BCS serialized `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)`, same as
+    /// `StateSnapshotBackup::proof`, proving the `root_hash` at `version`.
+    pub proof: FileHandle,
+}
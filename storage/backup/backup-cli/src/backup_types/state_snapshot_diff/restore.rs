@@ -0,0 +1,165 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::EpochHistory, state_snapshot_diff::manifest::StateSnapshotDiffBackup,
+    },
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOptions,
+        RestoreRunMode,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use diem_crypto::HashValue;
+use diem_logger::prelude::*;
+use diem_types::{
+    account_state_blob::AccountStateBlob,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleProof, TransactionInfoWithProof},
+    transaction::Version,
+};
+use std::sync::Arc;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct StateSnapshotDiffRestoreOpt {
+    #[structopt(
+        long = "state-diff-manifest",
+        help = "Manifest of the differential state snapshot to apply. The state at its base \
+        version must have been restored."
+    )]
+    pub manifest_handle: FileHandle,
+}
+
+pub struct StateSnapshotDiffRestoreController {
+    storage: Arc<dyn BackupStorage>,
+    run_mode: Arc<RestoreRunMode>,
+    manifest_handle: FileHandle,
+    /// Global "target_version" for the entire restore process, if the version of the diff is
+    /// newer than this, nothing will be done, otherwise, this has no effect.
+    target_version: Version,
+    epoch_history: Option<Arc<EpochHistory>>,
+}
+
+impl StateSnapshotDiffRestoreController {
+    pub fn new(
+        opt: StateSnapshotDiffRestoreOpt,
+        global_opt: GlobalRestoreOptions,
+        storage: Arc<dyn BackupStorage>,
+        epoch_history: Option<Arc<EpochHistory>>,
+    ) -> Self {
+        Self {
+            storage,
+            run_mode: global_opt.run_mode,
+            manifest_handle: opt.manifest_handle,
+            target_version: global_opt.target_version,
+            epoch_history,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let name = self.name();
+        info!("{} started. Manifest: {}", name, self.manifest_handle);
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("{} failed: {}", name, e))?;
+        info!("{} succeeded.", name);
+        Ok(())
+    }
+}
+
+impl StateSnapshotDiffRestoreController {
+    fn name(&self) -> String {
+        format!("differential state snapshot {}", self.run_mode.name())
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        let manifest: StateSnapshotDiffBackup =
+            self.storage.load_json_file(&self.manifest_handle).await?;
+        if manifest.version > self.target_version {
+            warn!(
+                "Trying to restore differential state snapshot to version {}, which is newer than the target version {}, skipping.",
+                manifest.version,
+                self.target_version,
+            );
+            return Ok(());
+        }
+
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(&manifest.proof).await?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        ensure!(
+            txn_info_with_proof.transaction_info().state_change_hash() == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            manifest.root_hash,
+            txn_info_with_proof.transaction_info().state_change_hash(),
+        );
+        if let Some(epoch_history) = self.epoch_history.as_ref() {
+            epoch_history.verify_ledger_info(&li)?;
+        }
+
+        // Chunks are applied one by one on top of the base, all written at the diff version.
+        // Nodes a later chunk overwrites are not considered stale, so the result is the same as
+        // applying the whole diff in one go.
+        let mut next_idx = 0;
+        let mut last_key: Option<HashValue> = None;
+        for chunk in manifest.chunks {
+            let blobs = self.read_account_state_chunk(chunk.blobs).await?;
+            let proofs: Vec<SparseMerkleProof<AccountStateBlob>> =
+                self.storage.load_bcs_file(&chunk.proofs).await?;
+            ensure!(
+                blobs.len() == chunk.last_idx - chunk.first_idx + 1
+                    && proofs.len() == blobs.len()
+                    && blobs.first().map(|(key, _)| *key) == Some(chunk.first_key)
+                    && blobs.last().map(|(key, _)| *key) == Some(chunk.last_key),
+                "Chunk [{}, {}] doesn't match its description in the manifest.",
+                chunk.first_idx,
+                chunk.last_idx,
+            );
+            ensure!(
+                chunk.first_idx == next_idx,
+                "Chunks not continuous, expecting index {}, got {}.",
+                next_idx,
+                chunk.first_idx,
+            );
+            for ((key, blob), proof) in blobs.iter().zip(proofs.iter()) {
+                ensure!(
+                    last_key.map_or(true, |last_key| last_key < *key),
+                    "Accounts in the differential state snapshot are not sorted by key.",
+                );
+                proof.verify(manifest.root_hash, *key, Some(blob))?;
+                last_key = Some(*key);
+            }
+
+            let base_version = if next_idx == 0 {
+                manifest.base_version
+            } else {
+                manifest.version
+            };
+            self.run_mode
+                .save_account_state_diff_chunk(blobs, base_version, manifest.version)?;
+            next_idx = chunk.last_idx + 1;
+        }
+
+        // Each account is proven to be in the target tree, make sure none is left out.
+        self.run_mode
+            .verify_state_root_hash(manifest.version, manifest.root_hash)
+    }
+
+    async fn read_account_state_chunk(
+        &self,
+        file_handle: FileHandle,
+    ) -> Result<Vec<(HashValue, AccountStateBlob)>> {
+        let mut file = self.storage.open_for_read(&file_handle).await?;
+
+        let mut chunk = vec![];
+
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            chunk.push(bcs::from_bytes(&record_bytes)?);
+        }
+
+        Ok(chunk)
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        },
+        state_snapshot_diff::{
+            backup::{StateSnapshotDiffBackupController, StateSnapshotDiffBackupOpt},
+            restore::{StateSnapshotDiffRestoreController, StateSnapshotDiffRestoreOpt},
+        },
+    },
+    metadata::{view::MetadataView, Metadata},
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        backup_service_client::BackupServiceClient, test_utils::start_local_backup_service,
+        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, GlobalRestoreOptions,
+        RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use diem_temppath::TempPath;
+use diem_types::{
+    account_address::HashAccountAddress,
+    account_config::{diem_root_address, treasury_compliance_account_address},
+};
+use diemdb::DiemDB;
+use executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+use std::{convert::TryInto, sync::Arc};
+use storage_interface::DbReader;
+use tokio::time::Duration;
+
+#[test]
+fn end_to_end() {
+    let src_db = test_execution_with_storage_impl();
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    // A state snapshot, followed by two differential state snapshots.
+    let latest_version = src_db.get_latest_version().unwrap();
+    let versions = [latest_version / 3, latest_version * 2 / 3, latest_version];
+    let expected_root_hash = src_db
+        .get_latest_tree_state()
+        .unwrap()
+        .account_state_root_hash;
+
+    let (rt, port) = start_local_backup_service(Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_backup_opt = GlobalBackupOpt {
+        max_chunk_size: 2048,
+    };
+
+    let snapshot_manifest = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt {
+                    version: versions[0],
+                },
+                global_backup_opt.clone(),
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    let diff_manifests: Vec<_> = versions
+        .windows(2)
+        .map(|w| {
+            rt.block_on(
+                StateSnapshotDiffBackupController::new(
+                    StateSnapshotDiffBackupOpt {
+                        base_version: w[0],
+                        version: w[1],
+                    },
+                    global_backup_opt.clone(),
+                    Arc::clone(&client),
+                    Arc::clone(&store),
+                )
+                .run(),
+            )
+            .unwrap()
+        })
+        .collect();
+
+    // Verify mode checks the proof of every account in the diffs, without a DB.
+    {
+        let verify_opt: GlobalRestoreOptions = GlobalRestoreOpt {
+            dry_run: true,
+            db_dir: None,
+            target_version: None, // max
+            trusted_waypoints: TrustedWaypointOpt::default(),
+            rocksdb_opt: RocksdbOpt::default(),
+            concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            account_count_migration: true,
+        }
+        .try_into()
+        .unwrap();
+        for manifest_handle in &diff_manifests {
+            rt.block_on(
+                StateSnapshotDiffRestoreController::new(
+                    StateSnapshotDiffRestoreOpt {
+                        manifest_handle: manifest_handle.clone(),
+                    },
+                    verify_opt.clone(),
+                    Arc::clone(&store),
+                    None, /* epoch_history */
+                )
+                .run(),
+            )
+            .unwrap();
+        }
+    }
+
+    {
+        let global_restore_opt: GlobalRestoreOptions = GlobalRestoreOpt {
+            dry_run: false,
+            db_dir: Some(tgt_db_dir.path().to_path_buf()),
+            target_version: None, // max
+            trusted_waypoints: TrustedWaypointOpt::default(),
+            rocksdb_opt: RocksdbOpt::default(),
+            concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            account_count_migration: true,
+        }
+        .try_into()
+        .unwrap();
+        rt.block_on(
+            StateSnapshotRestoreController::new(
+                StateSnapshotRestoreOpt {
+                    manifest_handle: snapshot_manifest,
+                    version: versions[0],
+                },
+                global_restore_opt.clone(),
+                Arc::clone(&store),
+                None, /* epoch_history */
+            )
+            .run(),
+        )
+        .unwrap();
+        // Each diff checks the resulting root hash against that in its proof.
+        for manifest_handle in diff_manifests {
+            rt.block_on(
+                StateSnapshotDiffRestoreController::new(
+                    StateSnapshotDiffRestoreOpt { manifest_handle },
+                    global_restore_opt.clone(),
+                    Arc::clone(&store),
+                    None, /* epoch_history */
+                )
+                .run(),
+            )
+            .unwrap();
+        }
+    }
+
+    let tgt_db = DiemDB::new_for_test(&tgt_db_dir);
+    for address in [diem_root_address(), treasury_compliance_account_address()] {
+        let (src_blob, _proof) = src_db
+            .get_account_state_with_proof_by_version(address, latest_version)
+            .unwrap();
+        let (tgt_blob, proof) = tgt_db
+            .get_account_state_with_proof_by_version(address, latest_version)
+            .unwrap();
+        assert_eq!(src_blob, tgt_blob);
+        proof
+            .verify(expected_root_hash, address.hash(), tgt_blob.as_ref())
+            .unwrap();
+    }
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+#[test]
+fn select_state_snapshot_chain() {
+    let view = MetadataView::from(vec![
+        Metadata::new_state_snapshot_backup(10, "s10".to_string()),
+        Metadata::new_state_snapshot_backup(30, "s30".to_string()),
+        Metadata::new_state_snapshot_diff_backup(10, 20, "d10-20".to_string()),
+        Metadata::new_state_snapshot_diff_backup(20, 40, "d20-40".to_string()),
+        Metadata::new_state_snapshot_diff_backup(20, 50, "d20-50".to_string()),
+        Metadata::new_state_snapshot_diff_backup(30, 40, "d30-40".to_string()),
+    ]);
+    view.check_state_snapshot_diff_bases().unwrap();
+
+    let select = |target_version| {
+        view.select_state_snapshot_chain(target_version)
+            .unwrap()
+            .map(|c| {
+                (
+                    c.snapshot.manifest.clone(),
+                    c.diffs
+                        .iter()
+                        .map(|d| d.manifest.clone())
+                        .collect::<Vec<_>>(),
+                    c.version(),
+                )
+            })
+    };
+    assert_eq!(select(5), None);
+    assert_eq!(select(15), Some(("s10".to_string(), vec![], 10)));
+    assert_eq!(
+        select(25),
+        Some(("s10".to_string(), vec!["d10-20".to_string()], 20))
+    );
+    // Shorter chain preferred.
+    assert_eq!(
+        select(45),
+        Some(("s30".to_string(), vec!["d30-40".to_string()], 40))
+    );
+    assert_eq!(
+        select(55),
+        Some((
+            "s10".to_string(),
+            vec!["d10-20".to_string(), "d20-50".to_string()],
+            50
+        ))
+    );
+
+    let broken = MetadataView::from(vec![
+        Metadata::new_state_snapshot_backup(10, "s10".to_string()),
+        Metadata::new_state_snapshot_diff_backup(15, 20, "d15-20".to_string()),
+    ]);
+    assert!(broken.check_state_snapshot_diff_bases().is_err());
+}
@@ -9,10 +9,19 @@ use structopt::StructOpt;
 use backup_cli::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
-        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            export::{StateSnapshotExportController, StateSnapshotExportOpt},
+        },
+        state_snapshot_diff::backup::{
+            StateSnapshotDiffBackupController, StateSnapshotDiffBackupOpt,
+        },
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
-    coordinators::backup::{BackupCoordinator, BackupCoordinatorOpt},
+    coordinators::{
+        backup::{BackupCoordinator, BackupCoordinatorOpt},
+        gc::{GcCoordinator, GcCoordinatorOpt},
+    },
     metadata::{cache, cache::MetadataCacheOpt},
     storage::StorageOpt,
     utils::{
@@ -30,6 +39,11 @@ enum Command {
     OneShot(OneShotCommand),
     #[structopt(about = "Long running process backing up the chain continuously.")]
     Coordinator(CoordinatorCommand),
+    #[structopt(
+        about = "Deletes backups no longer needed according to a retention policy, and compacts \
+        the metadata."
+    )]
+    Gc(GcOpt),
 }
 
 #[derive(StructOpt)]
@@ -38,6 +52,16 @@ enum OneShotCommand {
     Query(OneShotQueryType),
     #[structopt(about = "Do a one shot backup.")]
     Backup(OneShotBackupOpt),
+    #[structopt(
+        about = "Takes a consistent checkpoint of the DB of the local Diem node, via the backup \
+        service within it. The checkpoint can be used as the DB directory of a new node."
+    )]
+    Checkpoint(OneShotCheckpointOpt),
+    #[structopt(
+        about = "Exports a backup into files readable by common data analysis tools, together \
+        with the proof which the exported data can be verified against."
+    )]
+    Export(ExportType),
 }
 
 #[derive(StructOpt)]
@@ -69,6 +93,28 @@ struct OneShotQueryBackupStorageStateOpt {
     storage: StorageOpt,
 }
 
+#[derive(StructOpt)]
+struct OneShotCheckpointOpt {
+    #[structopt(flatten)]
+    client: BackupServiceClientOpt,
+
+    #[structopt(
+        long = "name",
+        help = "Name of the checkpoint, which must not exist yet. It's created by the node in the \
+        directory configured as `storage.backup_checkpoint.dir`, along with a manifest of the \
+        ledger state it captures."
+    )]
+    name: String,
+
+    #[structopt(
+        long = "admin-token",
+        env = "DIEM_BACKUP_ADMIN_TOKEN",
+        hide_env_values = true,
+        help = "Token configured as `storage.backup_checkpoint.admin_token` on the node."
+    )]
+    admin_token: String,
+}
+
 #[derive(StructOpt)]
 struct OneShotBackupOpt {
     #[structopt(flatten)]
@@ -95,6 +141,16 @@ enum BackupType {
         #[structopt(subcommand)]
         storage: StorageOpt,
     },
+    #[structopt(
+        about = "Backs up only the accounts changed since an existing state snapshot, or \
+        differential state snapshot."
+    )]
+    StateSnapshotDiff {
+        #[structopt(flatten)]
+        opt: StateSnapshotDiffBackupOpt,
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
     Transaction {
         #[structopt(flatten)]
         opt: TransactionBackupOpt,
@@ -103,6 +159,20 @@ enum BackupType {
     },
 }
 
+#[derive(StructOpt)]
+enum ExportType {
+    #[structopt(
+        about = "Exports a state snapshot into CSV files, one row per resource held by an account, \
+        with the resource decoded into JSON."
+    )]
+    StateSnapshot {
+        #[structopt(flatten)]
+        opt: StateSnapshotExportOpt,
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
+}
+
 #[derive(StructOpt)]
 enum CoordinatorCommand {
     #[structopt(about = "Run the coordinator.")]
@@ -124,6 +194,18 @@ struct CoordinatorRunOpt {
     storage: StorageOpt,
 }
 
+#[derive(StructOpt)]
+struct GcOpt {
+    #[structopt(flatten)]
+    gc: GcCoordinatorOpt,
+
+    #[structopt(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     main_impl().await.map_err(|e| {
@@ -158,6 +240,20 @@ async fn main_impl() -> Result<()> {
                     println!("{}", view.get_storage_state())
                 }
             },
+            OneShotCommand::Checkpoint(opt) => {
+                let client = BackupServiceClient::new_with_opt(opt.client);
+                let manifest = client
+                    .create_checkpoint(&opt.name, &opt.admin_token)
+                    .await?;
+                println!("{}", serde_json::to_string_pretty(&manifest)?)
+            }
+            OneShotCommand::Export(typ) => match typ {
+                ExportType::StateSnapshot { opt, storage } => {
+                    StateSnapshotExportController::new(opt, storage.init_storage().await?)
+                        .run()
+                        .await?;
+                }
+            },
             OneShotCommand::Backup(opt) => {
                 let client = Arc::new(BackupServiceClient::new_with_opt(opt.client));
                 let global_opt = opt.global;
@@ -183,6 +279,16 @@ async fn main_impl() -> Result<()> {
                         .run()
                         .await?;
                     }
+                    BackupType::StateSnapshotDiff { opt, storage } => {
+                        StateSnapshotDiffBackupController::new(
+                            opt,
+                            global_opt,
+                            client,
+                            storage.init_storage().await?,
+                        )
+                        .run()
+                        .await?;
+                    }
                     BackupType::Transaction { opt, storage } => {
                         TransactionBackupController::new(
                             opt,
@@ -208,6 +314,15 @@ async fn main_impl() -> Result<()> {
                 .await?;
             }
         },
+        Command::Gc(opt) => {
+            GcCoordinator::new(
+                opt.gc,
+                opt.concurrent_downloads.get(),
+                opt.storage.init_storage().await?,
+            )
+            .run()
+            .await?;
+        }
     }
     Ok(())
 }
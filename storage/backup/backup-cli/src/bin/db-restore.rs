@@ -6,6 +6,9 @@ use backup_cli::{
     backup_types::{
         epoch_ending::restore::{EpochEndingRestoreController, EpochEndingRestoreOpt},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        state_snapshot_diff::restore::{
+            StateSnapshotDiffRestoreController, StateSnapshotDiffRestoreOpt,
+        },
         transaction::restore::{TransactionRestoreController, TransactionRestoreOpt},
     },
     coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
@@ -40,6 +43,12 @@ enum RestoreType {
         #[structopt(subcommand)]
         storage: StorageOpt,
     },
+    StateSnapshotDiff {
+        #[structopt(flatten)]
+        opt: StateSnapshotDiffRestoreOpt,
+        #[structopt(subcommand)]
+        storage: StorageOpt,
+    },
     Transaction {
         #[structopt(flatten)]
         opt: TransactionRestoreOpt,
@@ -66,10 +75,19 @@ async fn main_impl() -> Result<()> {
     Logger::new().level(Level::Info).read_env().init();
     let _mp = MetricsPusher::start();
 
-    let opt = Opt::from_args();
-    let global_opt: GlobalRestoreOptions = opt.global.clone().try_into()?;
+    let Opt {
+        mut global,
+        restore_type,
+    } = Opt::from_args();
+    if let RestoreType::Auto { opt, .. } = &restore_type {
+        if opt.plan_only {
+            // Planning doesn't need the DB, so don't open it.
+            global.db_dir = None;
+        }
+    }
+    let global_opt: GlobalRestoreOptions = global.try_into()?;
 
-    match opt.restore_type {
+    match restore_type {
         RestoreType::EpochEnding { opt, storage } => {
             EpochEndingRestoreController::new(opt, global_opt, storage.init_storage().await?)
                 .run(None)
@@ -85,6 +103,16 @@ async fn main_impl() -> Result<()> {
             .run()
             .await?;
         }
+        RestoreType::StateSnapshotDiff { opt, storage } => {
+            StateSnapshotDiffRestoreController::new(
+                opt,
+                global_opt,
+                storage.init_storage().await?,
+                None, /* epoch_history */
+            )
+            .run()
+            .await?;
+        }
         RestoreType::Transaction { opt, storage } => {
             TransactionRestoreController::new(
                 opt,
@@ -57,8 +57,10 @@ async fn main_impl() -> Result<()> {
         opt.db_dir,
         false, /* read_only */
         None,  /* pruner */
+        None,  /* ledger_pruner */
         opt.rocksdb_opt.into(),
-        true, /* account_count_migration */
+        true,  /* account_count_migration */
+        false, /* account_by_resource_index */
     )?)
     .get_restore_handler();
     ReplayVerifyCoordinator::new(
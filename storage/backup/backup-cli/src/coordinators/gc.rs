@@ -0,0 +1,315 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::manifest::EpochEndingBackup, state_snapshot::manifest::StateSnapshotBackup,
+        state_snapshot_diff::manifest::StateSnapshotDiffBackup,
+    },
+    metadata,
+    metadata::{
+        cache::MetadataCacheOpt, view::MetadataView, Metadata, StateSnapshotBackupMeta,
+        StateSnapshotDiffBackupMeta,
+    },
+    metrics::gc::{
+        GC_COORDINATOR_FAIL_TS, GC_COORDINATOR_START_TS, GC_COORDINATOR_SUCC_TS,
+        GC_NUM_FILES_DELETED, GC_NUM_FILES_TO_DELETE,
+    },
+    storage::{BackupStorage, FileHandle, ShellSafeName},
+    utils::{storage_ext::BackupStorageExt, stream::StreamX, unix_timestamp_sec},
+};
+use anyhow::{ensure, Result};
+use diem_logger::prelude::*;
+use diem_types::transaction::Version;
+use futures::StreamExt;
+use itertools::Itertools;
+use rand::random;
+use std::{collections::HashSet, convert::TryInto, sync::Arc};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct GcCoordinatorOpt {
+    #[structopt(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+    #[structopt(
+        long,
+        default_value = "1",
+        help = "Number of the latest state snapshots to keep."
+    )]
+    pub keep_latest_state_snapshots: usize,
+    #[structopt(
+        long,
+        help = "Also keep the earliest state snapshot in every N epochs, i.e. one in epochs \
+        [0, N), one in [N, 2N) and so on."
+    )]
+    pub keep_state_snapshot_every_n_epochs: Option<u64>,
+    #[structopt(
+        long,
+        help = "Only log what is to be deleted, without changing anything in the storage."
+    )]
+    pub dry_run: bool,
+}
+
+/// Deletes state snapshots and differential state snapshots that are no longer needed according
+/// to the retention policy, and compacts all metadata into a single metadata file.
+///
+/// Epoch ending and transaction backups are always kept, since the restore needs them to be
+/// continuous from the genesis.
+///
+/// The compacted metadata file is saved before the existing metadata files are deleted, which in
+/// turn happens before the data files of the expired backups are deleted. So if interrupted, the
+/// storage is left with either duplicated metadata lines, which are tolerated, or data files not
+/// referred to by any metadata, which waste space but do no harm.
+pub struct GcCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    keep_latest_state_snapshots: usize,
+    keep_state_snapshot_every_n_epochs: Option<u64>,
+    dry_run: bool,
+    concurrent_downloads: usize,
+}
+
+impl GcCoordinator {
+    pub fn new(
+        opt: GcCoordinatorOpt,
+        concurrent_downloads: usize,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt: opt.metadata_cache_opt,
+            keep_latest_state_snapshots: opt.keep_latest_state_snapshots,
+            keep_state_snapshot_every_n_epochs: opt.keep_state_snapshot_every_n_epochs,
+            dry_run: opt.dry_run,
+            concurrent_downloads,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("GC coordinator started.");
+        GC_COORDINATOR_START_TS.set(unix_timestamp_sec());
+
+        let ret = self.run_impl().await;
+
+        if let Err(e) = &ret {
+            error!(
+                error = ?e,
+                "GC coordinator failed."
+            );
+            GC_COORDINATOR_FAIL_TS.set(unix_timestamp_sec());
+        } else {
+            info!("GC coordinator exiting with success.");
+            GC_COORDINATOR_SUCC_TS.set(unix_timestamp_sec());
+        }
+
+        ret
+    }
+}
+
+impl GcCoordinator {
+    async fn run_impl(self) -> Result<()> {
+        ensure!(
+            self.keep_latest_state_snapshots > 0,
+            "Must keep at least one state snapshot."
+        );
+        if let Some(n) = self.keep_state_snapshot_every_n_epochs {
+            ensure!(n > 0, "Can't keep state snapshots every 0 epochs.");
+        }
+
+        // Listed before loading the metadata, so that a metadata file saved in the meantime won't
+        // be deleted before its content gets compacted.
+        let metadata_files = self.storage.list_metadata_files().await?;
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+
+        let epoch_ending_versions = match self.keep_state_snapshot_every_n_epochs {
+            Some(_) => self.load_epoch_ending_versions(&metadata_view).await?,
+            None => Vec::new(),
+        };
+        let plan = self.plan(&metadata_view, &epoch_ending_versions);
+
+        let mut files_to_delete = Vec::new();
+        for backup in &plan.expired_state_snapshots {
+            info!(
+                "State snapshot at version {} expires. Manifest: {}",
+                backup.version, backup.manifest,
+            );
+            files_to_delete.extend(self.state_snapshot_files(backup).await?);
+        }
+        for backup in &plan.expired_state_snapshot_diffs {
+            info!(
+                "Differential state snapshot at version {} based on version {} expires. \
+                Manifest: {}",
+                backup.version, backup.base_version, backup.manifest,
+            );
+            files_to_delete.extend(self.state_snapshot_diff_files(backup).await?);
+        }
+        GC_NUM_FILES_TO_DELETE.set(files_to_delete.len() as i64);
+        info!(
+            "{} backups to keep, {} files in expired backups to delete, {} metadata files to \
+            compact.",
+            plan.kept.len(),
+            files_to_delete.len(),
+            metadata_files.len(),
+        );
+
+        if self.dry_run {
+            for file_handle in &files_to_delete {
+                info!("[dry run] To delete {}", file_handle);
+            }
+            info!("This is a dry run, the storage is untouched.");
+            return Ok(());
+        }
+        if files_to_delete.is_empty() && metadata_files.len() <= 1 {
+            info!("Nothing to do.");
+            return Ok(());
+        }
+
+        let lines = plan
+            .kept
+            .iter()
+            .map(Metadata::to_text_line)
+            .collect::<Result<Vec<_>>>()?;
+        let name: ShellSafeName = format!(
+            "compacted_{}.{:04x}.meta",
+            unix_timestamp_sec(),
+            random::<u16>()
+        )
+        .try_into()?;
+        self.storage.save_metadata_lines(&name, &lines).await?;
+        info!("Compacted metadata saved as {}.", name.as_ref());
+        for file_handle in &metadata_files {
+            self.storage.delete_file(file_handle).await?;
+        }
+
+        GC_NUM_FILES_DELETED.set(0);
+        let storage = &self.storage;
+        let futs = files_to_delete.iter().map(|file_handle| async move {
+            storage.delete_file(file_handle).await?;
+            GC_NUM_FILES_DELETED.inc();
+            Ok(())
+        });
+        futures::stream::iter(futs)
+            .buffered_x(self.concurrent_downloads * 2, self.concurrent_downloads)
+            .collect::<Result<Vec<_>>>()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the version of the epoch ending ledger info of epoch 0, 1, 2...
+    async fn load_epoch_ending_versions(&self, view: &MetadataView) -> Result<Vec<Version>> {
+        let mut versions = Vec::new();
+        for backup in view.select_epoch_ending_backups(Version::max_value())? {
+            let manifest: EpochEndingBackup = self.storage.load_json_file(&backup.manifest).await?;
+            manifest.verify()?;
+            versions.extend(manifest.waypoints.iter().map(|w| w.version()));
+        }
+        Ok(versions)
+    }
+
+    fn plan(&self, view: &MetadataView, epoch_ending_versions: &[Version]) -> GcPlan {
+        let snapshots = view
+            .state_snapshot_backups()
+            .iter()
+            .sorted_by_key(|s| s.version)
+            .collect::<Vec<_>>();
+
+        let mut keep = vec![false; snapshots.len()];
+        let num_latest = std::cmp::min(self.keep_latest_state_snapshots, snapshots.len());
+        keep[snapshots.len() - num_latest..]
+            .iter_mut()
+            .for_each(|k| *k = true);
+        if let Some(n) = self.keep_state_snapshot_every_n_epochs {
+            let mut last_bucket = None;
+            for (i, snapshot) in snapshots.iter().enumerate() {
+                // `epoch_ending_versions[e]` is the last version of epoch `e`.
+                let epoch = epoch_ending_versions.partition_point(|v| *v < snapshot.version);
+                if epoch == epoch_ending_versions.len() {
+                    // Can't tell which epoch it's in, since the epoch ending backups don't reach
+                    // that far yet.
+                    keep[i] = true;
+                } else if last_bucket != Some(epoch as u64 / n) {
+                    keep[i] = true;
+                    last_bucket = Some(epoch as u64 / n);
+                }
+            }
+        }
+
+        let mut plan = GcPlan::default();
+        let mut kept_state_versions = HashSet::new();
+        for (snapshot, keep) in snapshots.into_iter().zip(keep) {
+            if keep {
+                kept_state_versions.insert(snapshot.version);
+                plan.kept
+                    .push(Metadata::StateSnapshotBackup(snapshot.clone()));
+            } else {
+                plan.expired_state_snapshots.push(snapshot.clone());
+            }
+        }
+        // A differential state snapshot is kept only if what it's based on is kept.
+        for diff in view
+            .state_snapshot_diff_backups()
+            .iter()
+            .sorted_by_key(|d| d.version)
+        {
+            if kept_state_versions.contains(&diff.base_version) {
+                kept_state_versions.insert(diff.version);
+                plan.kept
+                    .push(Metadata::StateSnapshotDiffBackup(diff.clone()));
+            } else {
+                plan.expired_state_snapshot_diffs.push(diff.clone());
+            }
+        }
+
+        plan.kept.extend(
+            view.epoch_ending_backups()
+                .iter()
+                .cloned()
+                .map(Metadata::EpochEndingBackup),
+        );
+        plan.kept.extend(
+            view.transaction_backups()
+                .iter()
+                .cloned()
+                .map(Metadata::TransactionBackup),
+        );
+        plan
+    }
+
+    async fn state_snapshot_files(
+        &self,
+        backup: &StateSnapshotBackupMeta,
+    ) -> Result<Vec<FileHandle>> {
+        let manifest: StateSnapshotBackup = self.storage.load_json_file(&backup.manifest).await?;
+        let mut files = vec![backup.manifest.clone(), manifest.proof];
+        for chunk in manifest.chunks {
+            files.push(chunk.blobs);
+            files.push(chunk.proof);
+        }
+        Ok(files)
+    }
+
+    async fn state_snapshot_diff_files(
+        &self,
+        backup: &StateSnapshotDiffBackupMeta,
+    ) -> Result<Vec<FileHandle>> {
+        let manifest: StateSnapshotDiffBackup =
+            self.storage.load_json_file(&backup.manifest).await?;
+        let mut files = vec![backup.manifest.clone(), manifest.proof];
+        files.extend(manifest.chunks.into_iter().map(|chunk| chunk.blobs));
+        Ok(files)
+    }
+}
+
+#[derive(Default)]
+struct GcPlan {
+    /// Metadata of all backups to keep, to be saved as the compacted metadata.
+    kept: Vec<Metadata>,
+    expired_state_snapshots: Vec<StateSnapshotBackupMeta>,
+    expired_state_snapshot_diffs: Vec<StateSnapshotDiffBackupMeta>,
+}
@@ -5,10 +5,16 @@ use crate::{
     backup_types::{
         epoch_ending::restore::EpochHistoryRestoreController,
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        state_snapshot_diff::restore::{
+            StateSnapshotDiffRestoreController, StateSnapshotDiffRestoreOpt,
+        },
         transaction::restore::TransactionRestoreBatchController,
     },
     metadata,
-    metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
+    metadata::{
+        cache::MetadataCacheOpt, view::StateSnapshotChain, EpochEndingBackupMeta,
+        TransactionBackupMeta,
+    },
     metrics::restore::{
         COORDINATOR_FAIL_TS, COORDINATOR_START_TS, COORDINATOR_SUCC_TS, COORDINATOR_TARGET_VERSION,
     },
@@ -18,7 +24,7 @@ use crate::{
 use anyhow::{bail, Result};
 use diem_logger::prelude::*;
 use diem_types::transaction::Version;
-use std::sync::Arc;
+use std::{fmt, sync::Arc};
 use structopt::StructOpt;
 
 #[derive(StructOpt)]
@@ -39,6 +45,12 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Version,
     #[structopt(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[structopt(
+        long,
+        help = "Only print the restore plan, without opening the DB or running the plan. \
+        Transactions already in the DB are not taken into account."
+    )]
+    pub plan_only: bool,
 }
 
 pub struct RestoreCoordinator {
@@ -48,6 +60,7 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Version,
     skip_epoch_endings: bool,
+    plan_only: bool,
 }
 
 impl RestoreCoordinator {
@@ -63,6 +76,7 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            plan_only: opt.plan_only,
         }
     }
 
@@ -87,6 +101,75 @@ impl RestoreCoordinator {
     }
 
     async fn run_impl(self) -> Result<()> {
+        let plan = self.plan().await?;
+        info!("Restore plan:\n{}", plan);
+        if self.plan_only {
+            println!("{}", plan);
+            return Ok(());
+        }
+        COORDINATOR_TARGET_VERSION.set(plan.target_version as i64);
+
+        let epoch_history = if self.skip_epoch_endings {
+            None
+        } else {
+            Some(Arc::new(
+                EpochHistoryRestoreController::new(
+                    plan.epoch_endings
+                        .into_iter()
+                        .map(|backup| backup.manifest)
+                        .collect(),
+                    self.global_opt.clone(),
+                    self.storage.clone(),
+                )
+                .run()
+                .await?,
+            ))
+        };
+
+        if let Some(chain) = plan.state_snapshot {
+            let backup = chain.snapshot;
+            StateSnapshotRestoreController::new(
+                StateSnapshotRestoreOpt {
+                    manifest_handle: backup.manifest,
+                    version: backup.version,
+                },
+                self.global_opt.clone(),
+                Arc::clone(&self.storage),
+                epoch_history.clone(),
+            )
+            .run()
+            .await?;
+            for diff in chain.diffs {
+                StateSnapshotDiffRestoreController::new(
+                    StateSnapshotDiffRestoreOpt {
+                        manifest_handle: diff.manifest,
+                    },
+                    self.global_opt.clone(),
+                    Arc::clone(&self.storage),
+                    epoch_history.clone(),
+                )
+                .run()
+                .await?;
+            }
+        }
+
+        let txn_manifests = plan.transactions.into_iter().map(|b| b.manifest).collect();
+        TransactionRestoreBatchController::new(
+            self.global_opt,
+            self.storage,
+            txn_manifests,
+            Some(plan.replay_transactions_from_version),
+            epoch_history,
+        )
+        .run()
+        .await?;
+
+        Ok(())
+    }
+
+    /// Picks the backups to restore from, according to the metadata in the storage and what's
+    /// already in the DB.
+    pub(crate) async fn plan(&self) -> Result<RestorePlan> {
         let metadata_view = metadata::cache::sync_and_load(
             &self.metadata_cache_opt,
             Arc::clone(&self.storage),
@@ -101,14 +184,12 @@ impl RestoreCoordinator {
         let state_snapshot = if self.replay_all {
             None
         } else {
-            metadata_view.select_state_snapshot(actual_target_version)?
+            metadata_view.select_state_snapshot_chain(actual_target_version)?
         };
         let replay_transactions_from_version = match &state_snapshot {
-            Some(b) => b.version + 1,
+            Some(c) => c.version() + 1,
             None => 0,
         };
-        COORDINATOR_TARGET_VERSION.set(actual_target_version as i64);
-        info!("Planned to restore to version {}.", actual_target_version);
 
         let txn_resume_point = match self.global_opt.run_mode.as_ref() {
             RestoreRunMode::Restore { restore_handler } => {
@@ -121,7 +202,10 @@ impl RestoreCoordinator {
         };
         let start_version = std::cmp::min(
             self.ledger_history_start_version,
-            state_snapshot.as_ref().map(|s| s.version + 1).unwrap_or(0),
+            state_snapshot
+                .as_ref()
+                .map(|c| c.version() + 1)
+                .unwrap_or(0),
         );
         transactions = transactions
             .into_iter()
@@ -149,49 +233,17 @@ impl RestoreCoordinator {
             }
         }
 
-        let epoch_history = if self.skip_epoch_endings {
-            None
-        } else {
-            Some(Arc::new(
-                EpochHistoryRestoreController::new(
-                    epoch_endings
-                        .into_iter()
-                        .map(|backup| backup.manifest)
-                        .collect(),
-                    self.global_opt.clone(),
-                    self.storage.clone(),
-                )
-                .run()
-                .await?,
-            ))
-        };
-
-        if let Some(backup) = state_snapshot {
-            StateSnapshotRestoreController::new(
-                StateSnapshotRestoreOpt {
-                    manifest_handle: backup.manifest,
-                    version: backup.version,
-                },
-                self.global_opt.clone(),
-                Arc::clone(&self.storage),
-                epoch_history.clone(),
-            )
-            .run()
-            .await?;
-        }
-
-        let txn_manifests = transactions.into_iter().map(|b| b.manifest).collect();
-        TransactionRestoreBatchController::new(
-            self.global_opt,
-            self.storage,
-            txn_manifests,
-            Some(replay_transactions_from_version),
-            epoch_history,
-        )
-        .run()
-        .await?;
-
-        Ok(())
+        Ok(RestorePlan {
+            target_version: actual_target_version,
+            epoch_endings: if self.skip_epoch_endings {
+                Vec::new()
+            } else {
+                epoch_endings
+            },
+            state_snapshot,
+            transactions,
+            replay_transactions_from_version,
+        })
     }
 }
 
@@ -219,3 +271,61 @@ impl RestoreCoordinator {
         }
     }
 }
+
+/// The backups picked by the `RestoreCoordinator` to restore to the target version.
+pub struct RestorePlan {
+    /// The version to restore to, which can be smaller than the requested one if the backups don't
+    /// reach that far.
+    pub target_version: Version,
+    pub epoch_endings: Vec<EpochEndingBackupMeta>,
+    /// The state snapshot, and differential state snapshots on top of it, to start from.
+    pub state_snapshot: Option<StateSnapshotChain>,
+    pub transactions: Vec<TransactionBackupMeta>,
+    /// Transactions before this are saved to the DB without being replayed.
+    pub replay_transactions_from_version: Version,
+}
+
+impl fmt::Display for RestorePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "target version: {}", self.target_version)?;
+        writeln!(f, "epoch ending backups:")?;
+        for e in &self.epoch_endings {
+            writeln!(
+                f,
+                "    epochs [{}, {}], manifest: {}",
+                e.first_epoch, e.last_epoch, e.manifest
+            )?;
+        }
+        match &self.state_snapshot {
+            Some(chain) => {
+                writeln!(
+                    f,
+                    "state snapshot: version {}, manifest: {}",
+                    chain.snapshot.version, chain.snapshot.manifest
+                )?;
+                for d in &chain.diffs {
+                    writeln!(
+                        f,
+                        "    differential state snapshot: version {} based on version {}, \
+                        manifest: {}",
+                        d.version, d.base_version, d.manifest
+                    )?;
+                }
+            }
+            None => writeln!(f, "state snapshot: none")?,
+        }
+        writeln!(f, "transaction backups:")?;
+        for t in &self.transactions {
+            writeln!(
+                f,
+                "    versions [{}, {}], manifest: {}",
+                t.first_version, t.last_version, t.manifest
+            )?;
+        }
+        write!(
+            f,
+            "replay transactions from version: {}",
+            self.replay_transactions_from_version
+        )
+    }
+}
@@ -0,0 +1,408 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::manifest::{EpochEndingBackup, EpochEndingChunk},
+        state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
+        state_snapshot_diff::manifest::{StateSnapshotDiffBackup, StateSnapshotDiffChunk},
+    },
+    coordinators::{
+        gc::{GcCoordinator, GcCoordinatorOpt},
+        restore::{RestoreCoordinator, RestoreCoordinatorOpt, RestorePlan},
+    },
+    metadata::{cache, cache::MetadataCacheOpt, Metadata},
+    storage::{local_fs::LocalFs, BackupStorage, FileHandle},
+    utils::{ConcurrentDownloadsOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt},
+};
+use diem_crypto::HashValue;
+use diem_temppath::TempPath;
+use diem_types::{
+    block_info::BlockInfo, ledger_info::LedgerInfo, transaction::Version, waypoint::Waypoint,
+};
+use std::{convert::TryInto, sync::Arc};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    runtime::Runtime,
+};
+
+struct TestStorage {
+    storage: Arc<dyn BackupStorage>,
+    _backup_dir: TempPath,
+}
+
+impl TestStorage {
+    fn new() -> Self {
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        Self {
+            storage: Arc::new(LocalFs::new(backup_dir.path().to_path_buf())),
+            _backup_dir: backup_dir,
+        }
+    }
+
+    async fn save_file(&self, backup_handle: &str, name: &str, content: &[u8]) -> FileHandle {
+        let (file_handle, mut file) = self
+            .storage
+            .create_for_write(backup_handle, &name.try_into().unwrap())
+            .await
+            .unwrap();
+        file.write_all(content).await.unwrap();
+        file.shutdown().await.unwrap();
+        file_handle
+    }
+
+    async fn save_metadata(&self, metadata: Metadata) {
+        self.storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line().unwrap())
+            .await
+            .unwrap();
+    }
+
+    async fn file_exists(&self, file_handle: &str) -> bool {
+        let mut buf = Vec::new();
+        match self.storage.open_for_read(file_handle).await {
+            Ok(mut file) => file.read_to_end(&mut buf).await.is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns all files in the backup.
+    async fn add_state_snapshot(&self, version: Version) -> Vec<FileHandle> {
+        let backup_handle = self
+            .storage
+            .create_backup(&format!("state_ver_{}", version).try_into().unwrap())
+            .await
+            .unwrap();
+        let blobs = self.save_file(&backup_handle, "0-0.chunk", b"blobs").await;
+        let chunk_proof = self.save_file(&backup_handle, "0-0.proof", b"proof").await;
+        let proof = self
+            .save_file(&backup_handle, "state.proof", b"proof")
+            .await;
+        let manifest = StateSnapshotBackup {
+            version,
+            root_hash: HashValue::zero(),
+            chunks: vec![StateSnapshotChunk {
+                first_idx: 0,
+                last_idx: 0,
+                first_key: HashValue::zero(),
+                last_key: HashValue::zero(),
+                blobs: blobs.clone(),
+                proof: chunk_proof.clone(),
+            }],
+            proof: proof.clone(),
+        };
+        let manifest_handle = self
+            .save_file(
+                &backup_handle,
+                "state.manifest",
+                &serde_json::to_vec(&manifest).unwrap(),
+            )
+            .await;
+        self.save_metadata(Metadata::new_state_snapshot_backup(
+            version,
+            manifest_handle.clone(),
+        ))
+        .await;
+
+        vec![manifest_handle, blobs, chunk_proof, proof]
+    }
+
+    /// Returns all files in the backup.
+    async fn add_state_snapshot_diff(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Vec<FileHandle> {
+        let backup_handle = self
+            .storage
+            .create_backup(
+                &format!("state_diff_ver_{}-{}", base_version, version)
+                    .try_into()
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let blobs = self.save_file(&backup_handle, "0-0.chunk", b"blobs").await;
+        let proof = self
+            .save_file(&backup_handle, "state.proof", b"proof")
+            .await;
+        let manifest = StateSnapshotDiffBackup {
+            base_version,
+            version,
+            root_hash: HashValue::zero(),
+            chunks: vec![StateSnapshotDiffChunk {
+                first_idx: 0,
+                last_idx: 0,
+                first_key: HashValue::zero(),
+                last_key: HashValue::zero(),
+                blobs: blobs.clone(),
+            }],
+            proof: proof.clone(),
+        };
+        let manifest_handle = self
+            .save_file(
+                &backup_handle,
+                "state_diff.manifest",
+                &serde_json::to_vec(&manifest).unwrap(),
+            )
+            .await;
+        self.save_metadata(Metadata::new_state_snapshot_diff_backup(
+            base_version,
+            version,
+            manifest_handle.clone(),
+        ))
+        .await;
+
+        vec![manifest_handle, blobs, proof]
+    }
+
+    /// Adds an epoch ending backup of epoch 0, 1, 2... ending at `versions`.
+    async fn add_epoch_endings(&self, versions: &[Version]) {
+        let backup_handle = self
+            .storage
+            .create_backup(&"epoch_ending".try_into().unwrap())
+            .await
+            .unwrap();
+        let last_epoch = versions.len() as u64 - 1;
+        let ledger_infos = self.save_file(&backup_handle, "0-.chunk", b"li").await;
+        let waypoints = versions
+            .iter()
+            .enumerate()
+            .map(|(epoch, version)| {
+                Waypoint::new_any(&LedgerInfo::new(
+                    BlockInfo::new(
+                        epoch as u64,
+                        0,
+                        HashValue::zero(),
+                        HashValue::zero(),
+                        *version,
+                        0,
+                        None,
+                    ),
+                    HashValue::zero(),
+                ))
+            })
+            .collect();
+        let manifest = EpochEndingBackup {
+            first_epoch: 0,
+            last_epoch,
+            waypoints,
+            chunks: vec![EpochEndingChunk {
+                first_epoch: 0,
+                last_epoch,
+                ledger_infos,
+            }],
+        };
+        let manifest_handle = self
+            .save_file(
+                &backup_handle,
+                "epoch_ending.manifest",
+                &serde_json::to_vec(&manifest).unwrap(),
+            )
+            .await;
+        self.save_metadata(Metadata::new_epoch_ending_backup(
+            0,
+            last_epoch,
+            versions[0],
+            *versions.last().unwrap(),
+            manifest_handle,
+        ))
+        .await;
+    }
+
+    async fn add_transactions(&self, first_version: Version, last_version: Version) {
+        // Neither GC nor restore planning looks into transaction manifests, so they don't need to
+        // exist.
+        self.save_metadata(Metadata::new_transaction_backup(
+            first_version,
+            last_version,
+            format!(
+                "transaction_{}-{}/transaction.manifest",
+                first_version, last_version
+            ),
+        ))
+        .await;
+    }
+
+    async fn gc(
+        &self,
+        keep_latest_state_snapshots: usize,
+        keep_state_snapshot_every_n_epochs: Option<u64>,
+        dry_run: bool,
+    ) {
+        let cache_dir = TempPath::new();
+        GcCoordinator::new(
+            GcCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf())),
+                keep_latest_state_snapshots,
+                keep_state_snapshot_every_n_epochs,
+                dry_run,
+            },
+            4, /* concurrent_downloads */
+            Arc::clone(&self.storage),
+        )
+        .run()
+        .await
+        .unwrap()
+    }
+
+    /// Plans a dry run restore to `target_version`.
+    async fn restore_plan(&self, target_version: Option<Version>) -> RestorePlan {
+        let cache_dir = TempPath::new();
+        RestoreCoordinator::new(
+            RestoreCoordinatorOpt {
+                metadata_cache_opt: MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf())),
+                replay_all: false,
+                ledger_history_start_version: Version::max_value(),
+                skip_epoch_endings: false,
+                plan_only: true,
+            },
+            GlobalRestoreOpt {
+                dry_run: true,
+                db_dir: None,
+                target_version,
+                trusted_waypoints: TrustedWaypointOpt::default(),
+                rocksdb_opt: RocksdbOpt::default(),
+                concurernt_downloads: ConcurrentDownloadsOpt::default(),
+                account_count_migration: false,
+            }
+            .try_into()
+            .unwrap(),
+            Arc::clone(&self.storage),
+        )
+        .plan()
+        .await
+        .unwrap()
+    }
+
+    /// Returns the versions of state snapshots and differential state snapshots in the storage.
+    async fn state_versions(&self) -> (Vec<Version>, Vec<Version>) {
+        let cache_dir = TempPath::new();
+        let view = cache::sync_and_load(
+            &MetadataCacheOpt::new(Some(cache_dir.path().to_path_buf())),
+            Arc::clone(&self.storage),
+            4, /* concurrent_downloads */
+        )
+        .await
+        .unwrap();
+        (
+            view.state_snapshot_backups()
+                .iter()
+                .map(|s| s.version)
+                .collect(),
+            view.state_snapshot_diff_backups()
+                .iter()
+                .map(|d| d.version)
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_gc_keep_latest() {
+    Runtime::new().unwrap().block_on(async {
+        let store = TestStorage::new();
+        store.add_transactions(0, 99).await;
+        store.add_transactions(100, 199).await;
+        let mut expired_files = Vec::new();
+        for version in [10, 20, 30] {
+            expired_files.extend(store.add_state_snapshot(version).await);
+        }
+        expired_files.extend(store.add_state_snapshot_diff(20, 25).await);
+        expired_files.extend(store.add_state_snapshot_diff(25, 27).await);
+        let mut kept_files = store.add_state_snapshot(40).await;
+        kept_files.extend(store.add_state_snapshot_diff(40, 45).await);
+        kept_files.extend(store.add_state_snapshot_diff(45, 50).await);
+
+        // dry run changes nothing
+        store.gc(1, None, true).await;
+        assert_eq!(store.storage.list_metadata_files().await.unwrap().len(), 10);
+        for file_handle in expired_files.iter().chain(&kept_files) {
+            assert!(store.file_exists(file_handle).await);
+        }
+
+        store.gc(1, None, false).await;
+        assert_eq!(store.storage.list_metadata_files().await.unwrap().len(), 1);
+        for file_handle in &expired_files {
+            assert!(!store.file_exists(file_handle).await);
+        }
+        for file_handle in &kept_files {
+            assert!(store.file_exists(file_handle).await);
+        }
+        assert_eq!(store.state_versions().await, (vec![40], vec![45, 50]));
+
+        // nothing more to collect
+        store.gc(1, None, false).await;
+        assert_eq!(store.storage.list_metadata_files().await.unwrap().len(), 1);
+        assert_eq!(store.state_versions().await, (vec![40], vec![45, 50]));
+    })
+}
+
+#[test]
+fn test_gc_keep_every_n_epochs() {
+    Runtime::new().unwrap().block_on(async {
+        let store = TestStorage::new();
+        store.add_transactions(0, 99).await;
+        // epoch 0: [0, 0], epoch 1: [1, 15], epoch 2: [16, 35], epoch 3: [36, 50]
+        store.add_epoch_endings(&[0, 15, 35, 50]).await;
+        for version in [10, 20, 30, 40, 60, 70] {
+            store.add_state_snapshot(version).await;
+        }
+
+        store.gc(1, Some(2), false).await;
+        // 10 is the first in epochs [0, 2), 20 is the first in epochs [2, 4), while 60 and 70 are
+        // kept since their epochs are not known yet.
+        assert_eq!(store.state_versions().await, (vec![10, 20, 60, 70], vec![]));
+
+        store.gc(1, None, false).await;
+        assert_eq!(store.state_versions().await, (vec![70], vec![]));
+    })
+}
+
+#[test]
+fn test_restore_plan() {
+    Runtime::new().unwrap().block_on(async {
+        let store = TestStorage::new();
+        store.add_transactions(0, 99).await;
+        store.add_transactions(100, 199).await;
+        store.add_transactions(200, 299).await;
+        store.add_epoch_endings(&[0, 150, 250]).await;
+        store.add_state_snapshot(50).await;
+        store.add_state_snapshot(150).await;
+        store.add_state_snapshot_diff(150, 160).await;
+        store.add_state_snapshot_diff(160, 180).await;
+        store.add_state_snapshot(250).await;
+
+        // The nearest snapshot at or before the target, with diffs not passing the target.
+        let plan = store.restore_plan(Some(170)).await;
+        assert_eq!(plan.target_version, 170);
+        let chain = plan.state_snapshot.as_ref().unwrap();
+        assert_eq!(chain.snapshot.version, 150);
+        assert_eq!(
+            chain.diffs.iter().map(|d| d.version).collect::<Vec<_>>(),
+            vec![160]
+        );
+        assert_eq!(plan.replay_transactions_from_version, 161);
+        assert_eq!(
+            plan.transactions
+                .iter()
+                .map(|t| t.first_version)
+                .collect::<Vec<_>>(),
+            vec![100]
+        );
+        assert_eq!(plan.epoch_endings.len(), 1);
+        assert!(plan.to_string().contains("state snapshot: version 150"));
+
+        let plan = store.restore_plan(Some(40)).await;
+        assert!(plan.state_snapshot.is_none());
+        assert_eq!(plan.replay_transactions_from_version, 0);
+        assert_eq!(plan.transactions.len(), 1);
+
+        // Can't go beyond the transaction backups.
+        let plan = store.restore_plan(None).await;
+        assert_eq!(plan.target_version, 299);
+        assert_eq!(plan.state_snapshot.unwrap().snapshot.version, 250);
+        assert_eq!(plan.replay_transactions_from_version, 251);
+    })
+}
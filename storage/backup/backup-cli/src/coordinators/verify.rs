@@ -5,6 +5,9 @@ use crate::{
     backup_types::{
         epoch_ending::restore::EpochHistoryRestoreController,
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        state_snapshot_diff::restore::{
+            StateSnapshotDiffRestoreController, StateSnapshotDiffRestoreOpt,
+        },
         transaction::restore::TransactionRestoreBatchController,
     },
     metadata,
@@ -70,7 +73,8 @@ impl VerifyCoordinator {
         )
         .await?;
         let ver_max = Version::max_value();
-        let state_snapshot = metadata_view.select_state_snapshot(ver_max)?;
+        metadata_view.check_state_snapshot_diff_bases()?;
+        let state_snapshot = metadata_view.select_state_snapshot_chain(ver_max)?;
         let transactions = metadata_view.select_transaction_backups(0, ver_max)?;
         let epoch_endings = metadata_view.select_epoch_ending_backups(ver_max)?;
 
@@ -95,7 +99,8 @@ impl VerifyCoordinator {
             .await?,
         );
 
-        if let Some(backup) = state_snapshot {
+        if let Some(chain) = state_snapshot {
+            let backup = chain.snapshot;
             StateSnapshotRestoreController::new(
                 StateSnapshotRestoreOpt {
                     manifest_handle: backup.manifest,
@@ -107,6 +112,18 @@ impl VerifyCoordinator {
             )
             .run()
             .await?;
+            for diff in chain.diffs {
+                StateSnapshotDiffRestoreController::new(
+                    StateSnapshotDiffRestoreOpt {
+                        manifest_handle: diff.manifest,
+                    },
+                    global_opt.clone(),
+                    Arc::clone(&self.storage),
+                    Some(Arc::clone(&epoch_history)),
+                )
+                .run()
+                .await?;
+            }
         }
 
         let txn_manifests = transactions.into_iter().map(|b| b.manifest).collect();
@@ -15,6 +15,7 @@ use std::convert::TryInto;
 pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
     StateSnapshotBackup(StateSnapshotBackupMeta),
+    StateSnapshotDiffBackup(StateSnapshotDiffBackupMeta),
     TransactionBackup(TransactionBackupMeta),
 }
 
@@ -39,6 +40,18 @@ impl Metadata {
         Self::StateSnapshotBackup(StateSnapshotBackupMeta { version, manifest })
     }
 
+    pub fn new_state_snapshot_diff_backup(
+        base_version: Version,
+        version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::StateSnapshotDiffBackup(StateSnapshotDiffBackupMeta {
+            base_version,
+            version,
+            manifest,
+        })
+    }
+
     pub fn new_transaction_backup(
         first_version: Version,
         last_version: Version,
@@ -57,6 +70,10 @@ impl Metadata {
                 format!("epoch_ending_{}-{}.meta", e.first_epoch, e.last_epoch)
             }
             Self::StateSnapshotBackup(s) => format!("state_snapshot_ver_{}.meta", s.version),
+            Self::StateSnapshotDiffBackup(d) => format!(
+                "state_snapshot_diff_ver_{}-{}.meta",
+                d.base_version, d.version
+            ),
             Self::TransactionBackup(t) => {
                 format!("transaction_{}-{}.meta", t.first_version, t.last_version,)
             }
@@ -85,6 +102,13 @@ pub struct StateSnapshotBackupMeta {
     pub manifest: FileHandle,
 }
 
+#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct StateSnapshotDiffBackupMeta {
+    pub base_version: Version,
+    pub version: Version,
+    pub manifest: FileHandle,
+}
+
 #[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct TransactionBackupMeta {
     pub first_version: Version,
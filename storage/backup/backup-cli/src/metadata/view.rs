@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::metadata::{
-    EpochEndingBackupMeta, Metadata, StateSnapshotBackupMeta, TransactionBackupMeta,
+    EpochEndingBackupMeta, Metadata, StateSnapshotBackupMeta, StateSnapshotDiffBackupMeta,
+    TransactionBackupMeta,
 };
 use anyhow::{anyhow, ensure, Result};
 use diem_types::transaction::Version;
@@ -12,6 +13,7 @@ use std::{fmt, str::FromStr};
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
+    state_snapshot_diff_backups: Vec<StateSnapshotDiffBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
 }
 
@@ -34,6 +36,22 @@ impl MetadataView {
         }
     }
 
+    pub fn epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
+    pub fn state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    pub fn state_snapshot_diff_backups(&self) -> &[StateSnapshotDiffBackupMeta] {
+        &self.state_snapshot_diff_backups
+    }
+
+    pub fn transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
     pub fn select_state_snapshot(
         &self,
         target_version: Version,
@@ -47,6 +65,67 @@ impl MetadataView {
             .map(Clone::clone))
     }
 
+    /// Selects a state snapshot and a chain of differential state snapshots on top of it, which
+    /// together restore the state at the latest possible version no newer than `target_version`.
+    pub fn select_state_snapshot_chain(
+        &self,
+        target_version: Version,
+    ) -> Result<Option<StateSnapshotChain>> {
+        let mut res: Option<StateSnapshotChain> = None;
+        // Newer snapshots are visited first so that a shorter chain is preferred among those
+        // reaching the same version.
+        for snapshot in self
+            .state_snapshot_backups
+            .iter()
+            .sorted()
+            .rev()
+            .filter(|s| s.version <= target_version)
+        {
+            let mut chain = StateSnapshotChain {
+                snapshot: snapshot.clone(),
+                diffs: Vec::new(),
+            };
+            while let Some(diff) = self
+                .state_snapshot_diff_backups
+                .iter()
+                .filter(|d| {
+                    d.base_version == chain.version()
+                        && d.version > d.base_version
+                        && d.version <= target_version
+                })
+                .max_by_key(|d| d.version)
+            {
+                chain.diffs.push(diff.clone());
+            }
+            if res.as_ref().map_or(true, |c| c.version() < chain.version()) {
+                res = Some(chain);
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Makes sure every differential state snapshot is based on an existing state snapshot or
+    /// differential state snapshot, i.e., the chains are not broken.
+    pub fn check_state_snapshot_diff_bases(&self) -> Result<()> {
+        for diff in &self.state_snapshot_diff_backups {
+            ensure!(
+                self.state_snapshot_backups
+                    .iter()
+                    .any(|s| s.version == diff.base_version)
+                    || self
+                        .state_snapshot_diff_backups
+                        .iter()
+                        .any(|d| d.version == diff.base_version),
+                "Differential state snapshot at version {} is based on version {}, of which no \
+                state snapshot exists.",
+                diff.version,
+                diff.base_version,
+            );
+        }
+        Ok(())
+    }
+
     pub fn select_transaction_backups(
         &self,
         start_version: Version,
@@ -109,24 +188,53 @@ impl From<Vec<Metadata>> for MetadataView {
     fn from(metadata_vec: Vec<Metadata>) -> Self {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
+        let mut state_snapshot_diff_backups = Vec::new();
         let mut transaction_backups = Vec::new();
 
         for meta in metadata_vec {
             match meta {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
+                Metadata::StateSnapshotDiffBackup(d) => state_snapshot_diff_backups.push(d),
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
             }
         }
 
+        // The same line can show up in multiple metadata files if metadata compaction was
+        // interrupted, see `GcCoordinator`.
+        epoch_ending_backups.sort();
+        epoch_ending_backups.dedup();
+        state_snapshot_backups.sort();
+        state_snapshot_backups.dedup();
+        state_snapshot_diff_backups.sort();
+        state_snapshot_diff_backups.dedup();
+        transaction_backups.sort();
+        transaction_backups.dedup();
+
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
+            state_snapshot_diff_backups,
             transaction_backups,
         }
     }
 }
 
+/// A state snapshot followed by differential state snapshots each based on the previous one.
+pub struct StateSnapshotChain {
+    pub snapshot: StateSnapshotBackupMeta,
+    pub diffs: Vec<StateSnapshotDiffBackupMeta>,
+}
+
+impl StateSnapshotChain {
+    /// The version of the state restored by the whole chain.
+    pub fn version(&self) -> Version {
+        self.diffs
+            .last()
+            .map_or(self.snapshot.version, |d| d.version)
+    }
+}
+
 pub struct BackupStorageState {
     pub latest_epoch_ending_epoch: Option<u64>,
     pub latest_state_snapshot_version: Option<Version>,
@@ -0,0 +1,45 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use diem_secure_push_metrics::{register_int_gauge, IntGauge};
+use once_cell::sync::Lazy;
+
+pub static GC_NUM_FILES_TO_DELETE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_db_backup_gc_num_files_to_delete",
+        "Number of files in backups no longer needed, to be deleted by the GC coordinator."
+    )
+    .unwrap()
+});
+
+pub static GC_NUM_FILES_DELETED: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_db_backup_gc_num_files_deleted",
+        "Number of files deleted by the GC coordinator."
+    )
+    .unwrap()
+});
+
+pub static GC_COORDINATOR_START_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_db_backup_gc_coordinator_start_timestamp_s",
+        "Timestamp when the GC coordinator starts."
+    )
+    .unwrap()
+});
+
+pub static GC_COORDINATOR_SUCC_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_db_backup_gc_coordinator_succeed_timestamp_s",
+        "Timestamp when the GC coordinator succeeds."
+    )
+    .unwrap()
+});
+
+pub static GC_COORDINATOR_FAIL_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_db_backup_gc_coordinator_fail_timestamp_s",
+        "Timestamp when the GC coordinator fails."
+    )
+    .unwrap()
+});
@@ -18,7 +18,7 @@ use crate::{
     },
     utils::error_notes::ErrorNotes,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -113,7 +113,7 @@ impl BackupStorage for CommandAdapter {
         Ok(Box::new(child.into_data_source()))
     }
 
-    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
         let mut child = self
             .cmd(
                 &self.config.commands.save_metadata_line,
@@ -121,11 +121,13 @@ impl BackupStorage for CommandAdapter {
             )
             .spawn()?;
 
-        child
-            .stdin()
-            .write_all(content.as_ref().as_bytes())
-            .await
-            .err_notes(name)?;
+        for line in lines {
+            child
+                .stdin()
+                .write_all(line.as_ref().as_bytes())
+                .await
+                .err_notes(name)?;
+        }
         child.join().await?;
         Ok(())
     }
@@ -143,4 +145,14 @@ impl BackupStorage for CommandAdapter {
             .err_notes((file!(), line!(), &buf))?;
         Ok(buf.lines().map(str::to_string).collect())
     }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let cmd = self.config.commands.delete_file.as_ref().ok_or_else(|| {
+            anyhow!("Command adapter config doesn't have the \"delete_file\" command.")
+        })?;
+        self.cmd(cmd, vec![EnvVar::file_handle(file_handle.to_string())])
+            .spawn()?
+            .join()
+            .await
+    }
 }
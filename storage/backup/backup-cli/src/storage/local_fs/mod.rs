@@ -15,7 +15,7 @@ use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 use tokio::{
-    fs::{create_dir, create_dir_all, read_dir, OpenOptions},
+    fs::{create_dir, create_dir_all, read_dir, remove_dir, remove_file, OpenOptions},
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
@@ -91,7 +91,7 @@ impl BackupStorage for LocalFs {
         Ok(Box::new(file))
     }
 
-    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
         let dir = self.metadata_dir();
         create_dir_all(&dir).await.err_notes(name)?; // in case not yet created
 
@@ -102,9 +102,12 @@ impl BackupStorage for LocalFs {
             .open(&path)
             .await
             .err_notes(&path)?;
-        file.write_all(content.as_ref().as_bytes())
-            .await
-            .err_notes(&path)?;
+        for line in lines {
+            file.write_all(line.as_ref().as_bytes())
+                .await
+                .err_notes(&path)?;
+        }
+        file.shutdown().await.err_notes(&path)?;
 
         Ok(())
     }
@@ -122,4 +125,17 @@ impl BackupStorage for LocalFs {
         }
         Ok(res)
     }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.dir.join(file_handle);
+        remove_file(&path).await.err_notes(&path)?;
+
+        // Clean up the backup folder once it's empty, failing which is harmless.
+        if let Some(parent) = path.parent() {
+            if parent != self.dir && parent != self.metadata_dir() {
+                let _ = remove_dir(parent).await;
+            }
+        }
+        Ok(())
+    }
 }
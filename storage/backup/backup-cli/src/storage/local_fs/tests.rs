@@ -3,7 +3,7 @@
 
 use super::*;
 use crate::storage::test_util::{
-    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    arb_backups, arb_metadata_files, test_delete_impl, test_save_and_list_metadata_files_impl,
     test_write_and_read_impl,
 };
 use diem_temppath::TempPath;
@@ -25,6 +25,18 @@ proptest! {
         rt.block_on(test_write_and_read_impl(Box::new(store), backups));
     }
 
+    #[test]
+    fn test_delete(
+        backups in arb_backups()
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = LocalFs::new(tmpdir.path().to_path_buf());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_delete_impl(Box::new(store), backups));
+    }
+
     #[test]
     fn test_save_list_metadata_files(
         input in arb_metadata_files(),
@@ -3,6 +3,7 @@
 
 pub mod command_adapter;
 pub mod local_fs;
+pub mod s3;
 
 #[cfg(test)]
 mod test_util;
@@ -12,6 +13,7 @@ mod tests;
 use crate::storage::{
     command_adapter::{CommandAdapter, CommandAdapterOpt},
     local_fs::{LocalFs, LocalFsOpt},
+    s3::{S3Opt, S3},
 };
 use anyhow::{ensure, Result};
 use async_trait::async_trait;
@@ -159,7 +161,13 @@ pub trait BackupStorage: Send + Sync {
     /// Behavior on duplicated names is undefined, overwriting the content upon an existing name
     /// is straightforward and acceptable.
     /// See `list_metadata_files`.
-    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()>;
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.save_metadata_lines(name, std::slice::from_ref(content))
+            .await
+    }
+    /// Asks to save multiple metadata entries into one metadata file, which is how metadata gets
+    /// compacted. Same as `save_metadata_line` otherwise.
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()>;
     /// The backup system always asks for all metadata files and cache and build index on top of
     /// the content of them. This means:
     ///   1. The storage is free to reorganise the metadata files, like combining multiple ones to
@@ -167,6 +175,9 @@ pub trait BackupStorage: Send + Sync {
     ///   2. But the cache does expect the content stays the same for a file handle, so when
     /// reorganising metadata files, give them new unique names.
     async fn list_metadata_files(&self) -> Result<Vec<FileHandle>>;
+    /// Deletes a file, either one created by `create_for_write()` or a metadata file listed by
+    /// `list_metadata_files()`. Used to garbage collect backups no longer needed.
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()>;
 }
 
 #[derive(StructOpt)]
@@ -175,6 +186,8 @@ pub enum StorageOpt {
     LocalFs(LocalFsOpt),
     #[structopt(about = "Select the CommandAdapter backup store.")]
     CommandAdapter(CommandAdapterOpt),
+    #[structopt(about = "Select the S3 backup store.")]
+    S3(S3Opt),
 }
 
 impl StorageOpt {
@@ -182,6 +195,7 @@ impl StorageOpt {
         Ok(match self {
             StorageOpt::LocalFs(opt) => Arc::new(LocalFs::new_with_opt(opt)),
             StorageOpt::CommandAdapter(opt) => Arc::new(CommandAdapter::new_with_opt(opt).await?),
+            StorageOpt::S3(opt) => Arc::new(S3::new_with_opt(opt)?),
         })
     }
 }
@@ -0,0 +1,417 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A minimal client speaking the S3 HTTP API, covering only what the backup storage needs. Requests
+//! are signed with AWS Signature Version 4 by `rusoto_signature` and addressed path-style, i.e.
+//! "{endpoint}/{bucket}/{key}", which is supported by S3 as well as most S3-compatible stores.
+
+use anyhow::{anyhow, bail, ensure, Result};
+use bytes::Bytes;
+use diem_logger::prelude::*;
+use quick_xml::{events::Event, Reader};
+use reqwest::{Method, Response, StatusCode, Url};
+use rusoto_credential::AwsCredentials;
+use rusoto_signature::{Region, SignedRequest};
+use std::time::Duration;
+
+pub(super) struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    pub fn from_env() -> Result<Self> {
+        let get = |name: &str| {
+            std::env::var(name).map_err(|e| anyhow!("Failed to read env var {}: {}", name, e))
+        };
+        Ok(Self {
+            access_key_id: get("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: get("AWS_SECRET_ACCESS_KEY")?,
+            session_token: get("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+pub(super) struct S3Client {
+    http: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: Region,
+    credentials: AwsCredentials,
+    max_retries: usize,
+}
+
+impl S3Client {
+    pub fn new(
+        endpoint: Url,
+        bucket: String,
+        region: String,
+        credentials: Credentials,
+        max_retries: usize,
+    ) -> Self {
+        // The endpoint path, if any, is prepended to the request paths by `build_request`, so the
+        // region only carries the scheme and authority for the signer.
+        let region = Region::Custom {
+            name: region,
+            endpoint: endpoint.origin().ascii_serialization(),
+        };
+        Self {
+            http: reqwest::Client::new(),
+            endpoint,
+            bucket,
+            region,
+            credentials: AwsCredentials::new(
+                credentials.access_key_id,
+                credentials.secret_access_key,
+                credentials.session_token,
+                None, /* expires_at */
+            ),
+            max_retries,
+        }
+    }
+
+    pub async fn put_object(&self, key: &str, body: Bytes) -> Result<()> {
+        self.send(Method::PUT, key, &[], &[], body).await?;
+        Ok(())
+    }
+
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.send(Method::DELETE, key, &[], &[], Bytes::new())
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the size of the object.
+    pub async fn head_object(&self, key: &str) -> Result<u64> {
+        let resp = self.send(Method::HEAD, key, &[], &[], Bytes::new()).await?;
+        resp.headers()
+            .get(reqwest::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| anyhow!("Missing or bad Content-Length from HEAD {}.", key))
+    }
+
+    /// Gets bytes in range [start, end] (both inclusive) of the object.
+    pub async fn get_object_range(&self, key: &str, start: u64, end: u64) -> Result<Bytes> {
+        let range = format!("bytes={}-{}", start, end);
+        let bytes = self
+            .send(
+                Method::GET,
+                key,
+                &[],
+                &[("range", range.as_str())],
+                Bytes::new(),
+            )
+            .await?
+            .bytes()
+            .await?;
+        ensure!(
+            bytes.len() as u64 == end - start + 1,
+            "Expecting {} bytes from range {} of {}, got {}.",
+            end - start + 1,
+            range,
+            key,
+            bytes.len(),
+        );
+        Ok(bytes)
+    }
+
+    /// Lists all keys under `prefix`, following continuation tokens.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(token) = &continuation_token {
+                query.push(("continuation-token", token.as_str()));
+            }
+            let body = self
+                .send(Method::GET, "", &query, &[], Bytes::new())
+                .await?
+                .text()
+                .await?;
+            let page = ListObjectsPage::parse(&body)?;
+            keys.extend(page.keys);
+            match page.next_continuation_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+        Ok(keys)
+    }
+
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let body = self
+            .send(Method::POST, key, &[("uploads", "")], &[], Bytes::new())
+            .await?
+            .text()
+            .await?;
+        parse_upload_id(&body)
+    }
+
+    /// Uploads a part and returns its ETag.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<String> {
+        let part_number = part_number.to_string();
+        let resp = self
+            .send(
+                Method::PUT,
+                key,
+                &[
+                    ("partNumber", part_number.as_str()),
+                    ("uploadId", upload_id),
+                ],
+                &[],
+                body,
+            )
+            .await?;
+        resp.headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("No ETag for part {} of {}.", part_number, key))
+    }
+
+    /// `etags` are those of part 1, 2, 3...
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etags: &[String],
+    ) -> Result<()> {
+        let mut body = "<CompleteMultipartUpload>".to_string();
+        for (i, etag) in etags.iter().enumerate() {
+            body.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                i + 1,
+                String::from_utf8_lossy(&quick_xml::escape::escape(etag.as_bytes())),
+            ));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+
+        let resp = self
+            .send(
+                Method::POST,
+                key,
+                &[("uploadId", upload_id)],
+                &[],
+                body.into(),
+            )
+            .await?
+            .text()
+            .await?;
+        // S3 can respond with an error in the body after sending out 200 OK.
+        if let Some(error) = parse_error(&resp)? {
+            bail!("Failed to complete multipart upload of {}: {}", key, error);
+        }
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.send(
+            Method::DELETE,
+            key,
+            &[("uploadId", upload_id)],
+            &[],
+            Bytes::new(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Sends a signed request, retrying with exponential backoff on network errors, throttling and
+    /// server side errors.
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Bytes,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let res = self
+                .build_request(method.clone(), key, query, headers, body.clone())?
+                .send()
+                .await;
+            let retryable_err = match res {
+                Ok(resp) if resp.status().is_success() => return Ok(resp),
+                Ok(resp)
+                    if resp.status().is_server_error()
+                        || resp.status() == StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    anyhow!("{} {}: {}", method, key, resp.status())
+                }
+                Ok(resp) => {
+                    let status = resp.status();
+                    bail!(
+                        "{} {}: {}, {}",
+                        method,
+                        key,
+                        status,
+                        resp.text().await.unwrap_or_default(),
+                    )
+                }
+                Err(e) => anyhow!("{} {}: {}", method, key, e),
+            };
+
+            if attempt >= self.max_retries {
+                return Err(retryable_err);
+            }
+            let backoff = Duration::from_millis(100 << attempt.min(10));
+            warn!(
+                "S3 request failed, retrying in {:?}. Error: {}",
+                backoff, retryable_err
+            );
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    fn build_request(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        body: Bytes,
+    ) -> Result<reqwest::RequestBuilder> {
+        let mut path = format!(
+            "{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            self.bucket
+        );
+        if !key.is_empty() {
+            path.push('/');
+            path.push_str(key);
+        }
+
+        let mut signed = SignedRequest::new(method.as_str(), "s3", &self.region, &path);
+        signed.set_hostname(Some(match self.endpoint.port() {
+            Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+            None => self.endpoint.host_str().unwrap_or_default().to_string(),
+        }));
+        for (k, v) in query {
+            signed.add_param(*k, *v);
+        }
+        for (k, v) in headers {
+            signed.add_header(*k, *v);
+        }
+        if !body.is_empty() {
+            signed.set_payload(Some(body.clone()));
+        }
+        signed.sign(&self.credentials);
+
+        let mut url = self.endpoint.clone();
+        url.set_path(&signed.canonical_uri);
+        url.set_query(if signed.canonical_query_string.is_empty() {
+            None
+        } else {
+            Some(&signed.canonical_query_string)
+        });
+
+        let mut req = self.http.request(method, url).body(body);
+        // Host and Content-Length are set by the HTTP client.
+        for (name, values) in signed
+            .headers()
+            .iter()
+            .filter(|(name, _)| name.as_str() != "host" && name.as_str() != "content-length")
+        {
+            for value in values {
+                req = req.header(name.as_str(), value.as_slice());
+            }
+        }
+        Ok(req)
+    }
+}
+
+/// A page of the ListObjectsV2 response.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct ListObjectsPage {
+    pub keys: Vec<String>,
+    /// Set only if the result is truncated.
+    pub next_continuation_token: Option<String>,
+}
+
+impl ListObjectsPage {
+    pub fn parse(xml: &str) -> Result<Self> {
+        let mut page = Self::default();
+        let mut is_truncated = false;
+        for (path, text) in xml_texts(xml)? {
+            match path.as_str() {
+                "ListBucketResult/Contents/Key" => page.keys.push(text),
+                "ListBucketResult/IsTruncated" => is_truncated = text == "true",
+                "ListBucketResult/NextContinuationToken" => {
+                    page.next_continuation_token = Some(text)
+                }
+                _ => (),
+            }
+        }
+        if !is_truncated {
+            page.next_continuation_token = None;
+        }
+        ensure!(
+            !is_truncated || page.next_continuation_token.is_some(),
+            "Truncated list result without NextContinuationToken: {}",
+            xml,
+        );
+        Ok(page)
+    }
+}
+
+/// Parses the UploadId out of an InitiateMultipartUpload response.
+pub(super) fn parse_upload_id(xml: &str) -> Result<String> {
+    xml_texts(xml)?
+        .into_iter()
+        .find(|(path, _)| path == "InitiateMultipartUploadResult/UploadId")
+        .map(|(_, text)| text)
+        .ok_or_else(|| anyhow!("No UploadId in response: {}", xml))
+}
+
+/// Returns "{Code}: {Message}" if `xml` is an S3 error response.
+pub(super) fn parse_error(xml: &str) -> Result<Option<String>> {
+    let texts = xml_texts(xml)?;
+    if !texts.iter().any(|(path, _)| path.starts_with("Error/")) {
+        return Ok(None);
+    }
+    let get = |name: &str| {
+        texts
+            .iter()
+            .find(|(path, _)| path.strip_prefix("Error/") == Some(name))
+            .map_or("", |(_, text)| text.as_str())
+    };
+    Ok(Some(format!("{}: {}", get("Code"), get("Message"))))
+}
+
+/// Returns the unescaped text of each element in `xml` in document order, along with the path to
+/// the element, e.g. "ListBucketResult/Contents/Key".
+fn xml_texts(xml: &str) -> Result<Vec<(String, String)>> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut texts = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf)? {
+            Event::Start(e) => path.push(String::from_utf8_lossy(e.name()).into_owned()),
+            Event::End(_) => {
+                path.pop();
+            }
+            Event::Text(e) => {
+                texts.push((path.join("/"), e.unescape_and_decode(&reader)?));
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+        buf.clear();
+    }
+    Ok(texts)
+}
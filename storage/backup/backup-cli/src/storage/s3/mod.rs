@@ -0,0 +1,320 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        s3::client::{Credentials, S3Client},
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::{error_notes::ErrorNotes, stream::TryStreamX},
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use diem_logger::prelude::*;
+use futures::{
+    task::{Context, Poll},
+    Future, StreamExt, TryStreamExt,
+};
+use reqwest::Url;
+use std::{pin::Pin, sync::Arc};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    task::JoinHandle,
+};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+#[derive(StructOpt)]
+pub struct S3Opt {
+    #[structopt(long = "bucket", help = "Bucket to hold backups.")]
+    pub bucket: String,
+    #[structopt(
+        long = "prefix",
+        default_value = "",
+        help = "Key prefix under which backups are stored, e.g. \"backups/mainnet\"."
+    )]
+    pub prefix: String,
+    #[structopt(
+        long = "endpoint",
+        default_value = "https://s3.amazonaws.com",
+        help = "S3 endpoint URL. Set this to use an S3-compatible store. Buckets are addressed \
+        path-style, i.e. \"{endpoint}/{bucket}/{key}\"."
+    )]
+    pub endpoint: Url,
+    #[structopt(long = "region", default_value = "us-east-1", help = "S3 region.")]
+    pub region: String,
+    #[structopt(
+        long = "part-size-mb",
+        default_value = "64",
+        help = "Files larger than this are uploaded via multipart upload in parts of this size, \
+        and read back in ranges of this size."
+    )]
+    pub part_size_mb: usize,
+    #[structopt(
+        long = "max-retries",
+        default_value = "5",
+        help = "Max number of times to retry a request on network errors, throttling and \
+        server side errors."
+    )]
+    pub max_retries: usize,
+    #[structopt(
+        long = "concurrent-range-reads",
+        default_value = "4",
+        help = "Number of ranges to download in parallel when reading a file."
+    )]
+    pub concurrent_range_reads: usize,
+}
+
+/// A storage backend speaking the S3 HTTP API, which works with AWS S3 and S3-compatible stores.
+///
+/// Credentials are read from the env vars `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+/// optionally `AWS_SESSION_TOKEN`. Files are stored as objects keyed by "{prefix}/{file_handle}",
+/// so the layout under the prefix is the same as that of `LocalFs`.
+pub struct S3 {
+    client: Arc<S3Client>,
+    prefix: String,
+    part_size: usize,
+    concurrent_range_reads: usize,
+}
+
+impl S3 {
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        Ok(Self::new(
+            S3Client::new(
+                opt.endpoint,
+                opt.bucket,
+                opt.region,
+                Credentials::from_env()?,
+                opt.max_retries,
+            ),
+            opt.prefix,
+            opt.part_size_mb * 1024 * 1024,
+            opt.concurrent_range_reads,
+        ))
+    }
+
+    fn new(
+        client: S3Client,
+        prefix: String,
+        part_size: usize,
+        concurrent_range_reads: usize,
+    ) -> Self {
+        Self {
+            client: Arc::new(client),
+            prefix: prefix.trim_matches('/').to_string(),
+            part_size,
+            concurrent_range_reads,
+        }
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        if self.prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", self.prefix, file_handle)
+        }
+    }
+
+    fn file_handle<'a>(&self, key: &'a str) -> Option<&'a str> {
+        if self.prefix.is_empty() {
+            Some(key)
+        } else {
+            key.strip_prefix(&self.prefix)?.strip_prefix('/')
+        }
+    }
+
+    fn metadata_file_handle(name: &ShellSafeName) -> FileHandle {
+        format!("{}/{}", Self::METADATA_DIR, name.as_ref())
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3 {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There're no directories in S3, files in a backup are grouped by the key prefix only.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_ref());
+        let (writer, reader) = tokio::io::duplex(64 * 1024);
+        let upload = tokio::spawn(upload(
+            self.client.clone(),
+            self.key(&file_handle),
+            reader,
+            self.part_size,
+        ));
+        Ok((file_handle, Box::new(S3Upload::new(writer, upload))))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let key = self.key(file_handle);
+        let size = self.client.head_object(&key).await.err_notes(&key)?;
+        let part_size = self.part_size as u64;
+        let ranges = (0..size)
+            .step_by(self.part_size)
+            .map(move |start| (start, std::cmp::min(start + part_size, size) - 1));
+
+        let client = self.client.clone();
+        let stream = futures::stream::iter(ranges)
+            .map(move |(start, end)| {
+                let client = client.clone();
+                let key = key.clone();
+                Ok::<_, anyhow::Error>(
+                    async move { client.get_object_range(&key, start, end).await },
+                )
+            })
+            .try_buffered_x(self.concurrent_range_reads * 2, self.concurrent_range_reads)
+            .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
+            .boxed();
+        Ok(Box::new(stream.into_async_read().compat()))
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        let key = self.key(&Self::metadata_file_handle(name));
+        let content: String = lines.iter().map(AsRef::<str>::as_ref).collect();
+        self.client
+            .put_object(&key, content.into())
+            .await
+            .err_notes(&key)
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.key(&format!("{}/", Self::METADATA_DIR));
+        self.client
+            .list_objects(&prefix)
+            .await
+            .err_notes(&prefix)?
+            .iter()
+            .map(|key| {
+                self.file_handle(key)
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow!("Unexpected key listed: {}", key))
+            })
+            .collect()
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let key = self.key(file_handle);
+        self.client.delete_object(&key).await.err_notes(&key)
+    }
+}
+
+/// Reads the file content written to the other end of `reader` and uploads it to `key`, with a
+/// single PUT if it's smaller than `part_size`, otherwise with a multipart upload.
+async fn upload(
+    client: Arc<S3Client>,
+    key: String,
+    mut reader: DuplexStream,
+    part_size: usize,
+) -> Result<()> {
+    let first_part = read_part(&mut reader, part_size).await?;
+    if first_part.len() < part_size {
+        return client.put_object(&key, first_part).await.err_notes(&key);
+    }
+
+    let upload_id = client.create_multipart_upload(&key).await.err_notes(&key)?;
+    let res: Result<Vec<String>> = async {
+        let mut etags = Vec::new();
+        let mut part = first_part;
+        while !part.is_empty() {
+            etags.push(
+                client
+                    .upload_part(&key, &upload_id, etags.len() + 1, part)
+                    .await?,
+            );
+            part = read_part(&mut reader, part_size).await?;
+        }
+        Ok(etags)
+    }
+    .await;
+
+    match res {
+        Ok(etags) => client
+            .complete_multipart_upload(&key, &upload_id, &etags)
+            .await
+            .err_notes(&key),
+        Err(e) => {
+            if let Err(abort_err) = client.abort_multipart_upload(&key, &upload_id).await {
+                warn!(
+                    "Failed to abort multipart upload {} of {}: {}",
+                    upload_id, key, abort_err
+                );
+            }
+            Err(e).err_notes(&key)
+        }
+    }
+}
+
+async fn read_part(reader: &mut DuplexStream, part_size: usize) -> Result<Bytes> {
+    let mut buf = Vec::with_capacity(part_size);
+    reader.take(part_size as u64).read_to_end(&mut buf).await?;
+    Ok(buf.into())
+}
+
+/// The writer returned by `create_for_write()`. Data written is piped to the upload task, which is
+/// joined on shutdown so that the file is guaranteed to be in the storage once shutdown succeeds.
+struct S3Upload {
+    writer: DuplexStream,
+    upload: JoinHandle<Result<()>>,
+    writer_shut_down: bool,
+}
+
+impl S3Upload {
+    fn new(writer: DuplexStream, upload: JoinHandle<Result<()>>) -> Self {
+        Self {
+            writer,
+            upload,
+            writer_shut_down: false,
+        }
+    }
+}
+
+impl AsyncWrite for S3Upload {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, tokio::io::Error>> {
+        Pin::new(&mut self.writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), tokio::io::Error>> {
+        if !self.writer_shut_down {
+            futures::ready!(Pin::new(&mut self.writer).poll_shutdown(cx))?;
+            self.writer_shut_down = true;
+        }
+
+        Pin::new(&mut self.upload).poll(cx).map(|res| match res {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(tokio::io::Error::new(tokio::io::ErrorKind::Other, e)),
+            Err(e) => Err(tokio::io::Error::new(tokio::io::ErrorKind::Other, e)),
+        })
+    }
+}
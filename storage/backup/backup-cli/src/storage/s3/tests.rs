@@ -0,0 +1,324 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    s3::client::{parse_error, parse_upload_id, ListObjectsPage},
+    test_util::{
+        arb_backups, arb_metadata_files, test_delete_impl, test_save_and_list_metadata_files_impl,
+        test_write_and_read_impl,
+    },
+};
+use diem_infallible::Mutex;
+use proptest::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use tokio::runtime::Runtime;
+use warp::{
+    http::{Method, Response, StatusCode},
+    path::FullPath,
+    Filter,
+};
+
+const BUCKET: &str = "test-bucket";
+
+/// An in-memory stand-in for an S3-compatible store, which supports just enough of the API for
+/// `S3`, and fails every few requests to exercise retries.
+#[derive(Default)]
+struct FakeS3 {
+    objects: BTreeMap<String, Vec<u8>>,
+    uploads: HashMap<String, BTreeMap<usize, Vec<u8>>>,
+    num_requests: usize,
+}
+
+impl FakeS3 {
+    const FAIL_EVERY: usize = 5;
+    const LIST_PAGE_SIZE: usize = 3;
+
+    fn handle(
+        &mut self,
+        method: Method,
+        path: &str,
+        query: HashMap<String, String>,
+        range: Option<String>,
+        body: Vec<u8>,
+    ) -> Response<Vec<u8>> {
+        self.num_requests += 1;
+        if self.num_requests % Self::FAIL_EVERY == 0 {
+            return Self::respond(StatusCode::SERVICE_UNAVAILABLE, vec![]);
+        }
+
+        let key = match path.strip_prefix(&format!("/{}", BUCKET)) {
+            Some(key) => key.trim_start_matches('/').to_string(),
+            None => return Self::respond(StatusCode::NOT_FOUND, vec![]),
+        };
+
+        match method {
+            Method::PUT => match (query.get("uploadId"), query.get("partNumber")) {
+                (Some(upload_id), Some(part_number)) => {
+                    let part_number: usize = part_number.parse().unwrap();
+                    match self.uploads.get_mut(upload_id) {
+                        Some(parts) => {
+                            parts.insert(part_number, body);
+                            Response::builder()
+                                .header("etag", format!("\"etag-{}\"", part_number))
+                                .body(vec![])
+                                .unwrap()
+                        }
+                        None => Self::respond(StatusCode::NOT_FOUND, vec![]),
+                    }
+                }
+                _ => {
+                    self.objects.insert(key, body);
+                    Self::respond(StatusCode::OK, vec![])
+                }
+            },
+            Method::POST if query.contains_key("uploads") => {
+                let upload_id = format!("upload-{}", self.num_requests);
+                self.uploads.insert(upload_id.clone(), BTreeMap::new());
+                Self::respond(
+                    StatusCode::OK,
+                    format!(
+                        "<InitiateMultipartUploadResult><UploadId>{}</UploadId>\
+                        </InitiateMultipartUploadResult>",
+                        upload_id
+                    )
+                    .into_bytes(),
+                )
+            }
+            Method::POST => {
+                let parts = self.uploads.remove(&query["uploadId"]).unwrap();
+                let body = String::from_utf8(body).unwrap();
+                for part_number in parts.keys() {
+                    assert!(body.contains(&format!(
+                        "<Part><PartNumber>{}</PartNumber><ETag>\"etag-{}\"</ETag></Part>",
+                        part_number, part_number
+                    )));
+                }
+                self.objects
+                    .insert(key, parts.into_iter().flat_map(|(_, p)| p).collect());
+                Self::respond(StatusCode::OK, b"<CompleteMultipartUploadResult/>".to_vec())
+            }
+            Method::DELETE => {
+                if let Some(upload_id) = query.get("uploadId") {
+                    self.uploads.remove(upload_id);
+                } else {
+                    self.objects.remove(&key);
+                }
+                Self::respond(StatusCode::NO_CONTENT, vec![])
+            }
+            Method::HEAD => match self.objects.get(&key) {
+                Some(object) => Response::builder()
+                    .header("content-length", object.len())
+                    .body(vec![])
+                    .unwrap(),
+                None => Self::respond(StatusCode::NOT_FOUND, vec![]),
+            },
+            Method::GET if query.contains_key("list-type") => {
+                let prefix = &query["prefix"];
+                let start_after = query.get("continuation-token").cloned().unwrap_or_default();
+                let keys: Vec<_> = self
+                    .objects
+                    .keys()
+                    .filter(|k| k.starts_with(prefix) && k.as_str() > start_after.as_str())
+                    .take(Self::LIST_PAGE_SIZE + 1)
+                    .collect();
+                let truncated = keys.len() > Self::LIST_PAGE_SIZE;
+                let keys = &keys[..std::cmp::min(keys.len(), Self::LIST_PAGE_SIZE)];
+
+                let mut xml = "<ListBucketResult>".to_string();
+                for k in keys {
+                    xml.push_str(&format!("<Contents><Key>{}</Key></Contents>", k));
+                }
+                xml.push_str(&format!("<IsTruncated>{}</IsTruncated>", truncated));
+                if truncated {
+                    xml.push_str(&format!(
+                        "<NextContinuationToken>{}</NextContinuationToken>",
+                        keys.last().unwrap()
+                    ));
+                }
+                xml.push_str("</ListBucketResult>");
+                Self::respond(StatusCode::OK, xml.into_bytes())
+            }
+            Method::GET => match (self.objects.get(&key), range) {
+                (Some(object), Some(range)) => {
+                    let (start, end) = range
+                        .strip_prefix("bytes=")
+                        .unwrap()
+                        .split_once('-')
+                        .unwrap();
+                    let (start, end): (usize, usize) =
+                        (start.parse().unwrap(), end.parse().unwrap());
+                    Self::respond(StatusCode::PARTIAL_CONTENT, object[start..=end].to_vec())
+                }
+                (Some(object), None) => Self::respond(StatusCode::OK, object.clone()),
+                (None, _) => Self::respond(StatusCode::NOT_FOUND, vec![]),
+            },
+            _ => Self::respond(StatusCode::METHOD_NOT_ALLOWED, vec![]),
+        }
+    }
+
+    fn respond(status: StatusCode, body: Vec<u8>) -> Response<Vec<u8>> {
+        Response::builder().status(status).body(body).unwrap()
+    }
+}
+
+/// Starts a `FakeS3` server and returns an `S3` store talking to it.
+fn get_store() -> Box<dyn BackupStorage> {
+    let fake = Arc::new(Mutex::new(FakeS3::default()));
+    let routes = warp::method()
+        .and(warp::path::full())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::header::optional::<String>("range"))
+        .and(warp::header::<String>("authorization"))
+        .and(warp::body::bytes())
+        .map(
+            move |method, path: FullPath, query, range, authorization: String, body: Bytes| {
+                assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=test-key-id/"));
+                fake.lock()
+                    .handle(method, path.as_str(), query, range, body.to_vec())
+            },
+        );
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let client = S3Client::new(
+        Url::parse(&format!("http://{}", addr)).unwrap(),
+        BUCKET.to_string(),
+        "us-east-1".to_string(),
+        Credentials {
+            access_key_id: "test-key-id".to_string(),
+            secret_access_key: "test-secret".to_string(),
+            session_token: None,
+        },
+        5, /* max_retries */
+    );
+    // Small parts so that multipart uploads and parallel range reads are exercised.
+    Box::new(S3::new(
+        client,
+        "backups/test".to_string(),
+        100, /* part_size */
+        2,   /* concurrent_range_reads */
+    ))
+}
+
+fn block_on<F: Future<Output = ()>>(f: F) {
+    Runtime::new().unwrap().block_on(f)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        block_on(async move { test_write_and_read_impl(get_store(), backups).await });
+    }
+
+    #[test]
+    fn test_delete(
+        backups in arb_backups()
+    ) {
+        block_on(async move { test_delete_impl(get_store(), backups).await });
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        block_on(async move { test_save_and_list_metadata_files_impl(get_store(), input).await });
+    }
+}
+
+/// The ListObjectsV2 response example in the S3 API reference, with a continuation token.
+#[test]
+fn test_parse_list_objects() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Name>bucket</Name>
+    <Prefix/>
+    <NextContinuationToken>1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=</NextContinuationToken>
+    <KeyCount>2</KeyCount>
+    <MaxKeys>2</MaxKeys>
+    <IsTruncated>true</IsTruncated>
+    <Contents>
+        <Key>happyface.jpg</Key>
+        <LastModified>2014-11-21T19:40:05.000Z</LastModified>
+        <ETag>&quot;70ee1738b6b21e2c8a43f3a5ab0eee71&quot;</ETag>
+        <Size>11</Size>
+        <StorageClass>STANDARD</StorageClass>
+    </Contents>
+    <Contents>
+        <Key>backups/a&amp;b.chunk</Key>
+        <LastModified>2014-11-21T19:40:05.000Z</LastModified>
+        <ETag>&quot;becf17f89c30367a9a44495d62ed521a-1&quot;</ETag>
+        <Size>1111</Size>
+        <StorageClass>STANDARD</StorageClass>
+    </Contents>
+</ListBucketResult>"#;
+    assert_eq!(
+        ListObjectsPage::parse(xml).unwrap(),
+        ListObjectsPage {
+            keys: vec!["happyface.jpg".to_string(), "backups/a&b.chunk".to_string()],
+            next_continuation_token: Some(
+                "1ueGcxLPRx1Tr/XYExHnhbYLgveDs2J/wm36Hy4vbOwM=".to_string()
+            ),
+        },
+    );
+
+    let last_page = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Name>bucket</Name>
+    <Prefix>backups/</Prefix>
+    <KeyCount>0</KeyCount>
+    <MaxKeys>1000</MaxKeys>
+    <IsTruncated>false</IsTruncated>
+</ListBucketResult>"#;
+    assert_eq!(
+        ListObjectsPage::parse(last_page).unwrap(),
+        ListObjectsPage::default()
+    );
+}
+
+/// The InitiateMultipartUpload response example in the S3 API reference.
+#[test]
+fn test_parse_upload_id() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<InitiateMultipartUploadResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Bucket>example-bucket</Bucket>
+  <Key>example-object</Key>
+  <UploadId>VXBsb2FkIElEIGZvciA2aWWpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA</UploadId>
+</InitiateMultipartUploadResult>"#;
+    assert_eq!(
+        parse_upload_id(xml).unwrap(),
+        "VXBsb2FkIElEIGZvciA2aWWpbmcncyBteS1tb3ZpZS5tMnRzIHVwbG9hZA",
+    );
+    assert!(parse_upload_id("<Error><Code>AccessDenied</Code></Error>").is_err());
+}
+
+/// The CompleteMultipartUpload response examples in the S3 API reference, which can be an error
+/// even with 200 OK.
+#[test]
+fn test_parse_error() {
+    let error = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>InternalError</Code>
+  <Message>We encountered an internal error. Please try again.</Message>
+  <RequestId>656c76696e6727732072657175657374</RequestId>
+  <HostId>Uuag1LuByRx9e6j5Onimru9pO4ZVKnJ2Qz7/C1NPcfTWAtRPfTaOFg==</HostId>
+</Error>"#;
+    assert_eq!(
+        parse_error(error).unwrap(),
+        Some("InternalError: We encountered an internal error. Please try again.".to_string()),
+    );
+
+    let success = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CompleteMultipartUploadResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Location>http://Example-Bucket.s3.amazonaws.com/Example-Object</Location>
+  <Bucket>Example-Bucket</Bucket>
+  <Key>Example-Object</Key>
+  <ETag>"3858f62230ac3c915f300c664312c11f-9"</ETag>
+</CompleteMultipartUploadResult>"#;
+    assert_eq!(parse_error(success).unwrap(), None);
+    assert_eq!(parse_error("").unwrap(), None);
+}
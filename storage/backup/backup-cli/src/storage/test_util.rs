@@ -47,6 +47,41 @@ pub async fn test_write_and_read_impl(
     }
 }
 
+pub async fn test_delete_impl(
+    store: Box<dyn BackupStorage>,
+    backups: HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>,
+) {
+    let mut handles = Vec::new();
+    for (backup_name, files) in &backups {
+        let backup_handle = store.create_backup(backup_name).await.unwrap();
+        for (name, content) in files {
+            let (handle, mut file) = store.create_for_write(&backup_handle, name).await.unwrap();
+            file.write_all(content).await.unwrap();
+            file.shutdown().await.unwrap();
+            handles.push(handle);
+        }
+    }
+
+    // delete every other file
+    for handle in handles.iter().step_by(2) {
+        store.delete_file(handle).await.unwrap();
+    }
+
+    for (i, handle) in handles.iter().enumerate() {
+        let res = async {
+            let mut buf = Vec::new();
+            store
+                .open_for_read(handle)
+                .await?
+                .read_to_end(&mut buf)
+                .await?;
+            Result::<_>::Ok(buf)
+        }
+        .await;
+        assert_eq!(res.is_ok(), i % 2 == 1);
+    }
+}
+
 pub fn arb_backups(
 ) -> impl Strategy<Value = HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>> {
     hash_map(
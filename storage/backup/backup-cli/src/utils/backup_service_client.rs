@@ -5,7 +5,7 @@ use crate::utils::error_notes::ErrorNotes;
 use anyhow::Result;
 use diem_crypto::HashValue;
 use diem_types::transaction::Version;
-use diemdb::backup::backup_handler::DbState;
+use diemdb::backup::backup_handler::{DbCheckpointManifest, DbState};
 use futures::TryStreamExt;
 use structopt::StructOpt;
 use tokio::io::{AsyncRead, AsyncReadExt};
@@ -57,6 +57,29 @@ impl BackupServiceClient {
             .compat())
     }
 
+    /// Asks the node to make a checkpoint of its DB named `name`, in the checkpoint directory
+    /// configured for the node.
+    pub async fn create_checkpoint(
+        &self,
+        name: &str,
+        admin_token: &str,
+    ) -> Result<DbCheckpointManifest> {
+        let url = format!("{}/checkpoint/{}", self.address, name);
+        let bytes = self
+            .client
+            .post(&url)
+            .bearer_auth(admin_token)
+            .send()
+            .await
+            .err_notes(&url)?
+            .error_for_status()
+            .err_notes(&url)?
+            .bytes()
+            .await
+            .err_notes(&url)?;
+        Ok(bcs::from_bytes(&bytes)?)
+    }
+
     pub async fn get_db_state(&self) -> Result<Option<DbState>> {
         let mut buf = Vec::new();
         self.get("db_state").await?.read_to_end(&mut buf).await?;
@@ -76,6 +99,15 @@ impl BackupServiceClient {
         self.get(&format!("state_snapshot/{}", version)).await
     }
 
+    pub async fn get_state_snapshot_diff(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Result<impl AsyncRead> {
+        self.get(&format!("state_snapshot_diff/{}/{}", base_version, version))
+            .await
+    }
+
     pub async fn get_state_root_proof(&self, version: Version) -> Result<Vec<u8>> {
         let mut buf = Vec::new();
         self.get(&format!("state_root_proof/{}", version))
@@ -10,7 +10,7 @@ pub(crate) mod stream;
 #[cfg(test)]
 pub mod test_utils;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, ensure, Result};
 use diem_config::config::RocksdbConfig;
 use diem_crypto::HashValue;
 use diem_infallible::duration_since_epoch;
@@ -149,6 +149,46 @@ impl RestoreRunMode {
             ),
         }
     }
+
+    /// Restores a chunk of the accounts changed after `base_version` into the state at `version`,
+    /// see `RestoreHandler::save_account_state_diff_chunk`. In the verify mode, the base state is
+    /// not available, so nothing is done.
+    pub fn save_account_state_diff_chunk(
+        &self,
+        account_states: Vec<(HashValue, AccountStateBlob)>,
+        base_version: Version,
+        version: Version,
+    ) -> Result<()> {
+        match self {
+            Self::Restore { restore_handler } => {
+                restore_handler.save_account_state_diff_chunk(account_states, base_version, version)
+            }
+            Self::Verify => Ok(()),
+        }
+    }
+
+    /// Checks the root hash of the state restored from a differential state snapshot at
+    /// `version`, which proves that no changed account is missing. In the verify mode, the state
+    /// is not restored, so only the proofs of the individual accounts can be checked.
+    pub fn verify_state_root_hash(
+        &self,
+        version: Version,
+        expected_root_hash: HashValue,
+    ) -> Result<()> {
+        match self {
+            Self::Restore { restore_handler } => {
+                let root_hash = restore_handler.get_state_root_hash(version)?;
+                ensure!(
+                    root_hash == expected_root_hash,
+                    "Root hash mismatch after applying state diff. Root hash: {}, expected: {}",
+                    root_hash,
+                    expected_root_hash,
+                );
+                Ok(())
+            }
+            Self::Verify => Ok(()),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -171,8 +211,10 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
                 db_dir,
                 false, /* read_only */
                 None,  /* pruner */
+                None,  /* ledger_pruner */
                 opt.rocksdb_opt.into(),
                 opt.account_count_migration,
+                false, /* account_by_resource_index */
             )?)
             .get_restore_handler();
             RestoreRunMode::Restore { restore_handler }
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use backup_service::start_backup_service;
-use diem_config::utils::get_available_port;
+use diem_config::{config::BackupCheckpointConfig, utils::get_available_port};
 use diem_proptest_helpers::ValueGenerator;
 use diem_temppath::TempPath;
 use diem_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionToCommit};
@@ -44,6 +44,10 @@ pub fn tmp_db_with_random_content() -> (
 
 pub fn start_local_backup_service(db: Arc<DiemDB>) -> (Runtime, u16) {
     let port = get_available_port();
-    let rt = start_backup_service(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port), db);
+    let rt = start_backup_service(
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+        BackupCheckpointConfig::default(),
+        db,
+    );
     (rt, port)
 }
@@ -4,23 +4,30 @@
 mod utils;
 
 use crate::handlers::utils::{
-    handle_rejection, reply_with_async_channel_writer, reply_with_bcs_bytes,
+    handle_rejection, reply_with_async_channel_writer, reply_with_bcs_bytes, reply_with_checkpoint,
     send_size_prefixed_bcs_bytes, unwrap_or_500, LATENCY_HISTOGRAM,
 };
+use diem_config::config::BackupCheckpointConfig;
 use diem_crypto::hash::HashValue;
 use diem_types::transaction::Version;
 use diemdb::backup::backup_handler::BackupHandler;
-use warp::{filters::BoxedFilter, reply::Reply, Filter};
+use std::convert::Infallible;
+use warp::{filters::BoxedFilter, http::header::AUTHORIZATION, reply::Reply, Filter};
 
 static DB_STATE: &str = "db_state";
 static STATE_RANGE_PROOF: &str = "state_range_proof";
 static STATE_SNAPSHOT: &str = "state_snapshot";
+static STATE_SNAPSHOT_DIFF: &str = "state_snapshot_diff";
 static STATE_ROOT_PROOF: &str = "state_root_proof";
 static EPOCH_ENDING_LEDGER_INFOS: &str = "epoch_ending_ledger_infos";
 static TRANSACTIONS: &str = "transactions";
 static TRANSACTION_RANGE_PROOF: &str = "transaction_range_proof";
+static CHECKPOINT: &str = "checkpoint";
 
-pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Reply,)> {
+pub(crate) fn get_routes(
+    backup_handler: BackupHandler,
+    checkpoint_config: BackupCheckpointConfig,
+) -> BoxedFilter<(impl Reply,)> {
     // GET db_state
     let bh = backup_handler.clone();
     let db_state = warp::path::end()
@@ -50,6 +57,19 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         })
         .recover(handle_rejection);
 
+    // GET state_snapshot_diff/<base_version>/<version>
+    let bh = backup_handler.clone();
+    let state_snapshot_diff = warp::path!(Version / Version)
+        .map(move |base_version, version| {
+            reply_with_async_channel_writer(&bh, STATE_SNAPSHOT_DIFF, |bh, sender| {
+                send_size_prefixed_bcs_bytes(
+                    bh.get_account_diff_iter(base_version, version),
+                    sender,
+                )
+            })
+        })
+        .recover(handle_rejection);
+
     // GET state_root_proof/<version>
     let bh = backup_handler.clone();
     let state_root_proof = warp::path!(Version)
@@ -96,7 +116,7 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .recover(handle_rejection);
 
     // GET transaction_range_proof/<first_version>/<last_version>
-    let bh = backup_handler;
+    let bh = backup_handler.clone();
     let transaction_range_proof = warp::path!(Version / Version)
         .map(move |first_version, last_version| {
             reply_with_bcs_bytes(
@@ -107,19 +127,36 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         .map(unwrap_or_500)
         .recover(handle_rejection);
 
+    // POST checkpoint/<name>, authorized by the admin token in the `Authorization` header.
+    let bh = backup_handler;
+    let checkpoint = warp::path!(String)
+        .and(warp::header::optional::<String>(AUTHORIZATION.as_str()))
+        .and_then(move |name, authorization| {
+            let bh = bh.clone();
+            let config = checkpoint_config.clone();
+            async move {
+                Ok::<_, Infallible>(
+                    reply_with_checkpoint(bh, CHECKPOINT, &config, name, authorization).await,
+                )
+            }
+        })
+        .recover(handle_rejection);
+
     // Route by endpoint name.
     let routes = warp::any()
         .and(warp::path(DB_STATE).and(db_state))
         .or(warp::path(STATE_RANGE_PROOF).and(state_range_proof))
         .or(warp::path(STATE_SNAPSHOT).and(state_snapshot))
+        .or(warp::path(STATE_SNAPSHOT_DIFF).and(state_snapshot_diff))
         .or(warp::path(STATE_ROOT_PROOF).and(state_root_proof))
         .or(warp::path(EPOCH_ENDING_LEDGER_INFOS).and(epoch_ending_ledger_infos))
         .or(warp::path(TRANSACTIONS).and(transactions))
         .or(warp::path(TRANSACTION_RANGE_PROOF).and(transaction_range_proof));
 
-    // Serve all routes for GET only.
+    // Serve all routes above for GET only, the checkpoint route for POST only.
     warp::get()
         .and(routes)
+        .or(warp::path(CHECKPOINT).and(warp::post()).and(checkpoint))
         .with(warp::log::custom(|info| {
             let endpoint = info.path().split('/').nth(1).unwrap_or("-");
             LATENCY_HISTOGRAM
@@ -3,6 +3,7 @@
 
 use anyhow::Result;
 use bytes::Bytes;
+use diem_config::config::BackupCheckpointConfig;
 use diem_logger::prelude::*;
 use diem_metrics::{register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec};
 use diemdb::backup::backup_handler::BackupHandler;
@@ -10,7 +11,12 @@ use hyper::Body;
 use once_cell::sync::Lazy;
 use serde::Serialize;
 use std::{convert::Infallible, future::Future};
-use warp::{reply::Response, Rejection, Reply};
+use subtle::ConstantTimeEq;
+use warp::{http::StatusCode, reply::Response, Rejection, Reply};
+
+/// Name of the file recording the ledger state captured by a checkpoint, in the checkpoint
+/// directory.
+const CHECKPOINT_MANIFEST_NAME: &str = "checkpoint.manifest.json";
 
 pub(super) static LATENCY_HISTOGRAM: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
@@ -113,6 +119,58 @@ where
     Ok(())
 }
 
+/// Creates a checkpoint named `name` in the configured checkpoint directory and writes its
+/// manifest next to it, if `authorization` carries the admin token. Checkpointing blocks on IO
+/// for a while, so it runs off the async runtime.
+pub(super) async fn reply_with_checkpoint(
+    backup_handler: BackupHandler,
+    endpoint: &'static str,
+    config: &BackupCheckpointConfig,
+    name: String,
+    authorization: Option<String>,
+) -> Box<dyn Reply> {
+    let (dir, admin_token) = match (&config.dir, &config.admin_token) {
+        (Some(dir), Some(admin_token)) => (dir, admin_token),
+        _ => return Box::new(StatusCode::NOT_FOUND),
+    };
+    let expected = format!("Bearer {}", admin_token);
+    let authorized = authorization.map_or(false, |authorization| {
+        authorization.as_bytes().ct_eq(expected.as_bytes()).into()
+    });
+    if !authorized {
+        return Box::new(StatusCode::FORBIDDEN);
+    }
+    if !is_valid_checkpoint_name(&name) {
+        warn!("bad checkpoint name: {:?}", name);
+        return Box::new(StatusCode::BAD_REQUEST);
+    }
+
+    let checkpoint_root = dir.join(name);
+    let result = tokio::task::spawn_blocking(move || {
+        let manifest = backup_handler.create_checkpoint(&checkpoint_root)?;
+        std::fs::write(
+            checkpoint_root.join(CHECKPOINT_MANIFEST_NAME),
+            serde_json::to_vec_pretty(&manifest)?,
+        )?;
+        reply_with_bcs_bytes(endpoint, &manifest)
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result);
+    unwrap_or_500(result)
+}
+
+/// A checkpoint name must be a single plain path component, so that the checkpoint is created
+/// right in the checkpoint directory.
+fn is_valid_checkpoint_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
 /// Return 500 on any error raised by the request handler.
 pub(super) fn unwrap_or_500(result: Result<Box<dyn Reply>>) -> Box<dyn Reply> {
     match result {
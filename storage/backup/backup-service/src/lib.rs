@@ -4,14 +4,19 @@
 mod handlers;
 
 use crate::handlers::get_routes;
+use diem_config::config::BackupCheckpointConfig;
 use diem_logger::prelude::*;
 use diemdb::DiemDB;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::runtime::{Builder, Runtime};
 
-pub fn start_backup_service(address: SocketAddr, db: Arc<DiemDB>) -> Runtime {
+pub fn start_backup_service(
+    address: SocketAddr,
+    checkpoint_config: BackupCheckpointConfig,
+    db: Arc<DiemDB>,
+) -> Runtime {
     let backup_handler = db.get_backup_handler();
-    let routes = get_routes(backup_handler);
+    let routes = get_routes(backup_handler, checkpoint_config);
 
     let runtime = Builder::new_multi_thread()
         .thread_name("backup")
@@ -39,9 +44,14 @@ mod tests {
     use diem_config::utils::get_available_port;
     use diem_crypto::hash::HashValue;
     use diem_temppath::TempPath;
-    use reqwest::blocking::get;
+    use reqwest::{
+        blocking::{get, Client},
+        header::AUTHORIZATION,
+    };
     use std::net::{IpAddr, Ipv4Addr};
 
+    const ADMIN_TOKEN: &str = "admin-token";
+
     /// 404 - endpoint not found
     /// 400 - params not provided or failed parsing
     /// 500 - endpoint handler raised error
@@ -51,8 +61,17 @@ mod tests {
     fn routing_and_error_codes() {
         let tmpdir = TempPath::new();
         let db = Arc::new(DiemDB::new_for_test(&tmpdir));
+        let checkpoint_dir = TempPath::new();
+        let checkpoint_config = BackupCheckpointConfig {
+            dir: Some(checkpoint_dir.path().to_path_buf()),
+            admin_token: Some(ADMIN_TOKEN.to_string()),
+        };
         let port = get_available_port();
-        let _rt = start_backup_service(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port), db);
+        let _rt = start_backup_service(
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+            checkpoint_config,
+            db.clone(),
+        );
 
         // Endpoint doesn't exist.
         let resp = get(&format!("http://127.0.0.1:{}/", port)).unwrap();
@@ -86,6 +105,44 @@ mod tests {
         assert_eq!(resp.status(), 500);
         let resp = get(&format!("http://127.0.0.1:{}/state_root_proof/0", port,)).unwrap();
         assert_eq!(resp.status(), 500);
+        let post_checkpoint = |port, name, authorization: Option<&str>| {
+            let mut req =
+                Client::new().post(&format!("http://127.0.0.1:{}/checkpoint/{}", port, name));
+            if let Some(authorization) = authorization {
+                req = req.header(AUTHORIZATION, authorization);
+            }
+            req.send().unwrap().status()
+        };
+        let authorization = format!("Bearer {}", ADMIN_TOKEN);
+        assert_eq!(post_checkpoint(port, "cp", Some(&authorization)), 500);
+
+        // Checkpoint requests not authorized.
+        assert_eq!(post_checkpoint(port, "cp", None), 403);
+        assert_eq!(post_checkpoint(port, "cp", Some(ADMIN_TOKEN)), 403);
+        assert_eq!(post_checkpoint(port, "cp", Some("Bearer invalid")), 403);
+
+        // Checkpoint names escaping the checkpoint directory.
+        assert_eq!(post_checkpoint(port, "..", Some(&authorization)), 400);
+        assert_eq!(
+            post_checkpoint(port, "%2E%2E%2Fcp", Some(&authorization)),
+            400
+        );
+
+        // Wrong method.
+        let resp = get(&format!("http://127.0.0.1:{}/checkpoint/cp", port)).unwrap();
+        assert_eq!(resp.status(), 405);
+
+        // Checkpoints not configured.
+        let port_without_checkpoints = get_available_port();
+        let _rt_without_checkpoints = start_backup_service(
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port_without_checkpoints),
+            BackupCheckpointConfig::default(),
+            db,
+        );
+        assert_eq!(
+            post_checkpoint(port_without_checkpoints, "cp", Some(&authorization)),
+            404
+        );
 
         // an endpoint handled by `reply_with_async_channel_writer' always returns 200,
         // connection terminates prematurely when the channel writer errors.
@@ -10,24 +10,34 @@ use crate::{
     },
     state_store::StateStore,
     transaction_store::TransactionStore,
+    DiemDB, DIEMDB_NAME,
 };
 use anyhow::{anyhow, ensure, Result};
+use diem_config::config::RocksdbConfig;
 use diem_crypto::hash::HashValue;
 use diem_jellyfish_merkle::iterator::JellyfishMerkleIterator;
+use diem_logger::prelude::*;
 use diem_types::{
+    account_address::HashAccountAddress,
     account_state_blob::AccountStateBlob,
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
-    proof::{SparseMerkleRangeProof, TransactionAccumulatorRangeProof, TransactionInfoWithProof},
+    proof::{
+        SparseMerkleProof, SparseMerkleRangeProof, TransactionAccumulatorRangeProof,
+        TransactionInfoWithProof,
+    },
     transaction::{Transaction, TransactionInfo, Version},
+    waypoint::Waypoint,
 };
 use itertools::zip_eq;
+use schemadb::DB;
 use serde::{Deserialize, Serialize};
-use std::{fmt, sync::Arc};
+use std::{collections::BTreeMap, fmt, path::Path, sync::Arc, time::Instant};
 
 /// `BackupHandler` provides functionalities for DiemDB data backup.
 #[derive(Clone)]
 pub struct BackupHandler {
+    db: Arc<DB>,
     ledger_store: Arc<LedgerStore>,
     transaction_store: Arc<TransactionStore>,
     state_store: Arc<StateStore>,
@@ -36,12 +46,14 @@ pub struct BackupHandler {
 
 impl BackupHandler {
     pub(crate) fn new(
+        db: Arc<DB>,
         ledger_store: Arc<LedgerStore>,
         transaction_store: Arc<TransactionStore>,
         state_store: Arc<StateStore>,
         event_store: Arc<EventStore>,
     ) -> Self {
         Self {
+            db,
             ledger_store,
             transaction_store,
             state_store,
@@ -118,6 +130,61 @@ impl BackupHandler {
         Ok(Box::new(iterator))
     }
 
+    /// Gets an iterator which yields, in the order of keys, all accounts changed after
+    /// `base_version` till `version`, with their states at `version` and the proofs of those
+    /// against the state root at `version`.
+    ///
+    /// The changed accounts are found from the write sets in between, so these must not have
+    /// been pruned. The stale node index can't be used instead, because it only records replaced
+    /// nodes, leaving out the accounts created in between.
+    pub fn get_account_diff_iter(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Result<
+        Box<
+            dyn Iterator<
+                    Item = Result<(
+                        HashValue,
+                        AccountStateBlob,
+                        SparseMerkleProof<AccountStateBlob>,
+                    )>,
+                > + Send
+                + Sync,
+        >,
+    > {
+        ensure!(
+            base_version < version,
+            "Base version {} must be older than version {}.",
+            base_version,
+            version,
+        );
+
+        let mut changed_accounts = BTreeMap::new();
+        for ver in base_version + 1..=version {
+            for (access_path, _write_op) in self.transaction_store.get_write_set(ver)?.iter() {
+                changed_accounts.insert(access_path.address.hash(), access_path.address);
+            }
+        }
+
+        let state_store = Arc::clone(&self.state_store);
+        let iterator =
+            changed_accounts
+                .into_iter()
+                .enumerate()
+                .map(move |(idx, (key, address))| {
+                    BACKUP_STATE_SNAPSHOT_VERSION.set(version as i64);
+                    BACKUP_STATE_SNAPSHOT_LEAF_IDX.set(idx as i64);
+                    let (blob, proof) =
+                        state_store.get_account_state_with_proof_by_version(address, version)?;
+                    let blob = blob.ok_or_else(|| {
+                        anyhow!("Account {} doesn't exist at version {}.", address, version)
+                    })?;
+                    Ok((key, blob, proof))
+                });
+        Ok(Box::new(iterator))
+    }
+
     /// Gets the proof that proves a range of accounts.
     pub fn get_account_state_range_proof(
         &self,
@@ -181,6 +248,54 @@ impl BackupHandler {
                 li
             }))
     }
+
+    /// Creates a consistent physical checkpoint of the running DB under `checkpoint_root`, which
+    /// must not exist yet. The checkpoint is laid out so that `checkpoint_root` can be used as
+    /// the DB directory of a node directly.
+    ///
+    /// Returns the state of the ledger captured by the checkpoint. Since commits keep coming in
+    /// while the checkpoint is being taken, it's read from the checkpoint itself.
+    pub fn create_checkpoint(&self, checkpoint_root: &Path) -> Result<DbCheckpointManifest> {
+        ensure!(
+            !checkpoint_root.exists(),
+            "Checkpoint target {:?} already exists.",
+            checkpoint_root,
+        );
+        let start = Instant::now();
+        std::fs::create_dir_all(checkpoint_root)?;
+        self.db
+            .create_checkpoint(checkpoint_root.join(DIEMDB_NAME))?;
+
+        let checkpoint = DiemDB::open(
+            checkpoint_root,
+            true, /* readonly */
+            None, /* pruner */
+            None, /* ledger_pruner */
+            RocksdbConfig::default(),
+            true,  /* account_count_migration, ignored anyway */
+            false, /* account_by_resource_index */
+        )?;
+        let ledger_info_with_sigs = checkpoint.ledger_store.get_latest_ledger_info()?;
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        let txn_info = checkpoint
+            .ledger_store
+            .get_transaction_info(ledger_info.version())?;
+        let manifest = DbCheckpointManifest {
+            epoch: ledger_info.epoch(),
+            version: ledger_info.version(),
+            transaction_accumulator_root_hash: ledger_info.transaction_accumulator_hash(),
+            state_root_hash: txn_info.state_change_hash(),
+            waypoint: Waypoint::new_any(ledger_info),
+        };
+
+        info!(
+            path = checkpoint_root,
+            version = manifest.version,
+            time_ms = %start.elapsed().as_millis(),
+            "Made DiemDB checkpoint for backup."
+        );
+        Ok(manifest)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -190,6 +305,19 @@ pub struct DbState {
     pub synced_version: Version,
 }
 
+/// Describes the ledger state captured by a DB checkpoint made by
+/// [`BackupHandler::create_checkpoint`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DbCheckpointManifest {
+    pub epoch: u64,
+    /// Version of the latest ledger info in the checkpoint.
+    pub version: Version,
+    pub transaction_accumulator_root_hash: HashValue,
+    pub state_root_hash: HashValue,
+    /// Waypoint a node can be started with when using the checkpoint as its DB.
+    pub waypoint: Waypoint,
+}
+
 impl fmt::Display for DbState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
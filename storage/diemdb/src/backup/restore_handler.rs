@@ -64,6 +64,26 @@ impl RestoreHandler {
         )
     }
 
+    /// Restores part of the state tree at `version` by applying `account_states`, a chunk of the
+    /// accounts changed after `base_version`. The first chunk is applied on top of the tree at
+    /// `base_version`, which must already exist in the DB, and each following one on top of the
+    /// chunks before, by passing `version` as `base_version`.
+    pub fn save_account_state_diff_chunk(
+        &self,
+        account_states: Vec<(HashValue, AccountStateBlob)>,
+        base_version: Version,
+        version: Version,
+    ) -> Result<()> {
+        let mut cs = ChangeSet::new();
+        self.state_store
+            .put_account_state_diff(account_states, base_version, version, &mut cs)?;
+        self.db.write_schemas(cs.batch)
+    }
+
+    pub fn get_state_root_hash(&self, version: Version) -> Result<HashValue> {
+        self.state_store.get_root_hash(version)
+    }
+
     pub fn save_ledger_infos(&self, ledger_infos: &[LedgerInfoWithSignatures]) -> Result<()> {
         ensure!(!ledger_infos.is_empty(), "No LedgerInfos to save.");
 
@@ -5,7 +5,7 @@ use crate::{test_helper::arb_blocks_to_commit, DiemDB};
 use anyhow::Result;
 use diem_temppath::TempPath;
 use proptest::prelude::*;
-use storage_interface::DbWriter;
+use storage_interface::{DbReader, DbWriter};
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
@@ -41,4 +41,44 @@ proptest! {
             .unwrap();
         prop_assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_create_checkpoint(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in input.iter() {
+            db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+
+        let checkpoint_root = TempPath::new();
+        let manifest = db
+            .get_backup_handler()
+            .create_checkpoint(checkpoint_root.path())
+            .unwrap();
+        // Refuses to overwrite.
+        prop_assert!(db
+            .get_backup_handler()
+            .create_checkpoint(checkpoint_root.path())
+            .is_err());
+
+        let latest_li = input.last().unwrap().1.ledger_info().clone();
+        prop_assert_eq!(manifest.epoch, latest_li.epoch());
+        prop_assert_eq!(manifest.version, latest_li.version());
+        prop_assert_eq!(
+            manifest.transaction_accumulator_root_hash,
+            latest_li.transaction_accumulator_hash()
+        );
+        prop_assert_eq!(manifest.state_root_hash, db.get_latest_state_root().unwrap().1);
+
+        // The checkpoint opens as a normal DB.
+        let checkpoint = DiemDB::new_for_test(checkpoint_root.path());
+        prop_assert_eq!(
+            checkpoint.get_latest_ledger_info().unwrap().ledger_info(),
+            &latest_li
+        );
+    }
 }
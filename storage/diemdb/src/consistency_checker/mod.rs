@@ -0,0 +1,327 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `ConsistencyChecker`, which walks versions in the DB and recomputes hashes
+//! of what's stored, to find out the first version at which the DB is inconsistent with itself,
+//! e.g. after a disk fault.
+//!
+//! For each version, it checks that
+//!   1. the `TransactionInfo` hashes to the leaf in the transaction accumulator,
+//!   2. the transaction hashes to the `transaction_hash` in the `TransactionInfo`,
+//!   3. the events form an accumulator with the `event_root_hash` in the `TransactionInfo`,
+//!   4. the root of the Jellyfish Merkle tree hashes to the `state_change_hash` in the
+//!      `TransactionInfo`.
+//!
+//! Data removed by the pruners is not treated as inconsistent.
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    event_store::EventStore,
+    ledger_store::LedgerStore,
+    metrics::{
+        DIEM_STORAGE_CONSISTENCY_CHECKER_INCONSISTENCIES,
+        DIEM_STORAGE_CONSISTENCY_CHECKER_INCONSISTENT_VERSION,
+        DIEM_STORAGE_CONSISTENCY_CHECKER_NEXT_VERSION,
+    },
+    pruner::{DBPruner, LedgerStorePruner, StateStorePruner},
+    schema::{
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema, transaction::TransactionSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
+        transaction_info::TransactionInfoSchema,
+    },
+    DiemDB,
+};
+use anyhow::Result;
+use diem_config::config::ConsistencyCheckerConfig;
+use diem_crypto::{
+    hash::{CryptoHash, EventAccumulatorHasher},
+    HashValue,
+};
+use diem_jellyfish_merkle::node_type::NodeKey;
+use diem_logger::prelude::*;
+use diem_types::{
+    proof::{accumulator::InMemoryAccumulator, position::Position},
+    transaction::Version,
+};
+use schemadb::DB;
+use serde::Serialize;
+use std::{
+    sync::{
+        mpsc::{channel, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InconsistencyKind {
+    /// The accumulator leaf vs. the hash of the `TransactionInfo`.
+    TransactionInfoHash,
+    /// `TransactionInfo::transaction_hash` vs. the hash of the transaction.
+    TransactionHash,
+    /// `TransactionInfo::event_root_hash` vs. the root hash of the events.
+    EventRootHash,
+    /// `TransactionInfo::state_change_hash` vs. the hash of the Jellyfish Merkle tree root.
+    StateRootHash,
+}
+
+impl InconsistencyKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::TransactionInfoHash => "transaction_info_hash",
+            Self::TransactionHash => "transaction_hash",
+            Self::EventRootHash => "event_root_hash",
+            Self::StateRootHash => "state_root_hash",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Inconsistency {
+    pub version: Version,
+    pub kind: InconsistencyKind,
+    /// The hash recorded, `None` if it's missing.
+    pub expected: Option<HashValue>,
+    /// The hash of what's stored, `None` if it's missing.
+    pub actual: Option<HashValue>,
+}
+
+impl Inconsistency {
+    fn check(
+        version: Version,
+        kind: InconsistencyKind,
+        expected: Option<HashValue>,
+        actual: Option<HashValue>,
+    ) -> Option<Self> {
+        if expected == actual {
+            None
+        } else {
+            Some(Self {
+                version,
+                kind,
+                expected,
+                actual,
+            })
+        }
+    }
+
+    fn is_missing_data(&self) -> bool {
+        self.expected.is_none() || self.actual.is_none()
+    }
+}
+
+pub struct ConsistencyChecker {
+    db: Arc<DB>,
+    event_store: Arc<EventStore>,
+}
+
+impl ConsistencyChecker {
+    pub fn new(diem_db: &DiemDB) -> Self {
+        Self {
+            db: Arc::clone(&diem_db.db),
+            event_store: Arc::clone(&diem_db.event_store),
+        }
+    }
+
+    /// Versions before this have their transactions, transaction infos and events pruned.
+    pub fn least_readable_ledger_version(&self) -> Result<Version> {
+        LedgerStorePruner::new(Arc::clone(&self.db)).initialize_least_readable_version()
+    }
+
+    /// Versions before this have their state pruned.
+    pub fn least_readable_state_version(&self) -> Result<Version> {
+        StateStorePruner::new(Arc::clone(&self.db)).initialize_least_readable_version()
+    }
+
+    /// Checks versions in `[start_version, end_version)` in order, returning the first
+    /// inconsistency found.
+    pub fn check_range(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<Option<Inconsistency>> {
+        let mut least_readable_state_version = self.least_readable_state_version()?;
+        for version in start_version..end_version {
+            let check_state = version >= least_readable_state_version;
+            match self.check_version(version, check_state)? {
+                // Missing data might have just been removed by a pruner.
+                Some(inconsistency) if inconsistency.is_missing_data() => {
+                    least_readable_state_version = self.least_readable_state_version()?;
+                    let pruned = match inconsistency.kind {
+                        InconsistencyKind::StateRootHash => version < least_readable_state_version,
+                        _ => version < self.least_readable_ledger_version()?,
+                    };
+                    if !pruned {
+                        return Ok(Some(Self::report(inconsistency)));
+                    }
+                }
+                Some(inconsistency) => return Ok(Some(Self::report(inconsistency))),
+                None => (),
+            }
+            DIEM_STORAGE_CONSISTENCY_CHECKER_NEXT_VERSION.set(version as i64 + 1);
+        }
+        Ok(None)
+    }
+
+    fn report(inconsistency: Inconsistency) -> Inconsistency {
+        DIEM_STORAGE_CONSISTENCY_CHECKER_INCONSISTENCIES
+            .with_label_values(&[inconsistency.kind.as_str()])
+            .inc();
+        DIEM_STORAGE_CONSISTENCY_CHECKER_INCONSISTENT_VERSION.set(inconsistency.version as i64);
+        inconsistency
+    }
+
+    fn check_version(&self, version: Version, check_state: bool) -> Result<Option<Inconsistency>> {
+        let txn_info = self.db.get::<TransactionInfoSchema>(&version)?;
+        let inconsistency = Inconsistency::check(
+            version,
+            InconsistencyKind::TransactionInfoHash,
+            self.db
+                .get::<TransactionAccumulatorSchema>(&Position::from_leaf_index(version))?,
+            txn_info.as_ref().map(CryptoHash::hash),
+        );
+        let txn_info = match (inconsistency, txn_info) {
+            (Some(inconsistency), _) => return Ok(Some(inconsistency)),
+            (None, Some(txn_info)) => txn_info,
+            // Nothing at this version at all.
+            (None, None) => {
+                return Ok(Some(Inconsistency {
+                    version,
+                    kind: InconsistencyKind::TransactionInfoHash,
+                    expected: None,
+                    actual: None,
+                }))
+            }
+        };
+
+        let inconsistency = Inconsistency::check(
+            version,
+            InconsistencyKind::TransactionHash,
+            Some(txn_info.transaction_hash()),
+            self.db
+                .get::<TransactionSchema>(&version)?
+                .as_ref()
+                .map(CryptoHash::hash),
+        );
+        if inconsistency.is_some() {
+            return Ok(inconsistency);
+        }
+
+        let event_hashes: Vec<_> = self
+            .event_store
+            .get_events_by_version(version)?
+            .iter()
+            .map(CryptoHash::hash)
+            .collect();
+        let inconsistency = Inconsistency::check(
+            version,
+            InconsistencyKind::EventRootHash,
+            Some(txn_info.event_root_hash()),
+            Some(
+                InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes)
+                    .root_hash(),
+            ),
+        );
+        if inconsistency.is_some() || !check_state {
+            return Ok(inconsistency);
+        }
+
+        Ok(Inconsistency::check(
+            version,
+            InconsistencyKind::StateRootHash,
+            Some(txn_info.state_change_hash()),
+            self.db
+                .get::<JellyfishMerkleNodeSchema>(&NodeKey::new_empty_path(version))?
+                .map(|node| node.hash()),
+        ))
+    }
+}
+
+/// Runs a `ConsistencyChecker` in a background thread as part of a `DiemDB` instance, checking
+/// versions as they are committed, in batches separated by a configured interval so that it
+/// doesn't compete with the normal operation of the node for IO.
+///
+/// It stops checking once an inconsistency is found, which is logged and reported by the metrics.
+#[derive(Debug)]
+pub(crate) struct BackgroundConsistencyChecker {
+    quit_sender: Mutex<Sender<()>>,
+    worker_thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundConsistencyChecker {
+    pub fn new(diem_db: &DiemDB, config: ConsistencyCheckerConfig) -> Self {
+        let (quit_sender, quit_receiver) = channel();
+        let mut worker = Worker {
+            checker: ConsistencyChecker::new(diem_db),
+            ledger_store: Arc::clone(&diem_db.ledger_store),
+            next_version: 0,
+            batch_size: config.batch_size,
+        };
+
+        let worker_thread = std::thread::Builder::new()
+            .name("diemdb_consistency_checker".to_string())
+            .spawn(move || loop {
+                match quit_receiver.recv_timeout(Duration::from_millis(config.interval_ms)) {
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+                match worker.check_next_batch() {
+                    Ok(None) => (),
+                    Ok(Some(inconsistency)) => {
+                        error!(
+                            inconsistency = ?inconsistency,
+                            "DB inconsistency found, consistency checker stopped."
+                        );
+                        break;
+                    }
+                    Err(e) => warn!(error = ?e, "Consistency check failed, will retry."),
+                }
+            })
+            .expect("Creating consistency checker thread should succeed.");
+
+        Self {
+            quit_sender: Mutex::new(quit_sender),
+            worker_thread: Some(worker_thread),
+        }
+    }
+}
+
+struct Worker {
+    checker: ConsistencyChecker,
+    ledger_store: Arc<LedgerStore>,
+    next_version: Version,
+    batch_size: usize,
+}
+
+impl Worker {
+    fn check_next_batch(&mut self) -> Result<Option<Inconsistency>> {
+        let end_version = match self.ledger_store.get_latest_transaction_info_option()? {
+            Some((latest_version, _)) => latest_version + 1,
+            None => return Ok(None),
+        };
+        let start_version = std::cmp::max(
+            self.next_version,
+            self.checker.least_readable_ledger_version()?,
+        );
+        let end_version = std::cmp::min(end_version, start_version + self.batch_size as u64);
+        let inconsistency = self.checker.check_range(start_version, end_version)?;
+        self.next_version = end_version;
+        Ok(inconsistency)
+    }
+}
+
+impl Drop for BackgroundConsistencyChecker {
+    fn drop(&mut self) {
+        // The worker thread might have quit already, in which case the send fails.
+        let _ = self.quit_sender.lock().unwrap().send(());
+        self.worker_thread
+            .take()
+            .expect("Consistency checker thread must exist.")
+            .join()
+            .expect("Consistency checker thread should join peacefully.");
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{schema::event::EventSchema, test_helper::arb_blocks_to_commit};
+use diem_temppath::TempPath;
+use diem_types::{contract_event::ContractEvent, event::EventKey};
+use move_core_types::language_storage::TypeTag;
+use proptest::prelude::*;
+use schemadb::SchemaBatch;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_consistency_checker(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in &input {
+            db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+        let num_txns = cur_ver;
+        let latest_version = num_txns - 1;
+        let checker = ConsistencyChecker::new(&db);
+
+        prop_assert_eq!(checker.check_range(0, num_txns).unwrap(), None);
+
+        // Nothing beyond the latest version.
+        let inconsistency = checker.check_range(0, num_txns + 1).unwrap().unwrap();
+        prop_assert_eq!(inconsistency.version, num_txns);
+        prop_assert_eq!(inconsistency.kind, InconsistencyKind::TransactionInfoHash);
+
+        // An extra event.
+        let event_key = (latest_version, u64::max_value());
+        db.db
+            .put::<EventSchema>(
+                &event_key,
+                &ContractEvent::new(EventKey::random(), 0, TypeTag::Bool, vec![]),
+            )
+            .unwrap();
+        let inconsistency = checker.check_range(0, num_txns).unwrap().unwrap();
+        prop_assert_eq!(inconsistency.version, latest_version);
+        prop_assert_eq!(inconsistency.kind, InconsistencyKind::EventRootHash);
+        let mut batch = SchemaBatch::new();
+        batch.delete::<EventSchema>(&event_key).unwrap();
+        db.db.write_schemas(batch).unwrap();
+
+        // A missing state root.
+        let mut batch = SchemaBatch::new();
+        batch
+            .delete::<JellyfishMerkleNodeSchema>(&NodeKey::new_empty_path(latest_version))
+            .unwrap();
+        db.db.write_schemas(batch).unwrap();
+        let inconsistency = checker.check_range(0, num_txns).unwrap().unwrap();
+        prop_assert_eq!(inconsistency.version, latest_version);
+        prop_assert_eq!(inconsistency.kind, InconsistencyKind::StateRootHash);
+        prop_assert_eq!(inconsistency.actual, None);
+    }
+}
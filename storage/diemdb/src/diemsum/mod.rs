@@ -24,8 +24,10 @@ impl Diemsum {
             db_root_path,
             true, /* read only */
             None, /* no prune_window */
+            None, /* ledger_pruner */
             RocksdbConfig::default(),
-            true, /* account_count_migration, ignored anyway */
+            true,  /* account_count_migration, ignored anyway */
+            false, /* account_by_resource_index */
         )?;
         Ok(Diemsum { db })
     }
@@ -194,6 +194,15 @@ impl EventStore {
             if path != *event_key || ver > ledger_version {
                 break;
             }
+            if result.is_empty() && seq > cur_seq {
+                // Sequence numbers are contiguous, so the older events of the stream being
+                // missing means they were pruned, together with their index entries.
+                return Err(DiemDbError::Pruned(format!(
+                    "Event {} of seq num {}",
+                    event_key, cur_seq
+                ))
+                .into());
+            }
             ensure!(
                 seq == cur_seq,
                 "DB corrupt: Sequence number not continuous, expected: {}, actual: {}.",
@@ -0,0 +1,313 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides [`Inspector`], which gives debugging tools schema level access to a
+//! DiemDB, bypassing the higher level stores so that data can still be looked at when the DB is
+//! inconsistent.
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    schema::{
+        account_by_resource::AccountByResourceSchema, db_metadata::DbMetadataSchema,
+        epoch_by_version::EpochByVersionSchema, event::EventSchema,
+        event_accumulator::EventAccumulatorSchema, event_by_key::EventByKeySchema,
+        event_by_version::EventByVersionSchema, jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        ledger_counters::LedgerCountersSchema, ledger_info::LedgerInfoSchema,
+        stale_node_index::StaleNodeIndexSchema, transaction::TransactionSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
+        transaction_by_account::TransactionByAccountSchema,
+        transaction_by_hash::TransactionByHashSchema, transaction_info::TransactionInfoSchema,
+        write_set::WriteSetSchema,
+    },
+    DiemDB,
+};
+use anyhow::{bail, Result};
+use diem_config::config::RocksdbConfig;
+use diem_crypto::HashValue;
+use diem_jellyfish_merkle::node_type::{Node, NodeKey};
+use diem_types::{
+    account_state_blob::AccountStateBlob, contract_event::ContractEvent, event::EventKey,
+    transaction::Version,
+};
+use schemadb::{
+    schema::{KeyCodec, Schema, SeekKeyCodec},
+    ColumnFamilyName, ReadOptions,
+};
+use std::path::Path;
+
+/// RocksDB properties reported by [`Inspector::column_family_properties`].
+pub const ROCKSDB_PROPERTIES: &[&str] = &[
+    "rocksdb.estimate-num-keys",
+    "rocksdb.estimate-live-data-size",
+    "rocksdb.live-sst-files-size",
+    "rocksdb.total-sst-files-size",
+    "rocksdb.size-all-mem-tables",
+];
+
+pub struct Inspector {
+    db: DiemDB,
+}
+
+impl Inspector {
+    /// Opens the DB read-only, which requires that no other process has it open for writing.
+    pub fn open_readonly<P: AsRef<Path> + Clone>(db_root_path: P) -> Result<Self> {
+        let db = DiemDB::open(
+            db_root_path,
+            true, /* read only */
+            None, /* no prune_window */
+            None, /* ledger_pruner */
+            RocksdbConfig::default(),
+            true,  /* account_count_migration, ignored anyway */
+            false, /* account_by_resource_index */
+        )?;
+        Ok(Self { db })
+    }
+
+    /// Opens the DB as a RocksDB secondary instance, which works while a node has the DB open.
+    /// `secondary_path` is where the secondary instance keeps its own info logs.
+    pub fn open_as_secondary<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        secondary_path: P,
+    ) -> Result<Self> {
+        let db = DiemDB::open_as_secondary(db_root_path, secondary_path, RocksdbConfig::default())?;
+        Ok(Self { db })
+    }
+
+    pub fn db(&self) -> &DiemDB {
+        &self.db
+    }
+
+    /// Names of all column families, each of which holds one schema.
+    pub fn column_families() -> Vec<ColumnFamilyName> {
+        DiemDB::column_families()
+    }
+
+    /// Returns the value of each of [`ROCKSDB_PROPERTIES`] of each column family.
+    pub fn column_family_properties(&self) -> Result<Vec<(ColumnFamilyName, Vec<u64>)>> {
+        Self::column_families()
+            .into_iter()
+            .map(|cf_name| {
+                let values = ROCKSDB_PROPERTIES
+                    .iter()
+                    .map(|property| self.db.db.get_property(cf_name, property))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((cf_name, values))
+            })
+            .collect()
+    }
+
+    /// Returns events of the stream identified by `event_key`, starting from sequence number
+    /// `start_seq_num`.
+    ///
+    /// Unlike `DbReader::get_events`, this doesn't require the sequence numbers to be continuous,
+    /// and an event missing in the `EventSchema` results in `None` instead of an error.
+    pub fn get_events_by_key(
+        &self,
+        event_key: &EventKey,
+        start_seq_num: u64,
+        limit: usize,
+    ) -> Result<Vec<EventByKeyEntry>> {
+        let mut iter = self
+            .db
+            .db
+            .iter::<EventByKeySchema>(ReadOptions::default())?;
+        iter.seek(&(*event_key, start_seq_num))?;
+
+        let mut result = Vec::new();
+        for res in iter.take(limit) {
+            let ((key, seq_num), (version, index)) = res?;
+            if key != *event_key {
+                break;
+            }
+            result.push(EventByKeyEntry {
+                seq_num,
+                version,
+                index,
+                event: self.db.db.get::<EventSchema>(&(version, index))?,
+            });
+        }
+        Ok(result)
+    }
+
+    /// Walks the Jellyfish Merkle tree of the state at `version` depth first, calling `visit` on
+    /// each node no deeper than `max_depth` nibbles.
+    ///
+    /// The whole tree is walked regardless of `max_depth`, so that the leaves are actually counted
+    /// instead of trusting the leaf counts stored in the internal nodes.
+    pub fn walk_state_tree<F>(
+        &self,
+        version: Version,
+        max_depth: usize,
+        mut visit: F,
+    ) -> Result<StateTreeSummary>
+    where
+        F: FnMut(&NodeKey, &Node<AccountStateBlob>),
+    {
+        let mut summary = StateTreeSummary::default();
+        let mut stack = vec![NodeKey::new_empty_path(version)];
+        while let Some(node_key) = stack.pop() {
+            let node = match self.db.db.get::<JellyfishMerkleNodeSchema>(&node_key)? {
+                Some(node) => node,
+                None => {
+                    summary.missing_nodes.push(node_key);
+                    continue;
+                }
+            };
+            if node_key.nibble_path().num_nibbles() <= max_depth {
+                visit(&node_key, &node);
+            }
+            match &node {
+                Node::Internal(internal_node) => {
+                    summary.num_internal_nodes += 1;
+                    // Pushed in reverse so that children are visited in the order of the nibbles.
+                    let children = internal_node.children_sorted().collect::<Vec<_>>();
+                    for (nibble, child) in children.into_iter().rev() {
+                        stack.push(node_key.gen_child_node_key(child.version, *nibble));
+                    }
+                }
+                Node::Leaf(_) => summary.num_leaves += 1,
+                Node::Null => (),
+            }
+            if node_key.nibble_path().num_nibbles() == 0 {
+                summary.root_hash = Some(node.hash());
+                summary.root_leaf_count = node.leaf_count();
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Iterates the schema in column family `cf_name`, returning at most `limit` entries with
+    /// encoded keys in `[start_key, end_key)`.
+    ///
+    /// `start_key` doesn't need to be a complete key, e.g. the encoded `EventKey` alone can be used
+    /// to seek to the first entry of the event stream in `EventByKeySchema`.
+    pub fn scan_schema(
+        &self,
+        cf_name: &str,
+        start_key: &[u8],
+        end_key: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<SchemaEntry>> {
+        macro_rules! scan_by_cf_name {
+            ($($schema: ty),* $(,)?) => {
+                $(
+                    if cf_name == <$schema as Schema>::COLUMN_FAMILY_NAME {
+                        return self.scan::<$schema>(start_key, end_key, limit);
+                    }
+                )*
+            };
+        }
+        scan_by_cf_name!(
+            AccountByResourceSchema,
+            DbMetadataSchema,
+            EpochByVersionSchema,
+            EventAccumulatorSchema,
+            EventByKeySchema,
+            EventByVersionSchema,
+            EventSchema,
+            JellyfishMerkleNodeSchema,
+            LedgerCountersSchema,
+            LedgerInfoSchema,
+            StaleNodeIndexSchema,
+            TransactionAccumulatorSchema,
+            TransactionByAccountSchema,
+            TransactionByHashSchema,
+            TransactionInfoSchema,
+            TransactionSchema,
+            WriteSetSchema,
+        );
+        bail!("Unknown column family: {}", cf_name)
+    }
+
+    fn scan<S>(
+        &self,
+        start_key: &[u8],
+        end_key: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<SchemaEntry>>
+    where
+        S: Schema,
+        for<'a> RawSeekKey<'a>: SeekKeyCodec<S>,
+    {
+        let mut iter = self.db.db.iter::<S>(ReadOptions::default())?;
+        iter.seek(&RawSeekKey(start_key))?;
+
+        let mut result = Vec::new();
+        for res in iter.take(limit) {
+            let (key, value) = res?;
+            let encoded_key = <S::Key as KeyCodec<S>>::encode_key(&key)?;
+            if end_key.map_or(false, |end_key| encoded_key.as_slice() >= end_key) {
+                break;
+            }
+            result.push(SchemaEntry {
+                encoded_key,
+                key: format!("{:?}", key),
+                value: format!("{:?}", value),
+            });
+        }
+        Ok(result)
+    }
+}
+
+/// An entry read from a schema, with the key and value in their `Debug` format.
+pub struct SchemaEntry {
+    pub encoded_key: Vec<u8>,
+    pub key: String,
+    pub value: String,
+}
+
+pub struct EventByKeyEntry {
+    pub seq_num: u64,
+    pub version: Version,
+    /// Index among events emitted by the same transaction.
+    pub index: u64,
+    pub event: Option<ContractEvent>,
+}
+
+#[derive(Debug, Default)]
+pub struct StateTreeSummary {
+    pub root_hash: Option<HashValue>,
+    /// The leaf count stored in the root node, if it's an internal node with leaf count.
+    pub root_leaf_count: Option<usize>,
+    pub num_internal_nodes: usize,
+    pub num_leaves: usize,
+    /// Nodes referred to by their parents but not found in the DB.
+    pub missing_nodes: Vec<NodeKey>,
+}
+
+/// Encoded bytes to seek to, which can be a prefix of a key.
+struct RawSeekKey<'a>(&'a [u8]);
+
+macro_rules! impl_raw_seek_key {
+    ($($schema: ty),* $(,)?) => {
+        $(
+            impl SeekKeyCodec<$schema> for RawSeekKey<'_> {
+                fn encode_seek_key(&self) -> Result<Vec<u8>> {
+                    Ok(self.0.to_vec())
+                }
+            }
+        )*
+    };
+}
+
+impl_raw_seek_key!(
+    AccountByResourceSchema,
+    DbMetadataSchema,
+    EpochByVersionSchema,
+    EventAccumulatorSchema,
+    EventByKeySchema,
+    EventByVersionSchema,
+    EventSchema,
+    JellyfishMerkleNodeSchema,
+    LedgerCountersSchema,
+    LedgerInfoSchema,
+    StaleNodeIndexSchema,
+    TransactionAccumulatorSchema,
+    TransactionByAccountSchema,
+    TransactionByHashSchema,
+    TransactionInfoSchema,
+    TransactionSchema,
+    WriteSetSchema,
+);
@@ -0,0 +1,95 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{schema::TRANSACTION_CF_NAME, test_helper::arb_blocks_to_commit};
+use diem_temppath::TempPath;
+use proptest::prelude::*;
+use std::collections::HashSet;
+use storage_interface::DbReader;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_inspect(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in &input {
+            db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as u64;
+        }
+        let latest_version = cur_ver - 1;
+        let txns_to_commit = input
+            .iter()
+            .flat_map(|(txns_to_commit, _)| txns_to_commit)
+            .collect::<Vec<_>>();
+        let inspector = Inspector { db };
+
+        // Scan all, and a range of the transactions.
+        let entries = inspector
+            .scan_schema(TRANSACTION_CF_NAME, &[], None, usize::max_value())
+            .unwrap();
+        prop_assert_eq!(entries.len(), txns_to_commit.len());
+        let entries = inspector
+            .scan_schema(
+                TRANSACTION_CF_NAME,
+                &1u64.to_be_bytes(),
+                Some(&3u64.to_be_bytes()),
+                usize::max_value(),
+            )
+            .unwrap();
+        prop_assert_eq!(
+            entries.len(),
+            std::cmp::min(txns_to_commit.len().saturating_sub(1), 2)
+        );
+        prop_assert!(inspector.scan_schema("no_such_cf", &[], None, 1).is_err());
+
+        // Events by key.
+        for (version, txn) in txns_to_commit.iter().enumerate() {
+            for (index, event) in txn.events().iter().enumerate() {
+                let entries = inspector
+                    .get_events_by_key(event.key(), event.sequence_number(), 1)
+                    .unwrap();
+                prop_assert_eq!(entries.len(), 1);
+                prop_assert_eq!(entries[0].version, version as Version);
+                prop_assert_eq!(entries[0].index, index as u64);
+                prop_assert_eq!(entries[0].event.as_ref(), Some(event));
+            }
+        }
+
+        // Every account touched so far is a leaf in the latest state tree.
+        let num_accounts = txns_to_commit
+            .iter()
+            .flat_map(|txn| txn.account_states().keys())
+            .collect::<HashSet<_>>()
+            .len();
+        let mut num_visited = 0;
+        let summary = inspector
+            .walk_state_tree(latest_version, 0, |node_key, _node| {
+                assert_eq!(node_key.nibble_path().num_nibbles(), 0);
+                num_visited += 1;
+            })
+            .unwrap();
+        prop_assert_eq!(num_visited, 1);
+        prop_assert_eq!(summary.num_leaves, num_accounts);
+        prop_assert!(summary.missing_nodes.is_empty());
+        prop_assert_eq!(
+            summary.root_hash,
+            Some(
+                inspector
+                    .db
+                    .get_latest_state_root()
+                    .unwrap()
+                    .1
+            )
+        );
+
+        prop_assert_eq!(
+            inspector.column_family_properties().unwrap().len(),
+            Inspector::column_families().len()
+        );
+    }
+}
@@ -11,11 +11,14 @@
 
 #[cfg(any(feature = "diemsum"))]
 pub mod diemsum;
+#[cfg(any(test, feature = "inspector"))]
+pub mod inspector;
 // Used in this and other crates for testing.
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod test_helper;
 
 pub mod backup;
+pub mod consistency_checker;
 pub mod errors;
 pub mod metrics;
 pub mod schema;
@@ -39,6 +42,7 @@ pub use diemdb_test::test_save_blocks_impl;
 use crate::{
     backup::{backup_handler::BackupHandler, restore_handler::RestoreHandler},
     change_set::{ChangeSet, SealedChangeSet},
+    consistency_checker::BackgroundConsistencyChecker,
     errors::DiemDbError,
     event_store::EventStore,
     ledger_counters::LedgerCounters,
@@ -46,23 +50,27 @@ use crate::{
     metrics::{
         DIEM_STORAGE_API_LATENCY_SECONDS, DIEM_STORAGE_COMMITTED_TXNS,
         DIEM_STORAGE_LATEST_ACCOUNT_COUNT, DIEM_STORAGE_LATEST_TXN_VERSION,
-        DIEM_STORAGE_LEDGER_VERSION, DIEM_STORAGE_NEXT_BLOCK_EPOCH,
-        DIEM_STORAGE_OTHER_TIMERS_SECONDS, DIEM_STORAGE_ROCKSDB_PROPERTIES,
+        DIEM_STORAGE_LEDGER_PRUNE_WINDOW, DIEM_STORAGE_LEDGER_VERSION,
+        DIEM_STORAGE_NEXT_BLOCK_EPOCH, DIEM_STORAGE_OTHER_TIMERS_SECONDS,
+        DIEM_STORAGE_PRUNE_WINDOW, DIEM_STORAGE_ROCKSDB_PROPERTIES,
     },
-    pruner::Pruner,
+    pruner::{LedgerStorePruner, Pruner, StateStorePruner},
     schema::*,
     state_store::StateStore,
     system_store::SystemStore,
     transaction_store::TransactionStore,
 };
 use anyhow::{ensure, format_err, Result};
-use diem_config::config::RocksdbConfig;
+use diem_config::config::{ConsistencyCheckerConfig, RocksdbConfig};
 use diem_crypto::hash::{CryptoHash, HashValue, SPARSE_MERKLE_PLACEHOLDER_HASH};
 use diem_logger::prelude::*;
 use diem_types::{
     account_address::AccountAddress,
     account_state::AccountState,
-    account_state_blob::{AccountStateBlob, AccountStateWithProof, AccountStatesChunkWithProof},
+    account_state_blob::{
+        AccountStateBlob, AccountStateWithProof, AccountStatesChunkWithProof,
+        AccountStatesRangeWithProof,
+    },
     contract_event::{ContractEvent, EventByVersionWithProof, EventWithProof},
     epoch_change::EpochChangeProof,
     event::EventKey,
@@ -96,9 +104,13 @@ use std::{
 };
 use storage_interface::{
     DbReader, DbWriter, MoveDbReader, Order, StartupInfo, StateSnapshotReceiver, TreeState,
+    MAX_REQUEST_LIMIT,
 };
 
-const MAX_LIMIT: u64 = 5000;
+/// Name of the directory under the DB root path holding the RocksDB instance.
+pub(crate) const DIEMDB_NAME: &str = "diemdb";
+
+const MAX_LIMIT: u64 = MAX_REQUEST_LIMIT;
 
 // TODO: Either implement an iteration API to allow a very old client to loop through a long history
 // or guarantee that there is always a recent enough waypoint and client knows to boot from there.
@@ -220,12 +232,17 @@ pub struct DiemDB {
     rocksdb_property_reporter: RocksdbPropertyReporter,
     pruner: Option<Pruner>,
     prune_window: Option<u64>,
+    ledger_pruner: Option<Pruner>,
+    account_by_resource_index: bool,
+    consistency_checker: Mutex<Option<BackgroundConsistencyChecker>>,
 }
 
 impl DiemDB {
     fn column_families() -> Vec<ColumnFamilyName> {
         vec![
             /* LedgerInfo CF = */ DEFAULT_CF_NAME,
+            ACCOUNT_BY_RESOURCE_CF_NAME,
+            DB_METADATA_CF_NAME,
             EPOCH_BY_VERSION_CF_NAME,
             EVENT_ACCUMULATOR_CF_NAME,
             EVENT_BY_KEY_CF_NAME,
@@ -243,10 +260,23 @@ impl DiemDB {
         ]
     }
 
-    fn new_with_db(db: DB, prune_window: Option<u64>, account_count_migration: bool) -> Self {
+    fn new_with_db(
+        db: DB,
+        prune_window: Option<u64>,
+        ledger_prune_window: Option<u64>,
+        account_count_migration: bool,
+        account_by_resource_index: bool,
+    ) -> Result<Self> {
         let db = Arc::new(db);
 
-        DiemDB {
+        if let Some(n) = prune_window {
+            DIEM_STORAGE_PRUNE_WINDOW.set(n as i64);
+        }
+        if let Some(n) = ledger_prune_window {
+            DIEM_STORAGE_LEDGER_PRUNE_WINDOW.set(n as i64);
+        }
+
+        Ok(DiemDB {
             db: Arc::clone(&db),
             event_store: Arc::new(EventStore::new(Arc::clone(&db))),
             ledger_store: Arc::new(LedgerStore::new(Arc::clone(&db))),
@@ -254,24 +284,38 @@ impl DiemDB {
             transaction_store: Arc::new(TransactionStore::new(Arc::clone(&db))),
             system_store: SystemStore::new(Arc::clone(&db)),
             rocksdb_property_reporter: RocksdbPropertyReporter::new(Arc::clone(&db)),
-            pruner: prune_window.map(|n| Pruner::new(Arc::clone(&db), n)),
+            pruner: prune_window
+                .map(|n| Pruner::new(StateStorePruner::new(Arc::clone(&db)), n))
+                .transpose()?,
             prune_window,
-        }
+            // Reads the least readable version recorded by pruning before the DB was reopened.
+            ledger_pruner: ledger_prune_window
+                .map(|n| Pruner::new(LedgerStorePruner::new(Arc::clone(&db)), n))
+                .transpose()?,
+            account_by_resource_index,
+            consistency_checker: Mutex::new(None),
+        })
     }
 
     pub fn open<P: AsRef<Path> + Clone>(
         db_root_path: P,
         readonly: bool,
         prune_window: Option<u64>,
+        ledger_prune_window: Option<u64>,
         rocksdb_config: RocksdbConfig,
         account_count_migration: bool, // ignored when opening readonly
+        account_by_resource_index: bool,
     ) -> Result<Self> {
         ensure!(
             prune_window.is_none() || !readonly,
             "Do not set prune_window when opening readonly.",
         );
+        ensure!(
+            ledger_prune_window.is_none() || !readonly,
+            "Do not set ledger_prune_window when opening readonly.",
+        );
 
-        let path = db_root_path.as_ref().join("diemdb");
+        let path = db_root_path.as_ref().join(DIEMDB_NAME);
         let instant = Instant::now();
 
         let mut rocksdb_opts = gen_rocksdb_options(&rocksdb_config);
@@ -300,7 +344,13 @@ impl DiemDB {
             )
         };
 
-        let ret = Self::new_with_db(db, prune_window, account_count_migration);
+        let ret = Self::new_with_db(
+            db,
+            prune_window,
+            ledger_prune_window,
+            account_count_migration,
+            account_by_resource_index,
+        )?;
         info!(
             path = path,
             time_ms = %instant.elapsed().as_millis(),
@@ -314,13 +364,13 @@ impl DiemDB {
         secondary_path: P,
         mut rocksdb_config: RocksdbConfig,
     ) -> Result<Self> {
-        let primary_path = db_root_path.as_ref().join("diemdb");
+        let primary_path = db_root_path.as_ref().join(DIEMDB_NAME);
         let secondary_path = secondary_path.as_ref().to_path_buf();
         // Secondary needs `max_open_files = -1` per https://github.com/facebook/rocksdb/wiki/Secondary-instance
         rocksdb_config.max_open_files = -1;
         let rocksdb_opts = gen_rocksdb_options(&rocksdb_config);
 
-        Ok(Self::new_with_db(
+        Self::new_with_db(
             DB::open_as_secondary(
                 primary_path,
                 secondary_path,
@@ -328,24 +378,37 @@ impl DiemDB {
                 Self::column_families(),
                 &rocksdb_opts,
             )?,
-            None, // prune_window
-            true, // account_count_migration
-        ))
+            None,  // prune_window
+            None,  // ledger_prune_window
+            true,  // account_count_migration
+            false, // account_by_resource_index
+        )
     }
 
-    /// This opens db in non-readonly mode, without the pruner.
+    /// This opens db in non-readonly mode, without the pruner but with all optional indices.
     #[cfg(any(test, feature = "fuzzing"))]
     pub fn new_for_test<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
         Self::open(
             db_root_path,
             false, /* readonly */
             None,  /* pruner */
+            None,  /* ledger_pruner */
             RocksdbConfig::default(),
             true, /* account_count_migration */
+            true, /* account_by_resource_index */
         )
         .expect("Unable to open DiemDB")
     }
 
+    /// Starts checking the consistency of the ledger and state in a background thread, see
+    /// `ConsistencyChecker`. Does nothing if it's already started.
+    pub fn start_consistency_checker(&self, config: ConsistencyCheckerConfig) {
+        let mut consistency_checker = self.consistency_checker.lock().unwrap();
+        if consistency_checker.is_none() {
+            *consistency_checker = Some(BackgroundConsistencyChecker::new(self, config));
+        }
+    }
+
     /// This force the db to update rocksdb properties immediately.
     pub fn update_rocksdb_properties(&self) -> Result<()> {
         update_rocksdb_properties(&self.db)
@@ -413,12 +476,30 @@ impl DiemDB {
         Ok((lis, more))
     }
 
+    /// Returns a `DiemDbError::Pruned` error if ledger data (transactions, events, etc.) at
+    /// `version` has been removed by the ledger pruner.
+    fn error_if_ledger_pruned(&self, data_type: &str, version: Version) -> Result<()> {
+        if let Some(ledger_pruner) = self.ledger_pruner.as_ref() {
+            let least_readable_version = ledger_pruner.least_readable_version();
+            if version < least_readable_version {
+                return Err(DiemDbError::Pruned(format!(
+                    "{} at version {} (least readable version is {})",
+                    data_type, version, least_readable_version,
+                ))
+                .into());
+            }
+        }
+        Ok(())
+    }
+
     fn get_transaction_with_proof(
         &self,
         version: Version,
         ledger_version: Version,
         fetch_events: bool,
     ) -> Result<TransactionWithProof> {
+        self.error_if_ledger_pruned("Transaction", version)?;
+
         let proof = self
             .ledger_store
             .get_transaction_info_with_proof(version, ledger_version)?;
@@ -444,6 +525,7 @@ impl DiemDB {
     /// Gets an instance of `BackupHandler` for data backup purpose.
     pub fn get_backup_handler(&self) -> BackupHandler {
         BackupHandler::new(
+            Arc::clone(&self.db),
             Arc::clone(&self.ledger_store),
             Arc::clone(&self.transaction_store),
             Arc::clone(&self.state_store),
@@ -513,6 +595,7 @@ impl DiemDB {
         let mut events_with_proof = event_indices
             .into_iter()
             .map(|(seq, ver, idx)| {
+                self.error_if_ledger_pruned("Event", ver)?;
                 let (event, event_proof) = self
                     .event_store
                     .get_event_with_proof_by_version_and_index(ver, idx)?;
@@ -616,6 +699,13 @@ impl DiemDB {
                         txn_to_commit.transaction(),
                         &mut cs,
                     )?;
+                    if self.account_by_resource_index {
+                        self.state_store.put_account_by_resource_index(
+                            ver,
+                            txn_to_commit.write_set(),
+                            &mut cs,
+                        )?;
+                    }
                     self.transaction_store
                         .put_write_set(ver, txn_to_commit.write_set(), &mut cs)
                 },
@@ -654,6 +744,9 @@ impl DiemDB {
         if let Some(pruner) = self.pruner.as_ref() {
             pruner.wake(latest_version)
         }
+        if let Some(ledger_pruner) = self.ledger_pruner.as_ref() {
+            ledger_pruner.wake(latest_version)
+        }
     }
 }
 
@@ -736,7 +829,8 @@ impl DbReader for DiemDB {
         })
     }
 
-    /// This API is best-effort in that it CANNOT provide absense proof.
+    /// This API is best-effort in that it CANNOT provide absense proof. Transactions removed by
+    /// the ledger pruner are not found either, since their hashes are pruned with them.
     fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -776,6 +870,7 @@ impl DbReader for DiemDB {
             if start_version > ledger_version || limit == 0 {
                 return Ok(TransactionListWithProof::new_empty());
             }
+            self.error_if_ledger_pruned("Transaction", start_version)?;
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
@@ -837,6 +932,7 @@ impl DbReader for DiemDB {
             if start_version > ledger_version || limit == 0 {
                 return Ok(TransactionOutputListWithProof::new_empty());
             }
+            self.error_if_ledger_pruned("Transaction", start_version)?;
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
@@ -1000,6 +1096,7 @@ impl DbReader for DiemDB {
                 );
             }
 
+            self.error_if_ledger_pruned("TransactionInfo", version)?;
             let txn_info_with_proof = self
                 .ledger_store
                 .get_transaction_info_with_proof(version, ledger_version)?;
@@ -1067,6 +1164,7 @@ impl DbReader for DiemDB {
 
     fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         gauged_api("get_block_timestamp", || {
+            self.error_if_ledger_pruned("Transaction", version)?;
             let ts = match self.transaction_store.get_block_metadata(version)? {
                 Some((_v, block_meta)) => block_meta.into_inner().1,
                 // genesis timestamp is 0
@@ -1214,9 +1312,40 @@ impl DbReader for DiemDB {
         })
     }
 
+    fn get_account_range_with_proof(
+        &self,
+        version: Version,
+        first_key: HashValue,
+        last_key: HashValue,
+        limit: usize,
+    ) -> Result<AccountStatesRangeWithProof> {
+        gauged_api("get_account_range_with_proof", || {
+            self.state_store
+                .get_account_range_with_proof(version, first_key, last_key, limit)
+        })
+    }
+
     fn get_state_prune_window(&self) -> Option<usize> {
         self.prune_window.map(|u| u as usize)
     }
+
+    fn get_accounts_by_resource(
+        &self,
+        struct_tag: &StructTag,
+        start: Option<AccountAddress>,
+        limit: u64,
+    ) -> Result<Vec<AccountAddress>> {
+        gauged_api("get_accounts_by_resource", || {
+            ensure!(
+                self.account_by_resource_index,
+                "Account by resource index is not enabled.",
+            );
+            error_if_too_many_requested(limit, MAX_LIMIT)?;
+
+            self.state_store
+                .get_accounts_by_resource(struct_tag, start, limit as usize)
+        })
+    }
 }
 
 impl ModuleResolver for DiemDB {
@@ -1251,7 +1380,11 @@ impl ResourceResolver for DiemDB {
     }
 }
 
-impl MoveDbReader for DiemDB {}
+impl MoveDbReader for DiemDB {
+    fn as_db_reader(self: Arc<Self>) -> Arc<dyn DbReader> {
+        self
+    }
+}
 
 impl DbWriter for DiemDB {
     /// `first_version` is the version of the first transaction in `txns_to_commit`.
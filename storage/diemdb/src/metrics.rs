@@ -2,8 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use diem_metrics::{
-    register_histogram_vec, register_int_counter, register_int_gauge, register_int_gauge_vec,
-    HistogramVec, IntCounter, IntGauge, IntGaugeVec,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -71,6 +71,22 @@ pub static DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION: Lazy<IntGauge> = La
     .unwrap()
 });
 
+pub static DIEM_STORAGE_LEDGER_PRUNE_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_storage_ledger_prune_window",
+        "Diem storage ledger prune window"
+    )
+    .unwrap()
+});
+
+pub static DIEM_STORAGE_PRUNER_LEAST_READABLE_LEDGER_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_storage_pruner_least_readable_ledger_version",
+        "Diem storage pruner least readable ledger version"
+    )
+    .unwrap()
+});
+
 pub static DIEM_STORAGE_API_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         // metric name
@@ -108,6 +124,33 @@ pub static DIEM_STORAGE_ROCKSDB_PROPERTIES: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static DIEM_STORAGE_CONSISTENCY_CHECKER_NEXT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "diem_storage_consistency_checker_next_version",
+        "Versions before this have been checked by the consistency checker."
+    )
+    .unwrap()
+});
+
+pub static DIEM_STORAGE_CONSISTENCY_CHECKER_INCONSISTENT_VERSION: Lazy<IntGauge> =
+    Lazy::new(|| {
+        register_int_gauge!(
+            "diem_storage_consistency_checker_inconsistent_version",
+            "The first inconsistent version found by the consistency checker."
+        )
+        .unwrap()
+    });
+
+pub static DIEM_STORAGE_CONSISTENCY_CHECKER_INCONSISTENCIES: Lazy<IntCounterVec> =
+    Lazy::new(|| {
+        register_int_counter_vec!(
+            "diem_storage_consistency_checker_inconsistencies",
+            "Number of inconsistencies found by the consistency checker.",
+            &["kind"]
+        )
+        .unwrap()
+    });
+
 // Backup progress gauges:
 
 pub(crate) static BACKUP_EPOCH_ENDING_EPOCH: Lazy<IntGauge> = Lazy::new(|| {
@@ -0,0 +1,162 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `LedgerStorePruner` which prunes ledger history older than the least
+//! readable version: transactions, transaction infos, write sets, events and the event
+//! accumulators, together with the indices pointing to them.
+//!
+//! The transaction accumulator is kept intact, so that proofs can still be generated against
+//! ledger infos of any version.
+//!
+//! The least readable version is recorded in `DbMetadataSchema` in the same batch as the
+//! deletions, so that reads can tell pruned data from data that never existed, including right
+//! after a restart.
+
+use crate::{
+    metrics::{
+        DIEM_STORAGE_OTHER_TIMERS_SECONDS, DIEM_STORAGE_PRUNER_LEAST_READABLE_LEDGER_VERSION,
+    },
+    pruner::DBPruner,
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        event::EventSchema,
+        event_accumulator::EventAccumulatorSchema,
+        event_by_key::EventByKeySchema,
+        event_by_version::EventByVersionSchema,
+        transaction::TransactionSchema,
+        transaction_by_account::TransactionByAccountSchema,
+        transaction_by_hash::TransactionByHashSchema,
+        transaction_info::TransactionInfoSchema,
+        write_set::WriteSetSchema,
+    },
+};
+use anyhow::Result;
+use diem_crypto::hash::CryptoHash;
+use diem_types::transaction::{Transaction, Version};
+use schemadb::{ReadOptions, SchemaBatch, DB};
+use std::sync::Arc;
+
+pub(crate) struct LedgerStorePruner {
+    db: Arc<DB>,
+}
+
+impl LedgerStorePruner {
+    pub fn new(db: Arc<DB>) -> Self {
+        Self { db }
+    }
+}
+
+impl DBPruner for LedgerStorePruner {
+    fn name(&self) -> &'static str {
+        "ledger_store"
+    }
+
+    fn initialize_least_readable_version(&self) -> Result<Version> {
+        if let Some(version) = self.recorded_least_readable_version()? {
+            return Ok(version);
+        }
+        // Nothing pruned yet, but the DB may start at a later version, e.g. when it's restored
+        // from a backup.
+        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter.next().transpose()?.map_or(0, |(version, _)| version))
+    }
+
+    fn recorded_least_readable_version(&self) -> Result<Option<Version>> {
+        Ok(self
+            .db
+            .get::<DbMetadataSchema>(&DbMetadataKey::LedgerPrunerProgress)?
+            .map(DbMetadataValue::expect_version))
+    }
+
+    fn prune(
+        &mut self,
+        least_readable_version: Version,
+        target_least_readable_version: Version,
+        max_versions: usize,
+    ) -> Result<Version> {
+        prune_ledger(
+            &self.db,
+            least_readable_version,
+            target_least_readable_version,
+            max_versions,
+        )
+    }
+
+    fn record_progress(&self, least_readable_version: Version) {
+        DIEM_STORAGE_PRUNER_LEAST_READABLE_LEDGER_VERSION.set(least_readable_version as i64);
+    }
+}
+
+/// Deletes ledger data of versions in `[least_readable_version, target_least_readable_version)`,
+/// at most `max_versions` of them, in a single batch. Returns the new least readable version.
+pub fn prune_ledger(
+    db: &DB,
+    least_readable_version: Version,
+    target_least_readable_version: Version,
+    max_versions: usize,
+) -> Result<Version> {
+    let new_least_readable_version = std::cmp::min(
+        target_least_readable_version,
+        least_readable_version.saturating_add(max_versions as u64),
+    );
+    if new_least_readable_version <= least_readable_version {
+        return Ok(least_readable_version);
+    }
+
+    let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
+        .with_label_values(&["ledger_pruner_commit"])
+        .start_timer();
+    let mut batch = SchemaBatch::new();
+
+    // Transactions, together with their infos, write sets and indices.
+    let mut iter = db.iter::<TransactionSchema>(ReadOptions::default())?;
+    iter.seek(&least_readable_version)?;
+    for res in iter {
+        let (version, txn) = res?;
+        if version >= new_least_readable_version {
+            break;
+        }
+        if let Transaction::UserTransaction(signed_txn) = &txn {
+            batch.delete::<TransactionByAccountSchema>(&(
+                signed_txn.sender(),
+                signed_txn.sequence_number(),
+            ))?;
+        }
+        batch.delete::<TransactionByHashSchema>(&txn.hash())?;
+        batch.delete::<TransactionSchema>(&version)?;
+        batch.delete::<TransactionInfoSchema>(&version)?;
+        batch.delete::<WriteSetSchema>(&version)?;
+    }
+
+    // Events and their indices.
+    let mut iter = db.iter::<EventSchema>(ReadOptions::default())?;
+    iter.seek(&least_readable_version)?;
+    for res in iter {
+        let ((version, index), event) = res?;
+        if version >= new_least_readable_version {
+            break;
+        }
+        batch.delete::<EventByKeySchema>(&(*event.key(), event.sequence_number()))?;
+        batch.delete::<EventByVersionSchema>(&(*event.key(), version, event.sequence_number()))?;
+        batch.delete::<EventSchema>(&(version, index))?;
+    }
+
+    // Per transaction event accumulators.
+    let mut iter = db.iter::<EventAccumulatorSchema>(ReadOptions::default())?;
+    iter.seek(&least_readable_version)?;
+    for res in iter {
+        let (key, _hash) = res?;
+        if key.0 >= new_least_readable_version {
+            break;
+        }
+        batch.delete::<EventAccumulatorSchema>(&key)?;
+    }
+
+    batch.put::<DbMetadataSchema>(
+        &DbMetadataKey::LedgerPrunerProgress,
+        &DbMetadataValue::Version(new_least_readable_version),
+    )?;
+    db.write_schemas(batch)?;
+    Ok(new_least_readable_version)
+}
@@ -3,33 +3,65 @@
 
 //! This module provides `Pruner` which manages a thread pruning old data in the background and is
 //! meant to be triggered by other threads as they commit new data to the DB.
+//!
+//! What is pruned is defined by a `DBPruner` implementation: `StateStorePruner` removes stale
+//! Jellyfish Merkle nodes while `LedgerStorePruner` removes transactions, transaction infos,
+//! write sets, events and the indices pointing to them that fall out of the ledger prune window.
+
+mod ledger_store;
+mod state_store;
+
+pub(crate) use ledger_store::LedgerStorePruner;
+#[cfg(test)]
+pub use state_store::prune_state;
+pub(crate) use state_store::StateStorePruner;
 
-use crate::{
-    metrics::{
-        DIEM_STORAGE_OTHER_TIMERS_SECONDS, DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION,
-        DIEM_STORAGE_PRUNE_WINDOW,
-    },
-    schema::{
-        jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
-    },
-};
 use anyhow::Result;
 use diem_infallible::Mutex;
-use diem_jellyfish_merkle::StaleNodeIndex;
 use diem_logger::prelude::*;
 use diem_types::transaction::Version;
-use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
+#[cfg(test)]
+use std::time::Instant;
 use std::{
-    iter::Peekable,
     sync::{
         atomic::{AtomicU64, Ordering},
         mpsc::{channel, Receiver, Sender},
         Arc,
     },
     thread::{sleep, JoinHandle},
-    time::{Duration, Instant},
+    time::Duration,
 };
 
+/// Defines a kind of data that can be pruned by version in the background by a `Pruner`.
+pub(crate) trait DBPruner: Send + 'static {
+    /// A short name identifying the pruner, used in the worker thread name and logs.
+    fn name(&self) -> &'static str;
+
+    /// Finds out the least readable version by looking at what's in the DB. Called once upon
+    /// worker thread start.
+    fn initialize_least_readable_version(&self) -> Result<Version>;
+
+    /// The least readable version recorded in the DB by previous pruning, for pruners that record
+    /// it. Unlike `initialize_least_readable_version()` it must be cheap: it's read when the
+    /// `Pruner` is created, so that data pruned before a restart is known to be pruned before the
+    /// worker thread is initialized.
+    fn recorded_least_readable_version(&self) -> Result<Option<Version>> {
+        Ok(None)
+    }
+
+    /// Prunes data of at most `max_versions` versions, starting from `least_readable_version` and
+    /// not going beyond `target_least_readable_version`. Returns the new least readable version.
+    fn prune(
+        &mut self,
+        least_readable_version: Version,
+        target_least_readable_version: Version,
+        max_versions: usize,
+    ) -> Result<Version>;
+
+    /// Reports pruning progress, i.e. updates metrics.
+    fn record_progress(&self, least_readable_version: Version);
+}
+
 /// The `Pruner` is meant to be part of a `DiemDB` instance and runs in the background to prune old
 /// data.
 ///
@@ -45,32 +77,32 @@ pub(crate) struct Pruner {
     worker_thread: Option<JoinHandle<()>>,
     /// The sender side of the channel talking to the worker thread.
     command_sender: Mutex<Sender<Command>>,
-    /// (For tests) A way for the worker thread to inform the `Pruner` the pruning progress. If it
-    /// sets this atomic value to `V`, all versions before `V` can no longer be accessed.
-    #[allow(dead_code)]
+    /// A way for the worker thread to inform the `Pruner` the pruning progress. If it sets this
+    /// atomic value to `V`, all versions before `V` can no longer be accessed.
     worker_progress: Arc<AtomicU64>,
 }
 
 impl Pruner {
     /// Creates a worker thread that waits on a channel for pruning commands.
-    pub fn new(db: Arc<DB>, historical_versions_to_keep: u64) -> Self {
+    pub fn new<P: DBPruner>(db_pruner: P, historical_versions_to_keep: u64) -> Result<Self> {
         let (command_sender, command_receiver) = channel();
 
-        let worker_progress = Arc::new(AtomicU64::new(0));
+        let worker_progress = Arc::new(AtomicU64::new(
+            db_pruner.recorded_least_readable_version()?.unwrap_or(0),
+        ));
         let worker_progress_clone = Arc::clone(&worker_progress);
 
-        DIEM_STORAGE_PRUNE_WINDOW.set(historical_versions_to_keep as i64);
         let worker_thread = std::thread::Builder::new()
-            .name("diemdb_pruner".into())
-            .spawn(move || Worker::new(db, command_receiver, worker_progress_clone).work())
+            .name(format!("diemdb_{}_pruner", db_pruner.name()))
+            .spawn(move || Worker::new(db_pruner, command_receiver, worker_progress_clone).work())
             .expect("Creating pruner thread should succeed.");
 
-        Self {
+        Ok(Self {
             historical_versions_to_keep,
             worker_thread: Some(worker_thread),
             command_sender: Mutex::new(command_sender),
             worker_progress,
-        }
+        })
     }
 
     /// Sends pruning command to the worker thread when necessary.
@@ -86,6 +118,13 @@ impl Pruner {
         }
     }
 
+    /// Returns the version before which all data has been pruned. Note that the worker thread
+    /// might be in the middle of pruning more, so data at or after this version is not guaranteed
+    /// to be available.
+    pub fn least_readable_version(&self) -> Version {
+        self.worker_progress.load(Ordering::Relaxed)
+    }
+
     /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
     /// an internal counter.
     #[cfg(test)]
@@ -129,37 +168,33 @@ enum Command {
     Prune { least_readable_version: Version },
 }
 
-struct Worker {
-    db: Arc<DB>,
+struct Worker<P> {
+    db_pruner: P,
     command_receiver: Receiver<Command>,
     target_least_readable_version: Version,
     /// Keeps a record of the pruning progress. If this equals to version `V`, we know versions
     /// smaller than `V` are no longer readable.
-    /// This being an atomic value is to communicate the info with the Pruner thread (for tests).
+    /// This being an atomic value is to communicate the info with the Pruner thread.
     least_readable_version: Arc<AtomicU64>,
     /// Indicates if there's NOT any pending work to do currently, to hint
     /// `Self::receive_commands()` to `recv()` blocking-ly.
     blocking_recv: bool,
-    index_min_nonpurged_version: Version,
-    index_purged_at: Instant,
 }
 
-impl Worker {
+impl<P: DBPruner> Worker<P> {
     const MAX_VERSIONS_TO_PRUNE_PER_BATCH: usize = 100;
 
     fn new(
-        db: Arc<DB>,
+        db_pruner: P,
         command_receiver: Receiver<Command>,
         least_readable_version: Arc<AtomicU64>,
     ) -> Self {
         Self {
-            db,
+            db_pruner,
             command_receiver,
             least_readable_version,
             target_least_readable_version: 0,
             blocking_recv: true,
-            index_min_nonpurged_version: 0,
-            index_purged_at: Instant::now(),
         }
     }
 
@@ -170,8 +205,7 @@ impl Worker {
             // Process a reasonably small batch of work before trying to receive commands again,
             // in case `Command::Quit` is received (that's when we should quit.)
             let least_readable_version = self.least_readable_version.load(Ordering::Relaxed);
-            match prune_state(
-                Arc::clone(&self.db),
+            match self.db_pruner.prune(
                 least_readable_version,
                 self.target_least_readable_version,
                 Self::MAX_VERSIONS_TO_PRUNE_PER_BATCH,
@@ -179,22 +213,16 @@ impl Worker {
                 Ok(new_least_readable_version) => {
                     self.record_progress(new_least_readable_version);
 
-                    // Make next recv() blocking if nothing left to do.
-                    self.blocking_recv = new_least_readable_version == least_readable_version // did nothing
-                        || new_least_readable_version == self.target_least_readable_version; // did all
-
-                    // Try to purge the log.
-                    if let Err(e) = self.maybe_purge_index() {
-                        warn!(
-                            error = ?e,
-                            "Failed purging state node index, ignored.",
-                        );
-                    }
+                    // Make next recv() blocking if nothing left to do, i.e. either did nothing or
+                    // did all.
+                    self.blocking_recv = new_least_readable_version == least_readable_version
+                        || new_least_readable_version == self.target_least_readable_version;
                 }
                 Err(e) => {
                     error!(
                         error = ?e,
-                        "Error pruning stale state nodes.",
+                        pruner = self.db_pruner.name(),
+                        "Error pruning.",
                     );
                     // On error, stop retrying vigorously by making next recv() blocking.
                     self.blocking_recv = true;
@@ -203,17 +231,18 @@ impl Worker {
         }
     }
 
-    /// Find out the first undeleted item in the stale node index.
+    /// Find out the least readable version from the DB.
     ///
     /// Seeking from the beginning (version 0) is potentially costly, we do it once upon worker
     /// thread start, record the progress and seek from that position afterwards.
     fn initialize(&mut self) {
         loop {
-            match self.get_least_readable_version() {
+            match self.db_pruner.initialize_least_readable_version() {
                 Ok(least_readable_version) => {
                     info!(
                         least_readable_version = least_readable_version,
-                        "[state pruner worker] initialized."
+                        pruner = self.db_pruner.name(),
+                        "[pruner worker] initialized."
                     );
                     self.target_least_readable_version = least_readable_version;
                     self.record_progress(least_readable_version);
@@ -222,7 +251,8 @@ impl Worker {
                 Err(e) => {
                     error!(
                         error = ?e,
-                        "[state pruner worker] Error on first seek. Retrying in 1 second.",
+                        pruner = self.db_pruner.name(),
+                        "[pruner worker] Error on first seek. Retrying in 1 second.",
                     );
                     sleep(Duration::from_secs(1));
                 }
@@ -230,24 +260,11 @@ impl Worker {
         }
     }
 
-    fn get_least_readable_version(&self) -> Result<Version> {
-        let mut iter = self
-            .db
-            .iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
-        iter.seek_to_first();
-        Ok(iter.next().transpose()?.map_or(0, |(index, _)| {
-            index
-                .stale_since_version
-                .checked_sub(1)
-                .expect("Nothing is stale since version 0.")
-        }))
-    }
-
     /// Log the progress.
     fn record_progress(&mut self, least_readable_version: Version) {
         self.least_readable_version
             .store(least_readable_version, Ordering::Relaxed);
-        DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION.set(least_readable_version as i64);
+        self.db_pruner.record_progress(least_readable_version);
     }
 
     /// Tries to receive all pending commands, blocking waits for the next command if no work needs
@@ -288,124 +305,6 @@ impl Worker {
             }
         }
     }
-
-    /// Purge the stale node index so that after restart not too much already pruned stuff is dealt
-    /// with again (although no harm is done deleting those then non-existent things.)
-    ///
-    /// We issue (range) deletes on the index only periodically instead of after every pruning batch
-    /// to avoid sending too many deletions to the DB, which takes disk space and slows it down.
-    fn maybe_purge_index(&mut self) -> Result<()> {
-        const MIN_INTERVAL: Duration = Duration::from_secs(60);
-        const MIN_VERSIONS: u64 = 60000;
-
-        // A deletion is issued at most once in one minute and when the pruner has progressed by at
-        // least 60000 versions (assuming the pruner deletes as slow as 1000 versions per second,
-        // this imposes at most one minute of work in vain after restarting.)
-        let now = Instant::now();
-        if now - self.index_purged_at > MIN_INTERVAL {
-            let least_readable_version = self.least_readable_version.load(Ordering::Relaxed);
-
-            if least_readable_version - self.index_min_nonpurged_version + 1 > MIN_VERSIONS {
-                let new_min_non_purged_version = least_readable_version + 1;
-                self.db.range_delete::<StaleNodeIndexSchema, Version>(
-                    &self.index_min_nonpurged_version,
-                    &new_min_non_purged_version, // end is exclusive
-                )?;
-                self.index_min_nonpurged_version = new_min_non_purged_version;
-                self.index_purged_at = now;
-            }
-        }
-
-        Ok(())
-    }
-}
-
-struct StaleNodeIndicesByVersionIterator<'a> {
-    inner: Peekable<SchemaIterator<'a, StaleNodeIndexSchema>>,
-    target_least_readable_version: Version,
-}
-
-impl<'a> StaleNodeIndicesByVersionIterator<'a> {
-    fn new(
-        db: &'a DB,
-        least_readable_version: Version,
-        target_least_readable_version: Version,
-    ) -> Result<Self> {
-        let mut iter = db.iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
-        iter.seek(&least_readable_version)?;
-
-        Ok(Self {
-            inner: iter.peekable(),
-            target_least_readable_version,
-        })
-    }
-
-    fn next_result(&mut self) -> Result<Option<Vec<StaleNodeIndex>>> {
-        match self.inner.next().transpose()? {
-            None => Ok(None),
-            Some((index, _)) => {
-                let version = index.stale_since_version;
-                if version > self.target_least_readable_version {
-                    return Ok(None);
-                }
-
-                let mut indices = vec![index];
-                while let Some(res) = self.inner.peek() {
-                    if let Ok((index_ref, _)) = res {
-                        if index_ref.stale_since_version != version {
-                            break;
-                        }
-                    }
-
-                    let (index, _) = self.inner.next().transpose()?.expect("Should be Some.");
-                    indices.push(index);
-                }
-
-                Ok(Some(indices))
-            }
-        }
-    }
-}
-
-impl<'a> Iterator for StaleNodeIndicesByVersionIterator<'a> {
-    type Item = Result<Vec<StaleNodeIndex>>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.next_result().transpose()
-    }
-}
-
-pub fn prune_state(
-    db: Arc<DB>,
-    least_readable_version: Version,
-    target_least_readable_version: Version,
-    max_versions: usize,
-) -> Result<Version> {
-    let indices = StaleNodeIndicesByVersionIterator::new(
-        &db,
-        least_readable_version,
-        target_least_readable_version,
-    )?
-    .take(max_versions) // Iterator<Item = Result<Vec<StaleNodeIndex>>>
-    .collect::<Result<Vec<_>>>()? // now Vec<Vec<StaleNodeIndex>>
-    .into_iter()
-    .flatten()
-    .collect::<Vec<_>>();
-
-    if indices.is_empty() {
-        Ok(least_readable_version)
-    } else {
-        let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
-            .with_label_values(&["pruner_commit"])
-            .start_timer();
-        let new_least_readable_version = indices.last().expect("Should exist.").stale_since_version;
-        let mut batch = SchemaBatch::new();
-        indices
-            .into_iter()
-            .try_for_each(|index| batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key))?;
-        db.write_schemas(batch)?;
-        Ok(new_least_readable_version)
-    }
 }
 
 #[cfg(test)]
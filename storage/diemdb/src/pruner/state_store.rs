@@ -0,0 +1,204 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides `StateStorePruner` which prunes Jellyfish Merkle nodes that became stale
+//! before the least readable version, according to `StaleNodeIndexSchema`.
+
+use crate::{
+    metrics::{
+        DIEM_STORAGE_OTHER_TIMERS_SECONDS, DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION,
+    },
+    pruner::DBPruner,
+    schema::{
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
+    },
+};
+use anyhow::Result;
+use diem_jellyfish_merkle::StaleNodeIndex;
+use diem_logger::prelude::*;
+use diem_types::transaction::Version;
+use schemadb::{ReadOptions, SchemaBatch, SchemaIterator, DB};
+use std::{
+    iter::Peekable,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+pub(crate) struct StateStorePruner {
+    db: Arc<DB>,
+    index_min_nonpurged_version: Version,
+    index_purged_at: Instant,
+}
+
+impl StateStorePruner {
+    pub fn new(db: Arc<DB>) -> Self {
+        Self {
+            db,
+            index_min_nonpurged_version: 0,
+            index_purged_at: Instant::now(),
+        }
+    }
+
+    /// Purge the stale node index so that after restart not too much already pruned stuff is dealt
+    /// with again (although no harm is done deleting those then non-existent things.)
+    ///
+    /// We issue (range) deletes on the index only periodically instead of after every pruning batch
+    /// to avoid sending too many deletions to the DB, which takes disk space and slows it down.
+    fn maybe_purge_index(&mut self, least_readable_version: Version) -> Result<()> {
+        const MIN_INTERVAL: Duration = Duration::from_secs(60);
+        const MIN_VERSIONS: u64 = 60000;
+
+        // A deletion is issued at most once in one minute and when the pruner has progressed by at
+        // least 60000 versions (assuming the pruner deletes as slow as 1000 versions per second,
+        // this imposes at most one minute of work in vain after restarting.)
+        let now = Instant::now();
+        if now - self.index_purged_at > MIN_INTERVAL
+            && least_readable_version - self.index_min_nonpurged_version + 1 > MIN_VERSIONS
+        {
+            let new_min_non_purged_version = least_readable_version + 1;
+            self.db.range_delete::<StaleNodeIndexSchema, Version>(
+                &self.index_min_nonpurged_version,
+                &new_min_non_purged_version, // end is exclusive
+            )?;
+            self.index_min_nonpurged_version = new_min_non_purged_version;
+            self.index_purged_at = now;
+        }
+
+        Ok(())
+    }
+}
+
+impl DBPruner for StateStorePruner {
+    fn name(&self) -> &'static str {
+        "state_store"
+    }
+
+    fn initialize_least_readable_version(&self) -> Result<Version> {
+        let mut iter = self
+            .db
+            .iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter.next().transpose()?.map_or(0, |(index, _)| {
+            index
+                .stale_since_version
+                .checked_sub(1)
+                .expect("Nothing is stale since version 0.")
+        }))
+    }
+
+    fn prune(
+        &mut self,
+        least_readable_version: Version,
+        target_least_readable_version: Version,
+        max_versions: usize,
+    ) -> Result<Version> {
+        let new_least_readable_version = prune_state(
+            Arc::clone(&self.db),
+            least_readable_version,
+            target_least_readable_version,
+            max_versions,
+        )?;
+
+        // Try to purge the log.
+        if let Err(e) = self.maybe_purge_index(new_least_readable_version) {
+            warn!(
+                error = ?e,
+                "Failed purging state node index, ignored.",
+            );
+        }
+
+        Ok(new_least_readable_version)
+    }
+
+    fn record_progress(&self, least_readable_version: Version) {
+        DIEM_STORAGE_PRUNER_LEAST_READABLE_STATE_VERSION.set(least_readable_version as i64);
+    }
+}
+
+struct StaleNodeIndicesByVersionIterator<'a> {
+    inner: Peekable<SchemaIterator<'a, StaleNodeIndexSchema>>,
+    target_least_readable_version: Version,
+}
+
+impl<'a> StaleNodeIndicesByVersionIterator<'a> {
+    fn new(
+        db: &'a DB,
+        least_readable_version: Version,
+        target_least_readable_version: Version,
+    ) -> Result<Self> {
+        let mut iter = db.iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
+        iter.seek(&least_readable_version)?;
+
+        Ok(Self {
+            inner: iter.peekable(),
+            target_least_readable_version,
+        })
+    }
+
+    fn next_result(&mut self) -> Result<Option<Vec<StaleNodeIndex>>> {
+        match self.inner.next().transpose()? {
+            None => Ok(None),
+            Some((index, _)) => {
+                let version = index.stale_since_version;
+                if version > self.target_least_readable_version {
+                    return Ok(None);
+                }
+
+                let mut indices = vec![index];
+                while let Some(res) = self.inner.peek() {
+                    if let Ok((index_ref, _)) = res {
+                        if index_ref.stale_since_version != version {
+                            break;
+                        }
+                    }
+
+                    let (index, _) = self.inner.next().transpose()?.expect("Should be Some.");
+                    indices.push(index);
+                }
+
+                Ok(Some(indices))
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for StaleNodeIndicesByVersionIterator<'a> {
+    type Item = Result<Vec<StaleNodeIndex>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_result().transpose()
+    }
+}
+
+pub fn prune_state(
+    db: Arc<DB>,
+    least_readable_version: Version,
+    target_least_readable_version: Version,
+    max_versions: usize,
+) -> Result<Version> {
+    let indices = StaleNodeIndicesByVersionIterator::new(
+        &db,
+        least_readable_version,
+        target_least_readable_version,
+    )?
+    .take(max_versions) // Iterator<Item = Result<Vec<StaleNodeIndex>>>
+    .collect::<Result<Vec<_>>>()? // now Vec<Vec<StaleNodeIndex>>
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    if indices.is_empty() {
+        Ok(least_readable_version)
+    } else {
+        let _timer = DIEM_STORAGE_OTHER_TIMERS_SECONDS
+            .with_label_values(&["pruner_commit"])
+            .start_timer();
+        let new_least_readable_version = indices.last().expect("Should exist.").stale_since_version;
+        let mut batch = SchemaBatch::new();
+        indices
+            .into_iter()
+            .try_for_each(|index| batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key))?;
+        db.write_schemas(batch)?;
+        Ok(new_least_readable_version)
+    }
+}
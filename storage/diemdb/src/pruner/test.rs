@@ -2,11 +2,28 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use crate::{change_set::ChangeSet, state_store::StateStore, DiemDB};
-use diem_crypto::HashValue;
+use crate::{
+    change_set::ChangeSet,
+    errors::DiemDbError,
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        event_by_key::EventByKeySchema,
+        transaction_by_account::TransactionByAccountSchema,
+    },
+    state_store::StateStore,
+    test_helper::arb_blocks_to_commit,
+    DiemDB,
+};
+use diem_config::config::RocksdbConfig;
+use diem_crypto::{hash::CryptoHash, HashValue};
 use diem_temppath::TempPath;
-use diem_types::{account_address::AccountAddress, account_state_blob::AccountStateBlob};
+use diem_types::{
+    account_address::AccountAddress, account_state_blob::AccountStateBlob, transaction::Transaction,
+};
+use proptest::prelude::*;
+use schemadb::DB;
 use std::collections::HashMap;
+use storage_interface::{DbReader, DbWriter, Order};
 
 fn put_account_state_set(
     db: &DB,
@@ -28,6 +45,13 @@ fn put_account_state_set(
     root
 }
 
+fn is_pruned<T>(res: Result<T>) -> bool {
+    matches!(
+        res.err().and_then(|e| e.downcast::<DiemDbError>().ok()),
+        Some(DiemDbError::Pruned(_))
+    )
+}
+
 fn verify_state_in_store(
     state_store: &StateStore,
     address: AccountAddress,
@@ -50,7 +74,11 @@ fn test_pruner() {
     let tmp_dir = TempPath::new();
     let db = DiemDB::new_for_test(&tmp_dir).db;
     let state_store = &StateStore::new(Arc::clone(&db), true /* account_count_migration */);
-    let pruner = Pruner::new(Arc::clone(&db), 0 /* historical_versions_to_keep */);
+    let pruner = Pruner::new(
+        StateStorePruner::new(Arc::clone(&db)),
+        0, /* historical_versions_to_keep */
+    )
+    .unwrap();
 
     let _root0 = put_account_state_set(
         &db,
@@ -134,7 +162,7 @@ fn test_worker_quit_eagerly() {
     {
         let (command_sender, command_receiver) = channel();
         let worker = Worker::new(
-            Arc::clone(&db),
+            StateStorePruner::new(Arc::clone(&db)),
             command_receiver,
             Arc::new(AtomicU64::new(0)), /* progress */
         );
@@ -156,3 +184,224 @@ fn test_worker_quit_eagerly() {
         verify_state_in_store(state_store, address, Some(&value2), 2);
     }
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_ledger_pruner(input in arb_blocks_to_commit()) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in &input {
+            db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as Version;
+        }
+        let latest_version = cur_ver - 1;
+        let historical_versions_to_keep = latest_version / 2;
+        let least_readable_version = latest_version - historical_versions_to_keep;
+
+        let pruner = Pruner::new(
+            LedgerStorePruner::new(Arc::clone(&db.db)),
+            historical_versions_to_keep,
+        )
+        .unwrap();
+        pruner.wake_and_wait(latest_version).unwrap();
+        prop_assert_eq!(pruner.least_readable_version(), least_readable_version);
+        prop_assert_eq!(
+            db.db
+                .get::<DbMetadataSchema>(&DbMetadataKey::LedgerPrunerProgress)
+                .unwrap(),
+            Some(DbMetadataValue::Version(least_readable_version))
+        );
+
+        let txns_to_commit = input.iter().flat_map(|(txns, _)| txns);
+        for (ver, txn_to_commit) in txns_to_commit.enumerate() {
+            let ver = ver as Version;
+            let txn_version_by_hash = db
+                .transaction_store
+                .get_transaction_version_by_hash(&txn_to_commit.transaction().hash(), latest_version)
+                .unwrap();
+            let events = db.event_store.get_events_by_version(ver).unwrap();
+            if ver < least_readable_version {
+                prop_assert!(db.transaction_store.get_transaction(ver).is_err());
+                prop_assert!(db.transaction_store.get_write_set(ver).is_err());
+                prop_assert!(db.ledger_store.get_transaction_info(ver).is_err());
+                prop_assert_eq!(txn_version_by_hash, None);
+                prop_assert!(events.is_empty());
+                for event in txn_to_commit.events() {
+                    prop_assert_eq!(
+                        db.db
+                            .get::<EventByKeySchema>(&(*event.key(), event.sequence_number()))
+                            .unwrap(),
+                        None
+                    );
+                }
+                if let Transaction::UserTransaction(signed_txn) = txn_to_commit.transaction() {
+                    prop_assert_eq!(
+                        db.db
+                            .get::<TransactionByAccountSchema>(&(
+                                signed_txn.sender(),
+                                signed_txn.sequence_number()
+                            ))
+                            .unwrap(),
+                        None
+                    );
+                }
+            } else {
+                prop_assert_eq!(
+                    db.transaction_store.get_transaction(ver).unwrap(),
+                    txn_to_commit.transaction().clone()
+                );
+                prop_assert_eq!(
+                    db.transaction_store.get_write_set(ver).unwrap(),
+                    txn_to_commit.write_set().clone()
+                );
+                prop_assert!(db.ledger_store.get_transaction_info(ver).is_ok());
+                prop_assert_eq!(txn_version_by_hash, Some(ver));
+                prop_assert_eq!(events, txn_to_commit.events().to_vec());
+            }
+        }
+        prop_assert_eq!(
+            db.transaction_store.get_first_txn_version().unwrap(),
+            Some(least_readable_version)
+        );
+    }
+
+    #[test]
+    fn test_reads_of_pruned_ledger(input in arb_blocks_to_commit()) {
+        let num_txns: usize = input.iter().map(|(txns, _)| txns.len()).sum();
+        let latest_version = num_txns as Version - 1;
+        let historical_versions_to_keep = latest_version / 2;
+        let least_readable_version = latest_version - historical_versions_to_keep;
+
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::open(
+            &tmp_dir,
+            false, /* readonly */
+            None,  /* pruner */
+            Some(historical_versions_to_keep),
+            RocksdbConfig::default(),
+            true, /* account_count_migration */
+            true, /* account_by_resource_index */
+        )
+        .unwrap();
+        let mut cur_ver = 0;
+        for (txns_to_commit, ledger_info_with_sigs) in &input {
+            db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                .unwrap();
+            cur_ver += txns_to_commit.len() as Version;
+        }
+        let ledger_pruner = db.ledger_pruner.as_ref().unwrap();
+        ledger_pruner.wake_and_wait(latest_version).unwrap();
+        prop_assert_eq!(ledger_pruner.least_readable_version(), least_readable_version);
+
+        let txns_to_commit: Vec<_> = input.iter().flat_map(|(txns, _)| txns).collect();
+        let kept_txns = &txns_to_commit[least_readable_version as usize..];
+        for (ver, txn_to_commit) in txns_to_commit.iter().enumerate() {
+            let ver = ver as Version;
+            let pruned = ver < least_readable_version;
+            let txn = txn_to_commit.transaction();
+
+            let res = db.get_transaction_by_version(ver, latest_version, true);
+            prop_assert_eq!(is_pruned(res), pruned);
+            // Hashes are pruned together with the transactions, so can't be told from unknown
+            // ones.
+            let res = db.get_transaction_by_hash(txn.hash(), latest_version, true);
+            prop_assert_eq!(res.unwrap().is_some(), !pruned);
+            if let Transaction::UserTransaction(signed_txn) = txn {
+                // A pruned transaction is known to be pruned when later transactions of the
+                // same account are kept.
+                let sender_has_kept_txns = kept_txns.iter().any(|kept| match kept.transaction() {
+                    Transaction::UserTransaction(kept) => kept.sender() == signed_txn.sender(),
+                    _ => false,
+                });
+                let res = db.get_account_transaction(
+                    signed_txn.sender(),
+                    signed_txn.sequence_number(),
+                    true, /* include_events */
+                    latest_version,
+                );
+                if !pruned {
+                    prop_assert!(res.unwrap().is_some());
+                } else if sender_has_kept_txns {
+                    prop_assert!(is_pruned(res));
+                } else {
+                    prop_assert!(res.unwrap().is_none());
+                }
+                let res = db.get_account_transactions(
+                    signed_txn.sender(),
+                    signed_txn.sequence_number(),
+                    1,    /* limit */
+                    true, /* include_events */
+                    latest_version,
+                );
+                if !pruned {
+                    prop_assert_eq!(res.unwrap().len(), 1);
+                } else if sender_has_kept_txns {
+                    prop_assert!(is_pruned(res));
+                } else {
+                    prop_assert_eq!(res.unwrap().len(), 0);
+                }
+            }
+            for event in txn_to_commit.events() {
+                let stream_has_kept_events = kept_txns
+                    .iter()
+                    .flat_map(|kept| kept.events())
+                    .any(|kept| kept.key() == event.key());
+                let res = db.get_events(event.key(), event.sequence_number(), Order::Ascending, 1);
+                if !pruned {
+                    prop_assert_eq!(res.unwrap().len(), 1);
+                } else if stream_has_kept_events {
+                    prop_assert!(is_pruned(res));
+                } else {
+                    prop_assert!(res.unwrap().is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ledger_pruner_progress_survives_reopen(input in arb_blocks_to_commit()) {
+        let num_txns: usize = input.iter().map(|(txns, _)| txns.len()).sum();
+        let latest_version = num_txns as Version - 1;
+        let historical_versions_to_keep = latest_version / 2;
+        let least_readable_version = latest_version - historical_versions_to_keep;
+        prop_assume!(least_readable_version > 0);
+
+        let tmp_dir = TempPath::new();
+        let open_db = || {
+            DiemDB::open(
+                &tmp_dir,
+                false, /* readonly */
+                None,  /* pruner */
+                Some(historical_versions_to_keep),
+                RocksdbConfig::default(),
+                true, /* account_count_migration */
+                true, /* account_by_resource_index */
+            )
+            .unwrap()
+        };
+        {
+            let db = open_db();
+            let mut cur_ver = 0;
+            for (txns_to_commit, ledger_info_with_sigs) in &input {
+                db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+                    .unwrap();
+                cur_ver += txns_to_commit.len() as Version;
+            }
+            db.ledger_pruner.as_ref().unwrap().wake_and_wait(latest_version).unwrap();
+        }
+
+        // Known right away, without waiting for the pruner worker.
+        let db = open_db();
+        prop_assert_eq!(
+            db.ledger_pruner.as_ref().unwrap().least_readable_version(),
+            least_readable_version
+        );
+        let res = db.get_transaction_by_version(0, latest_version, false);
+        prop_assert!(is_pruned(res));
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an optional index via which all accounts
+//! holding a resource of a given type can be found. The value is the version of the latest
+//! transaction that wrote the resource under the account.
+//!
+//! ```text
+//! |<----------key---------->|<-value->|
+//! | struct_tag |  address   | txn_ver |
+//! ```
+//!
+//! `struct_tag` is BCS encoded. Because BCS encoding is prefix free, entries for the same resource
+//! type are adjacent and sorted by address, which allows seeking with the `struct_tag` alone.
+
+use crate::schema::{ensure_slice_len_eq, ensure_slice_len_gt, ACCOUNT_BY_RESOURCE_CF_NAME};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use diem_types::{account_address::AccountAddress, transaction::Version};
+use move_core_types::language_storage::StructTag;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, SeekKeyCodec, ValueCodec},
+};
+use std::{convert::TryFrom, mem::size_of};
+
+define_schema!(
+    AccountByResourceSchema,
+    Key,
+    Version,
+    ACCOUNT_BY_RESOURCE_CF_NAME
+);
+
+type Key = (StructTag, AccountAddress);
+
+impl KeyCodec<AccountByResourceSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref struct_tag, ref address) = *self;
+
+        let mut encoded = bcs::to_bytes(struct_tag)?;
+        encoded.extend_from_slice(address.as_ref());
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_gt(data, AccountAddress::LENGTH)?;
+
+        let tag_len = data.len() - AccountAddress::LENGTH;
+        let struct_tag = bcs::from_bytes(&data[..tag_len])?;
+        let address = AccountAddress::try_from(&data[tag_len..])?;
+
+        Ok((struct_tag, address))
+    }
+}
+
+impl ValueCodec<AccountByResourceSchema> for Version {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl SeekKeyCodec<AccountByResourceSchema> for StructTag {
+    fn encode_seek_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
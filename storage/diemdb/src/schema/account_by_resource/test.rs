@@ -0,0 +1,19 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        struct_tag in any::<StructTag>(),
+        address in any::<AccountAddress>(),
+        version in any::<Version>(),
+    ) {
+        assert_encode_decode::<AccountByResourceSchema>(&(struct_tag, address), &version);
+    }
+}
+
+test_no_panic_decoding!(AccountByResourceSchema);
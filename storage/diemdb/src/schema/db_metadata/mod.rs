@@ -0,0 +1,74 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for metadata about the DB itself, e.g. the progress
+//! of the pruners, that needs to survive a restart.
+//!
+//! ```text
+//! |<-------key------->|<------value------>|
+//! | metadata key      | metadata value    |
+//! ```
+//!
+//! Both key and value are serialized with BCS.
+
+use crate::schema::DB_METADATA_CF_NAME;
+use anyhow::Result;
+use diem_types::transaction::Version;
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub(crate) enum DbMetadataKey {
+    /// Versions before this have their ledger history removed by the ledger pruner.
+    LedgerPrunerProgress,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub(crate) enum DbMetadataValue {
+    Version(Version),
+}
+
+impl DbMetadataValue {
+    pub fn expect_version(self) -> Version {
+        match self {
+            Self::Version(version) => version,
+        }
+    }
+}
+
+define_schema!(
+    DbMetadataSchema,
+    DbMetadataKey,
+    DbMetadataValue,
+    DB_METADATA_CF_NAME
+);
+
+impl KeyCodec<DbMetadataSchema> for DbMetadataKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+impl ValueCodec<DbMetadataSchema> for DbMetadataValue {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,15 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(key in any::<DbMetadataKey>(), value in any::<DbMetadataValue>()) {
+        assert_encode_decode::<DbMetadataSchema>(&key, &value);
+    }
+}
+
+test_no_panic_decoding!(DbMetadataSchema);
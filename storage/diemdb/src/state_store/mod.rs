@@ -10,6 +10,7 @@ use crate::{
     change_set::ChangeSet,
     ledger_counters::LedgerCounter,
     schema::{
+        account_by_resource::AccountByResourceSchema,
         jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
     },
     DiemDbError,
@@ -22,13 +23,17 @@ use diem_jellyfish_merkle::{
 };
 use diem_types::{
     account_address::{AccountAddress, HashAccountAddress},
-    account_state_blob::{AccountStateBlob, AccountStatesChunkWithProof},
+    account_state_blob::{
+        AccountStateBlob, AccountStatesChunkWithProof, AccountStatesRangeWithProof,
+    },
     nibble::{nibble_path::NibblePath, ROOT_NIBBLE_HEIGHT},
-    proof::{SparseMerkleProof, SparseMerkleRangeProof},
+    proof::{SparseMerkleKeyRangeProof, SparseMerkleProof, SparseMerkleRangeProof},
     transaction::Version,
+    write_set::{WriteOp, WriteSet},
 };
 use itertools::process_results;
-use schemadb::{SchemaBatch, DB};
+use move_core_types::language_storage::StructTag;
+use schemadb::{ReadOptions, SchemaBatch, DB};
 use std::{collections::HashMap, sync::Arc};
 use storage_interface::StateSnapshotReceiver;
 
@@ -121,6 +126,32 @@ impl StateStore {
         Ok(new_root_hash_vec)
     }
 
+    /// Put the tree nodes resulted from applying `account_states`, a chunk of the accounts changed
+    /// after `base_version` till `version`, on top of the tree at `base_version` to `batch`, and
+    /// return the root hash of the (partial) tree at `version`. Following chunks are applied with
+    /// `base_version` being `version`. Nodes of the base tree replaced are indexed as stale since
+    /// `version`, so that the pruner removes them once `version` falls out of the prune window.
+    pub fn put_account_state_diff(
+        &self,
+        account_states: Vec<(HashValue, AccountStateBlob)>,
+        base_version: Version,
+        version: Version,
+        cs: &mut ChangeSet,
+    ) -> Result<HashValue> {
+        let (new_root_hash, tree_update_batch) =
+            JellyfishMerkleTree::new_migration(self, self.account_count_migration)
+                .put_value_set_on_base(account_states, base_version, version)?;
+
+        add_node_batch(&mut cs.batch, &tree_update_batch.node_batch)?;
+        tree_update_batch
+            .stale_node_index_batch
+            .iter()
+            .map(|row| cs.batch.put::<StaleNodeIndexSchema>(row, &()))
+            .collect::<Result<Vec<()>>>()?;
+
+        Ok(new_root_hash)
+    }
+
     pub fn get_root_hash(&self, version: Version) -> Result<HashValue> {
         JellyfishMerkleTree::new_migration(self, self.account_count_migration)
             .get_root_hash(version)
@@ -192,6 +223,93 @@ impl StateStore {
         })
     }
 
+    pub fn get_account_range_with_proof(
+        self: &Arc<Self>,
+        version: Version,
+        first_key: HashValue,
+        last_key: HashValue,
+        limit: usize,
+    ) -> Result<AccountStatesRangeWithProof> {
+        ensure!(
+            first_key <= last_key,
+            "First key {:x} is greater than last key {:x}.",
+            first_key,
+            last_key,
+        );
+        ensure!(limit > 0, "Limit must be positive.");
+
+        let result_iter = JellyfishMerkleIterator::new(Arc::clone(self), version, first_key)?
+            .take_while(|res| res.as_ref().map_or(true, |(key, _)| *key <= last_key))
+            .take(limit);
+        let account_blobs: Vec<(HashValue, AccountStateBlob)> =
+            process_results(result_iter, |iter| iter.collect())?;
+        // If the limit is hit, the range ends at the last account returned.
+        let last_key = if account_blobs.len() == limit {
+            account_blobs.last().expect("Limit is positive.").0
+        } else {
+            last_key
+        };
+
+        let tree = JellyfishMerkleTree::new_migration(&**self, self.account_count_migration);
+        let (_, first_key_proof) = tree.get_with_proof(first_key, version)?;
+        let (_, last_key_proof) = tree.get_with_proof(last_key, version)?;
+
+        Ok(AccountStatesRangeWithProof {
+            first_key,
+            last_key,
+            account_blobs,
+            proof: SparseMerkleKeyRangeProof::new(first_key_proof, last_key_proof),
+        })
+    }
+
+    /// Updates the account-by-resource index according to the resources created and deleted by
+    /// `write_set`.
+    pub fn put_account_by_resource_index(
+        &self,
+        version: Version,
+        write_set: &WriteSet,
+        cs: &mut ChangeSet,
+    ) -> Result<()> {
+        write_set
+            .iter()
+            .filter_map(|(access_path, write_op)| {
+                access_path
+                    .get_struct_tag()
+                    .map(|struct_tag| ((struct_tag, access_path.address), write_op))
+            })
+            .try_for_each(|(key, write_op)| match write_op {
+                WriteOp::Value(_) => cs.batch.put::<AccountByResourceSchema>(&key, &version),
+                WriteOp::Deletion => cs.batch.delete::<AccountByResourceSchema>(&key),
+            })
+    }
+
+    /// Returns up to `limit` addresses of accounts currently holding a resource of type
+    /// `struct_tag`, in ascending order and starting from `start` (inclusive) if provided.
+    pub fn get_accounts_by_resource(
+        &self,
+        struct_tag: &StructTag,
+        start: Option<AccountAddress>,
+        limit: usize,
+    ) -> Result<Vec<AccountAddress>> {
+        let mut iter = self
+            .db
+            .iter::<AccountByResourceSchema>(ReadOptions::default())?;
+        match start {
+            Some(address) => iter.seek(&(struct_tag.clone(), address))?,
+            None => iter.seek(struct_tag)?,
+        }
+
+        let mut addresses = Vec::new();
+        for res in iter.take(limit) {
+            let ((tag, address), _version) = res?;
+            if &tag != struct_tag {
+                break;
+            }
+            addresses.push(address);
+        }
+        Ok(addresses)
+    }
+
     pub fn get_snapshot_receiver(
         self: &Arc<Self>,
         version: Version,
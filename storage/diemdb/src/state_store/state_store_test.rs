@@ -7,8 +7,14 @@ use diem_config::config::RocksdbConfig;
 use diem_jellyfish_merkle::restore::JellyfishMerkleRestore;
 use diem_temppath::TempPath;
 use diem_types::{
+    access_path::AccessPath,
     account_address::{AccountAddress, HashAccountAddress},
     account_state_blob::AccountStateBlob,
+    write_set::WriteSetMut,
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ResourceKey, CORE_CODE_ADDRESS},
 };
 use proptest::{
     collection::{hash_map, vec},
@@ -233,6 +239,93 @@ fn test_retired_records() {
     }
 }
 
+#[test]
+fn test_account_by_resource_index() {
+    let tmp_dir = TempPath::new();
+    let db = DiemDB::new_for_test(&tmp_dir);
+    let store = &db.state_store;
+    let address1 = AccountAddress::new([1u8; AccountAddress::LENGTH]);
+    let address2 = AccountAddress::new([2u8; AccountAddress::LENGTH]);
+    let address3 = AccountAddress::new([3u8; AccountAddress::LENGTH]);
+    let tag = |name: &str| StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new("M").unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    };
+    let put = |address: AccountAddress, name: &str| {
+        (
+            AccessPath::resource_access_path(ResourceKey::new(address, tag(name))),
+            WriteOp::Value(vec![]),
+        )
+    };
+    let delete = |address: AccountAddress, name: &str| {
+        (
+            AccessPath::resource_access_path(ResourceKey::new(address, tag(name))),
+            WriteOp::Deletion,
+        )
+    };
+    let write = |version: Version, write_set: Vec<(AccessPath, WriteOp)>| {
+        let mut cs = ChangeSet::new();
+        store
+            .put_account_by_resource_index(
+                version,
+                &WriteSetMut::new(write_set).freeze().unwrap(),
+                &mut cs,
+            )
+            .unwrap();
+        store.db.write_schemas(cs.batch).unwrap();
+    };
+
+    write(
+        0,
+        vec![put(address3, "A"), put(address1, "A"), put(address2, "B")],
+    );
+    assert_eq!(
+        store.get_accounts_by_resource(&tag("A"), None, 10).unwrap(),
+        vec![address1, address3],
+    );
+    assert_eq!(
+        store.get_accounts_by_resource(&tag("B"), None, 10).unwrap(),
+        vec![address2],
+    );
+    assert!(store
+        .get_accounts_by_resource(&tag("C"), None, 10)
+        .unwrap()
+        .is_empty());
+
+    write(1, vec![delete(address1, "A"), put(address2, "A")]);
+    assert_eq!(
+        store.get_accounts_by_resource(&tag("A"), None, 10).unwrap(),
+        vec![address2, address3],
+    );
+    // Paginated.
+    assert_eq!(
+        store.get_accounts_by_resource(&tag("A"), None, 1).unwrap(),
+        vec![address2],
+    );
+    assert_eq!(
+        store
+            .get_accounts_by_resource(&tag("A"), Some(address3), 1)
+            .unwrap(),
+        vec![address3],
+    );
+}
+
+/// Returns the key right after `key`, if any.
+fn next_key(key: HashValue) -> Option<HashValue> {
+    let mut bytes = key.to_vec();
+    for byte in bytes.iter_mut().rev() {
+        if *byte == u8::max_value() {
+            *byte = 0;
+        } else {
+            *byte += 1;
+            return Some(HashValue::from_slice(&bytes).unwrap());
+        }
+    }
+    None
+}
+
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(10))]
 
@@ -265,6 +358,48 @@ proptest! {
         }
     }
 
+    #[test]
+    fn test_get_account_range_with_proof(
+        input in hash_map(any::<AccountAddress>(), any::<AccountStateBlob>(), 1..200),
+        key1 in any::<HashValue>(),
+        key2 in any::<HashValue>(),
+        limit in 1..20usize,
+    ) {
+        let tmp_dir = TempPath::new();
+        let db = DiemDB::new_for_test(&tmp_dir);
+        let store = &db.state_store;
+        init_store(store, input.clone().into_iter());
+
+        let version = (input.len() - 1) as Version;
+        let root_hash = store.get_root_hash(version).unwrap();
+        let (first_key, last_key) = if key1 <= key2 { (key1, key2) } else { (key2, key1) };
+
+        let mut expected_values: Vec<_> = input
+            .into_iter()
+            .map(|(addr, account)| (addr.hash(), account))
+            .filter(|(key, _)| first_key <= *key && *key <= last_key)
+            .collect();
+        expected_values.sort_unstable_by_key(|item| item.0);
+
+        // Fetch the range in pages, verifying each one independently.
+        let mut actual_values = vec![];
+        let mut start_key = Some(first_key);
+        while let Some(key) = start_key {
+            let range = store
+                .get_account_range_with_proof(version, key, last_key, limit)
+                .unwrap();
+            prop_assert!(range.account_blobs.len() <= limit);
+            range.verify(root_hash).unwrap();
+            start_key = if range.last_key == last_key {
+                None
+            } else {
+                next_key(range.last_key)
+            };
+            actual_values.extend(range.account_blobs);
+        }
+        prop_assert_eq!(actual_values, expected_values);
+    }
+
     #[test]
     fn test_raw_restore(
         (input, batch1_size) in hash_map(any::<AccountAddress>(), any::<AccountStateBlob>(), 2..1000)
@@ -406,8 +541,10 @@ proptest! {
                 &tgt_tmp_dir,
                 false, /* readonly */
                 None,  /* pruner */
+                None,  /* ledger_pruner */
                 RocksdbConfig::default(),
-                true, /* account_count_migration */
+                true,  /* account_count_migration */
+                false, /* account_by_resource_index */
             ).unwrap();
             let store2 = &db2.state_store;
             // confirm that leaf counts were not written
@@ -496,8 +633,10 @@ proptest! {
                 &tmp_dir,
                 false, /* read_only */
                 None,
+                None,
                 RocksdbConfig::default(),
                 false, /* account_count_migration */
+                false, /* account_by_resource_index */
             ).unwrap();
             let store = &db.state_store;
             init_store(store, before.into_iter());
@@ -46,6 +46,19 @@ impl TransactionStore {
             if version <= ledger_version {
                 return Ok(Some(version));
             }
+            return Ok(None);
+        }
+
+        // Sequence numbers are contiguous, so a later transaction of the account being indexed
+        // means that this one was pruned, together with its index entry.
+        let mut iter = self
+            .db
+            .iter::<TransactionByAccountSchema>(ReadOptions::default())?;
+        iter.seek(&(address, sequence_number))?;
+        if let Some(((next_address, _), _)) = iter.next().transpose()? {
+            if next_address == address {
+                return Err(pruned_account_transaction(address, sequence_number));
+            }
         }
 
         Ok(None)
@@ -266,6 +279,15 @@ impl<'a> AccountTransactionVersionIter<'a> {
                     return Ok(None);
                 }
 
+                // Sequence numbers are contiguous, so the first transaction being missing means
+                // it was pruned, together with its index entry.
+                if self.prev_version.is_none() && seq_num > self.expected_next_seq_num {
+                    return Err(pruned_account_transaction(
+                        self.address,
+                        self.expected_next_seq_num,
+                    ));
+                }
+
                 // Ensure seq_num_{i+1} == seq_num_{i} + 1
                 ensure!(
                     seq_num == self.expected_next_seq_num,
@@ -308,5 +330,13 @@ impl<'a> Iterator for AccountTransactionVersionIter<'a> {
     }
 }
 
+fn pruned_account_transaction(address: AccountAddress, sequence_number: u64) -> anyhow::Error {
+    DiemDbError::Pruned(format!(
+        "Transaction of account {} with seq num {}",
+        address, sequence_number
+    ))
+    .into()
+}
+
 #[cfg(test)]
 mod test;
@@ -0,0 +1,90 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+use anyhow::Result;
+use diem_logger::info;
+use diem_types::transaction::Version;
+use diemdb::{
+    consistency_checker::{ConsistencyChecker, Inconsistency},
+    inspector::Inspector,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+use storage_interface::DbReader;
+use structopt::StructOpt;
+
+/// Checks the consistency of the ledger and state in a DiemDB over a range of versions, printing
+/// the result as a line of JSON. Exits with 1 if an inconsistency is found.
+#[derive(Debug, StructOpt)]
+struct Opt {
+    #[structopt(long, parse(from_os_str))]
+    db: PathBuf,
+
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "Open the DB as a secondary instance keeping its info logs in this directory, \
+        which works while the DB is opened by a running node. By default the DB is opened \
+        read-only."
+    )]
+    secondary_dir: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        help = "Defaults to the first version whose ledger data is not pruned."
+    )]
+    start_version: Option<Version>,
+
+    #[structopt(
+        long,
+        help = "Exclusive. Defaults to the one after the latest version."
+    )]
+    end_version: Option<Version>,
+}
+
+#[derive(Serialize)]
+struct Output {
+    start_version: Version,
+    end_version: Version,
+    /// The first inconsistency found, if any.
+    inconsistency: Option<Inconsistency>,
+}
+
+fn main() -> Result<()> {
+    ::diem_logger::DiemLogger::builder().build();
+    let opt = Opt::from_args();
+
+    let inspector = match &opt.secondary_dir {
+        Some(secondary_dir) => {
+            Inspector::open_as_secondary(opt.db.as_path(), secondary_dir.as_path())
+        }
+        None => Inspector::open_readonly(opt.db.as_path()),
+    }?;
+    let checker = ConsistencyChecker::new(inspector.db());
+
+    let start_version = match opt.start_version {
+        Some(v) => v,
+        None => checker.least_readable_ledger_version()?,
+    };
+    let end_version = match opt.end_version {
+        Some(v) => v,
+        None => inspector.db().get_latest_version()? + 1,
+    };
+    info!(
+        "Checking versions [{}, {}) in {:?}.",
+        start_version, end_version, opt.db
+    );
+
+    let output = Output {
+        start_version,
+        end_version,
+        inconsistency: checker.check_range(start_version, end_version)?,
+    };
+    println!("{}", serde_json::to_string(&output)?);
+    if output.inconsistency.is_some() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -4,15 +4,22 @@
 #![forbid(unsafe_code)]
 
 use anyhow::Result;
-use diem_config::config::RocksdbConfig;
 use diem_framework_releases::name_for_script;
+use diem_jellyfish_merkle::node_type::Node;
 use diem_logger::info;
-use diemdb::DiemDB;
-use std::path::PathBuf;
+use diemdb::{
+    inspector::{Inspector, ROCKSDB_PROPERTIES},
+    DiemDB,
+};
+use std::{
+    io::{BufRead, Write},
+    path::PathBuf,
+};
 use storage_interface::DbReader;
 
 use diem_types::{
     account_address::AccountAddress, account_config::AccountResource, account_state::AccountState,
+    event::EventKey, transaction::Version,
 };
 use std::convert::TryFrom;
 use structopt::StructOpt;
@@ -22,6 +29,15 @@ struct Opt {
     #[structopt(long, parse(from_os_str))]
     db: PathBuf,
 
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "Open the DB as a secondary instance keeping its info logs in this directory, \
+        which works while the DB is opened by a running node. By default the DB is opened \
+        read-only."
+    )]
+    secondary_dir: Option<PathBuf>,
+
     #[structopt(subcommand)] // Note that we mark a field as a subcommand
     cmd: Option<Command>,
 }
@@ -39,6 +55,49 @@ enum Command {
     },
     #[structopt(name = "list-accounts")]
     ListAccounts,
+    /// Print the size related RocksDB properties of each column family.
+    #[structopt(name = "cf-sizes")]
+    CfSizes,
+    /// Print entries of the schema in a column family, decoded, in the order of the encoded keys.
+    #[structopt(name = "scan")]
+    Scan {
+        /// Column family holding the schema, e.g. "transaction_info". Run `cf-sizes` for the full
+        /// list.
+        cf_name: String,
+        /// Hex encoded key to start from, inclusive. Can be a prefix of a key, e.g. just the
+        /// 8 bytes big endian version of a (version, index) key.
+        #[structopt(long, default_value = "")]
+        start_key: String,
+        /// Hex encoded key to stop at, exclusive.
+        #[structopt(long)]
+        end_key: Option<String>,
+        #[structopt(long, default_value = "100")]
+        limit: usize,
+    },
+    /// Print events in an event stream, looking up the index by event key.
+    #[structopt(name = "events-by-key")]
+    EventsByKey {
+        #[structopt(parse(try_from_str))]
+        event_key: EventKey,
+        #[structopt(long, default_value = "0")]
+        start_seq_num: u64,
+        #[structopt(long, default_value = "100")]
+        limit: usize,
+    },
+    /// Walk the Jellyfish Merkle tree of the state, printing nodes down to a depth and counting
+    /// all the leaves.
+    #[structopt(name = "walk-state-tree")]
+    WalkStateTree {
+        /// Defaults to the latest version.
+        #[structopt(long)]
+        version: Option<Version>,
+        /// In nibbles.
+        #[structopt(long, default_value = "1")]
+        max_depth: usize,
+    },
+    /// Read and run commands from stdin, one per line, without reopening the DB.
+    #[structopt(name = "interactive")]
+    Interactive,
 }
 
 /// Print out latest information stored in the DB.
@@ -152,6 +211,156 @@ fn list_accounts(db: &DiemDB) {
     info!("Total Accounts: {}", num_account);
 }
 
+fn print_cf_sizes(inspector: &Inspector) -> Result<()> {
+    println!("{:<24}{}", "column family", ROCKSDB_PROPERTIES.join("  "));
+    for (cf_name, values) in inspector.column_family_properties()? {
+        let values: Vec<_> = values.iter().map(u64::to_string).collect();
+        println!("{:<24}{}", cf_name, values.join("  "));
+    }
+    Ok(())
+}
+
+fn scan(
+    inspector: &Inspector,
+    cf_name: &str,
+    start_key: &str,
+    end_key: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    let start_key = hex::decode(start_key)?;
+    let end_key = end_key.map(hex::decode).transpose()?;
+    let entries = inspector.scan_schema(cf_name, &start_key, end_key.as_deref(), limit)?;
+    for entry in &entries {
+        println!(
+            "[{}] {} => {}",
+            hex::encode(&entry.encoded_key),
+            entry.key,
+            entry.value
+        );
+    }
+    info!("{} entries.", entries.len());
+    Ok(())
+}
+
+fn print_events_by_key(
+    inspector: &Inspector,
+    event_key: &EventKey,
+    start_seq_num: u64,
+    limit: usize,
+) -> Result<()> {
+    for entry in inspector.get_events_by_key(event_key, start_seq_num, limit)? {
+        match entry.event {
+            Some(event) => println!(
+                "Seq {} at version {} index {}: {}",
+                entry.seq_num, entry.version, entry.index, event
+            ),
+            None => println!(
+                "Seq {} at version {} index {}: MISSING",
+                entry.seq_num, entry.version, entry.index
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn walk_state_tree(
+    inspector: &Inspector,
+    version: Option<Version>,
+    max_depth: usize,
+) -> Result<()> {
+    let version = match version {
+        Some(v) => v,
+        None => inspector.db().get_latest_version()?,
+    };
+    let summary = inspector.walk_state_tree(version, max_depth, |node_key, node| {
+        let indent = "  ".repeat(node_key.nibble_path().num_nibbles());
+        match node {
+            Node::Internal(internal_node) => println!(
+                "{}{:?} version {}: internal, hash {:x}, {} children, leaf count {:?}",
+                indent,
+                node_key.nibble_path(),
+                node_key.version(),
+                internal_node.hash(),
+                internal_node.children_sorted().count(),
+                internal_node.leaf_count(),
+            ),
+            Node::Leaf(leaf_node) => println!(
+                "{}{:?} version {}: leaf, account key {:x}, value hash {:x}",
+                indent,
+                node_key.nibble_path(),
+                node_key.version(),
+                leaf_node.account_key(),
+                leaf_node.value_hash(),
+            ),
+            Node::Null => println!("{}null", indent),
+        }
+    })?;
+    println!("State tree at version {}: {:?}", version, summary);
+    Ok(())
+}
+
+fn run_cmd(inspector: &Inspector, cmd: Command) -> Result<()> {
+    let db = inspector.db();
+    match cmd {
+        Command::ListTXNs => {
+            list_txns(db);
+        }
+        Command::PrintTXN { version } => {
+            print_txn(db, version);
+        }
+        Command::PrintAccount { address } => {
+            print_account(db, address);
+        }
+        Command::ListAccounts => {
+            list_accounts(db);
+        }
+        Command::CfSizes => print_cf_sizes(inspector)?,
+        Command::Scan {
+            cf_name,
+            start_key,
+            end_key,
+            limit,
+        } => scan(inspector, &cf_name, &start_key, end_key.as_deref(), limit)?,
+        Command::EventsByKey {
+            event_key,
+            start_seq_num,
+            limit,
+        } => print_events_by_key(inspector, &event_key, start_seq_num, limit)?,
+        Command::WalkStateTree { version, max_depth } => {
+            walk_state_tree(inspector, version, max_depth)?
+        }
+        Command::Interactive => interactive(inspector)?,
+    }
+    Ok(())
+}
+
+fn interactive(inspector: &Inspector) -> Result<()> {
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let words: Vec<_> = line.split_whitespace().collect();
+        match words.first() {
+            None => continue,
+            Some(&"exit") | Some(&"quit") => return Ok(()),
+            _ => (),
+        }
+        match Command::from_iter_safe(std::iter::once("inspector").chain(words)) {
+            Ok(Command::Interactive) => println!("Already in interactive mode."),
+            Ok(cmd) => {
+                if let Err(e) = run_cmd(inspector, cmd) {
+                    println!("Error: {:?}", e);
+                }
+            }
+            Err(e) => println!("{}", e.message),
+        }
+    }
+}
+
 fn main() {
     ::diem_logger::DiemLogger::builder().build();
 
@@ -167,33 +376,17 @@ fn main() {
     let log_dir = tempfile::tempdir().expect("Unable to get temp dir");
     info!("Opening DB at: {:?}, log at {:?}", p, log_dir.path());
 
-    let db = DiemDB::open(
-        p,
-        true, /* readonly */
-        None, /* pruner */
-        RocksdbConfig::default(),
-        true, /* account_count_migration, ignored anyway */
-    )
+    let inspector = match &opt.secondary_dir {
+        Some(secondary_dir) => Inspector::open_as_secondary(p, secondary_dir.as_path()),
+        None => Inspector::open_readonly(p),
+    }
     .expect("Unable to open DiemDB");
     info!("DB opened successfully.");
 
     if let Some(cmd) = opt.cmd {
-        match cmd {
-            Command::ListTXNs => {
-                list_txns(&db);
-            }
-            Command::PrintTXN { version } => {
-                print_txn(&db, version);
-            }
-            Command::PrintAccount { address } => {
-                print_account(&db, address);
-            }
-            Command::ListAccounts => {
-                list_accounts(&db);
-            }
-        }
+        run_cmd(&inspector, cmd).expect("Command failed");
     } else {
-        print_head(&db).expect("Unable to read information from DB");
+        print_head(inspector.db()).expect("Unable to read information from DB");
 
         Opt::clap().print_help().unwrap();
         println!();
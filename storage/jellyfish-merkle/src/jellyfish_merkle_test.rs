@@ -12,7 +12,7 @@ use diem_types::{nibble::Nibble, transaction::PRE_GENESIS_VERSION};
 use mock_tree_store::MockTreeStore;
 use proptest::{collection::hash_set, prelude::*};
 use rand::{rngs::StdRng, Rng, SeedableRng};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 fn update_nibble(original_key: &HashValue, n: usize, nibble: u8) -> HashValue {
     assert!(nibble < 16);
@@ -570,6 +570,103 @@ fn test_put_value_sets() {
     }
 }
 
+#[test]
+fn test_put_value_set_on_base() {
+    let base_kvs: Vec<_> = (0..20)
+        .map(|_| {
+            (
+                HashValue::random(),
+                ValueBlob::from(HashValue::random().to_vec()),
+            )
+        })
+        .collect();
+    // Updates half of the existing keys and adds some new ones, over a few versions.
+    let updates: Vec<Vec<_>> = (0..5)
+        .map(|i| {
+            vec![
+                (
+                    base_kvs[i * 2].0,
+                    ValueBlob::from(HashValue::random().to_vec()),
+                ),
+                (
+                    HashValue::random(),
+                    ValueBlob::from(HashValue::random().to_vec()),
+                ),
+            ]
+        })
+        .collect();
+
+    // Version by version.
+    let expected_root = {
+        let db = MockTreeStore::default();
+        let tree = JellyfishMerkleTree::new(&db);
+        let (_root, batch) = tree.put_value_set(base_kvs.clone(), 0).unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+        let (roots, batch) = tree
+            .batch_put_value_sets(updates.clone(), None, 1 /* first_version */)
+            .unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+        *roots.last().unwrap()
+    };
+
+    // Skipping the versions in between.
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::new(&db);
+    let (_root, batch) = tree.put_value_set(base_kvs.clone(), 0).unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+    let diff: Vec<_> = updates.iter().flatten().cloned().collect();
+    let (root, batch) = tree
+        .put_value_set_on_base(
+            diff.clone(),
+            0, /* base_version */
+            5, /* version */
+        )
+        .unwrap();
+    assert_eq!(root, expected_root);
+    let expected_stale_node_indices = batch.stale_node_index_batch.clone();
+    db.write_tree_update_batch(batch).unwrap();
+    assert_eq!(tree.get_root_hash(5).unwrap(), expected_root);
+    for (k, v) in &diff {
+        assert_eq!(tree.get(*k, 5).unwrap().as_ref(), Some(v));
+    }
+    // The base tree is still readable.
+    for (k, v) in &base_kvs {
+        assert_eq!(tree.get(*k, 0).unwrap().as_ref(), Some(v));
+    }
+
+    // The base version must exist.
+    assert!(tree
+        .put_value_set_on_base(
+            diff.clone(),
+            3, /* base_version */
+            6  /* version */
+        )
+        .is_err());
+
+    // In chunks, each one on top of the partial tree resulted from the ones before.
+    let db = MockTreeStore::new(true /* allow_overwrite */);
+    let tree = JellyfishMerkleTree::new(&db);
+    let (_root, base_batch) = tree.put_value_set(base_kvs, 0).unwrap();
+    db.write_node_batch(&base_batch.node_batch).unwrap();
+    let mut sorted_diff = diff;
+    sorted_diff.sort_by_key(|(k, _)| *k);
+    let mut stale_node_indices = BTreeSet::new();
+    let mut chunk_root = HashValue::zero();
+    for (i, chunk) in sorted_diff.chunks(3).enumerate() {
+        let base_version = if i == 0 { 0 } else { 5 };
+        let (root, batch) = tree
+            .put_value_set_on_base(chunk.to_vec(), base_version, 5 /* version */)
+            .unwrap();
+        db.write_node_batch(&batch.node_batch).unwrap();
+        stale_node_indices.extend(batch.stale_node_index_batch);
+        chunk_root = root;
+    }
+    assert_eq!(chunk_root, expected_root);
+    assert_eq!(tree.get_root_hash(5).unwrap(), expected_root);
+    // Only nodes of the base tree become stale, same as applying the diff in one go.
+    assert_eq!(stale_node_indices, expected_stale_node_indices);
+}
+
 fn many_keys_get_proof_and_verify_tree_root(seed: &[u8], num_keys: usize) {
     assert!(seed.len() < 32);
     let mut actual_seed = [0u8; 32];
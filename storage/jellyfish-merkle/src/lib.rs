@@ -557,6 +557,55 @@ where
         Ok((root_hashes[0], tree_update_batch))
     }
 
+    /// Returns the new nodes and values in a batch after applying `value_set` on top of the tree
+    /// at `base_version`, producing the tree at `version`. Unlike
+    /// [`put_value_sets`](struct.JellyfishMerkleTree.html#method.put_value_sets), the trees at the
+    /// versions between `base_version` and `version` are not expected to exist, so `value_set`
+    /// needs to include all values updated in these versions.
+    ///
+    /// This is used to restore the tree at a version from a tree at an earlier version and the
+    /// values that changed in between. To do that in chunks, `base_version` can be `version`
+    /// itself, which applies `value_set` on top of the partial tree at `version` resulted from the
+    /// chunks before. Nodes of the partial tree replaced this way are overwritten in place by the
+    /// new nodes with the same keys, so they are not reported stale.
+    pub fn put_value_set_on_base(
+        &self,
+        value_set: Vec<(HashValue, V)>,
+        base_version: Version,
+        version: Version,
+    ) -> Result<(HashValue, TreeUpdateBatch<V>)> {
+        ensure!(!value_set.is_empty(), "Value set to put is empty.");
+        let mut tree_cache = TreeCache::new_on_base(self.reader, base_version, version)?;
+        let deduped_and_sorted_kvs = value_set
+            .into_iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let root_node_key = tree_cache.get_root_node_key().clone();
+        let (new_root_node_key, _) = self.batch_insert_at(
+            root_node_key,
+            version,
+            deduped_and_sorted_kvs.as_slice(),
+            0,
+            &None,
+            &mut tree_cache,
+        )?;
+        tree_cache.set_root_node_key(new_root_node_key);
+        tree_cache.freeze();
+
+        let (root_hashes, mut tree_update_batch): (Vec<HashValue>, TreeUpdateBatch<V>) =
+            tree_cache.into();
+        assert_eq!(
+            root_hashes.len(),
+            1,
+            "root_hashes must consist of a single value.",
+        );
+        tree_update_batch
+            .stale_node_index_batch
+            .retain(|index| index.node_key.version() != version);
+        Ok((root_hashes[0], tree_update_batch))
+    }
+
     /// Returns the new nodes and values in a batch after applying `value_set`. For
     /// example, if after transaction `T_i` the committed state of tree in the persistent storage
     /// looks like the following structure:
@@ -74,7 +74,7 @@ use crate::{
     node_type::{Node, NodeKey},
     NodeBatch, NodeStats, StaleNodeIndex, StaleNodeIndexBatch, TreeReader, TreeUpdateBatch,
 };
-use anyhow::{bail, Result};
+use anyhow::{bail, ensure, Result};
 use diem_crypto::HashValue;
 use diem_types::transaction::{Version, PRE_GENESIS_VERSION};
 use std::collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet};
@@ -165,6 +165,37 @@ where
         } else {
             NodeKey::new_empty_path(next_version - 1)
         };
+        Self::new_impl(reader, node_cache, root_node_key, next_version)
+    }
+
+    /// Constructs a new `TreeCache` instance on top of the tree at `base_version` rather than
+    /// `next_version - 1`, skipping the versions in between. `base_version` can also be
+    /// `next_version`, to continue building the tree at `next_version` partially written before.
+    pub fn new_on_base(
+        reader: &'a R,
+        base_version: Version,
+        next_version: Version,
+    ) -> Result<Self> {
+        ensure!(
+            base_version <= next_version,
+            "Base version {} must not be newer than next version {}.",
+            base_version,
+            next_version,
+        );
+        Self::new_impl(
+            reader,
+            HashMap::new(),
+            NodeKey::new_empty_path(base_version),
+            next_version,
+        )
+    }
+
+    fn new_impl(
+        reader: &'a R,
+        node_cache: HashMap<NodeKey, Node<V>>,
+        root_node_key: NodeKey,
+        next_version: Version,
+    ) -> Result<Self> {
         Ok(Self {
             node_cache,
             stale_node_index_cache: HashSet::new(),
@@ -36,8 +36,10 @@ pub fn run_benchmark(num_updates: usize, max_accounts: u64, blob_size: usize, db
         &db_dir,
         false, /* readonly */
         None,  /* pruner */
+        None,  /* ledger_pruner */
         RocksdbConfig::default(),
-        true, /* account_count_migration, ignored anyway */
+        true,  /* account_count_migration, ignored anyway */
+        false, /* account_by_resource_index */
     )
     .expect("DB should open.");
 
@@ -8,7 +8,10 @@ use diem_types::{
     account_address::AccountAddress,
     account_config::diem_root_address,
     account_state::AccountState,
-    account_state_blob::{AccountStateBlob, AccountStateWithProof, AccountStatesChunkWithProof},
+    account_state_blob::{
+        AccountStateBlob, AccountStateWithProof, AccountStatesChunkWithProof,
+        AccountStatesRangeWithProof,
+    },
     contract_event::{ContractEvent, EventByVersionWithProof, EventWithProof},
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
@@ -28,7 +31,10 @@ use diem_types::{
         TransactionOutputListWithProof, TransactionToCommit, TransactionWithProof, Version,
     },
 };
-use move_core_types::resolver::{ModuleResolver, ResourceResolver};
+use move_core_types::{
+    language_storage::StructTag,
+    resolver::{ModuleResolver, ResourceResolver},
+};
 use serde::{Deserialize, Serialize};
 use std::{convert::TryFrom, sync::Arc};
 use thiserror::Error;
@@ -37,6 +43,9 @@ use thiserror::Error;
 pub mod mock;
 pub mod state_view;
 
+/// The maximum number of items, e.g. transactions or events, that can be requested at once.
+pub const MAX_REQUEST_LIMIT: u64 = 5000;
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct StartupInfo {
     /// The latest ledger info.
@@ -507,10 +516,37 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Get the account data with hashed addresses in `[first_key, last_key]`, up to `limit`
+    /// accounts. If there are more accounts in the range, the range in the result ends at the
+    /// last account returned.
+    fn get_account_range_with_proof(
+        &self,
+        version: Version,
+        first_key: HashValue,
+        last_key: HashValue,
+        limit: usize,
+    ) -> Result<AccountStatesRangeWithProof> {
+        unimplemented!()
+    }
+
     /// Get the state prune window config value.
     fn get_state_prune_window(&self) -> Option<usize> {
         unimplemented!()
     }
+
+    /// Returns up to `limit` addresses of accounts holding a resource of type `struct_tag` in the
+    /// latest state, in ascending order and starting from `start` (inclusive) if provided.
+    ///
+    /// This is served by an optional index which only covers transactions committed while it's
+    /// enabled.
+    fn get_accounts_by_resource(
+        &self,
+        struct_tag: &StructTag,
+        start: Option<AccountAddress>,
+        limit: u64,
+    ) -> Result<Vec<AccountAddress>> {
+        unimplemented!()
+    }
 }
 
 impl MoveStorage for &dyn DbReader {
@@ -609,6 +645,8 @@ pub trait DbWriter: Send + Sync {
 pub trait MoveDbReader:
     DbReader + ResourceResolver<Error = anyhow::Error> + ModuleResolver<Error = anyhow::Error>
 {
+    /// Returns this reader as a `DbReader`, e.g. to build a `VerifiedStateView` on top of it.
+    fn as_db_reader(self: Arc<Self>) -> Arc<dyn DbReader>;
 }
 
 #[derive(Clone)]
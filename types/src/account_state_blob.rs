@@ -6,7 +6,7 @@ use crate::{
     account_config::{AccountResource, BalanceResource, DiemAccountResource},
     account_state::AccountState,
     ledger_info::LedgerInfo,
-    proof::{AccountStateProof, SparseMerkleRangeProof},
+    proof::{AccountStateProof, SparseMerkleKeyRangeProof, SparseMerkleRangeProof},
     transaction::Version,
 };
 use anyhow::{anyhow, ensure, Error, Result};
@@ -243,6 +243,34 @@ pub struct AccountStatesChunkWithProof {
     pub proof: SparseMerkleRangeProof, // The proof to ensure the chunk is in the account states
 }
 
+/// All account states at a specific version with hashed addresses in `[first_key, last_key]`.
+/// Unlike `AccountStatesChunkWithProof`, this can be verified without knowing the account states
+/// before the range, so that the state can be downloaded in shards verified independently.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccountStatesRangeWithProof {
+    /// The first key in the range.
+    pub first_key: HashValue,
+    /// The last key in the range (inclusive).
+    pub last_key: HashValue,
+    /// The account blobs in the range, sorted by key.
+    pub account_blobs: Vec<(HashValue, AccountStateBlob)>,
+    /// The proof that there are no other accounts in the range.
+    pub proof: SparseMerkleKeyRangeProof<AccountStateBlob>,
+}
+
+impl AccountStatesRangeWithProof {
+    /// Verifies that `account_blobs` are all the accounts in the range in the state with root hash
+    /// `expected_root_hash`.
+    pub fn verify(&self, expected_root_hash: HashValue) -> Result<()> {
+        self.proof.verify(
+            expected_root_hash,
+            self.first_key,
+            self.last_key,
+            &self.account_blobs,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{AccountStateWithProof, *};
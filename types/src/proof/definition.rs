@@ -659,6 +659,208 @@ impl SparseMerkleRangeProof {
     }
 }
 
+/// A proof that can be used to authenticate that a list of leaves are all the leaves with keys in a
+/// range `[first_key, last_key]` in a sparse Merkle tree. Unlike `SparseMerkleRangeProof`, the
+/// leaves on the left of the range don't need to be known, so ranges of the tree can be verified
+/// independently of each other.
+///
+/// It consists of the proofs of `first_key` and `last_key`, each of which can be an inclusion or a
+/// non-inclusion proof. The siblings on the left of the path to `first_key` and the ones on the
+/// right of the path to `last_key` cover exactly the parts of the tree out of the range, so
+/// together with the leaves in the range they determine the root hash.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SparseMerkleKeyRangeProof<V> {
+    first_key_proof: SparseMerkleProof<V>,
+    last_key_proof: SparseMerkleProof<V>,
+}
+
+impl<V> SparseMerkleKeyRangeProof<V>
+where
+    V: CryptoHash,
+{
+    /// Constructs a new `SparseMerkleKeyRangeProof` from the proofs of the keys at both ends of
+    /// the range.
+    pub fn new(
+        first_key_proof: SparseMerkleProof<V>,
+        last_key_proof: SparseMerkleProof<V>,
+    ) -> Self {
+        Self {
+            first_key_proof,
+            last_key_proof,
+        }
+    }
+
+    /// Returns the proof of the first key in the range.
+    pub fn first_key_proof(&self) -> &SparseMerkleProof<V> {
+        &self.first_key_proof
+    }
+
+    /// Returns the proof of the last key in the range.
+    pub fn last_key_proof(&self) -> &SparseMerkleProof<V> {
+        &self.last_key_proof
+    }
+
+    /// Verifies that `leaves`, sorted by key, are all the leaves in the tree with keys in
+    /// `[first_key, last_key]`, and that the resulting root hash matches the expected root hash.
+    pub fn verify(
+        &self,
+        expected_root_hash: HashValue,
+        first_key: HashValue,
+        last_key: HashValue,
+        leaves: &[(HashValue, V)],
+    ) -> Result<()> {
+        ensure!(
+            first_key <= last_key,
+            "First key {:x} is greater than last key {:x}.",
+            first_key,
+            last_key,
+        );
+
+        let mut nodes = Vec::with_capacity(leaves.len() + 2);
+        for (i, (key, value)) in leaves.iter().enumerate() {
+            ensure!(
+                first_key <= *key && *key <= last_key,
+                "Leaf key {:x} is out of the range.",
+                key,
+            );
+            ensure!(
+                i == 0 || leaves[i - 1].0 < *key,
+                "Leaf keys are not strictly increasing.",
+            );
+            nodes.push(KeyRangeProofNode::Leaf(SparseMerkleLeafNode::new(
+                *key,
+                value.hash(),
+            )));
+        }
+        Self::add_boundary_nodes(
+            &mut nodes,
+            &self.first_key_proof,
+            first_key,
+            true, /* left siblings */
+            (first_key, last_key),
+        )?;
+        Self::add_boundary_nodes(
+            &mut nodes,
+            &self.last_key_proof,
+            last_key,
+            false, /* right siblings */
+            (first_key, last_key),
+        )?;
+
+        let actual_root_hash = KeyRangeProofNode::root_hash(nodes, 0)?;
+        ensure!(
+            actual_root_hash == expected_root_hash,
+            "Root hashes do not match. Actual root hash: {:x}. Expected root hash: {:x}.",
+            actual_root_hash,
+            expected_root_hash,
+        );
+
+        Ok(())
+    }
+
+    /// Adds the siblings on one side of the path to `key` and the leaf the path ends at, if the
+    /// leaf is out of the range, to `nodes`.
+    fn add_boundary_nodes(
+        nodes: &mut Vec<KeyRangeProofNode>,
+        proof: &SparseMerkleProof<V>,
+        key: HashValue,
+        left_siblings: bool,
+        (first_key, last_key): (HashValue, HashValue),
+    ) -> Result<()> {
+        let siblings = proof.siblings();
+        ensure!(
+            siblings.len() <= HashValue::LENGTH_IN_BITS,
+            "Sparse Merkle Tree proof has more than {} ({}) siblings.",
+            HashValue::LENGTH_IN_BITS,
+            siblings.len(),
+        );
+
+        // Siblings are ordered from the bottom level to the root level.
+        for (i, sibling) in siblings.iter().enumerate() {
+            let depth = siblings.len() - i;
+            if key.bit(depth - 1) == left_siblings && *sibling != *SPARSE_MERKLE_PLACEHOLDER_HASH {
+                nodes.push(KeyRangeProofNode::Subtree {
+                    key,
+                    depth,
+                    hash: *sibling,
+                });
+            }
+        }
+
+        if let Some(leaf) = proof.leaf() {
+            ensure!(
+                leaf.key().common_prefix_bits_len(key) >= siblings.len(),
+                "Leaf {:x} in the proof is not on the path to key {:x}.",
+                leaf.key(),
+                key,
+            );
+            // Leaves in the range are expected to be provided by the caller.
+            let in_range = first_key <= leaf.key() && leaf.key() <= last_key;
+            let added = nodes.iter().any(|node| match node {
+                KeyRangeProofNode::Leaf(other) => other.key() == leaf.key(),
+                KeyRangeProofNode::Subtree { .. } => false,
+            });
+            if !in_range && !added {
+                nodes.push(KeyRangeProofNode::Leaf(leaf));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A node known to the verifier of a `SparseMerkleKeyRangeProof`.
+enum KeyRangeProofNode {
+    Leaf(SparseMerkleLeafNode),
+    /// A subtree at `depth`, whose root is a sibling on the path to `key`.
+    Subtree {
+        key: HashValue,
+        depth: usize,
+        hash: HashValue,
+    },
+}
+
+impl KeyRangeProofNode {
+    /// Returns the bit that decides which child of the node at `depth` this is under.
+    fn bit(&self, depth: usize) -> bool {
+        match self {
+            Self::Leaf(leaf) => leaf.key().bit(depth),
+            // The subtree is a sibling, so it's on the other side of the path at its own depth.
+            Self::Subtree { key, depth: d, .. } => key.bit(depth) != (depth + 1 == *d),
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match self {
+            Self::Leaf(_) => HashValue::LENGTH_IN_BITS,
+            Self::Subtree { depth, .. } => *depth,
+        }
+    }
+
+    /// Computes the hash of the node at `depth` which has exactly `nodes` under it.
+    fn root_hash(nodes: Vec<Self>, depth: usize) -> Result<HashValue> {
+        match nodes.as_slice() {
+            [] => return Ok(*SPARSE_MERKLE_PLACEHOLDER_HASH),
+            // A leaf alone in a subtree is placed at the root of the subtree.
+            [Self::Leaf(leaf)] => return Ok(leaf.hash()),
+            [Self::Subtree { depth: d, hash, .. }] if *d == depth => return Ok(*hash),
+            _ => (),
+        }
+        ensure!(
+            nodes.iter().all(|node| node.depth() > depth),
+            "Nodes in the proof overlap at depth {}.",
+            depth,
+        );
+
+        let (left, right): (Vec<_>, Vec<_>) = nodes.into_iter().partition(|node| !node.bit(depth));
+        Ok(SparseMerkleInternalNode::new(
+            Self::root_hash(left, depth + 1)?,
+            Self::root_hash(right, depth + 1)?,
+        )
+        .hash())
+    }
+}
+
 /// `TransactionInfo` and a `TransactionAccumulatorProof` connecting it to the ledger root.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(Arbitrary))]
@@ -36,6 +36,7 @@ use diem_crypto::{
 use move_core_types::language_storage::TypeTag;
 
 type SparseMerkleProof = crate::proof::SparseMerkleProof<AccountStateBlob>;
+type SparseMerkleKeyRangeProof = crate::proof::SparseMerkleKeyRangeProof<AccountStateBlob>;
 
 #[test]
 fn test_verify_empty_accumulator() {
@@ -256,6 +257,111 @@ fn test_verify_three_element_sparse_merkle() {
     }
 }
 
+#[test]
+fn test_verify_sparse_merkle_key_range() {
+    // Same tree as in `test_verify_three_element_sparse_merkle`.
+    //            root
+    //           /    \
+    //          a      default
+    //         / \
+    //     key1   b
+    //           / \
+    //       key2   key3
+    let key1 = b"hello".test_only_hash();
+    let key2 = b"world".test_only_hash();
+    let key3 = b"!".test_only_hash();
+    let non_existing_key1 = b"abc".test_only_hash();
+    assert_eq!(non_existing_key1[0], 0b0011_1010);
+
+    let blob1 = AccountStateBlob::from(b"1".to_vec());
+    let blob2 = AccountStateBlob::from(b"2".to_vec());
+    let blob3 = AccountStateBlob::from(b"3".to_vec());
+
+    let leaf1 = SparseMerkleLeafNode::new(key1, blob1.hash());
+    let leaf2 = SparseMerkleLeafNode::new(key2, blob2.hash());
+    let leaf3 = SparseMerkleLeafNode::new(key3, blob3.hash());
+    let internal_b_hash = SparseMerkleInternalNode::new(leaf2.hash(), leaf3.hash()).hash();
+    let internal_a_hash = SparseMerkleInternalNode::new(leaf1.hash(), internal_b_hash).hash();
+    let root_hash =
+        SparseMerkleInternalNode::new(internal_a_hash, *SPARSE_MERKLE_PLACEHOLDER_HASH).hash();
+
+    let proof1 = SparseMerkleProof::new(
+        Some(leaf1),
+        vec![internal_b_hash, *SPARSE_MERKLE_PLACEHOLDER_HASH],
+    );
+    let proof2 = SparseMerkleProof::new(
+        Some(leaf2),
+        vec![leaf3.hash(), leaf1.hash(), *SPARSE_MERKLE_PLACEHOLDER_HASH],
+    );
+    let proof3 = SparseMerkleProof::new(
+        Some(leaf3),
+        vec![leaf2.hash(), leaf1.hash(), *SPARSE_MERKLE_PLACEHOLDER_HASH],
+    );
+    let proof_of_default = SparseMerkleProof::new(None, vec![internal_a_hash]);
+
+    {
+        // [key2, key3]
+        let proof = SparseMerkleKeyRangeProof::new(proof2.clone(), proof3.clone());
+        let leaves = vec![(key2, blob2.clone()), (key3, blob3.clone())];
+        assert!(proof.verify(root_hash, key2, key3, &leaves).is_ok());
+        // Missing a leaf.
+        assert!(proof.verify(root_hash, key2, key3, &leaves[..1]).is_err());
+        // Wrong value.
+        let wrong_leaves = vec![(key2, blob2.clone()), (key3, blob1.clone())];
+        assert!(proof.verify(root_hash, key2, key3, &wrong_leaves).is_err());
+        // Wrong range.
+        assert!(proof.verify(root_hash, key1, key3, &leaves).is_err());
+        assert!(proof.verify(root_hash, key3, key2, &leaves).is_err());
+    }
+
+    {
+        // [key1, key2]
+        let proof = SparseMerkleKeyRangeProof::new(proof1.clone(), proof2.clone());
+        let leaves = vec![(key1, blob1.clone()), (key2, blob2.clone())];
+        assert!(proof.verify(root_hash, key1, key2, &leaves).is_ok());
+        // Not sorted.
+        let unsorted_leaves = vec![(key2, blob2.clone()), (key1, blob1.clone())];
+        assert!(proof
+            .verify(root_hash, key1, key2, &unsorted_leaves)
+            .is_err());
+    }
+
+    {
+        // [non_existing_key1, key2], the path to non_existing_key1 ends at key1, which is out of
+        // the range.
+        let proof = SparseMerkleKeyRangeProof::new(proof1.clone(), proof2.clone());
+        let leaves = vec![(key2, blob2.clone())];
+        assert!(proof
+            .verify(root_hash, non_existing_key1, key2, &leaves)
+            .is_ok());
+        assert!(proof
+            .verify(root_hash, non_existing_key1, key2, &[])
+            .is_err());
+    }
+
+    {
+        // The whole tree, where the path to the max key ends at the default node.
+        let proof = SparseMerkleKeyRangeProof::new(proof1, proof_of_default);
+        let leaves = vec![(key1, blob1), (key2, blob2), (key3, blob3)];
+        assert!(proof
+            .verify(
+                root_hash,
+                HashValue::zero(),
+                HashValue::new([0xff; 32]),
+                &leaves
+            )
+            .is_ok());
+        assert!(proof
+            .verify(
+                root_hash,
+                HashValue::zero(),
+                HashValue::new([0xff; 32]),
+                &leaves[1..]
+            )
+            .is_err());
+    }
+}
+
 #[test]
 fn test_verify_transaction() {
     //            root